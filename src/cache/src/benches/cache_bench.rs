@@ -0,0 +1,91 @@
+use criterion::{black_box, criterion_group, criterion_main, Criterion};
+use cache::group_store::{MappedGroups, PreloadedGroups};
+use rs2cache::Cache;
+use rs2cache::js5_compression::Js5Compression;
+use rs2cache::js5_index::Js5Index;
+use rs2cache::js5_masterindex::Js5MasterIndex;
+
+/// Needs a real cache at `paths.cache`, the same one the servers use.
+fn open_cache() -> (Cache, Js5MasterIndex) {
+    let cache = Cache::open(&config::get().paths.cache).expect("Benchmarks need a cache at paths.cache");
+    let master_index = Js5MasterIndex::create(&cache.store);
+    (cache, master_index)
+}
+
+/// Every 16th group of every archive, spread out so the mapped reads aren't all neighbours.
+fn sample_groups(cache: &Cache, master_index: &Js5MasterIndex) -> Vec<(u8, u16)> {
+    let mut groups = Vec::new();
+    for archive in 0..master_index.entries.len() {
+        let compressed = cache.store.read(255, archive as u32).unwrap();
+        let index = Js5Index::read(Js5Compression::uncompress(compressed, None).unwrap()).unwrap();
+
+        let mut ids: Vec<u32> = index.groups.keys().copied().collect();
+        ids.sort_unstable();
+        groups.extend(ids.iter().step_by(16).map(|group| (archive as u8, *group as u16)));
+    }
+    groups
+}
+
+fn benchmark_startup(c: &mut Criterion) {
+    let (cache, master_index) = open_cache();
+    let path = config::get().paths.cache.clone();
+
+    let mut group = c.benchmark_group("cache_startup");
+    group.sample_size(10);
+    group.bench_function("preload", |b| {
+        b.iter(|| black_box(PreloadedGroups::load(&cache, &master_index).unwrap()));
+    });
+    group.bench_function("mmap", |b| {
+        b.iter(|| black_box(MappedGroups::open(&path, 64 * 1024 * 1024).unwrap()));
+    });
+    group.finish();
+}
+
+fn benchmark_get(c: &mut Criterion) {
+    let (cache, master_index) = open_cache();
+    let path = config::get().paths.cache.clone();
+    let groups = sample_groups(&cache, &master_index);
+
+    let preloaded = PreloadedGroups::load(&cache, &master_index).unwrap();
+    let hot = MappedGroups::open(&path, usize::MAX).unwrap();
+    for (archive, group) in &groups {
+        hot.get(*archive, *group).unwrap();
+    }
+    // Nothing fits, every read goes to the mapped files.
+    let cold = MappedGroups::open(&path, 0).unwrap();
+
+    let mut group = c.benchmark_group("cache_get");
+    // What get_data used to cost, a copy of the preloaded Vec per request.
+    group.bench_function("preload_copy", |b| {
+        b.iter(|| {
+            for (archive, group) in &groups {
+                black_box(preloaded.get(*archive, *group).unwrap().to_vec());
+            }
+        });
+    });
+    group.bench_function("preload_shared", |b| {
+        b.iter(|| {
+            for (archive, group) in &groups {
+                black_box(preloaded.get(*archive, *group).unwrap());
+            }
+        });
+    });
+    group.bench_function("mmap_hot", |b| {
+        b.iter(|| {
+            for (archive, group) in &groups {
+                black_box(hot.get(*archive, *group).unwrap());
+            }
+        });
+    });
+    group.bench_function("mmap_cold", |b| {
+        b.iter(|| {
+            for (archive, group) in &groups {
+                black_box(cold.get(*archive, *group).unwrap());
+            }
+        });
+    });
+    group.finish();
+}
+
+criterion_group!(benches, benchmark_startup, benchmark_get);
+criterion_main!(benches);
@@ -1,17 +1,23 @@
-use std::collections::HashMap;
-use std::sync::RwLock;
+use std::collections::{BTreeMap, HashMap};
+use std::path::Path;
+use std::sync::{Arc, RwLock};
 use std::error;
-use std::time::Instant;
 use once_cell::sync::Lazy;
-use log::{debug, error, info};
+use log::{debug, error, info, warn};
+use config::CacheBackend;
 use rs2cache::Cache;
 use rs2cache::js5_compression::Js5Compression;
-use rs2cache::js5_index::Js5Index;
 use rs2cache::js5_masterindex::Js5MasterIndex;
 use rs2cache::store::ARCHIVESET;
+use crate::group_store::{GroupStore, MappedGroups, PreloadedGroups};
+use crate::overlay::{container_checksum, patch_master_index, read_overlay, uncompressed_container, ArchiveIndex};
+
+type SharedGroups = HashMap<(u8, u16), Arc<[u8]>>;
 
 struct CacheData {
-    preloaded_data: HashMap<(u8, u16), Vec<u8>>,
+    groups: Option<GroupStore>,
+    /// Groups from the overlay directory and the indexes patched to match, checked before `groups`.
+    overlay: SharedGroups,
     master_index: Option<Vec<u8>>,
     checksums: Vec<u32>,
     cache_path: String
@@ -19,10 +25,11 @@ struct CacheData {
 
 static GLOBAL_CACHE_DATA: Lazy<RwLock<CacheData>> = Lazy::new(|| {
     RwLock::new(CacheData {
-        preloaded_data: HashMap::with_capacity(67551),
+        groups: None,
+        overlay: HashMap::new(),
         master_index: None,
         checksums: Vec::new(),
-        cache_path: "../../src/cacheLocal".to_string()
+        cache_path: config::get().paths.cache.clone()
     })
 });
 
@@ -35,8 +42,8 @@ static INIT: Lazy<()> = Lazy::new(|| {
 });
 
 fn initialize_cache() -> Result<(), Box<dyn error::Error>> {
-    let start = Instant::now();
-    let cache_path = "../../src/cacheLocal";
+    let config = config::get();
+    let cache_path = config.paths.cache.as_str();
 
     let cache = match Cache::open(cache_path) {
         Ok(cache) => cache,
@@ -46,65 +53,22 @@ fn initialize_cache() -> Result<(), Box<dyn error::Error>> {
     };
 
     let master_index = Js5MasterIndex::create(&cache.store);
-    let master_index_data = master_index.write();
+    let mut master_index_data = master_index.write();
+    let mut checksums: Vec<u32> = master_index.entries.iter().map(|entry| entry.checksum).collect();
 
-    let mut checksums: Vec<u32> = Vec::with_capacity(master_index.entries.len());
+    let overlay = load_overlay(&config.paths.overlay, &cache, &mut master_index_data, &mut checksums)?;
 
-    let mut preloaded_data = HashMap::with_capacity(67553);
-    let mut total_entries = 0;
-    let mut successful_loads = 0;
-    let mut failed_loads = 0;
-    
-    for archive_id in 0..master_index.entries.len() {
-        checksums.push(master_index.entries[archive_id].checksum);
-        let js5_index_compressed = cache.store.read(255, archive_id as u32).unwrap();
-        let js5_index_decompressed = Js5Compression::uncompress(js5_index_compressed, None)?;
-        let js5_index = Js5Index::read(js5_index_decompressed).unwrap();
-        for (group_id, _) in js5_index.groups.iter() {
-            match cache.store.read(archive_id as u8, *group_id) {
-                Ok(data) => {
-                    preloaded_data.insert((archive_id as u8, *group_id as u16), data);
-                    successful_loads += 1;
-                },
-                Err(e) => {
-                    error!("Archive: {}, group: {} : {}",archive_id, group_id, e);
-                    failed_loads += 1;
-                }
-            }
-            total_entries += 1
+    let groups = match config.cache.backend {
+        CacheBackend::Preload => GroupStore::Preloaded(PreloadedGroups::load(&cache, &master_index)?),
+        CacheBackend::Mmap => {
+            info!("Serving groups from the mapped cache, keeping up to {} bytes in memory", config.cache.lru_capacity);
+            GroupStore::Mapped(MappedGroups::open(cache_path, config.cache.lru_capacity)?)
         }
-    }
-
-    // Handle the master index data separately.
-    let master_index_entries_len = master_index.entries.len();
-    for group_id in 0..master_index_entries_len {
-        match cache.store.read(ARCHIVESET, group_id as u32) {
-            Ok(data) => {
-                preloaded_data.insert((ARCHIVESET, group_id as u16), data);
-            },
-            Err(e) => {
-                error!("Archive: {}, group: {} : {}", ARCHIVESET, group_id, e);
-                failed_loads += 1;
-            }
-        }
-    }
-
-    info!("Preloaded cache in {:?}", start.elapsed());
-    info!(
-        "Preloaded {}/{} cache entries ({:.3}%)",
-        successful_loads,
-        total_entries,
-        (successful_loads as f64 / total_entries as f64) * 100.0
-    );
-    info!(
-        "Failed to preload {}/{} cache entries ({:.3}%)",
-        failed_loads,
-        total_entries,
-        (failed_loads as f64 / total_entries as f64) * 100.0
-    );
+    };
 
     let mut global_data = GLOBAL_CACHE_DATA.write().unwrap();
-    global_data.preloaded_data = preloaded_data;
+    global_data.groups = Some(groups);
+    global_data.overlay = overlay;
     global_data.master_index = Some(master_index_data);
     global_data.checksums = checksums;
     global_data.cache_path = cache_path.to_string();
@@ -113,18 +77,79 @@ fn initialize_cache() -> Result<(), Box<dyn error::Error>> {
 }
 
 
+/// Reads the overlay and rewrites the archive indexes and master index so the client accepts the
+/// replaced groups. Each overlay group gets its checksum in the index, is added to it when it's
+/// new, and a group directory also sets the group's file ids.
+fn load_overlay(path: &str, cache: &Cache, master_index: &mut [u8], checksums: &mut [u32]) -> Result<SharedGroups, Box<dyn error::Error>> {
+    let mut groups = read_overlay(Path::new(path))?;
+    if groups.is_empty() {
+        return Ok(HashMap::new());
+    }
+
+    let mut by_archive: BTreeMap<u8, Vec<u16>> = BTreeMap::new();
+    for (archive, group) in groups.keys() {
+        by_archive.entry(*archive).or_default().push(*group);
+    }
+
+    let mut indexes = Vec::new();
+    for (archive, group_ids) in by_archive {
+        if archive == ARCHIVESET || archive as usize >= checksums.len() {
+            warn!("Overlay archive {} is not in the cache, skipping it", archive);
+            groups.retain(|(group_archive, _), _| *group_archive != archive);
+            continue;
+        }
+
+        let compressed = cache.store.read(ARCHIVESET, archive as u32)?;
+        let mut index = ArchiveIndex::decode(&Js5Compression::uncompress(compressed, None)?)
+            .map_err(|e| format!("Failed to read the index of archive {}: {}", archive, e))?;
+
+        for group in group_ids {
+            let overlay = &groups[&(archive, group)];
+            if index.replace_group(group as u32, container_checksum(&overlay.container), overlay.files.as_deref()) {
+                info!("Overlay adds group {}/{} to the archive index", archive, group);
+            }
+        }
+
+        let container = uncompressed_container(&index.encode().map_err(|e| format!("Failed to write the index of archive {}: {}", archive, e))?);
+        let checksum = container_checksum(&container);
+        patch_master_index(master_index, checksums.len(), archive, checksum)?;
+        checksums[archive as usize] = checksum;
+
+        indexes.push(((ARCHIVESET, archive as u16), container));
+    }
+
+    info!("Overlay from {} serves {} groups and {} rewritten indexes", path, groups.len(), indexes.len());
+    Ok(groups
+        .into_iter()
+        .map(|(key, group)| (key, group.container))
+        .chain(indexes)
+        .map(|(key, data)| (key, Arc::from(data)))
+        .collect())
+}
+
 pub fn ensure_initialized() -> Result<(), Box<dyn error::Error>> {
     Lazy::force(&INIT);
     Ok(())
 }
 
-pub fn get_data(archive: u8, group: u16) -> Result<Vec<u8>, Box<dyn error::Error>> {
+/// The group's container as stored, shared rather than copied.
+pub fn get_group(archive: u8, group: u16) -> Result<Arc<[u8]>, Box<dyn error::Error>> {
     ensure_initialized()?;
     
     {
         let data_cache = GLOBAL_CACHE_DATA.read().unwrap();
-        if let Some(data) = data_cache.preloaded_data.get(&(archive, group)) {
-            return Ok(data.clone());
+        if let Some(data) = data_cache.overlay.get(&(archive, group)) {
+            return Ok(Arc::clone(data));
+        }
+
+        match &data_cache.groups {
+            Some(GroupStore::Mapped(groups)) => return groups.get(archive, group),
+            Some(GroupStore::Preloaded(groups)) => {
+                if let Some(data) = groups.get(archive, group) {
+                    return Ok(data);
+                }
+            }
+            None => {}
         }
     }
 
@@ -137,14 +162,20 @@ pub fn get_data(archive: u8, group: u16) -> Result<Vec<u8>, Box<dyn error::Error
     debug!("Data for archive {}, group {} not in preloaded cache, loading directly", archive, group);
 
     let cache = Cache::open(&cache_path)?;
-    let data = cache.store.read(archive, group as u32)?;
+    let data: Arc<[u8]> = Arc::from(cache.store.read(archive, group as u32)?);
 
-    let mut preloaded_data = GLOBAL_CACHE_DATA.write().unwrap();
-    preloaded_data.preloaded_data.insert((archive, group), data.clone());
+    let mut data_cache = GLOBAL_CACHE_DATA.write().unwrap();
+    if let Some(GroupStore::Preloaded(groups)) = &mut data_cache.groups {
+        groups.insert(archive, group, Arc::clone(&data));
+    }
 
     Ok(data)
 }
 
+pub fn get_data(archive: u8, group: u16) -> Result<Vec<u8>, Box<dyn error::Error>> {
+    Ok(get_group(archive, group)?.to_vec())
+}
+
 pub fn get_master_index() -> Result<Vec<u8>, Box<dyn error::Error>> {
     ensure_initialized()?;
     let data_cache = GLOBAL_CACHE_DATA.read().unwrap();
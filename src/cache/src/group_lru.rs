@@ -0,0 +1,83 @@
+use std::collections::{BTreeMap, HashMap};
+use std::sync::Arc;
+
+/// Archive and group id.
+type GroupKey = (u8, u16);
+
+/// Recently served groups, bounded by their total size rather than a count since they range from
+/// a few bytes to megabytes.
+#[derive(Debug)]
+pub struct GroupLru {
+    capacity: usize,
+    size: usize,
+    tick: u64,
+    groups: HashMap<GroupKey, (Arc<[u8]>, u64)>,
+    /// Last use of every cached group, oldest first.
+    usage: BTreeMap<u64, GroupKey>,
+}
+
+impl GroupLru {
+    pub fn new(capacity: usize) -> GroupLru {
+        GroupLru {
+            capacity,
+            size: 0,
+            tick: 0,
+            groups: HashMap::new(),
+            usage: BTreeMap::new(),
+        }
+    }
+
+    pub fn get(&mut self, archive: u8, group: u16) -> Option<Arc<[u8]>> {
+        self.tick += 1;
+        let (data, last_used) = self.groups.get_mut(&(archive, group))?;
+
+        self.usage.remove(last_used);
+        *last_used = self.tick;
+        self.usage.insert(self.tick, (archive, group));
+
+        Some(Arc::clone(data))
+    }
+
+    /// Caches `data`, evicting the least recently used groups to make room. Groups bigger than the
+    /// whole capacity are never cached.
+    pub fn insert(&mut self, archive: u8, group: u16, data: Arc<[u8]>) {
+        if data.len() > self.capacity {
+            return;
+        }
+
+        self.remove(archive, group);
+        while self.size + data.len() > self.capacity {
+            let Some((_, oldest)) = self.usage.pop_first() else {
+                break;
+            };
+            if let Some((evicted, _)) = self.groups.remove(&oldest) {
+                self.size -= evicted.len();
+            }
+        }
+
+        self.tick += 1;
+        self.size += data.len();
+        self.usage.insert(self.tick, (archive, group));
+        self.groups.insert((archive, group), (data, self.tick));
+    }
+
+    fn remove(&mut self, archive: u8, group: u16) {
+        if let Some((data, last_used)) = self.groups.remove(&(archive, group)) {
+            self.usage.remove(&last_used);
+            self.size -= data.len();
+        }
+    }
+
+    pub fn len(&self) -> usize {
+        self.groups.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.groups.is_empty()
+    }
+
+    /// Bytes currently cached.
+    pub fn size(&self) -> usize {
+        self.size
+    }
+}
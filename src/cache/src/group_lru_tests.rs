@@ -0,0 +1,66 @@
+use std::sync::Arc;
+use crate::group_lru::GroupLru;
+
+fn group(size: usize) -> Arc<[u8]> {
+    Arc::from(vec![0u8; size])
+}
+
+#[test]
+fn test_evicts_least_recently_used() {
+    let mut lru = GroupLru::new(30);
+    lru.insert(0, 1, group(10));
+    lru.insert(0, 2, group(10));
+    lru.insert(0, 3, group(10));
+
+    // Touching 1 makes 2 the oldest.
+    assert!(lru.get(0, 1).is_some());
+    lru.insert(0, 4, group(10));
+
+    assert!(lru.get(0, 2).is_none());
+    assert!(lru.get(0, 1).is_some());
+    assert!(lru.get(0, 3).is_some());
+    assert!(lru.get(0, 4).is_some());
+    assert_eq!(lru.size(), 30);
+}
+
+#[test]
+fn test_bounded_by_bytes() {
+    let mut lru = GroupLru::new(100);
+    lru.insert(1, 0, group(40));
+    lru.insert(1, 1, group(40));
+    lru.insert(1, 2, group(90));
+
+    assert_eq!(lru.len(), 1);
+    assert_eq!(lru.size(), 90);
+    assert!(lru.get(1, 2).is_some());
+}
+
+#[test]
+fn test_oversized_group_not_cached() {
+    let mut lru = GroupLru::new(10);
+    lru.insert(0, 0, group(5));
+    lru.insert(0, 1, group(11));
+
+    assert!(lru.get(0, 1).is_none());
+    assert!(lru.get(0, 0).is_some());
+}
+
+#[test]
+fn test_reinsert_replaces() {
+    let mut lru = GroupLru::new(100);
+    lru.insert(2, 7, group(10));
+    lru.insert(2, 7, group(20));
+
+    assert_eq!(lru.len(), 1);
+    assert_eq!(lru.size(), 20);
+    assert_eq!(lru.get(2, 7).unwrap().len(), 20);
+}
+
+#[test]
+fn test_shares_data() {
+    let mut lru = GroupLru::new(100);
+    let data = group(10);
+    lru.insert(0, 0, Arc::clone(&data));
+
+    assert!(Arc::ptr_eq(&lru.get(0, 0).unwrap(), &data));
+}
@@ -0,0 +1,117 @@
+use std::collections::HashMap;
+use std::error;
+use std::sync::{Arc, Mutex};
+use std::time::Instant;
+use log::{error, info};
+use rs2cache::Cache;
+use rs2cache::js5_compression::Js5Compression;
+use rs2cache::js5_index::Js5Index;
+use rs2cache::js5_masterindex::Js5MasterIndex;
+use rs2cache::store::ARCHIVESET;
+use crate::group_lru::GroupLru;
+use crate::mmap_store::MmapStore;
+
+/// Where [`crate::file_handler`] gets groups from, picked by `cache.backend` in the config.
+pub enum GroupStore {
+    Preloaded(PreloadedGroups),
+    Mapped(MappedGroups),
+}
+
+/// Every group read into memory on startup.
+pub struct PreloadedGroups {
+    groups: HashMap<(u8, u16), Arc<[u8]>>,
+}
+
+impl PreloadedGroups {
+    pub fn load(cache: &Cache, master_index: &Js5MasterIndex) -> Result<PreloadedGroups, Box<dyn error::Error>> {
+        let start = Instant::now();
+
+        let mut groups = HashMap::with_capacity(67553);
+        let mut total_entries = 0;
+        let mut successful_loads = 0;
+        let mut failed_loads = 0;
+
+        for archive_id in 0..master_index.entries.len() {
+            let js5_index_compressed = cache.store.read(255, archive_id as u32).unwrap();
+            let js5_index_decompressed = Js5Compression::uncompress(js5_index_compressed, None)?;
+            let js5_index = Js5Index::read(js5_index_decompressed).unwrap();
+            for (group_id, _) in js5_index.groups.iter() {
+                match cache.store.read(archive_id as u8, *group_id) {
+                    Ok(data) => {
+                        groups.insert((archive_id as u8, *group_id as u16), Arc::from(data));
+                        successful_loads += 1;
+                    },
+                    Err(e) => {
+                        error!("Archive: {}, group: {} : {}",archive_id, group_id, e);
+                        failed_loads += 1;
+                    }
+                }
+                total_entries += 1
+            }
+        }
+
+        // Handle the master index data separately.
+        for group_id in 0..master_index.entries.len() {
+            match cache.store.read(ARCHIVESET, group_id as u32) {
+                Ok(data) => {
+                    groups.insert((ARCHIVESET, group_id as u16), Arc::from(data));
+                },
+                Err(e) => {
+                    error!("Archive: {}, group: {} : {}", ARCHIVESET, group_id, e);
+                    failed_loads += 1;
+                }
+            }
+        }
+
+        info!("Preloaded cache in {:?}", start.elapsed());
+        info!(
+            "Preloaded {}/{} cache entries ({:.3}%)",
+            successful_loads,
+            total_entries,
+            (successful_loads as f64 / total_entries as f64) * 100.0
+        );
+        info!(
+            "Failed to preload {}/{} cache entries ({:.3}%)",
+            failed_loads,
+            total_entries,
+            (failed_loads as f64 / total_entries as f64) * 100.0
+        );
+
+        Ok(PreloadedGroups { groups })
+    }
+
+    pub fn get(&self, archive: u8, group: u16) -> Option<Arc<[u8]>> {
+        self.groups.get(&(archive, group)).cloned()
+    }
+
+    pub fn insert(&mut self, archive: u8, group: u16, data: Arc<[u8]>) {
+        self.groups.insert((archive, group), data);
+    }
+}
+
+/// Groups read from the memory-mapped store the first time they are asked for, the most recently
+/// used are kept in a [`GroupLru`].
+pub struct MappedGroups {
+    store: MmapStore,
+    lru: Mutex<GroupLru>,
+}
+
+impl MappedGroups {
+    pub fn open(path: &str, lru_capacity: usize) -> Result<MappedGroups, Box<dyn error::Error>> {
+        Ok(MappedGroups {
+            store: MmapStore::open(path)?,
+            lru: Mutex::new(GroupLru::new(lru_capacity)),
+        })
+    }
+
+    pub fn get(&self, archive: u8, group: u16) -> Result<Arc<[u8]>, Box<dyn error::Error>> {
+        if let Some(data) = self.lru.lock().unwrap().get(archive, group) {
+            return Ok(data);
+        }
+
+        // Read without holding the lock, two requests racing for the same group both just read it.
+        let data: Arc<[u8]> = Arc::from(self.store.read(archive, group as u32)?);
+        self.lru.lock().unwrap().insert(archive, group, Arc::clone(&data));
+        Ok(data)
+    }
+}
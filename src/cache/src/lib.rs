@@ -1,3 +1,14 @@
 pub mod version_trailer;
 pub mod file_handler;
-pub mod xtea;
\ No newline at end of file
+pub mod xtea;
+pub mod group_lru;
+pub mod group_store;
+pub mod mmap_store;
+pub mod overlay;
+
+#[cfg(test)]
+mod group_lru_tests;
+#[cfg(test)]
+mod mmap_store_tests;
+#[cfg(test)]
+mod overlay_tests;
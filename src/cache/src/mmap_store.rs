@@ -0,0 +1,106 @@
+use std::cmp::min;
+use std::error;
+use std::fs::File;
+use std::path::Path;
+use memmap2::Mmap;
+
+pub const DATA_FILE: &str = "main_file_cache.dat2";
+pub const INDEX_FILE_PREFIX: &str = "main_file_cache.idx";
+
+pub const SECTOR_SIZE: usize = 520;
+const INDEX_ENTRY_SIZE: usize = 6;
+const SECTOR_HEADER_SIZE: usize = 8;
+/// Groups past 0xFFFF need a 4 byte id in the header.
+const EXTENDED_SECTOR_HEADER_SIZE: usize = 10;
+
+/// Read only view of an on-disk store, the data file and every index file are memory-mapped and
+/// a group is only pieced together from its sectors when it is read.
+pub struct MmapStore {
+    data: Mmap,
+    indexes: Vec<Option<Mmap>>,
+}
+
+fn map(path: &Path) -> Result<Mmap, Box<dyn error::Error>> {
+    let file = File::open(path).map_err(|e| format!("Failed to open {}: {}", path.display(), e))?;
+    // The server never writes to the cache, it must not be replaced underneath a running server.
+    let mmap = unsafe { Mmap::map(&file) }.map_err(|e| format!("Failed to map {}: {}", path.display(), e))?;
+    Ok(mmap)
+}
+
+fn u24(bytes: &[u8]) -> usize {
+    (bytes[0] as usize) << 16 | (bytes[1] as usize) << 8 | bytes[2] as usize
+}
+
+impl MmapStore {
+    pub fn open(path: &str) -> Result<MmapStore, Box<dyn error::Error>> {
+        let root = Path::new(path);
+        let data = map(&root.join(DATA_FILE))?;
+
+        let mut indexes = Vec::with_capacity(256);
+        for archive in 0..=255u8 {
+            let index_path = root.join(format!("{}{}", INDEX_FILE_PREFIX, archive));
+            indexes.push(if index_path.exists() { Some(map(&index_path)?) } else { None });
+        }
+
+        Ok(MmapStore { data, indexes })
+    }
+
+    /// Location of `group` in the data file, its size and first sector.
+    fn entry(&self, archive: u8, group: u32) -> Option<(usize, usize)> {
+        let index = self.indexes[archive as usize].as_ref()?;
+        let offset = group as usize * INDEX_ENTRY_SIZE;
+        let entry = index.get(offset..offset + INDEX_ENTRY_SIZE)?;
+
+        let sector = u24(&entry[3..6]);
+        if sector == 0 {
+            return None;
+        }
+        Some((u24(&entry[0..3]), sector))
+    }
+
+    pub fn exists(&self, archive: u8, group: u32) -> bool {
+        self.entry(archive, group).is_some()
+    }
+
+    pub fn read(&self, archive: u8, group: u32) -> Result<Vec<u8>, Box<dyn error::Error>> {
+        let (size, mut sector) = self.entry(archive, group)
+            .ok_or_else(|| format!("Archive: {}, group: {} not found", archive, group))?;
+
+        let extended = group > 0xFFFF;
+        let header_size = if extended { EXTENDED_SECTOR_HEADER_SIZE } else { SECTOR_HEADER_SIZE };
+        let data_size = SECTOR_SIZE - header_size;
+
+        let mut data = Vec::with_capacity(size);
+        let mut num = 0;
+
+        while data.len() < size {
+            if sector == 0 {
+                return Err(format!("Archive: {}, group: {} ends after {} of {} bytes", archive, group, data.len(), size).into());
+            }
+
+            let chunk_size = min(size - data.len(), data_size);
+            let start = sector * SECTOR_SIZE;
+            let bytes = self.data.get(start..start + header_size + chunk_size)
+                .ok_or_else(|| format!("Archive: {}, group: {} points past the data file at sector {}", archive, group, sector))?;
+
+            let (actual_group, header) = if extended {
+                ((bytes[0] as u32) << 24 | (bytes[1] as u32) << 16 | (bytes[2] as u32) << 8 | bytes[3] as u32, &bytes[4..])
+            } else {
+                ((bytes[0] as u32) << 8 | bytes[1] as u32, &bytes[2..])
+            };
+            let actual_num = (header[0] as usize) << 8 | header[1] as usize;
+            let next_sector = u24(&header[2..5]);
+            let actual_archive = header[5];
+
+            if actual_group != group || actual_num != num || actual_archive != archive {
+                return Err(format!("Archive: {}, group: {} has a corrupt header in sector {}", archive, group, sector).into());
+            }
+
+            data.extend_from_slice(&bytes[header_size..]);
+            sector = next_sector;
+            num += 1;
+        }
+
+        Ok(data)
+    }
+}
@@ -0,0 +1,113 @@
+use std::fs;
+use std::path::PathBuf;
+use crate::mmap_store::{MmapStore, DATA_FILE, INDEX_FILE_PREFIX, SECTOR_SIZE};
+
+/// Writes groups in the on-disk sector layout, sector 0 is left empty like a real store.
+fn write_store(name: &str, groups: &[(u8, u32, Vec<u8>)]) -> PathBuf {
+    let dir = std::env::temp_dir().join(format!("rt4_mmap_store_{}_{}", name, std::process::id()));
+    let _ = fs::remove_dir_all(&dir);
+    fs::create_dir_all(&dir).unwrap();
+
+    let mut data = vec![0u8; SECTOR_SIZE];
+    let mut indexes: Vec<Vec<u8>> = vec![Vec::new(); 256];
+
+    for (archive, group, bytes) in groups {
+        let extended = *group > 0xFFFF;
+        let chunk_size = if extended { SECTOR_SIZE - 10 } else { SECTOR_SIZE - 8 };
+        let first_sector = data.len() / SECTOR_SIZE;
+
+        let index = &mut indexes[*archive as usize];
+        let offset = *group as usize * 6;
+        if index.len() < offset + 6 {
+            index.resize(offset + 6, 0);
+        }
+        index[offset..offset + 3].copy_from_slice(&(bytes.len() as u32).to_be_bytes()[1..]);
+        index[offset + 3..offset + 6].copy_from_slice(&(first_sector as u32).to_be_bytes()[1..]);
+
+        let chunks: Vec<&[u8]> = bytes.chunks(chunk_size).collect();
+        for (num, chunk) in chunks.iter().enumerate() {
+            let sector = data.len() / SECTOR_SIZE;
+            let next = if num + 1 < chunks.len() { sector + 1 } else { 0 };
+
+            let mut sector_data = Vec::with_capacity(SECTOR_SIZE);
+            if extended {
+                sector_data.extend_from_slice(&group.to_be_bytes());
+            } else {
+                sector_data.extend_from_slice(&(*group as u16).to_be_bytes());
+            }
+            sector_data.extend_from_slice(&(num as u16).to_be_bytes());
+            sector_data.extend_from_slice(&(next as u32).to_be_bytes()[1..]);
+            sector_data.push(*archive);
+            sector_data.extend_from_slice(chunk);
+            sector_data.resize(SECTOR_SIZE, 0);
+            data.extend_from_slice(&sector_data);
+        }
+    }
+
+    fs::write(dir.join(DATA_FILE), data).unwrap();
+    for (archive, index) in indexes.iter().enumerate() {
+        if !index.is_empty() {
+            fs::write(dir.join(format!("{}{}", INDEX_FILE_PREFIX, archive)), index).unwrap();
+        }
+    }
+    dir
+}
+
+fn bytes(length: usize) -> Vec<u8> {
+    (0..length).map(|i| (i * 7 % 251) as u8).collect()
+}
+
+#[test]
+fn test_read_groups() {
+    let small = bytes(10);
+    let multi_sector = bytes(2000);
+    let dir = write_store("read", &[(2, 5, small.clone()), (255, 2, multi_sector.clone()), (2, 0, Vec::new())]);
+
+    let store = MmapStore::open(dir.to_str().unwrap()).unwrap();
+    assert_eq!(store.read(2, 5).unwrap(), small);
+    assert_eq!(store.read(255, 2).unwrap(), multi_sector);
+    assert!(store.read(2, 0).unwrap().is_empty());
+
+    fs::remove_dir_all(dir).unwrap();
+}
+
+#[test]
+fn test_extended_group() {
+    let data = bytes(1200);
+    let dir = write_store("extended", &[(7, 70000, data.clone())]);
+
+    let store = MmapStore::open(dir.to_str().unwrap()).unwrap();
+    assert_eq!(store.read(7, 70000).unwrap(), data);
+
+    fs::remove_dir_all(dir).unwrap();
+}
+
+#[test]
+fn test_missing_groups() {
+    let dir = write_store("missing", &[(1, 3, bytes(4))]);
+
+    let store = MmapStore::open(dir.to_str().unwrap()).unwrap();
+    assert!(store.exists(1, 3));
+    assert!(!store.exists(1, 2), "zeroed index entry");
+    assert!(!store.exists(1, 50), "past the end of the index");
+    assert!(!store.exists(4, 0), "no index file");
+    assert!(store.read(1, 2).is_err());
+
+    fs::remove_dir_all(dir).unwrap();
+}
+
+#[test]
+fn test_corrupt_header() {
+    let dir = write_store("corrupt", &[(1, 3, bytes(600))]);
+
+    // Point the second sector at a different archive.
+    let data_path = dir.join(DATA_FILE);
+    let mut data = fs::read(&data_path).unwrap();
+    data[2 * SECTOR_SIZE + 7] = 9;
+    fs::write(&data_path, data).unwrap();
+
+    let store = MmapStore::open(dir.to_str().unwrap()).unwrap();
+    assert!(store.read(1, 3).is_err());
+
+    fs::remove_dir_all(dir).unwrap();
+}
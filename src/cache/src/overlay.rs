@@ -0,0 +1,380 @@
+use std::collections::{BTreeMap, HashMap};
+use std::error;
+use std::fs;
+use std::io::Write;
+use std::path::Path;
+use flate2::write::GzEncoder;
+use flate2::Compression;
+use log::warn;
+
+/// Extension of overlay files that already are JS5 containers, anything without an extension is
+/// the raw group and gets compressed on load.
+pub const CONTAINER_EXTENSION: &str = "dat";
+
+pub struct OverlayGroup {
+    pub container: Vec<u8>,
+    /// File ids of a group given as a directory of files, `None` keeps the ids in the index.
+    pub files: Option<Vec<u32>>,
+}
+
+/// Overlay groups by archive and group.
+pub type OverlayGroups = HashMap<(u8, u16), OverlayGroup>;
+
+const COMPRESSION_NONE: u8 = 0;
+const COMPRESSION_GZIP: u8 = 2;
+
+/// Reads `<dir>/<archive>/<group>`, `<dir>/<archive>/<group>.dat` and the files of
+/// `<dir>/<archive>/<group>/<file>`, every group as a container.
+pub fn read_overlay(dir: &Path) -> Result<OverlayGroups, Box<dyn error::Error>> {
+    let mut groups = HashMap::new();
+    if !dir.is_dir() {
+        return Ok(groups);
+    }
+
+    for archive_entry in fs::read_dir(dir)? {
+        let archive_path = archive_entry?.path();
+        let Some(archive) = archive_path.file_name().and_then(|name| name.to_str()?.parse::<u8>().ok()) else {
+            warn!("Ignoring {} in the overlay, expected an archive id", archive_path.display());
+            continue;
+        };
+        if !archive_path.is_dir() {
+            continue;
+        }
+
+        for group_entry in fs::read_dir(&archive_path)? {
+            let path = group_entry?.path();
+            let stem = path.file_stem().and_then(|stem| stem.to_str()).and_then(|stem| stem.parse::<u16>().ok());
+            let extension = path.extension().and_then(|extension| extension.to_str());
+
+            let group = match (stem, extension) {
+                (Some(group), None) | (Some(group), Some(CONTAINER_EXTENSION)) => group,
+                _ => {
+                    warn!("Ignoring {} in the overlay, expected <group>, <group>.{} or <group>/<file>", path.display(), CONTAINER_EXTENSION);
+                    continue;
+                }
+            };
+
+            if path.is_dir() && extension.is_none() {
+                groups.insert((archive, group), read_group_dir(&path)?);
+                continue;
+            }
+
+            let data = fs::read(&path).map_err(|e| format!("Failed to read {}: {}", path.display(), e))?;
+            let container = if extension.is_some() {
+                container_length(&data).ok_or_else(|| format!("{} is not a valid container", path.display()))?;
+                data
+            } else {
+                gzip_container(&data)?
+            };
+            groups.insert((archive, group), OverlayGroup { container, files: None });
+        }
+    }
+
+    Ok(groups)
+}
+
+/// Packs the files of a group directory in id order, the ids become the group's file list.
+fn read_group_dir(dir: &Path) -> Result<OverlayGroup, Box<dyn error::Error>> {
+    let mut files = BTreeMap::new();
+    for entry in fs::read_dir(dir)? {
+        let path = entry?.path();
+        let Some(file) = path.file_name().and_then(|name| name.to_str()?.parse::<u16>().ok()).filter(|_| path.is_file()) else {
+            warn!("Ignoring {} in the overlay, expected a file id", path.display());
+            continue;
+        };
+        let data = fs::read(&path).map_err(|e| format!("Failed to read {}: {}", path.display(), e))?;
+        files.insert(file as u32, data);
+    }
+
+    if files.is_empty() {
+        return Err(format!("{} has no files", dir.display()).into());
+    }
+
+    let (ids, files): (Vec<u32>, Vec<Vec<u8>>) = files.into_iter().unzip();
+    Ok(OverlayGroup { container: gzip_container(&pack_group(&files))?, files: Some(ids) })
+}
+
+/// Packs the files of a group as a single chunk, a group of one file is the file itself.
+pub fn pack_group(files: &[Vec<u8>]) -> Vec<u8> {
+    if let [file] = files {
+        return file.clone();
+    }
+
+    let mut group: Vec<u8> = files.concat();
+
+    // Chunk sizes are deltas from the previous file.
+    let mut previous = 0;
+    for file in files {
+        group.extend_from_slice(&(file.len() as i32 - previous).to_be_bytes());
+        previous = file.len() as i32;
+    }
+
+    group.push(1);
+    group
+}
+
+/// Length of the container at the start of `data`, anything after it is the version trailer.
+pub fn container_length(data: &[u8]) -> Option<usize> {
+    let compression = *data.first()?;
+    let length = u32::from_be_bytes(data.get(1..5)?.try_into().ok()?) as usize;
+    let total = length + if compression == COMPRESSION_NONE { 5 } else { 9 };
+    if total > data.len() {
+        return None;
+    }
+    Some(total)
+}
+
+pub fn uncompressed_container(data: &[u8]) -> Vec<u8> {
+    let mut container = Vec::with_capacity(data.len() + 5);
+    container.push(COMPRESSION_NONE);
+    container.extend_from_slice(&(data.len() as u32).to_be_bytes());
+    container.extend_from_slice(data);
+    container
+}
+
+pub fn gzip_container(data: &[u8]) -> Result<Vec<u8>, Box<dyn error::Error>> {
+    let mut encoder = GzEncoder::new(Vec::new(), Compression::best());
+    encoder.write_all(data)?;
+    let compressed = encoder.finish()?;
+
+    let mut container = Vec::with_capacity(compressed.len() + 9);
+    container.push(COMPRESSION_GZIP);
+    container.extend_from_slice(&(compressed.len() as u32).to_be_bytes());
+    container.extend_from_slice(&(data.len() as u32).to_be_bytes());
+    container.extend_from_slice(&compressed);
+    Ok(container)
+}
+
+/// CRC-32 of a container without its version trailer, what the indexes store.
+pub fn container_checksum(container: &[u8]) -> u32 {
+    let length = container_length(container).unwrap_or(container.len());
+    crc32fast::hash(&container[..length])
+}
+
+/// Group entries of an archive index are flagged as named when they carry name hashes.
+const FLAG_NAMED: u8 = 0x1;
+
+/// A decompressed archive index of protocol 5 to 7.
+#[derive(Debug, Clone, PartialEq)]
+pub struct ArchiveIndex {
+    pub protocol: u8,
+    /// Only stored from protocol 6.
+    pub version: i32,
+    pub named: bool,
+    pub groups: BTreeMap<u32, IndexGroup>,
+}
+
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct IndexGroup {
+    pub name_hash: i32,
+    pub checksum: u32,
+    pub version: i32,
+    /// File ids in ascending order and their name hashes.
+    pub files: Vec<(u32, i32)>,
+}
+
+/// Reads the sizes and ids of an index, u16 before protocol 7 and big smarts after.
+struct IndexReader<'a> {
+    data: &'a [u8],
+    position: usize,
+    smart: bool,
+}
+
+impl IndexReader<'_> {
+    fn bytes(&mut self, length: usize) -> Result<&[u8], String> {
+        let bytes = self.data.get(self.position..self.position + length).ok_or("Index ends early")?;
+        self.position += length;
+        Ok(bytes)
+    }
+
+    fn u32(&mut self) -> Result<u32, String> {
+        let bytes = self.bytes(4)?;
+        Ok(u32::from_be_bytes([bytes[0], bytes[1], bytes[2], bytes[3]]))
+    }
+
+    fn id(&mut self) -> Result<u32, String> {
+        if self.smart && self.data.get(self.position).is_some_and(|byte| byte & 0x80 != 0) {
+            Ok(self.u32()? & 0x7FFFFFFF)
+        } else {
+            let bytes = self.bytes(2)?;
+            Ok(u16::from_be_bytes([bytes[0], bytes[1]]) as u32)
+        }
+    }
+
+    /// `count` ids stored as deltas from the previous one.
+    fn deltas(&mut self, count: usize) -> Result<Vec<u32>, String> {
+        let mut ids = Vec::new();
+        let mut id = 0u32;
+        for _ in 0..count {
+            id = id.wrapping_add(self.id()?);
+            ids.push(id);
+        }
+        Ok(ids)
+    }
+}
+
+/// Writes the sizes and ids of an index, the inverse of `IndexReader`.
+struct IndexWriter {
+    data: Vec<u8>,
+    smart: bool,
+}
+
+impl IndexWriter {
+    fn id(&mut self, value: u32) -> Result<(), String> {
+        if self.smart && value >= 0x8000 {
+            if value > 0x7FFFFFFF {
+                return Err(format!("{} doesn't fit in the index", value));
+            }
+            self.data.extend_from_slice(&(value | 0x80000000).to_be_bytes());
+        } else {
+            let value = u16::try_from(value).map_err(|_| format!("{} doesn't fit in a protocol 5 or 6 index", value))?;
+            self.data.extend_from_slice(&value.to_be_bytes());
+        }
+        Ok(())
+    }
+
+    fn deltas(&mut self, ids: impl Iterator<Item = u32>) -> Result<(), String> {
+        let mut previous = None;
+        for id in ids {
+            if previous.is_some_and(|previous| id <= previous) {
+                return Err(format!("Id {} is out of order in the index", id));
+            }
+            self.id(id - previous.unwrap_or(0))?;
+            previous = Some(id);
+        }
+        Ok(())
+    }
+}
+
+impl ArchiveIndex {
+    pub fn decode(data: &[u8]) -> Result<Self, String> {
+        let mut reader = IndexReader { data, position: 0, smart: false };
+
+        let protocol = reader.bytes(1)?[0];
+        if !(5..=7).contains(&protocol) {
+            return Err(format!("Unsupported index protocol {}", protocol));
+        }
+        let version = if protocol >= 6 { reader.u32()? as i32 } else { 0 };
+        reader.smart = protocol >= 7;
+
+        let flags = reader.bytes(1)?[0];
+        if flags & !FLAG_NAMED != 0 {
+            return Err(format!("Unsupported index flags {:#x}", flags));
+        }
+        let named = flags & FLAG_NAMED != 0;
+
+        let size = reader.id()? as usize;
+        let ids = reader.deltas(size)?;
+        let mut groups: Vec<IndexGroup> = ids.iter().map(|_| IndexGroup::default()).collect();
+
+        if named {
+            for group in groups.iter_mut() {
+                group.name_hash = reader.u32()? as i32;
+            }
+        }
+        for group in groups.iter_mut() {
+            group.checksum = reader.u32()?;
+        }
+        for group in groups.iter_mut() {
+            group.version = reader.u32()? as i32;
+        }
+
+        let mut counts = Vec::new();
+        for _ in 0..size {
+            counts.push(reader.id()? as usize);
+        }
+        for (group, count) in groups.iter_mut().zip(counts) {
+            group.files = reader.deltas(count)?.into_iter().map(|file| (file, 0)).collect();
+        }
+
+        if named {
+            for group in groups.iter_mut() {
+                for (_, name_hash) in group.files.iter_mut() {
+                    *name_hash = reader.u32()? as i32;
+                }
+            }
+        }
+
+        let mut by_id = BTreeMap::new();
+        for (id, group) in ids.into_iter().zip(groups) {
+            if by_id.insert(id, group).is_some() {
+                return Err(format!("Index lists group {} twice", id));
+            }
+        }
+
+        Ok(Self { protocol, version, named, groups: by_id })
+    }
+
+    pub fn encode(&self) -> Result<Vec<u8>, String> {
+        let mut writer = IndexWriter { data: vec![self.protocol], smart: self.protocol >= 7 };
+        if self.protocol >= 6 {
+            writer.data.extend_from_slice(&self.version.to_be_bytes());
+        }
+        writer.data.push(if self.named { FLAG_NAMED } else { 0 });
+
+        writer.id(self.groups.len() as u32)?;
+        writer.deltas(self.groups.keys().copied())?;
+
+        if self.named {
+            for group in self.groups.values() {
+                writer.data.extend_from_slice(&group.name_hash.to_be_bytes());
+            }
+        }
+        for group in self.groups.values() {
+            writer.data.extend_from_slice(&group.checksum.to_be_bytes());
+        }
+        for group in self.groups.values() {
+            writer.data.extend_from_slice(&group.version.to_be_bytes());
+        }
+
+        for group in self.groups.values() {
+            writer.id(group.files.len() as u32)?;
+        }
+        for group in self.groups.values() {
+            writer.deltas(group.files.iter().map(|(file, _)| *file))?;
+        }
+
+        if self.named {
+            for group in self.groups.values() {
+                for (_, name_hash) in &group.files {
+                    writer.data.extend_from_slice(&name_hash.to_be_bytes());
+                }
+            }
+        }
+
+        Ok(writer.data)
+    }
+
+    /// Points `group` at an overlay container with `checksum`, adding the group when the index
+    /// doesn't have it. `files` replaces its file ids, keeping the name hashes of files that stay,
+    /// without it the file list is left alone and a new group is the single file 0. Returns
+    /// whether the group was added.
+    pub fn replace_group(&mut self, group: u32, checksum: u32, files: Option<&[u32]>) -> bool {
+        let added = !self.groups.contains_key(&group);
+        let entry = self.groups.entry(group).or_insert_with(|| IndexGroup { files: vec![(0, 0)], ..IndexGroup::default() });
+        entry.checksum = checksum;
+
+        if let Some(files) = files {
+            let name_hashes: HashMap<u32, i32> = entry.files.iter().copied().collect();
+            entry.files = files.iter().map(|file| (*file, name_hashes.get(file).copied().unwrap_or(0))).collect();
+        }
+
+        added
+    }
+}
+
+/// Overwrites the checksum of `archive` in the master index, entries start with their checksum.
+pub fn patch_master_index(master_index: &mut [u8], archives: usize, archive: u8, checksum: u32) -> Result<(), String> {
+    if archives == 0 || !master_index.len().is_multiple_of(archives) {
+        return Err(format!("Master index of {} bytes doesn't split into {} entries", master_index.len(), archives));
+    }
+
+    let stride = master_index.len() / archives;
+    let offset = archive as usize * stride;
+    if stride < 4 || archive as usize >= archives {
+        return Err(format!("No master index entry for archive {}", archive));
+    }
+
+    master_index[offset..offset + 4].copy_from_slice(&checksum.to_be_bytes());
+    Ok(())
+}
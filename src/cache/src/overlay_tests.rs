@@ -0,0 +1,173 @@
+use std::fs;
+use std::io::Read;
+use flate2::read::GzDecoder;
+use crate::overlay::{container_checksum, container_length, gzip_container, pack_group, patch_master_index, read_overlay, uncompressed_container, ArchiveIndex, IndexGroup};
+
+/// Protocol 6 index with names, groups 0, 3 and 4 with checksums 0x11, 0x33 and 0x44. Group 3
+/// has files 0 and 2, the others only file 0.
+fn index_data() -> Vec<u8> {
+    let mut index = vec![6];
+    index.extend_from_slice(&1i32.to_be_bytes());
+    index.push(0x1);
+    index.extend_from_slice(&3u16.to_be_bytes());
+    for delta in [0u16, 3, 1] {
+        index.extend_from_slice(&delta.to_be_bytes());
+    }
+    for name_hash in [-1i32, -2, -3] {
+        index.extend_from_slice(&name_hash.to_be_bytes());
+    }
+    for checksum in [0x11u32, 0x33, 0x44] {
+        index.extend_from_slice(&checksum.to_be_bytes());
+    }
+    for version in [5i32, 6, 7] {
+        index.extend_from_slice(&version.to_be_bytes());
+    }
+    for count in [1u16, 2, 1] {
+        index.extend_from_slice(&count.to_be_bytes());
+    }
+    for delta in [0u16, 0, 2, 0] {
+        index.extend_from_slice(&delta.to_be_bytes());
+    }
+    for name_hash in [-10i32, -30, -32, -40] {
+        index.extend_from_slice(&name_hash.to_be_bytes());
+    }
+    index
+}
+
+fn decompress(container: &[u8]) -> Vec<u8> {
+    let mut data = Vec::new();
+    GzDecoder::new(&container[9..]).read_to_end(&mut data).unwrap();
+    data
+}
+
+#[test]
+fn test_containers() {
+    let raw = uncompressed_container(b"abc");
+    assert_eq!(raw, vec![0, 0, 0, 0, 3, b'a', b'b', b'c']);
+    assert_eq!(container_length(&raw), Some(8));
+
+    let mut with_trailer = raw.clone();
+    with_trailer.extend_from_slice(&[0, 9]);
+    assert_eq!(container_length(&with_trailer), Some(8));
+    assert_eq!(container_checksum(&with_trailer), container_checksum(&raw));
+
+    assert_eq!(container_length(&raw[..6]), None);
+    assert_eq!(container_length(&[]), None);
+}
+
+#[test]
+fn test_gzip_container() {
+    let data = vec![7u8; 1000];
+    let container = gzip_container(&data).unwrap();
+
+    assert_eq!(container[0], 2);
+    assert_eq!(container_length(&container), Some(container.len()));
+    assert_eq!(u32::from_be_bytes(container[5..9].try_into().unwrap()), 1000);
+    assert_eq!(decompress(&container), data);
+}
+
+#[test]
+fn test_index_round_trip() {
+    let index = ArchiveIndex::decode(&index_data()).unwrap();
+    assert_eq!((index.protocol, index.version, index.named), (6, 1, true));
+    assert_eq!(index.groups.keys().copied().collect::<Vec<_>>(), vec![0, 3, 4]);
+    assert_eq!(index.groups[&3], IndexGroup { name_hash: -2, checksum: 0x33, version: 6, files: vec![(0, -30), (2, -32)] });
+    assert_eq!(index.encode().unwrap(), index_data());
+}
+
+#[test]
+fn test_index_round_trip_with_smarts() {
+    let mut index = ArchiveIndex { protocol: 7, version: 2, named: false, groups: Default::default() };
+    index.groups.insert(1, IndexGroup { checksum: 1, files: vec![(0, 0)], ..IndexGroup::default() });
+    index.groups.insert(40000, IndexGroup { checksum: 2, files: vec![(0, 0), (70000, 0)], ..IndexGroup::default() });
+
+    let data = index.encode().unwrap();
+    // Protocol, version, flags, size, two group deltas, checksums, versions, counts and file deltas.
+    assert_eq!(data.len(), 1 + 4 + 1 + 2 + (2 + 4) + 8 + 8 + 4 + (2 + 2 + 4));
+    assert_eq!(ArchiveIndex::decode(&data).unwrap(), index);
+
+    index.protocol = 6;
+    assert!(index.encode().is_err(), "ids past u16 need protocol 7");
+}
+
+#[test]
+fn test_replace_group() {
+    let mut index = ArchiveIndex::decode(&index_data()).unwrap();
+
+    assert!(!index.replace_group(0, 0xDEADBEEF, None));
+    assert_eq!(index.groups[&0].checksum, 0xDEADBEEF);
+    assert_eq!(index.groups[&0].files, vec![(0, -10)]);
+
+    assert!(!index.replace_group(3, 0x1234, Some(&[0, 1, 2, 5])));
+    assert_eq!(index.groups[&3].files, vec![(0, -30), (1, 0), (2, -32), (5, 0)]);
+    assert_eq!(index.groups[&3].version, 6);
+
+    assert!(index.replace_group(9, 0x99, None));
+    assert_eq!(index.groups[&9], IndexGroup { checksum: 0x99, files: vec![(0, 0)], ..IndexGroup::default() });
+
+    let rewritten = ArchiveIndex::decode(&index.encode().unwrap()).unwrap();
+    assert_eq!(rewritten, index);
+}
+
+#[test]
+fn test_index_rejects_bad_input() {
+    assert!(ArchiveIndex::decode(&[4, 0, 0]).is_err());
+
+    let mut truncated = index_data();
+    truncated.truncate(40);
+    assert!(ArchiveIndex::decode(&truncated).is_err());
+
+    let mut flags = index_data();
+    flags[5] = 0x3;
+    assert!(ArchiveIndex::decode(&flags).is_err(), "digests aren't supported");
+
+    let mut repeated = index_data();
+    repeated[10..12].copy_from_slice(&0u16.to_be_bytes());
+    assert!(ArchiveIndex::decode(&repeated).is_err());
+}
+
+#[test]
+fn test_patch_master_index() {
+    let mut master_index = vec![0u8; 3 * 8];
+    patch_master_index(&mut master_index, 3, 1, 0x01020304).unwrap();
+    assert_eq!(master_index[8..16], [1, 2, 3, 4, 0, 0, 0, 0]);
+
+    assert!(patch_master_index(&mut master_index, 3, 3, 0).is_err());
+    assert!(patch_master_index(&mut master_index, 5, 0, 0).is_err());
+}
+
+#[test]
+fn test_read_overlay() {
+    let dir = std::env::temp_dir().join(format!("rt4_overlay_{}", std::process::id()));
+    let _ = fs::remove_dir_all(&dir);
+    fs::create_dir_all(dir.join("3").join("13")).unwrap();
+    fs::create_dir_all(dir.join("7")).unwrap();
+
+    let container = uncompressed_container(b"model");
+    fs::write(dir.join("3").join("12"), b"interface").unwrap();
+    fs::write(dir.join("3").join("13").join("0"), b"layer").unwrap();
+    fs::write(dir.join("3").join("13").join("4"), b"button").unwrap();
+    fs::write(dir.join("3").join("13").join("notes.txt"), b"ignored").unwrap();
+    fs::write(dir.join("7").join("100.dat"), &container).unwrap();
+    fs::write(dir.join("7").join("notes.txt"), b"ignored").unwrap();
+
+    let groups = read_overlay(&dir).unwrap();
+    assert_eq!(groups.len(), 3);
+    assert_eq!(groups[&(3, 12)].container[0], 2, "raw groups are gzipped");
+    assert_eq!(groups[&(3, 12)].files, None);
+    assert_eq!(groups[&(7, 100)].container, container);
+
+    let directory = &groups[&(3, 13)];
+    assert_eq!(directory.files, Some(vec![0, 4]));
+    assert_eq!(decompress(&directory.container), pack_group(&[b"layer".to_vec(), b"button".to_vec()]));
+
+    fs::create_dir_all(dir.join("3").join("14")).unwrap();
+    assert!(read_overlay(&dir).is_err(), "empty group directories are rejected");
+    fs::remove_dir(dir.join("3").join("14")).unwrap();
+
+    fs::write(dir.join("7").join("101.dat"), b"bad").unwrap();
+    assert!(read_overlay(&dir).is_err());
+
+    fs::remove_dir_all(&dir).unwrap();
+    assert!(read_overlay(&dir).unwrap().is_empty());
+}
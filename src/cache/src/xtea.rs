@@ -35,16 +35,28 @@ struct XTEAData {
     key: XTEAKey,
 }
 
+fn read_xtea_file() -> Result<Vec<XTEAData>, Box<dyn error::Error>> {
+    let mut file = File::open(format!("{}/xteaKeys.json", config::get().paths.cache))?;
+    let mut contents = String::new();
+    file.read_to_string(&mut contents)?;
+
+    Ok(serde_json::from_str(&contents)?)
+}
+
+/// Every key in `xteaKeys.json` by archive and group, for tools that walk the cache rather than the map.
+pub fn read_xtea_keys_by_group() -> Result<HashMap<(u8, u16), XTEAKey>, Box<dyn error::Error>> {
+    Ok(read_xtea_file()?
+        .into_iter()
+        .map(|data| ((data.archive as u8, data.group as u16), data.key))
+        .collect())
+}
+
 static XTEA_MAP: OnceLock<HashMap<i32, XTEAKey>> = OnceLock::new();
 pub fn initialize_xtea() -> Result<bool, Box<dyn error::Error>> {
     let start = Instant::now();
     info!("Initializing XTEA module.");
     
-    let mut file = File::open("../../src/cacheLocal/xteaKeys.json")?;
-    let mut contents = String::new();
-    file.read_to_string(&mut contents)?;
-    
-    let keys_list: Vec<XTEAData> = serde_json::from_str(&contents)?;
+    let keys_list = read_xtea_file()?;
     
     let mut map = HashMap::new();
     
@@ -0,0 +1,94 @@
+use std::collections::HashMap;
+use crate::{CacheBackend, ServerConfig};
+
+#[test]
+fn test_empty_config_uses_defaults() {
+    let config = ServerConfig::from_toml("").unwrap();
+    assert_eq!(config, ServerConfig::default());
+    assert_eq!(config.world.revision, 530);
+    assert_eq!(config.network.game, "127.0.0.1:40001");
+}
+
+#[test]
+fn test_partial_config_keeps_other_defaults() {
+    let config = ServerConfig::from_toml(r#"
+        [world]
+        id = 2
+        members = true
+
+        [network]
+        game = "0.0.0.0:40002"
+
+        [cache]
+        backend = "mmap"
+    "#).unwrap();
+
+    assert_eq!(config.world.id, 2);
+    assert!(config.world.members);
+    assert_eq!(config.world.tick_rate_ms, 600);
+    assert_eq!(config.network.game, "0.0.0.0:40002");
+    assert_eq!(config.network.js5, "127.0.0.1:43595");
+    assert_eq!(config.paths.data, "./data");
+    assert_eq!(config.cache.backend, CacheBackend::Mmap);
+    assert_eq!(config.cache.lru_capacity, 64 * 1024 * 1024);
+}
+
+#[test]
+fn test_invalid_config_is_an_error() {
+    assert!(ServerConfig::from_toml("[world]\nid = \"one\"").is_err());
+}
+
+#[test]
+fn test_env_overrides_file_values() {
+    let env: HashMap<&str, &str> = HashMap::from([
+        ("RT4_WORLD_ID", "3"),
+        ("RT4_MEMBERS", "true"),
+        ("RT4_JS5_ADDR", "127.0.0.1:53595"),
+        ("RT4_CACHE_PATH", "/srv/cache"),
+        ("RT4_MAX_PLAYERS", "lots"),
+        ("RT4_JS5_MAX_CONNECTIONS_PER_IP", "2"),
+        ("RT4_JAGGRAB_MAX_CONNECTIONS", "20"),
+        ("RT4_CACHE_BACKEND", "mmap"),
+    ]);
+
+    let mut config = ServerConfig::from_toml("[world]\nid = 2").unwrap();
+    config.apply_env(|key| env.get(key).map(|value| value.to_string()));
+
+    assert_eq!(config.world.id, 3);
+    assert!(config.world.members);
+    assert_eq!(config.network.js5, "127.0.0.1:53595");
+    assert_eq!(config.paths.cache, "/srv/cache");
+    assert_eq!(config.js5.max_connections_per_ip, 2);
+    assert_eq!(config.js5.max_connections, 1000);
+    assert_eq!(config.jaggrab.max_connections, 20);
+    assert_eq!(config.jaggrab.max_connections_per_ip, 5);
+    assert_eq!(config.cache.backend, CacheBackend::Mmap);
+    // Values that don't parse are ignored.
+    assert_eq!(config.world.max_players, 2048);
+}
+
+#[test]
+fn test_compiler_config() {
+    let mut config = ServerConfig::from_toml(r#"
+        [compiler]
+        offline = true
+        sha256 = "ABC123"
+    "#).unwrap();
+
+    assert!(config.compiler.compile);
+    assert!(config.compiler.offline);
+    assert_eq!(config.compiler.jar, "./RuneScriptCompiler.jar");
+    assert_eq!(config.compiler.neptune, "./neptune.toml");
+
+    let env: HashMap<&str, &str> = HashMap::from([
+        ("RT4_COMPILER_COMPILE", "false"),
+        ("RT4_COMPILER_JAR", "/opt/rsc/RuneScriptCompiler.jar"),
+        ("RT4_JAVA", "/usr/lib/jvm/bin/java"),
+    ]);
+    config.apply_env(|key| env.get(key).map(|value| value.to_string()));
+
+    assert!(!config.compiler.compile);
+    assert_eq!(config.compiler.jar, "/opt/rsc/RuneScriptCompiler.jar");
+    assert_eq!(config.compiler.java, "/usr/lib/jvm/bin/java");
+    assert_eq!(config.compiler.sha256, "ABC123");
+}
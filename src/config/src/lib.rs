@@ -0,0 +1,295 @@
+use std::path::Path;
+use std::str::FromStr;
+use std::sync::OnceLock;
+use log::{info, warn};
+use serde::Deserialize;
+
+#[cfg(test)]
+mod config_tests;
+
+/// Environment variable pointing at the config file, defaults to [`DEFAULT_CONFIG_PATH`].
+pub const CONFIG_PATH_VAR: &str = "RT4_CONFIG";
+pub const DEFAULT_CONFIG_PATH: &str = "server.toml";
+
+/// Settings shared by the engine, js5, jaggrab, proxy and worldlist binaries.
+///
+/// Read from a TOML file, anything missing falls back to the defaults, and every value can be
+/// overridden through an `RT4_*` environment variable so several worlds can run side by side.
+#[derive(Debug, Clone, PartialEq, Deserialize, Default)]
+#[serde(default)]
+pub struct ServerConfig {
+    pub world: WorldConfig,
+    pub network: NetworkConfig,
+    pub js5: Js5Config,
+    pub jaggrab: JaggrabConfig,
+    pub cache: CacheConfig,
+    pub paths: PathConfig,
+    pub compiler: CompilerConfig,
+}
+
+#[derive(Debug, Clone, PartialEq, Deserialize)]
+#[serde(default)]
+pub struct WorldConfig {
+    pub id: u16,
+    pub members: bool,
+    /// Client revision accepted on login and js5.
+    pub revision: i32,
+    pub max_players: usize,
+    pub tick_rate_ms: u64,
+}
+
+impl Default for WorldConfig {
+    fn default() -> Self {
+        Self {
+            id: 1,
+            members: false,
+            revision: 530,
+            max_players: 2048,
+            tick_rate_ms: 600,
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Deserialize)]
+#[serde(default)]
+pub struct NetworkConfig {
+    pub game: String,
+    pub proxy: String,
+    pub js5: String,
+    pub worldlist: String,
+    pub jaggrab: String,
+    /// Host handed out to clients in the world list.
+    pub public_host: String,
+}
+
+impl Default for NetworkConfig {
+    fn default() -> Self {
+        Self {
+            game: "127.0.0.1:40001".to_string(),
+            proxy: "127.0.0.1:40000".to_string(),
+            js5: "127.0.0.1:43595".to_string(),
+            worldlist: "127.0.0.1:43596".to_string(),
+            jaggrab: "127.0.0.1:443".to_string(),
+            public_host: "localhost".to_string(),
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Deserialize)]
+#[serde(default)]
+pub struct Js5Config {
+    pub max_connections: usize,
+    /// Loopback connections don't count towards this, they all come through the proxy.
+    pub max_connections_per_ip: usize,
+    /// Urgent and prefetch requests a connection may have waiting, each, before it is dropped.
+    pub max_queued_requests: usize,
+}
+
+impl Default for Js5Config {
+    fn default() -> Self {
+        Self {
+            max_connections: 1000,
+            max_connections_per_ip: 10,
+            max_queued_requests: 200,
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Deserialize)]
+#[serde(default)]
+pub struct JaggrabConfig {
+    pub max_connections: usize,
+    /// Loopback connections don't count towards this, like on js5.
+    pub max_connections_per_ip: usize,
+}
+
+impl Default for JaggrabConfig {
+    fn default() -> Self {
+        Self {
+            max_connections: 100,
+            max_connections_per_ip: 5,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum CacheBackend {
+    /// Reads every group into memory on startup.
+    Preload,
+    /// Maps the cache files and reads groups on first use, keeping the hot ones in an LRU.
+    Mmap,
+}
+
+impl FromStr for CacheBackend {
+    type Err = String;
+
+    fn from_str(value: &str) -> Result<Self, Self::Err> {
+        match value {
+            "preload" => Ok(CacheBackend::Preload),
+            "mmap" => Ok(CacheBackend::Mmap),
+            _ => Err(format!("Unknown cache backend {}", value)),
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Deserialize)]
+#[serde(default)]
+pub struct CacheConfig {
+    pub backend: CacheBackend,
+    /// Bytes of groups the mmap backend keeps around, unused by preload.
+    pub lru_capacity: usize,
+}
+
+impl Default for CacheConfig {
+    fn default() -> Self {
+        Self {
+            backend: CacheBackend::Preload,
+            lru_capacity: 64 * 1024 * 1024,
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Deserialize)]
+#[serde(default)]
+pub struct PathConfig {
+    /// Root of the content, `src/` for sources and `pack/` for the compiled output.
+    pub data: String,
+    pub cache: String,
+    /// Login private key written by `engine keygen`, the built-in key is used when it's missing.
+    pub rsa_key: String,
+    /// Loader jars and other files the JAGGRAB server hands out by name.
+    pub loader: String,
+    /// Replacement and new groups served next to the cache, `<archive>/<group>[.dat]` or
+    /// `<archive>/<group>/<file>`.
+    pub overlay: String,
+}
+
+impl Default for PathConfig {
+    fn default() -> Self {
+        Self {
+            data: "./data".to_string(),
+            cache: "../../src/cacheLocal".to_string(),
+            rsa_key: "./data/config/private.key".to_string(),
+            loader: "./data/loader".to_string(),
+            overlay: "./data/overlay".to_string(),
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Deserialize)]
+#[serde(default)]
+pub struct CompilerConfig {
+    /// Compile the scripts on startup, the world doesn't start when that fails. Off loads whatever
+    /// is already in `pack/server`.
+    pub compile: bool,
+    /// Never contact GitHub, the jar must already be at `jar` and match `sha256`.
+    pub offline: bool,
+    pub jar: String,
+    /// Hex sha256 the jar is pinned to, required offline.
+    pub sha256: String,
+    /// The compiler config naming the sources, symbols and output, the compiler runs from its
+    /// directory.
+    pub neptune: String,
+    pub java: String,
+}
+
+impl Default for CompilerConfig {
+    fn default() -> Self {
+        Self {
+            compile: true,
+            offline: false,
+            jar: "./RuneScriptCompiler.jar".to_string(),
+            sha256: String::new(),
+            neptune: "./neptune.toml".to_string(),
+            java: "java".to_string(),
+        }
+    }
+}
+
+impl ServerConfig {
+    pub fn from_toml(contents: &str) -> Result<ServerConfig, String> {
+        toml::from_str(contents).map_err(|e| format!("Invalid config: {}", e))
+    }
+
+    /// Reads `path` if it exists, then applies the environment on top.
+    pub fn load(path: &str) -> Result<ServerConfig, String> {
+        let mut config = if Path::new(path).exists() {
+            let contents = std::fs::read_to_string(path)
+                .map_err(|e| format!("Failed to read config {}: {}", path, e))?;
+            Self::from_toml(&contents)?
+        } else {
+            info!("No config found at {}, using defaults.", path);
+            ServerConfig::default()
+        };
+
+        config.apply_env(|key| std::env::var(key).ok());
+        Ok(config)
+    }
+
+    /// Overrides values from `lookup`, normally the process environment.
+    pub fn apply_env<F: Fn(&str) -> Option<String>>(&mut self, lookup: F) {
+        override_value(&lookup, "RT4_WORLD_ID", &mut self.world.id);
+        override_value(&lookup, "RT4_MEMBERS", &mut self.world.members);
+        override_value(&lookup, "RT4_REVISION", &mut self.world.revision);
+        override_value(&lookup, "RT4_MAX_PLAYERS", &mut self.world.max_players);
+        override_value(&lookup, "RT4_TICK_RATE_MS", &mut self.world.tick_rate_ms);
+
+        override_value(&lookup, "RT4_GAME_ADDR", &mut self.network.game);
+        override_value(&lookup, "RT4_PROXY_ADDR", &mut self.network.proxy);
+        override_value(&lookup, "RT4_JS5_ADDR", &mut self.network.js5);
+        override_value(&lookup, "RT4_WORLDLIST_ADDR", &mut self.network.worldlist);
+        override_value(&lookup, "RT4_JAGGRAB_ADDR", &mut self.network.jaggrab);
+        override_value(&lookup, "RT4_PUBLIC_HOST", &mut self.network.public_host);
+
+        override_value(&lookup, "RT4_JS5_MAX_CONNECTIONS", &mut self.js5.max_connections);
+        override_value(&lookup, "RT4_JS5_MAX_CONNECTIONS_PER_IP", &mut self.js5.max_connections_per_ip);
+        override_value(&lookup, "RT4_JS5_MAX_QUEUED_REQUESTS", &mut self.js5.max_queued_requests);
+
+        override_value(&lookup, "RT4_JAGGRAB_MAX_CONNECTIONS", &mut self.jaggrab.max_connections);
+        override_value(&lookup, "RT4_JAGGRAB_MAX_CONNECTIONS_PER_IP", &mut self.jaggrab.max_connections_per_ip);
+
+        override_value(&lookup, "RT4_CACHE_BACKEND", &mut self.cache.backend);
+        override_value(&lookup, "RT4_CACHE_LRU_CAPACITY", &mut self.cache.lru_capacity);
+
+        override_value(&lookup, "RT4_DATA_PATH", &mut self.paths.data);
+        override_value(&lookup, "RT4_CACHE_PATH", &mut self.paths.cache);
+        override_value(&lookup, "RT4_RSA_KEY_PATH", &mut self.paths.rsa_key);
+        override_value(&lookup, "RT4_LOADER_PATH", &mut self.paths.loader);
+        override_value(&lookup, "RT4_OVERLAY_PATH", &mut self.paths.overlay);
+
+        override_value(&lookup, "RT4_COMPILER_COMPILE", &mut self.compiler.compile);
+        override_value(&lookup, "RT4_COMPILER_OFFLINE", &mut self.compiler.offline);
+        override_value(&lookup, "RT4_COMPILER_JAR", &mut self.compiler.jar);
+        override_value(&lookup, "RT4_COMPILER_SHA256", &mut self.compiler.sha256);
+        override_value(&lookup, "RT4_COMPILER_NEPTUNE", &mut self.compiler.neptune);
+        override_value(&lookup, "RT4_JAVA", &mut self.compiler.java);
+    }
+}
+
+fn override_value<F: Fn(&str) -> Option<String>, T: std::str::FromStr>(lookup: &F, key: &str, value: &mut T) {
+    if let Some(raw) = lookup(key) {
+        match raw.parse() {
+            Ok(parsed) => *value = parsed,
+            Err(_) => warn!("Ignoring {}={:?}, not a valid value.", key, raw),
+        }
+    }
+}
+
+static CONFIG: OnceLock<ServerConfig> = OnceLock::new();
+
+/// Loads the config for this process, binaries call this once before anything else reads it.
+pub fn init() -> Result<&'static ServerConfig, String> {
+    let path = std::env::var(CONFIG_PATH_VAR).unwrap_or_else(|_| DEFAULT_CONFIG_PATH.to_string());
+    let config = ServerConfig::load(&path)?;
+    Ok(CONFIG.get_or_init(|| config))
+}
+
+/// The process config, falls back to the defaults and environment when [`init`] was never called.
+pub fn get() -> &'static ServerConfig {
+    CONFIG.get_or_init(|| {
+        let mut config = ServerConfig::default();
+        config.apply_env(|key| std::env::var(key).ok());
+        config
+    })
+}
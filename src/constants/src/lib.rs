@@ -1,7 +1,6 @@
 pub mod js5_in;
 pub mod js5_out;
 pub mod title_protocol;
-pub mod server_addresses;
 pub mod proxy;
 pub mod login_out;
 pub mod rsa;
@@ -1,28 +1,41 @@
 use std::net::{IpAddr, TcpListener};
-use std::sync::{Arc, Mutex, Once};
-use std::thread;
+use std::sync::{Arc, Mutex};
 use std::thread::sleep;
 use std::time::{Duration, Instant};
 use log::{debug, error, info};
+use rand::rngs::StdRng;
+use rand::{Rng, SeedableRng};
+use tokio::runtime::Runtime;
 use cache::file_handler::{ensure_initialized, get_checksum};
 use cache::xtea::{initialize_xtea, XTEAKey};
 use constants::window_mode::window_mode;
 use constants::login_out::login_out;
 use constants::title_protocol::title_protocol;
-use crate::io::client_state::ConnectionState;
+use crate::io::connection::Connection;
 use crate::io::rsa::rsa;
 use crate::engine_stat::engine_stat;
 use crate::entity::entity::EntityBehavior;
+use crate::entity::entity_queue_request::EntityQueueState;
 use crate::entity::entity_list::{NPCList, PlayerList};
-use crate::entity::player::Player;
+use crate::entity::interface_tree::ModalType;
+use crate::entity::player::{Player, ScriptRequest};
 use crate::entity::window_status::WindowStatus;
 use crate::game_connection::GameClient;
 use crate::grid::coord_grid::CoordGrid;
 use crate::io::packet::Packet;
+use crate::script::script_pointer::ScriptPointer;
 use crate::script::script_provider::ScriptProvider;
+use crate::script::script_runner::ScriptRunner;
+use crate::script::script_state::ScriptState;
 use crate::util::base37::decode37;
+use crate::util::cache::config::enum_type::EnumTypeProvider;
+use crate::util::cache::config::loc_type::LocTypeProvider;
+use crate::util::cache::config::param_type::ParamTypeProvider;
+use crate::util::cache::config::seq_type::SeqTypeProvider;
+use crate::util::cache::config::spot_anim_type::SpotAnimTypeProvider;
+use crate::util::cache::config::struct_type::StructTypeProvider;
 use crate::util::pack_file::revalidate_pack;
-use crate::util::runescript_compiler::update_compiler;
+use crate::util::runescript_compiler::{compile_scripts, prepare_compiler};
 use crate::util::symbols::generate_server_symbols;
 
 pub struct Engine {
@@ -36,13 +49,16 @@ pub struct Engine {
     pub players: PlayerList,
     pub npcs: NPCList,
     pub new_players: Arc<Mutex<Vec<Player>>>,
+    /// Scripts suspended on the world, see [`Engine::enqueue_script`].
+    pub world_queue: Vec<EntityQueueState>,
+    /// Every random roll in the world goes through here, so a seeded engine plays out the same way every time.
+    pub rng: StdRng,
+    /// Runs the game socket tasks, only started once the engine listens.
+    runtime: Option<Runtime>,
     // TODO - game_map
     // TODO - zone_tracking
 }
 
-static mut ENGINE: Option<Engine> = None;
-static INIT: Once = Once::new();
-
 impl Engine {
     const MAX_PLAYERS: usize = 2048;
     const MAX_NPCS: usize = 8192;
@@ -53,55 +69,53 @@ impl Engine {
     const AFK_EVENTRATE: i32 = 500;
     
     const INVALID_PID: usize = 5000;
+
+    /// How long a client may take to send each part of the title handshake before it is dropped.
+    const HANDSHAKE_TIMEOUT: Duration = Duration::from_secs(30);
     
-    // We don't need safety, we're smart
-    pub fn init() {
-        INIT.call_once(|| {
-            info!("Initializing global engine instance.");
-            unsafe {
-                ENGINE = Some(Engine::new());
-            }
-        })
+    pub fn new() -> Engine {
+        Self::with_rng(StdRng::from_os_rng())
     }
 
-    pub fn get() -> &'static mut Engine {
-        unsafe {
-            match &mut ENGINE {
-                Some(engine) => engine,
-                None => {
-                    // Auto-initialize if needed;
-                    Self::init();
-                    ENGINE.as_mut().unwrap()
-                }
-            }
-        }
+    /// An engine whose random rolls are reproducible, for simulations and tests.
+    pub fn with_seed(seed: u64) -> Engine {
+        Self::with_rng(StdRng::seed_from_u64(seed))
     }
 
-    pub fn current_tick() -> i32 {
-        Self::get().current_tick
-    }
+    fn with_rng(rng: StdRng) -> Engine {
+        let config = config::get();
 
-    pub fn new() -> Engine {
         Engine {
-            members: false,
+            members: config.world.members,
             current_tick: 0,
-            tick_rate: Duration::from_millis(600),
+            tick_rate: Duration::from_millis(config.world.tick_rate_ms),
             cycle_stats: vec![Duration::new(0, 0); 12],
             last_cycle_stats: vec![Duration::new(0, 0); 12],
-            players: PlayerList::new(Engine::MAX_PLAYERS - 1),
+            // Pids are sent as 11 bits, the config can only lower the limit.
+            players: PlayerList::new(config.world.max_players.clamp(1, Engine::MAX_PLAYERS) - 1),
             npcs: NPCList::new(Engine::MAX_NPCS - 1),
             new_players: Default::default(),
+            world_queue: Vec::new(),
+            rng,
+            runtime: None,
         }
     }
 
-    pub fn start(&mut self, start_cycle: bool) {
-        if let Err(e) = update_compiler() {
-            error!("Failed to update compiler: {}", e);
-        }
-
+    /// Loads everything the world needs, then accepts game connections from `listener` if one is given.
+    /// Fails when the world can't run, e.g. when the scripts don't compile.
+    pub fn start(&mut self, listener: Option<TcpListener>, start_cycle: bool) -> Result<(), String> {
         revalidate_pack();
         generate_server_symbols();
 
+        // A world without scripts does nothing on login or any trigger, so a failed compile stops
+        // startup. Turning compile off runs the last script.dat instead.
+        let compiler = &config::get().compiler;
+        if compiler.compile {
+            prepare_compiler(compiler)
+                .and_then(|()| compile_scripts(compiler))
+                .map_err(|e| format!("Scripts failed to compile:\n{}", e))?;
+        }
+
         if let Err(e) = ensure_initialized() {
             error!("Failed to initialize cache: {}", e);
         } else {
@@ -115,48 +129,80 @@ impl Engine {
         }
 
         ScriptProvider::load();
+        LocTypeProvider::load();
+        EnumTypeProvider::load();
+        StructTypeProvider::load();
+        ParamTypeProvider::load();
+        SeqTypeProvider::load();
+        SpotAnimTypeProvider::load();
+
+        // Running with a key the clients weren't patched for would reject every login
+        rsa::load_key(&config::get().paths.rsa_key).map_err(|e| format!("Failed to load RSA key: {}", e))?;
+
+        if let Some(listener) = listener {
+            self.listen(listener);
+        }
+
+        // TODO - load map
+        info!("World ready!");
+        if start_cycle {
+            self.cycle();
+        }
+        Ok(())
+    }
+
+    /// Accepts game connections on `listener`, logins are handed over to the tick through `new_players`.
+    pub fn listen(&mut self, listener: TcpListener) {
+        match listener.local_addr() {
+            Ok(addr) => info!("Starting server on {}", addr),
+            Err(e) => error!("Failed to read listener address: {}", e),
+        }
+
+        if let Err(e) = listener.set_nonblocking(true) {
+            error!("Failed to make listener nonblocking: {}", e);
+            return;
+        }
+
+        if self.runtime.is_none() {
+            match Runtime::new() {
+                Ok(runtime) => self.runtime = Some(runtime),
+                Err(e) => {
+                    error!("Failed to start network runtime: {}", e);
+                    return;
+                }
+            }
+        }
 
-        info!("Starting server on port 40001");
-        let listen_addr = "127.0.0.1:40001";
         let thread_new_players = Arc::clone(&self.new_players);
-        
-        thread::spawn(move || {
-            match TcpListener::bind(listen_addr) {
-                Ok(listener) => {
-                    for stream in listener.incoming() {
-                        match stream {
-                            Ok(stream) => {
-                                let thread_player = Arc::clone(&thread_new_players);
-
-                                thread::spawn(move || {
-                                    let mut game_client = GameClient::new(stream);
-
-                                    loop {
-                                        if game_client.state == ConnectionState::New && game_client.is_connection_active() {
-                                            Self::on_new_connection(&mut game_client, thread_player.clone());
-                                        } else {
-                                            break
-                                        }
-                                    }
-                                });
-                            }
-                            Err(e) => {
-                                error!("Connection failed: {}", e);
+        let mut listener_rng = StdRng::seed_from_u64(self.rng.random());
+
+        self.runtime.as_ref().unwrap().spawn(async move {
+            let listener = match tokio::net::TcpListener::from_std(listener) {
+                Ok(listener) => listener,
+                Err(e) => {
+                    error!("Failed to register listener: {}", e);
+                    return;
+                }
+            };
+
+            loop {
+                match listener.accept().await {
+                    Ok((stream, addr)) => {
+                        let thread_player = Arc::clone(&thread_new_players);
+                        let mut connection_rng = StdRng::seed_from_u64(listener_rng.random());
+
+                        tokio::spawn(async move {
+                            if let Err(e) = Self::on_new_connection(Connection::new(stream), thread_player, &mut connection_rng).await {
+                                debug!("Connection from {} dropped during login: {}", addr, e);
                             }
-                        }
+                        });
+                    }
+                    Err(e) => {
+                        error!("Connection failed: {}", e);
                     }
-                },
-                Err(e) => {
-                    error!("Failed to bind to {}: {}", listen_addr, e);
                 }
             }
         });
-
-        // TODO - load map
-        info!("World ready!");
-        if start_cycle {
-            self.cycle();
-        }
     }
 
     #[rustfmt::skip]
@@ -169,29 +215,14 @@ impl Engine {
         let mut next_tick_time = Instant::now();
 
         loop {
-            let start = Instant::now();
-            
-            self.process_world();
-            self.process_in();
-            self.process_npcs();
-            self.process_players();
-            self.process_logouts();
-            self.process_logins();
-            self.process_zones();
-            self.process_info();
-            self.process_out();
-            self.process_cleanup();
-
-            let elapsed = start.elapsed();
-            self.cycle_stats[engine_stat::CYCLE] = elapsed;
-            std::mem::swap(&mut self.cycle_stats, &mut self.last_cycle_stats);
+            let elapsed = self.tick();
 
             aggregate_duration += elapsed;
             min_duration = min_duration.min(elapsed);
             max_duration = max_duration.max(elapsed);
             tick_count += 1;
 
-            if self.current_tick % 10 == 9 {
+            if (self.current_tick - 1) % 10 == 9 {
                 let avg_duration = if tick_count > 0 {
                     aggregate_duration / tick_count as u32
                 } else {
@@ -200,8 +231,8 @@ impl Engine {
 
                 info!(
                     "Ticks: {} to {} | Total: {:?} | Avg: {:?} | Min: {:?} | Max: {:?}",
-                    self.current_tick - tick_count + 1,
-                    self.current_tick + 1,
+                    self.current_tick - tick_count,
+                    self.current_tick,
                     aggregate_duration,
                     avg_duration,
                     min_duration,
@@ -214,8 +245,6 @@ impl Engine {
                 tick_count = 0;
             }
 
-            self.current_tick += 1;
-
             next_tick_time += self.tick_rate;
         
             let now = Instant::now();
@@ -229,13 +258,58 @@ impl Engine {
             }
         }
     }
+
+    /// Processes a single game tick, returns how long it took.
+    #[rustfmt::skip]
+    pub fn tick(&mut self) -> Duration {
+        let start = Instant::now();
+
+        self.process_world();
+        self.process_in();
+        self.process_npcs();
+        self.process_players();
+        self.process_logouts();
+        self.process_logins();
+        self.process_zones();
+        self.process_info();
+        self.process_out();
+        self.process_cleanup();
+
+        let elapsed = start.elapsed();
+        self.cycle_stats[engine_stat::CYCLE] = elapsed;
+        std::mem::swap(&mut self.cycle_stats, &mut self.last_cycle_stats);
+
+        self.current_tick += 1;
+
+        elapsed
+    }
     
     /// - World Queue
     /// - NPC Spawn script
     /// - NPC Hunt
     fn process_world(&mut self) {
         let start: Instant = Instant::now();
-        // TODO
+
+        let queue = std::mem::take(&mut self.world_queue);
+        let mut waiting = Vec::new();
+        for mut request in queue {
+            let delay = request.delay;
+            request.delay -= 1;
+
+            if delay > 0 {
+                waiting.push(request);
+                continue;
+            }
+
+            self.execute_script(request.script_state);
+        }
+
+        // Scripts queued while running these count this tick down too, so a world_delay(0) resumes next tick.
+        for request in self.world_queue.iter_mut() {
+            request.delay -= 1;
+        }
+        waiting.append(&mut self.world_queue);
+        self.world_queue = waiting;
 
         // NPC [ai_spawn] scripts
         // NPC hunt players if not busy
@@ -259,14 +333,20 @@ impl Engine {
         // TODO - separate out stat?
         //self.cycle_stats[EngineStat::BandwidthIn as usize] = 0;
 
+        let mut decoded = Vec::new();
         self.players.for_each_mut(|player| {
             player.playtime += 1;
 
-            if player.is_client_connected() && player.decode_in(self.current_tick) {
-
+            // Not connected players are skipped inside, fake clients are decoded too.
+            if player.decode_in(self.current_tick) {
+                decoded.push(player.get_pid());
             }
         });
 
+        for pid in decoded {
+            self.process_script_requests(pid);
+        }
+
         // TODO - client input tracking
 
         // TODO - process pathfinding/following
@@ -286,6 +366,25 @@ impl Engine {
     /// Modes
     fn process_npcs(&mut self) {
         let start: Instant = Instant::now();
+
+        let current_tick = self.current_tick;
+        let mut resumed = Vec::new();
+        self.npcs.for_each_mut(|npc| {
+            if npc.delayed() && current_tick >= npc.pathing_entity.delayed_until {
+                npc.pathing_entity.delayed = false;
+            }
+
+            if !npc.delayed() && npc.active_script.as_ref().is_some_and(|script| script.execution == ScriptState::NPC_SUSPENDED) {
+                if let Some(script) = npc.active_script.take() {
+                    resumed.push(*script);
+                }
+            }
+        });
+
+        for script in resumed {
+            self.execute_script(script);
+        }
+
         // TODO
         self.cycle_stats[engine_stat::NPCS] = start.elapsed();
     }
@@ -310,6 +409,22 @@ impl Engine {
     fn process_players(&mut self) {
         let start: Instant = Instant::now();
 
+        let current_tick = self.current_tick;
+        let mut resumed = Vec::new();
+        self.players.for_each_mut(|player| {
+            if player.delayed() && current_tick >= player.pathing_entity.delayed_until {
+                player.pathing_entity.delayed = false;
+            }
+
+            if !player.delayed() && player.active_script.as_ref().is_some_and(|script| script.execution == ScriptState::SUSPENDED) {
+                resumed.push(player.get_pid());
+            }
+        });
+
+        for pid in resumed {
+            self.resume_player_script(pid, ScriptState::SUSPENDED, None);
+        }
+
         self.cycle_stats[engine_stat::PLAYERS] = start.elapsed();
     }
     
@@ -390,18 +505,25 @@ impl Engine {
 
             match self.get_next_pid(Some(&player.client)) {
                 Ok(pid) => {
-                    player.client.write_packet().expect("Failed to write packet to new connection");
+                    if player.is_client_connected() {
+                        if let Err(e) = player.client.write_packet() {
+                            debug!("Failed to write login response: {}", e);
+                        }
+                    }
                     player.set_pid(pid);
                     self.players.set(pid, player).expect("Failed to set player!");
 
                     if let Some(player_ref) = self.players.get_mut(pid) {
                         player_ref.on_login();
                     }
+                    self.process_script_requests(pid);
                 },
                 Err(_err) => {
                     player.client.outbound = Packet::new(1);
                     player.client.outbound.p1(login_out::WORLD_FULL);
-                    player.client.write_packet().expect("Failed to write packet to new connection");
+                    if let Err(e) = player.client.write_packet() {
+                        debug!("Failed to write world full response: {}", e);
+                    }
                     player.client.shutdown();
                 }
             };
@@ -478,17 +600,143 @@ impl Engine {
         // TODO
         
         // Reset players
-        //for (pid, player) in &self.players {
-            //let _: Player = player.borrow().player;
-            // TODO
-        //}
+        self.players.for_each_mut(|player| player.pathing_entity.reset_masks());
         // Reset npcs
-        // TODO
+        self.npcs.for_each_mut(|npc| npc.pathing_entity.reset_masks());
         // Reset inventories
         // TODO
         self.cycle_stats[engine_stat::CLEANUP] = start.elapsed();
     }
 
+    /// Queues a script on the world, it resumes during [`Engine::process_world`] once `delay` ticks have passed.
+    pub fn enqueue_script(&mut self, script: ScriptState, delay: i32) {
+        self.world_queue.push(EntityQueueState::new(script, delay + 1));
+    }
+
+    /// Runs a script that isn't owned by a player.
+    pub fn execute_script(&mut self, mut script: ScriptState) {
+        let state = ScriptRunner::execute(&mut script, self, false, false);
+
+        if state != ScriptState::FINISHED && state != ScriptState::ABORTED {
+            self.suspend_script(script, state);
+        }
+    }
+
+    /// Runs a script for the player in `pid`, `protect` asks for protected access which is refused while the player is busy.
+    pub fn execute_player_script(&mut self, pid: usize, script: ScriptState, protect: bool, force: bool) {
+        self.run_player_script(pid, script, protect, force, false);
+    }
+
+    /// Continues the player's suspended script if it is waiting on `execution`, `value` is pushed for it to pick up.
+    pub fn resume_player_script(&mut self, pid: usize, execution: i32, value: Option<i32>) -> bool {
+        let Some(player) = self.players.get_mut(pid) else {
+            return false;
+        };
+
+        if !player.active_script.as_ref().is_some_and(|script| script.execution == execution) {
+            return false;
+        }
+
+        let Some(mut script) = player.active_script.take() else {
+            return false;
+        };
+
+        if let Some(value) = value {
+            script.push_int(value);
+        }
+
+        // Forced, the suspended script still holds whatever protected access it had.
+        let protect = player.protect;
+        self.run_player_script(pid, *script, protect, true, true);
+        true
+    }
+
+    /// Runs whatever scripts the player asked for while its packets were decoded.
+    fn process_script_requests(&mut self, pid: usize) {
+        let Some(player) = self.players.get_mut(pid) else {
+            return;
+        };
+
+        for request in std::mem::take(&mut player.script_requests) {
+            match request {
+                ScriptRequest::Execute { script, protect } => self.execute_player_script(pid, script, protect, false),
+                ScriptRequest::Resume { execution, value } => {
+                    self.resume_player_script(pid, execution, value);
+                }
+            }
+        }
+    }
+
+    fn run_player_script(&mut self, pid: usize, mut script: ScriptState, protect: bool, force: bool, resumed: bool) {
+        let Some(player) = self.players.get_mut(pid) else {
+            return;
+        };
+
+        if !force && protect && (player.protect || player.delayed()) {
+            debug!("Cannot get protected access for script: {}", script.script.name());
+            return;
+        }
+
+        if protect {
+            script.pointer_add(ScriptPointer::ProtectedActivePlayer);
+            player.protect = true;
+        }
+
+        let state = ScriptRunner::execute(&mut script, self, false, false);
+
+        // Protected access is only held while running, it's granted again when the script resumes.
+        if script.pointer_get(ScriptPointer::ProtectedActivePlayer) {
+            script.pointer_remove(ScriptPointer::ProtectedActivePlayer);
+        }
+
+        if script.pointer_get(ScriptPointer::ProtectedActivePlayer2) {
+            script.pointer_remove(ScriptPointer::ProtectedActivePlayer2);
+            if let Some(player) = script.active_player2.and_then(|pid| self.players.get_mut(pid)) {
+                player.protect = false;
+            }
+        }
+
+        let Some(player) = self.players.get_mut(pid) else {
+            return;
+        };
+
+        if protect {
+            player.protect = false;
+        }
+
+        if state != ScriptState::FINISHED && state != ScriptState::ABORTED {
+            if state == ScriptState::WORLD_SUSPENDED || state == ScriptState::NPC_SUSPENDED {
+                self.suspend_script(script, state);
+            } else {
+                player.active_script = Some(Box::new(script));
+                player.protect = protect;
+            }
+        } else if resumed {
+            // Dialogues close themselves once the script driving them is done.
+            if player.interfaces.get_modal(ModalType::Main).is_none() {
+                player.close_modal();
+            }
+        }
+    }
+
+    /// Parks a suspended script on whatever it is waiting on, the world, its npc or its player.
+    pub fn suspend_script(&mut self, mut script: ScriptState, state: i32) {
+        if state == ScriptState::WORLD_SUSPENDED {
+            let delay = script.pop_int();
+            self.enqueue_script(script, delay);
+        } else if state == ScriptState::NPC_SUSPENDED {
+            match script.active_npc.and_then(|nid| self.npcs.get_mut(nid)) {
+                Some(npc) => npc.active_script = Some(Box::new(script)),
+                None => debug!("Dropping suspended script {} with no active npc", script.script.name()),
+            }
+        } else {
+            match script.active_player.and_then(|pid| self.players.get_mut(pid)) {
+                Some(player) => player.active_script = Some(Box::new(script)),
+                None => debug!("Dropping suspended script {} with no active player", script.script.name()),
+            }
+        }
+    }
+
     #[inline]
     pub fn remove_player(&mut self, pid: usize) {
         if let Some(player_ref) = self.players.get_mut(pid) {
@@ -500,127 +748,127 @@ impl Engine {
         self.players.remove(pid);
     }
 
-    fn on_new_connection(client: &mut GameClient, thread_player: Arc<Mutex<Vec<Player>>>) {
-        
-        if let Err(err) = client.read_packet_with_size(1) {
-            error!("Failed to read packet from client: {}", err);
-            client.shutdown();
-            return
-        }
-
-        client.opcode = client.inbound().g1();
-        if client.opcode == title_protocol::INIT_GAME_CONNECTION {
-            client.read_packet_with_size(1).unwrap();
-
-            // Used to load-balance.
-            let _username_hash = client.inbound().g1();
-            client.outbound.p1(0);
-
-            // Server session key for this connection, used in decrypting return values.
-            let session_key: u64 = ((rand::random::<f64>() * 99999999.0) as u64) << 32 | ((rand::random::<f64>() * 99999999.0) as u64);
-            client.outbound.p8(session_key as i64);
-            client.write_packet().expect("Failed to write packet to new connection");
-        }  else if client.opcode == title_protocol::RECONNECT || client.opcode == title_protocol::LOGIN {
-            // RECONNECT & LOGIN packet length is variable, length indicated by 'short' after opcode.
-            client.read_packet_with_size(2).unwrap();
-            let payload_length = client.inbound.g2();
-            client.read_packet_with_size(payload_length as usize).unwrap();
-
-            let client_revision = client.inbound.g4();
-            if client_revision != 530 {
-                client.outbound.p1(login_out::CLIENT_OUT_OF_DATE);
-                client.write_packet().expect("Failed to write packet to new connection");
-                client.shutdown();
-                return
+    /// Runs the title handshake for a freshly accepted socket, once logged in the socket is handed
+    /// over to a [GameClient] and the player queued for the next tick.
+    async fn on_new_connection(mut client: Connection, thread_player: Arc<Mutex<Vec<Player>>>, rng: &mut StdRng) -> std::io::Result<()> {
+        loop {
+            Self::read_handshake(&mut client, 1).await?;
+
+            let opcode = client.inbound().g1();
+            if opcode == title_protocol::INIT_GAME_CONNECTION {
+                Self::read_handshake(&mut client, 1).await?;
+
+                // Used to load-balance.
+                let _username_hash = client.inbound().g1();
+                client.outbound.p1(0);
+
+                // Server session key for this connection, used in decrypting return values.
+                let session_key: u64 = ((rng.random::<f64>() * 99999999.0) as u64) << 32 | ((rng.random::<f64>() * 99999999.0) as u64);
+                client.outbound.p8(session_key as i64);
+                client.write_packet().await?;
+            } else if opcode == title_protocol::RECONNECT || opcode == title_protocol::LOGIN {
+                return Self::on_login_request(client, opcode, thread_player).await;
+            } else {
+                debug!("Invalid opcode from initial connection: [{}]", opcode);
+                return Self::reject(client, login_out::INVALID_LOGIN_PACKET).await;
             }
+        }
+    }
 
-            // Data here is unknown. 
-            // Populated through client script opcode [5600]. 
-            let bytes1 = client.inbound().g1b();
-            
-            let adverts_suppressed = client.inbound().g1b();
-            let client_signed = client.inbound().g1b();
-            
-            // Window status block
-            let window_mode = window_mode::from_i8(client.inbound.g1b());
-            let canvas_width = client.inbound().g2()  as u32;
-            let canvas_height = client.inbound().g2()  as u32;
-            let anti_aliasing_mode = client.inbound().g1b() as u32;
-            let window_status: WindowStatus = WindowStatus::new(window_mode, canvas_width, canvas_height, anti_aliasing_mode);
-            
-            let uuid = client.inbound().gbytes(24);
-            let site_settings_cookie = client.inbound().gjstr();
-            let affiliate_id = client.inbound().g4();
-            let detail_options = client.inbound().g4();
-            let verify_id = client.inbound().g2();
-            
-            for i in 0..28 {
-                let checksum = client.inbound().g4() as u32;
-                if checksum != get_checksum(i).expect("Failed to get checksum for archive") {
-                    client.outbound.p1(login_out::CLIENT_OUT_OF_DATE);
-                    client.write_packet().expect("Failed to write packet to new connection");
-                    client.shutdown();
-                    return
-                }
-            }
+    /// Reads the next `size` bytes of the handshake, a client that stops sending is dropped
+    /// instead of holding on to its task.
+    async fn read_handshake(client: &mut Connection, size: usize) -> std::io::Result<usize> {
+        tokio::time::timeout(Self::HANDSHAKE_TIMEOUT, client.read_packet_with_size(size))
+            .await
+            .map_err(|_| std::io::Error::new(std::io::ErrorKind::TimedOut, "Handshake timed out"))?
+    }
 
-            let rsa_block_length = client.inbound().g1();
-            let mut rsa_packet_decrypted = rsa::decrypt_rsa_block(client.inbound.clone(), rsa_block_length as usize);
+    async fn on_login_request(mut client: Connection, opcode: u8, thread_player: Arc<Mutex<Vec<Player>>>) -> std::io::Result<()> {
+        // RECONNECT & LOGIN packet length is variable, length indicated by 'short' after opcode.
+        Self::read_handshake(&mut client, 2).await?;
+        let payload_length = client.inbound.g2();
+        Self::read_handshake(&mut client, payload_length as usize).await?;
 
-            let rsa_verification = rsa_packet_decrypted.g1();
-            if rsa_verification != 10 {
-                debug!("RSA verification failed, received value: {}", rsa_verification);
-                client.outbound.p1(login_out::INVALID_LOGIN_PACKET);
-                client.write_packet().expect("Failed to write packet to new connection");
-                client.shutdown();
-                return
+        let client_revision = client.inbound.g4();
+        if client_revision != config::get().world.revision {
+            return Self::reject(client, login_out::CLIENT_OUT_OF_DATE).await;
+        }
+
+        // Data here is unknown. 
+        // Populated through client script opcode [5600]. 
+        let bytes1 = client.inbound().g1b();
+        
+        let adverts_suppressed = client.inbound().g1b();
+        let client_signed = client.inbound().g1b();
+        
+        // Window status block
+        let window_mode = window_mode::from_i8(client.inbound.g1b());
+        let canvas_width = client.inbound().g2()  as u32;
+        let canvas_height = client.inbound().g2()  as u32;
+        let anti_aliasing_mode = client.inbound().g1b() as u32;
+        let window_status: WindowStatus = WindowStatus::new(window_mode, canvas_width, canvas_height, anti_aliasing_mode);
+        
+        let uuid = client.inbound().gbytes(24);
+        let site_settings_cookie = client.inbound().gjstr();
+        let affiliate_id = client.inbound().g4();
+        let detail_options = client.inbound().g4();
+        let verify_id = client.inbound().g2();
+        
+        for i in 0..28 {
+            let checksum = client.inbound().g4() as u32;
+            if checksum != get_checksum(i).expect("Failed to get checksum for archive") {
+                return Self::reject(client, login_out::CLIENT_OUT_OF_DATE).await;
             }
+        }
 
-            // Sent on login, however it has no function in revision 530.
-            let _xtea_key = XTEAKey(
-                rsa_packet_decrypted.g4() + 50,
-                rsa_packet_decrypted.g4() + 50,
-                rsa_packet_decrypted.g4() + 50, 
-                rsa_packet_decrypted.g4() + 50
-            );
-            
-            let username = decode37(rsa_packet_decrypted.g8());
-            let password = rsa_packet_decrypted.gjstr();
-
-            if client.opcode == title_protocol::RECONNECT {
-                client.outbound.p1(login_out::RECONNECT_OK);
-            } else if client.opcode == title_protocol::LOGIN {
-                client.outbound.p1(login_out::OK);
+        let rsa_block_length = client.inbound().g1();
+        let mut rsa_packet_decrypted = match rsa::decrypt_login_block(client.inbound.clone(), rsa_block_length as usize) {
+            Ok(packet) => packet,
+            Err(e) => {
+                debug!("RSA verification failed: {}", e);
+                return Self::reject(client, login_out::INVALID_LOGIN_PACKET).await;
             }
+        };
 
-            client.opcode = 0;
-            client.state = ConnectionState::Connected;
-
-            let mut new_client = Some(std::mem::replace(
-                client,
-                GameClient::new_dummy()
-            ));
-            
-            let player = Player::new(
-                &mut new_client, 
-                CoordGrid::from(3200, 0, 3200), 
-                0, 
-                window_status, 
-                0, 
-                Self::INVALID_PID,
-                verify_id,
-                username
-            );
-
-            let mut players_lock = thread_player.lock().unwrap();
-            players_lock.push(player);
-        } else {
-            debug!("Invalid opcode from initial connection: [{}]", client.opcode);
-            client.outbound.p1(login_out::INVALID_LOGIN_PACKET);
-            client.write_packet().expect("Failed to write packet to new connection");
-            client.shutdown();
-            return
+        // Sent on login, however it has no function in revision 530.
+        let _xtea_key = XTEAKey(
+            rsa_packet_decrypted.g4() + 50,
+            rsa_packet_decrypted.g4() + 50,
+            rsa_packet_decrypted.g4() + 50, 
+            rsa_packet_decrypted.g4() + 50
+        );
+        
+        let username = decode37(rsa_packet_decrypted.g8());
+        let password = rsa_packet_decrypted.gjstr();
+
+        let mut game_client = GameClient::spawn(client.stream)?;
+        if opcode == title_protocol::RECONNECT {
+            game_client.outbound.p1(login_out::RECONNECT_OK);
+        } else if opcode == title_protocol::LOGIN {
+            game_client.outbound.p1(login_out::OK);
         }
+
+        let player = Player::new(
+            &mut Some(game_client), 
+            CoordGrid::from(3200, 0, 3200), 
+            0, 
+            window_status, 
+            0, 
+            Self::INVALID_PID,
+            verify_id,
+            username
+        );
+
+        let mut players_lock = thread_player.lock().unwrap();
+        players_lock.push(player);
+        Ok(())
+    }
+
+    /// Answers a login attempt with `response` and closes the socket.
+    async fn reject(mut client: Connection, response: i32) -> std::io::Result<()> {
+        client.outbound.p1(response);
+        client.write_packet().await?;
+        client.shutdown().await
     }
 
     fn get_next_pid(&self, client: Option<&GameClient>) -> Result<usize, &'static str>  {
@@ -630,12 +878,7 @@ impl Engine {
         };
         
         let default = || self.players.next(false, None);
-        let stream = match &client.stream {
-            Some(s) => s,
-            None => return default(),
-        };
-
-        let peer_addr = match stream.peer_addr() {
+        let peer_addr = match client.peer_addr() {
             Ok(addr) => addr,
             Err(_) => return default(),
         };
@@ -0,0 +1,26 @@
+use crate::engine::Engine;
+use crate::entity::player::Player;
+use crate::grid::coord_grid::CoordGrid;
+
+#[test]
+fn test_engines_are_independent() {
+    let mut first = Engine::new();
+    let second = Engine::new();
+
+    first.tick();
+    first.tick();
+
+    assert_eq!(first.current_tick, 2);
+    assert_eq!(second.current_tick, 0);
+}
+
+#[test]
+fn test_tick_logs_in_new_players() {
+    let mut engine = Engine::new();
+    engine.new_players.lock().unwrap().push(Player::new_dummy(CoordGrid { coord: 0 }, 0, 0));
+
+    engine.tick();
+
+    assert_eq!(engine.players.count(), 1);
+    assert!(engine.new_players.lock().unwrap().is_empty());
+}
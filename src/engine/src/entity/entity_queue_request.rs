@@ -21,6 +21,7 @@ pub enum QueueType {
     Player(PlayerQueueType),
 }
 
+#[derive(Debug, Clone, PartialEq)]
 pub enum ScriptArgument {
     Number(i32),
     String(String),
@@ -1,12 +1,10 @@
-use crate::entity::loc::Loc;
-use crate::entity::npc::NPC;
-use crate::entity::obj::Obj;
-use crate::entity::player::Player;
-
-#[derive(Clone, PartialEq)]
+/// Handle to an entity in the world, resolved through the engine whenever it's needed.
+///
+/// Players and npcs are referenced by their slot (pid / nid), locs and objs by their uid.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
 pub enum EntityType {
-    Player(Player),
-    NPC(NPC),
-    Loc(Loc),
-    Obj(Obj)
+    Player(usize),
+    NPC(usize),
+    Loc(u64),
+    Obj(u64)
 }
\ No newline at end of file
@@ -0,0 +1,104 @@
+use std::collections::HashMap;
+
+/// Fixed size top level window.
+pub const FIXED_WINDOW: u32 = 548;
+/// Resizable top level window.
+pub const RESIZABLE_WINDOW: u32 = 746;
+/// Chatbox window, opened as a sub of the top level window.
+pub const CHATBOX_WINDOW: u32 = 752;
+
+/// Component of the top level window that holds the main modal.
+const FIXED_MAIN_COMPONENT: u32 = 11;
+const RESIZABLE_MAIN_COMPONENT: u32 = 6;
+/// Component of the chatbox window that holds the chat modal.
+const CHATBOX_MODAL_COMPONENT: u32 = 12;
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum ModalType {
+    Main,
+    Chat,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct SubInterface {
+    pub interface_id: u32,
+    pub flags: u32,
+    pub modal: Option<ModalType>,
+}
+
+/// Server side view of the interfaces a player has open, keyed the same way the client keys them
+/// (`window << 16 | component`).
+#[derive(Debug, Clone, PartialEq, Default)]
+pub struct InterfaceTree {
+    top: Option<u32>,
+    subs: HashMap<u32, SubInterface>,
+}
+
+impl InterfaceTree {
+    pub fn new() -> InterfaceTree {
+        InterfaceTree {
+            top: None,
+            subs: HashMap::new(),
+        }
+    }
+
+    #[inline]
+    pub fn pointer(window_id: u32, component_id: u32) -> u32 {
+        window_id << 16 | component_id
+    }
+
+    pub fn top(&self) -> Option<u32> {
+        self.top
+    }
+
+    /// Replaces the top level window, every sub that was open is discarded.
+    pub fn open_top(&mut self, interface_id: u32) {
+        self.top = Some(interface_id);
+        self.subs.clear();
+    }
+
+    /// Opens `interface_id` inside `window_id:component_id`, returns what was open there before.
+    pub fn open_sub(&mut self, window_id: u32, component_id: u32, interface_id: u32, flags: u32, modal: Option<ModalType>) -> Option<SubInterface> {
+        let previous = self.close_sub(window_id, component_id);
+        self.subs.insert(Self::pointer(window_id, component_id), SubInterface { interface_id, flags, modal });
+        previous
+    }
+
+    /// Closes whatever is open inside `window_id:component_id`, including anything nested in it.
+    pub fn close_sub(&mut self, window_id: u32, component_id: u32) -> Option<SubInterface> {
+        let closed = self.subs.remove(&Self::pointer(window_id, component_id))?;
+
+        let nested: Vec<u32> = self.subs.keys()
+            .filter(|pointer| *pointer >> 16 == closed.interface_id)
+            .copied()
+            .collect();
+
+        for pointer in nested {
+            self.close_sub(pointer >> 16, pointer & 0xFFFF);
+        }
+
+        Some(closed)
+    }
+
+    pub fn is_open(&self, interface_id: u32) -> bool {
+        self.top == Some(interface_id) || self.subs.values().any(|sub| sub.interface_id == interface_id)
+    }
+
+    /// Where a modal of the given type is opened, depends on the current top level window.
+    pub fn modal_pointer(&self, modal: ModalType) -> (u32, u32) {
+        match modal {
+            ModalType::Main => match self.top {
+                Some(RESIZABLE_WINDOW) => (RESIZABLE_WINDOW, RESIZABLE_MAIN_COMPONENT),
+                _ => (FIXED_WINDOW, FIXED_MAIN_COMPONENT),
+            },
+            ModalType::Chat => (CHATBOX_WINDOW, CHATBOX_MODAL_COMPONENT),
+        }
+    }
+
+    pub fn get_modal(&self, modal: ModalType) -> Option<u32> {
+        let (window_id, component_id) = self.modal_pointer(modal);
+        self.subs.get(&Self::pointer(window_id, component_id))
+            .filter(|sub| sub.modal == Some(modal))
+            .map(|sub| sub.interface_id)
+    }
+}
@@ -0,0 +1,103 @@
+use crate::entity::interface_tree::{InterfaceTree, ModalType, SubInterface, CHATBOX_WINDOW, FIXED_WINDOW, RESIZABLE_WINDOW};
+
+const BANK: u32 = 762;
+const BANK_SIDE: u32 = 763;
+const SHOP: u32 = 620;
+
+/// A fixed window with the chatbox and an inventory tab open.
+fn tree() -> InterfaceTree {
+    let mut tree = InterfaceTree::new();
+    tree.open_top(FIXED_WINDOW);
+    tree.open_sub(FIXED_WINDOW, 75, CHATBOX_WINDOW, 1, None);
+    tree.open_sub(FIXED_WINDOW, 83, 149, 1, None);
+    tree
+}
+
+fn open_modal(tree: &mut InterfaceTree, modal: ModalType, interface_id: u32) -> Option<SubInterface> {
+    let (window_id, component_id) = tree.modal_pointer(modal);
+    tree.open_sub(window_id, component_id, interface_id, 0, Some(modal))
+}
+
+#[test]
+fn test_open_top() {
+    let mut tree = tree();
+    assert_eq!(tree.top(), Some(FIXED_WINDOW));
+    assert!(tree.is_open(149));
+
+    // Switching to resizable starts over, subs belong to the old window.
+    tree.open_top(RESIZABLE_WINDOW);
+    assert_eq!(tree.top(), Some(RESIZABLE_WINDOW));
+    assert!(tree.is_open(RESIZABLE_WINDOW));
+    assert!(!tree.is_open(FIXED_WINDOW));
+    assert!(!tree.is_open(149));
+    assert_eq!(tree.modal_pointer(ModalType::Main), (RESIZABLE_WINDOW, 6));
+}
+
+#[test]
+fn test_open_sub() {
+    let mut tree = tree();
+
+    assert_eq!(tree.open_sub(FIXED_WINDOW, 86, 387, 1, None), None);
+    assert!(tree.is_open(387));
+
+    // The component already holds a sub, it's handed back as it's replaced.
+    let previous = tree.open_sub(FIXED_WINDOW, 83, 150, 1, None);
+    assert_eq!(previous, Some(SubInterface { interface_id: 149, flags: 1, modal: None }));
+    assert!(tree.is_open(150));
+    assert!(!tree.is_open(149));
+}
+
+#[test]
+fn test_close_sub() {
+    let mut tree = tree();
+
+    assert_eq!(tree.close_sub(FIXED_WINDOW, 83), Some(SubInterface { interface_id: 149, flags: 1, modal: None }));
+    assert!(!tree.is_open(149));
+    assert_eq!(tree.close_sub(FIXED_WINDOW, 83), None);
+    assert!(tree.is_open(CHATBOX_WINDOW));
+}
+
+#[test]
+fn test_replacing_an_open_modal() {
+    let mut tree = tree();
+
+    assert_eq!(open_modal(&mut tree, ModalType::Main, BANK), None);
+    tree.open_sub(BANK, 3, BANK_SIDE, 1, None);
+    assert_eq!(tree.get_modal(ModalType::Main), Some(BANK));
+
+    let previous = open_modal(&mut tree, ModalType::Main, SHOP);
+    assert_eq!(previous.map(|sub| sub.interface_id), Some(BANK));
+    assert_eq!(tree.get_modal(ModalType::Main), Some(SHOP));
+    assert!(!tree.is_open(BANK));
+    assert!(!tree.is_open(BANK_SIDE));
+
+    // Only subs opened as a modal count as one.
+    tree.open_sub(FIXED_WINDOW, 11, BANK, 0, None);
+    assert_eq!(tree.get_modal(ModalType::Main), None);
+    assert_eq!(tree.get_modal(ModalType::Chat), None);
+}
+
+#[test]
+fn test_closing_a_parent_closes_its_children() {
+    let mut tree = tree();
+    open_modal(&mut tree, ModalType::Chat, 241);
+    assert_eq!(tree.get_modal(ModalType::Chat), Some(241));
+
+    open_modal(&mut tree, ModalType::Main, BANK);
+    tree.open_sub(BANK, 3, BANK_SIDE, 1, None);
+    tree.open_sub(BANK_SIDE, 0, 665, 1, None);
+
+    // The chatbox goes with everything nested in it, the bank is unrelated.
+    tree.close_sub(FIXED_WINDOW, 75);
+    assert!(!tree.is_open(CHATBOX_WINDOW));
+    assert!(!tree.is_open(241));
+    assert_eq!(tree.get_modal(ModalType::Chat), None);
+    assert!(tree.is_open(BANK_SIDE));
+
+    let (window_id, component_id) = tree.modal_pointer(ModalType::Main);
+    tree.close_sub(window_id, component_id);
+    for interface_id in [BANK, BANK_SIDE, 665] {
+        assert!(!tree.is_open(interface_id), "{} is still open", interface_id);
+    }
+    assert!(tree.is_open(149));
+}
@@ -1,6 +1,9 @@
+use crate::entity::entity::EntityBehavior;
 use crate::entity::entity_lifecycle::EntityLifeCycle;
+use crate::entity::entity_type::EntityType;
 use crate::entity::non_pathing_entity::NonPathingEntity;
 use crate::grid::coord_grid::CoordGrid;
+use crate::util::cache::config::loc_type::{LocType, LocTypeProvider};
 
 #[derive(Clone, PartialEq)]
 pub struct Loc {
@@ -25,21 +28,41 @@ impl Loc {
                 length,
                 lifecycle,
             ),
-            info: ((id & 0x3fff) as u32)
-                | (((shape & 0x1f) as u32) << 14)
-                | (((angle & 0x1f) as u32) << 19)
+            info: (id as u32)
+                | (((shape & 0x1f) as u32) << 16)
+                | (((angle & 0x3) as u32) << 21)
         }
     }
     
+    /// A loc sized by its type, turned by `angle`.
+    pub fn from_type(coord: CoordGrid, loc_type: &LocType, lifecycle: EntityLifeCycle, shape: u8, angle: u8) -> Loc {
+        let (width, length) = loc_type.size(angle);
+        Loc::new(coord, width, length, lifecycle, loc_type.id as u16, shape, angle)
+    }
+
+    /// Unique for every loc in the world, a coord only holds one loc per layer.
+    pub fn uid(&self) -> u64 {
+        (self.entity.entity.coord().coord as u64) << 32 | self.info as u64
+    }
+
+    pub fn as_entity_type(&self) -> EntityType {
+        EntityType::Loc(self.uid())
+    }
+
     pub fn id(&self) -> u16 {
-        (self.info & 0x3ff) as u16
+        (self.info & 0xffff) as u16
+    }
+    
+    /// The type of this loc, `None` until the loc types are loaded.
+    pub fn loc_type(&self) -> Option<&'static LocType> {
+        LocTypeProvider::get(self.id())
     }
     
     pub fn shape(&self) -> u8 {
-        ((self.info >> 14) & 0x1f) as u8
+        ((self.info >> 16) & 0x1f) as u8
     }
     
     pub fn angle(&self) -> u8 {
-        ((self.info >> 19) & 0x3) as u8
+        ((self.info >> 21) & 0x3) as u8
     }
 }
\ No newline at end of file
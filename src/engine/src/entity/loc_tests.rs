@@ -0,0 +1,22 @@
+use crate::entity::entity_lifecycle::EntityLifeCycle;
+use crate::entity::loc::Loc;
+use crate::grid::coord_grid::CoordGrid;
+
+#[test]
+fn test_info_round_trips() {
+    let coord = CoordGrid::from(3222, 0, 3222);
+
+    for (id, shape, angle) in [(0, 0, 0), (16383, 10, 1), (16384, 22, 3), (40000, 31, 2), (u16::MAX, 31, 3)] {
+        let loc = Loc::new(coord, 1, 1, EntityLifeCycle::FOREVER, id, shape, angle);
+        assert_eq!((loc.id(), loc.shape(), loc.angle()), (id, shape, angle));
+    }
+}
+
+#[test]
+fn test_uid_keeps_high_ids_apart() {
+    let coord = CoordGrid::from(3222, 0, 3222);
+    let low = Loc::new(coord, 1, 1, EntityLifeCycle::FOREVER, 1, 10, 0);
+    let high = Loc::new(coord, 1, 1, EntityLifeCycle::FOREVER, 16385, 10, 0);
+
+    assert_ne!(low.uid(), high.uid());
+}
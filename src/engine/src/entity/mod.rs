@@ -2,11 +2,13 @@ pub mod block_walk;
 pub mod entity;
 pub mod entity_lifecycle;
 pub mod loc;
+mod loc_tests;
 pub mod move_restrict;
 pub mod move_speed;
 pub mod move_strategy;
 pub mod npc;
 pub mod obj;
+mod obj_tests;
 pub mod player;
 
 mod hunt;
@@ -22,8 +24,10 @@ pub mod window_status;
 mod npc_event_request;
 pub mod entity_queue_request;
 mod non_pathing_entity;
-mod pathing_entity;
+pub mod pathing_entity;
 pub mod entity_type;
+pub mod interface_tree;
+mod interface_tree_tests;
 mod player_type;
 mod level_experience;
 mod entity_timer;
\ No newline at end of file
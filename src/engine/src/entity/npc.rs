@@ -6,6 +6,7 @@ use crate::entity::move_restrict::MoveRestrict;
 use crate::entity::move_strategy::MoveStrategy;
 use crate::entity::pathing_entity::PathingEntity;
 use crate::grid::coord_grid::CoordGrid;
+use crate::script::script_state::ScriptState;
 
 #[derive(Clone, PartialEq)]
 pub struct NPC {
@@ -15,6 +16,7 @@ pub struct NPC {
     pub move_strategy: MoveStrategy,
     pub nid: i32,
     pub id: u16, // Cache 'ID'
+    pub active_script: Option<Box<ScriptState>>,
 }
 
 impl NPC {
@@ -31,6 +33,7 @@ impl NPC {
             move_strategy: MoveStrategy::Naive,
             nid,
             id,
+            active_script: None,
         }
     }
     
@@ -38,7 +41,11 @@ impl NPC {
         &self.pathing_entity.entity
     }
     
-    pub fn into_entity_type(self) -> EntityType {
-        EntityType::NPC(self)
+    pub fn delayed(&self) -> bool {
+        self.pathing_entity.delayed
+    }
+
+    pub fn as_entity_type(&self) -> EntityType {
+        EntityType::NPC(self.nid as usize)
     }
 }
\ No newline at end of file
@@ -1,11 +1,18 @@
-use crate::entity::entity::Entity;
+use std::sync::atomic::{AtomicU16, Ordering};
+use crate::entity::entity::{Entity, EntityBehavior};
 use crate::entity::entity_lifecycle::EntityLifeCycle;
+use crate::entity::entity_type::EntityType;
 use crate::grid::coord_grid::CoordGrid;
 
+/// Handed out to every obj as it's created, wraps around after 65536 spawns.
+static NEXT_STACK: AtomicU16 = AtomicU16::new(0);
+
 #[derive(Clone, PartialEq)]
 pub struct Obj {
     pub entity: Entity,
     pub id: u16,
+    /// Tells apart stacks of the same type on the same coord, e.g. two untradeable drops.
+    stack: u16,
     pub count: u32,
     pub receiver_id: i32,
     pub reveal: i32,
@@ -25,10 +32,20 @@ impl Obj {
                 lifecycle,
             ),
             id,
+            stack: NEXT_STACK.fetch_add(1, Ordering::Relaxed),
             count,
             receiver_id: -1,
             reveal: -1,
             last_change: -1,
         }
     }
+
+    /// Identifies this stack, unique as long as a tile doesn't see 65536 spawns while it lies there.
+    pub fn uid(&self) -> u64 {
+        (self.entity.coord().coord as u64) << 32 | (self.stack as u64) << 16 | self.id as u64
+    }
+
+    pub fn as_entity_type(&self) -> EntityType {
+        EntityType::Obj(self.uid())
+    }
 }
\ No newline at end of file
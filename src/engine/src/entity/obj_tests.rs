@@ -0,0 +1,18 @@
+use crate::entity::entity_lifecycle::EntityLifeCycle;
+use crate::entity::obj::Obj;
+use crate::grid::coord_grid::CoordGrid;
+
+#[test]
+fn test_same_type_stacks_on_one_tile_have_their_own_uid() {
+    let coord = CoordGrid::from(3222, 0, 3222);
+    let first = Obj::new(coord, EntityLifeCycle::DESPAWN, 995, 10);
+    let second = Obj::new(coord, EntityLifeCycle::DESPAWN, 995, 10);
+
+    assert_ne!(first.uid(), second.uid());
+    assert_eq!(first.uid(), first.clone().uid());
+
+    for obj in [&first, &second] {
+        assert_eq!((obj.uid() >> 32) as u32, coord.coord);
+        assert_eq!(obj.uid() as u16, 995);
+    }
+}
@@ -7,6 +7,7 @@ use crate::entity::move_strategy::MoveStrategy;
 use crate::entity::npc_mode::NpcMode;
 use crate::grid::coord_grid::CoordGrid;
 use crate::script::server_trigger_types::ServerTriggerTypes;
+use crate::util::cache::config::seq_type::SeqType;
 
 pub struct TargetSubject {
     pub type_: u32,
@@ -32,6 +33,17 @@ pub struct PathingEntity {
     move_speed: MoveSpeed,
     pub(crate) delayed: bool,
     pub(crate) delayed_until: i32,
+
+    // Update blocks, cleared at the end of every cycle. There is no player or npc info encoder
+    // yet, so nothing sends these to the client.
+    pub(crate) masks: u32,
+    pub(crate) anim_id: i32,
+    pub(crate) anim_delay: i32,
+    anim_priority: u8,
+    pub(crate) spotanim_id: i32,
+    pub(crate) spotanim_height: i32,
+    pub(crate) spotanim_delay: i32,
+    spotanim_priority: u8,
 }
 
 impl PathingEntity {
@@ -41,6 +53,59 @@ impl PathingEntity {
             move_speed: MoveSpeed::INSTANT,
             delayed: false,
             delayed_until: -1,
+            masks: 0,
+            anim_id: -1,
+            anim_delay: 0,
+            anim_priority: 0,
+            spotanim_id: -1,
+            spotanim_height: 0,
+            spotanim_delay: 0,
+            spotanim_priority: 0,
+        }
+    }
+
+    pub const MASK_ANIM: u32 = 0x1;
+    pub const MASK_SPOTANIM: u32 = 0x2;
+
+    /// Queues `seq` for the anim update block after `delay` client cycles, `None` stops the
+    /// current anim. An anim queued earlier this cycle is only replaced by a stop or by a seq of
+    /// at least its priority, the same rule the client applies to the anim it is playing.
+    pub fn anim(&mut self, seq: Option<&SeqType>, delay: i32) -> bool {
+        if let Some(seq) = seq {
+            if self.masks & Self::MASK_ANIM != 0 && self.anim_id != -1 && seq.priority < self.anim_priority {
+                return false;
+            }
+        }
+
+        self.anim_id = seq.map_or(-1, |seq| seq.id as i32);
+        self.anim_priority = seq.map_or(0, |seq| seq.priority);
+        self.anim_delay = delay;
+        self.masks |= Self::MASK_ANIM;
+        true
+    }
+
+    /// Queues spotanim `id` for the spotanim update block, `seq` is the seq it plays. A spotanim
+    /// queued earlier this cycle is only replaced by one whose seq has at least its priority.
+    pub fn spotanim(&mut self, id: i32, seq: Option<&SeqType>, height: i32, delay: i32) -> bool {
+        let priority = seq.map_or(0, |seq| seq.priority);
+        if self.masks & Self::MASK_SPOTANIM != 0 && priority < self.spotanim_priority {
+            return false;
         }
+
+        self.spotanim_id = id;
+        self.spotanim_priority = priority;
+        self.spotanim_height = height;
+        self.spotanim_delay = delay;
+        self.masks |= Self::MASK_SPOTANIM;
+        true
+    }
+
+    /// Clears the update blocks once they have been sent.
+    pub fn reset_masks(&mut self) {
+        self.masks = 0;
+        self.anim_id = -1;
+        self.anim_priority = 0;
+        self.spotanim_id = -1;
+        self.spotanim_priority = 0;
     }
 }
\ No newline at end of file
@@ -1,5 +1,4 @@
 use std::cmp::PartialEq;
-use std::error::Error;
 use std::time::Instant;
 use crate::entity::block_walk::BlockWalk;
 use crate::entity::entity::{Entity, EntityBehavior};
@@ -9,25 +8,40 @@ use crate::entity::move_strategy::MoveStrategy;
 use crate::entity::window_status::WindowStatus;
 use crate::grid::coord_grid::CoordGrid;
 use constants::window_mode::window_mode;
-use log::{debug, error, trace};
+use log::debug;
+use crate::entity::entity_queue_request::ScriptArgument;
 use crate::entity::entity_type::EntityType;
+use crate::entity::interface_tree::{InterfaceTree, ModalType};
 use crate::entity::pathing_entity::PathingEntity;
 use crate::entity::player_type::PlayerType;
 use crate::game_connection::GameClient;
 use crate::io::client::protocol::client_protocol::get_protocol_by_id;
 use crate::io::client::protocol::client_protocol_category::ClientProtocolCategory;
 use crate::io::client::protocol::client_protocol_repository::{get_decoder, get_handler};
+use crate::io::packet::Packet;
+use crate::io::server::model::if_closesub::If_CloseSub;
 use crate::io::server::model::if_opensub::If_OpenSub;
 use crate::io::server::model::if_opentop::If_OpenTop;
 use crate::io::server::model::rebuild_normal::RebuildNormal;
+use crate::io::server::model::run_clientscript::RunClientScript;
 use crate::io::server::outgoing_message::{OutgoingMessage, OutgoingMessageEnum};
 use crate::io::server::protocol::server_protocol_priority::ServerProtocolPriority;
 use crate::io::server::protocol::server_protocol_repository::{ServerProtocolRepository, SERVER_PROTOCOL_REPOSITORY};
-use crate::script::script_pointer::ScriptPointer;
 use crate::script::script_provider::ScriptProvider;
 use crate::script::script_runner::ScriptRunner;
 use crate::script::script_state::ScriptState;
 use crate::script::server_trigger_types::ServerTriggerTypes;
+use crate::simulation::FakeClient;
+
+/// Client script that shows the chatbox number prompt, the client answers with RESUME_P_COUNTDIALOG.
+const COUNT_DIALOG_SCRIPT: i32 = 108;
+
+/// Script work picked up while decoding packets, the engine runs it once the player's packets are read.
+#[derive(Clone, PartialEq)]
+pub enum ScriptRequest {
+    Execute { script: ScriptState, protect: bool },
+    Resume { execution: i32, value: Option<i32> },
+}
 
 #[derive(Clone, PartialEq)]
 pub struct Player {
@@ -74,9 +88,20 @@ pub struct Player {
     pub last_response: i32,
     pub last_connected: i32,
     pub verify_id: u16,
+    /// Last verify id the client echoed back to us.
+    pub client_verify_id: u16,
+
+    pub interfaces: InterfaceTree,
+    pub last_com: i32,
+    pub last_slot: i32,
+    pub last_int: i32,
     
     pub protect: bool,  // Whether protected access is available.
     pub active_script: Option<Box<ScriptState>>,
+    pub script_requests: Vec<ScriptRequest>,
+
+    /// Stands in for the socket in headless simulations, see [`crate::simulation::Simulation`].
+    pub fake_client: Option<FakeClient>,
 }
 impl Player {
     pub fn new(client: &mut Option<GameClient>, coord: CoordGrid, gender: u8, window_status: WindowStatus, staff_mod_level: i32, pid: usize, verify_id: u16, username: String) -> Player {
@@ -113,8 +138,15 @@ impl Player {
             last_response: -1,
             last_connected: -1,
             verify_id,
+            client_verify_id: 0,
+            interfaces: InterfaceTree::new(),
+            last_com: -1,
+            last_slot: -1,
+            last_int: -1,
             protect: false,
             active_script: None,
+            script_requests: Vec::new(),
+            fake_client: None,
         }
     }
     
@@ -152,8 +184,15 @@ impl Player {
             last_response: -1,
             last_connected: -1,
             verify_id: 0,
+            client_verify_id: 0,
+            interfaces: InterfaceTree::new(),
+            last_com: -1,
+            last_slot: -1,
+            last_int: -1,
             protect: false,
             active_script: None,
+            script_requests: Vec::new(),
+            fake_client: None,
         }
     }
 
@@ -170,8 +209,8 @@ impl Player {
         &self.pathing_entity.entity
     }
 
-    pub fn as_entity_type(self) -> EntityType {
-        EntityType::Player(self)
+    pub fn as_entity_type(&self) -> EntityType {
+        EntityType::Player(self.pid)
     }
     
     pub(crate) fn get_coord(&self) -> CoordGrid {
@@ -226,177 +265,107 @@ impl Player {
         self.pathing_entity.delayed
     }
     
-    pub fn run_script(&mut self, mut script: ScriptState, protected: Option<bool>, force: Option<bool>) -> Result<i32, Box<dyn Error>>{
-        let protect = protected.unwrap_or(false);
-        let force = force.unwrap_or(false);
-        
-        if !force && protect && (self.protect || self.delayed()) {
-            return Err(Box::new(std::io::Error::new(
-                std::io::ErrorKind::PermissionDenied,
-                format!("Cannot get protected access for script: {}", script.script.name())
-            )));
-        }
-        
-        if protect {
-            script.pointer_add(ScriptPointer::ProtectedActivePlayer);
-            self.protect = true;
-        }
-        
-        let state = ScriptRunner::execute(&mut script, false, false);
-        
-        if protect {
-            self.protect = false;
-        }
-        
-        if script.pointer_get(ScriptPointer::ProtectedActivePlayer) && script.active_player.is_some() {
-            script.pointer_remove(ScriptPointer::ProtectedActivePlayer);
-            if let Some(player) = script.active_player.as_mut() {
-                player.protect = false;
-            }
-        }
-        
-        if script.pointer_get(ScriptPointer::ProtectedActivePlayer2) && script.active_player2.is_some() {
-            script.pointer_remove(ScriptPointer::ProtectedActivePlayer2);
-            if let Some(player) = script.active_player2.as_mut() {
-                player.protect = false;
-            }
-        }
-
-        Ok(state)
+    /// Asks the engine to run `script` for this player once its packets have been read.
+    pub fn queue_script(&mut self, script: ScriptState, protect: bool) {
+        self.script_requests.push(ScriptRequest::Execute { script, protect });
     }
-    
-    pub fn execute_script(&mut self, script: ScriptState, protected: Option<bool>, force: Option<bool>) {
-        let protect = protected.unwrap_or(false);
-        let force = force.unwrap_or(false);
 
-        let mut script_clone = script.clone();
+    /// Asks the engine to resume the suspended script, only if it is waiting on `execution`.
+    pub fn queue_resume(&mut self, execution: i32, value: Option<i32>) -> bool {
+        if !self.active_script.as_ref().is_some_and(|script| script.execution == execution) {
+            return false;
+        }
 
-        let state = match self.run_script(script, Some(protect), Some(force)) {
-            Ok(state) => state,
-            Err(err) => {
-                debug!("Script execution failed: {}", err);
-                return;
-            }
-        };
+        self.script_requests.push(ScriptRequest::Resume { execution, value });
+        true
+    }
 
-        if state != ScriptState::FINISHED && state != ScriptState::ABORTED {
-            if state == ScriptState::WORLD_SUSPENDED {
-                // TODO - Engine.enqueueScript
-            } else if state == ScriptState::NPC_SUSPENDED {
+    /// Drops a script waiting on player input, the modal it was waiting on is going away.
+    fn cancel_suspended_script(&mut self) {
+        let waiting = self.active_script.as_ref().is_some_and(|script| {
+            script.execution == ScriptState::PAUSEBUTTON || script.execution == ScriptState::COUNTDIALOG
+        });
 
-            } else {
-                if let Some(mut player) = script_clone.active_player.take() {
-                    let boxed_script = Box::new(script_clone.clone());
-                    player.active_script = Some(boxed_script);
-                    player.protect = protect;
-                    script_clone.active_player = Some(player);
-                }
-            }
-        } else if self.active_script.as_ref().map_or(false, |active_script| &script_clone == active_script.as_ref()) {
+        if waiting {
             self.active_script = None;
-            // TODO - close modal crap goes here
+            self.protect = false;
         }
     }
 
-    const MAX_PACKET_SIZE: i32 = 20000;
+    pub fn open_top(&mut self, interface_id: u32) {
+        self.interfaces.open_top(interface_id);
 
-    #[inline(always)]
-    fn read(&mut self) -> bool {
-        // Early return with proper error handling for insufficient data
-        if !self.client.has_available(1).unwrap_or_else(|e| {
-            debug!("Error checking available data: {}", e);
-            false
-        }) {
-            return false;
+        let verify_id = self.get_incremented_verify_id();
+        self.write(If_OpenTop::new(interface_id, false, verify_id));
+    }
+
+    pub fn open_sub(&mut self, window_id: u32, component_id: u32, interface_id: u32, flags: u32) {
+        self.interfaces.open_sub(window_id, component_id, interface_id, flags, None);
+
+        let verify_id = self.get_incremented_verify_id();
+        self.write(If_OpenSub::new(window_id, component_id, interface_id, flags, verify_id));
+    }
+
+    pub fn close_sub(&mut self, window_id: u32, component_id: u32) {
+        if self.interfaces.close_sub(window_id, component_id).is_none() {
+            return;
         }
 
-        // Store the retrieved protocol to avoid duplicate lookups
-        let mut protocol = None;
+        let verify_id = self.get_incremented_verify_id();
+        self.write(If_CloseSub::new(window_id, component_id, verify_id));
+    }
 
-        // Read opcode if needed
-        if self.client.opcode == 0 {
-            // Read the packet opcode
-            if let Err(e) = self.client.read_packet_with_size(1) {
-                error!("Error reading packet opcode: {}", e);
-                self.client.shutdown();
-                return false;
-            }
+    /// Opens a modal, only one modal can be open at a time so any existing one is closed first.
+    pub fn open_modal(&mut self, modal: ModalType, interface_id: u32) {
+        self.close_modal();
 
-            if self.client.decryptor.is_some() {
-                // TODO - ISAAC stuff
-            } else {
-                self.client.opcode = self.client.inbound.g1();
-            }
+        let (window_id, component_id) = self.interfaces.modal_pointer(modal);
+        self.interfaces.open_sub(window_id, component_id, interface_id, 0, Some(modal));
 
-            // Get packet type using lookup - store the result to avoid duplicate lookups later
-            protocol = get_protocol_by_id(self.client.opcode as u32);
+        let verify_id = self.get_incremented_verify_id();
+        self.write(If_OpenSub::new(window_id, component_id, interface_id, 0, verify_id));
+    }
 
-            if let Some(pt) = &protocol {
-                self.client.waiting = pt.length;
-            } else {
-                if cfg!(debug_assertions) {
-                    debug!("Unknown packet type: {}", self.client.opcode);
-                }
-                self.client.opcode = 0;
-                self.client.shutdown();
-                return false;
-            }
-        }
+    pub fn close_modal(&mut self) {
+        self.cancel_suspended_script();
 
-        // Handle variable-length packets
-        if self.client.waiting == -1 {
-            if let Err(e) = self.client.read_packet_with_size(1) {
-                error!("Error reading packet size (byte): {}", e);
-                return false;
-            }
-            self.client.waiting = self.client.inbound.g1() as i32;
-        } else if self.client.waiting == -2 {
-            if let Err(e) = self.client.read_packet_with_size(2) {
-                error!("Error reading packet size (short): {}", e);
-                return false;
+        for modal in [ModalType::Main, ModalType::Chat] {
+            if self.interfaces.get_modal(modal).is_none() {
+                continue;
             }
-            self.client.waiting = self.client.inbound.g2() as i32;
 
-            // Security check - reject overly large packets
-            if self.client.waiting > Self::MAX_PACKET_SIZE {
-                debug!("Rejecting oversized packet of {} bytes", self.client.waiting);
-                self.client.shutdown();
-                return false;
-            }
+            let (window_id, component_id) = self.interfaces.modal_pointer(modal);
+            self.close_sub(window_id, component_id);
         }
+    }
 
-        // Check if we have enough data for full packet
-        if !self.client.has_available(self.client.waiting as usize).unwrap_or(false) {
-            trace!("Waiting for more data ({} bytes needed)", self.client.waiting);
-            return false;
-        }
+    /// Prompts the client for an amount, the script waiting on it is resumed with the answer.
+    pub fn open_count_dialog(&mut self, prompt: &str) {
+        let verify_id = self.get_incremented_verify_id();
+        self.write(RunClientScript::new(COUNT_DIALOG_SCRIPT, vec![ScriptArgument::String(prompt.to_string())], verify_id));
+    }
 
-        // Read the full packet
-        if let Err(e) = self.client.read_packet_with_size(self.client.waiting as usize) {
-            error!("Error reading packet body: {}", e);
-            self.client.opcode = 0;
+    #[inline(always)]
+    fn read(&mut self) -> bool {
+        // Packets are framed by the connection's reader task, anything not complete yet waits there.
+        let Some(packet) = self.client.poll_packet() else {
             return false;
-        }
+        };
 
-        // Use the cached protocol if we have it, otherwise look it up again 
-        // (this should only happen if we're continuing a packet read from a previous call)
-        let packet_type = if let Some(pt) = protocol {
-            pt
-        } else {
-            match get_protocol_by_id(self.client.opcode as u32) {
-                Some(pt) => pt,
-                None => {
-                    debug!("Packet type disappeared? Opcode: {}", self.client.opcode);
-                    self.client.opcode = 0;
-                    return false;
-                }
+        let packet_type = match get_protocol_by_id(packet.opcode as u32) {
+            Some(pt) => pt,
+            None => {
+                debug!("Packet type disappeared? Opcode: {}", packet.opcode);
+                return false;
             }
         };
 
+        let waiting_size = packet.data.len();
+        self.client.inbound = Packet::from(packet.data);
+
         // Process the packet with the appropriate decoder
         let mut processed = false;
         if let Some(decoder) = get_decoder(packet_type) {
-            let waiting_size = self.client.waiting as usize;
             let message = decoder.decode_erased(self.client.inbound(), waiting_size);
 
             // Process with handler if available
@@ -418,9 +387,8 @@ impl Player {
             }
         }
 
-        // Update read statistics and reset for next packet
-        self.bytes_read += self.client.inbound.position;
-        self.client.opcode = 0;
+        // Update read statistics
+        self.bytes_read += waiting_size + 1;
 
         true
     }
@@ -457,18 +425,12 @@ impl Player {
 
         let window_id = if self.window_status.window_mode.is_resizeable() { 746 } else { 548 };
 
-        // Get verification ID once and reuse
-        let mut verify_id = self.get_incremented_verify_id();
-
-        // Create and send top interface message
-        self.write(If_OpenTop::new(window_id, false, verify_id));
-
-        verify_id = self.get_incremented_verify_id();
-        self.write(If_OpenSub::new(window_id, 100, 662, 1, verify_id));
+        self.open_top(window_id);
+        self.open_sub(window_id, 100, 662, 1);
 
         if let Some(trigger) = ScriptProvider::get_by_trigger_specific(ServerTriggerTypes::LOGIN, -1, -1) {
-            let script = ScriptRunner::init(trigger, Some(self.clone().as_entity_type()), None, None);
-            self.execute_script(script, Some(true), None);
+            let script = ScriptRunner::init(trigger, Some(self.as_entity_type()), None, None);
+            self.queue_script(script, true);
         }
 
         self.set_active(true);
@@ -528,6 +490,10 @@ impl Player {
         self.user_path.clear();
         self.op_called = false;
 
+        if self.fake_client.is_some() {
+            return self.decode_fake(current_tick);
+        }
+
         if !self.is_client_connected() {
             return false;
         }
@@ -558,8 +524,55 @@ impl Player {
         true
     }
 
+    /// Same as reading from the socket, except the messages were already decoded by the test.
+    fn decode_fake(&mut self, current_tick: i32) -> bool {
+        // A fake client never drops, so it never times out either.
+        self.last_connected = current_tick;
+        self.last_response = current_tick;
+
+        self.user_limit = 0;
+        self.client_limit = 0;
+        self.restricted_limit = 0;
+
+        let max_user = ClientProtocolCategory::USER_EVENT.limit;
+        let max_client = ClientProtocolCategory::CLIENT_EVENT.limit;
+        let max_restricted = ClientProtocolCategory::RESTRICTED_EVENT.limit;
+
+        while self.user_limit < max_user &&
+            self.client_limit < max_client &&
+            self.restricted_limit < max_restricted {
+            let injected = match self.fake_client.as_mut().and_then(|fake| fake.inbound.pop_front()) {
+                Some(injected) => injected,
+                None => break,
+            };
+
+            let handler = match get_protocol_by_id(injected.protocol.0).and_then(get_handler) {
+                Some(handler) => handler,
+                None => {
+                    debug!("No handler for injected packet: {:?}", injected.protocol);
+                    continue;
+                }
+            };
+
+            if handler.handle_erased(&*injected.message, self) {
+                match injected.message.category() {
+                    ClientProtocolCategory::USER_EVENT => self.user_limit += 1,
+                    ClientProtocolCategory::RESTRICTED_EVENT => self.restricted_limit += 1,
+                    _ => self.client_limit += 1,
+                }
+            }
+        }
+
+        true
+    }
+
     #[inline(always)]
     pub fn write_inner(&mut self, message: OutgoingMessageEnum) {
+        if let Some(fake) = self.fake_client.as_mut() {
+            fake.outgoing.push(message);
+            return;
+        }
+
         if !self.is_client_connected() {
             return;
         }
@@ -586,6 +599,11 @@ impl Player {
 
     #[inline(always)]
     pub fn write<T: OutgoingMessage + Into<OutgoingMessageEnum>>(&mut self, message: T) {
+        if let Some(fake) = self.fake_client.as_mut() {
+            fake.outgoing.push(message.into());
+            return;
+        }
+
         if !self.is_client_connected() {
             return;
         }
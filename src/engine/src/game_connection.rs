@@ -1,14 +1,121 @@
 use std::io::ErrorKind;
-use std::io::{Read, Write};
+use std::net::SocketAddr;
+use std::sync::atomic::{AtomicBool, AtomicUsize, Ordering};
+use std::sync::Arc;
 use constants::proxy::proxy::BUFFER_SIZE;
-use std::net::{Shutdown, TcpStream};
+use log::debug;
+use tokio::io::{AsyncReadExt, AsyncWriteExt};
+use tokio::net::tcp::{OwnedReadHalf, OwnedWriteHalf};
+use tokio::net::TcpStream;
+use tokio::sync::mpsc::error::TrySendError;
+use tokio::sync::mpsc::{channel, unbounded_channel, Receiver, Sender, UnboundedReceiver, UnboundedSender};
+use tokio::task::AbortHandle;
+use crate::io::client::protocol::client_protocol::get_protocol_by_id;
 use crate::io::client_state::ConnectionState;
 use crate::io::isaac::Isaac;
 use crate::io::packet::Packet;
 
+/// Largest variable length packet a client may send before it gets dropped.
+pub const MAX_PACKET_SIZE: usize = 20000;
+/// Bytes allowed to sit in the writer queue before the client is considered too slow and dropped.
+pub const MAX_PENDING_OUTBOUND: usize = 1024 * 1024;
+/// Packets allowed to wait for the tick before the client is considered to be flooding and dropped.
+pub const MAX_PENDING_INBOUND: usize = 256;
+
+/// A single framed client packet, the opcode and exactly the bytes of its body.
+#[derive(Debug, Clone, PartialEq)]
+pub struct InboundPacket {
+    pub opcode: u8,
+    pub data: Vec<u8>,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum FrameError {
+    UnknownOpcode(u8),
+    Oversized(usize),
+}
+
+impl std::fmt::Display for FrameError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            FrameError::UnknownOpcode(opcode) => write!(f, "Unknown packet type: {}", opcode),
+            FrameError::Oversized(size) => write!(f, "Rejecting oversized packet of {} bytes", size),
+        }
+    }
+}
+
+/// Splits the raw client stream into packets using the lengths in the client protocol table.
+#[derive(Debug, Default)]
+pub struct PacketFramer {
+    buffer: Vec<u8>,
+}
+
+impl PacketFramer {
+    pub fn new() -> PacketFramer {
+        PacketFramer {
+            buffer: Vec::with_capacity(BUFFER_SIZE),
+        }
+    }
+
+    pub fn push(&mut self, bytes: &[u8]) {
+        self.buffer.extend_from_slice(bytes);
+    }
+
+    /// Bytes received that are not part of a complete packet yet.
+    pub fn buffered(&self) -> usize {
+        self.buffer.len()
+    }
+
+    /// Takes the next complete packet off the buffer, `Ok(None)` while one is still arriving.
+    pub fn next_packet(&mut self) -> Result<Option<InboundPacket>, FrameError> {
+        let Some(&opcode) = self.buffer.first() else {
+            return Ok(None);
+        };
+
+        // TODO - ISAAC stuff, the opcode needs decrypting here once the client encrypts them.
+        let protocol = get_protocol_by_id(opcode as u32).ok_or(FrameError::UnknownOpcode(opcode))?;
+
+        let (header, length) = match protocol.length {
+            -1 => match self.buffer.get(1) {
+                Some(&size) => (2, size as usize),
+                None => return Ok(None),
+            },
+            -2 => match self.buffer.get(1..3) {
+                Some(size) => (3, (size[0] as usize) << 8 | size[1] as usize),
+                None => return Ok(None),
+            },
+            length => (1, length as usize),
+        };
+
+        if length > MAX_PACKET_SIZE {
+            return Err(FrameError::Oversized(length));
+        }
+
+        if self.buffer.len() < header + length {
+            return Ok(None);
+        }
+
+        let data = self.buffer[header..header + length].to_vec();
+        self.buffer.drain(..header + length);
+
+        Ok(Some(InboundPacket { opcode, data }))
+    }
+}
+
+/// The tick thread's end of a game socket, the actual IO happens on tokio tasks.
+#[derive(Debug)]
+struct GameSocket {
+    peer_addr: SocketAddr,
+    inbound: Receiver<InboundPacket>,
+    outbound: UnboundedSender<Vec<u8>>,
+    pending_outbound: Arc<AtomicUsize>,
+    closed: Arc<AtomicBool>,
+    reader: AbortHandle,
+}
+
 #[derive(Debug)]
 pub struct GameClient {
-    pub stream: Option<TcpStream>,
+    socket: Option<GameSocket>,
     pub inbound: Packet,
     pub outbound: Packet,
     pub state: ConnectionState,
@@ -16,20 +123,13 @@ pub struct GameClient {
     total_bytes_written: usize,
     pub encryptor: Option<Isaac>,
     pub decryptor: Option<Isaac>,
-    /// Current opcode being read.
-    pub opcode: u8,
-    /// Bytes to wait for (if any)
-    pub waiting: i32,
-    read_buffer: Vec<u8>,
-    // Track nonblocking state to avoid toggling
-    nonblocking: bool,
 }
 
 impl Clone for GameClient {
     fn clone(&self) -> Self {
         GameClient {
-            // For TcpStream, we can't clone it, so we'll use None
-            stream: None,  // The clone will need to re-establish connection
+            // The socket tasks belong to one client only, the clone will need to re-establish connection
+            socket: None,
             inbound: self.inbound.clone(),
             outbound: self.outbound.clone(),
             state: self.state.clone(),
@@ -37,17 +137,12 @@ impl Clone for GameClient {
             total_bytes_written: self.total_bytes_written,
             encryptor: self.encryptor.clone(),
             decryptor: self.decryptor.clone(),
-            opcode: self.opcode,
-            waiting: self.waiting,
-            read_buffer: self.read_buffer.clone(),
-            nonblocking: false,
         }
     }
 }
 
 impl PartialEq for GameClient {
-    /// Since [TcpStream] isn't built to handle full comparisons we can't derive 'PartialEq', we will eventually handle
-    /// parts of it in comparisons.
+    /// The socket can't be compared, we will eventually handle parts of it in comparisons.
     fn eq(&self, other: &Self) -> bool {
         self.inbound == other.inbound &&
             self.outbound == other.outbound &&
@@ -55,34 +150,47 @@ impl PartialEq for GameClient {
             self.total_bytes_read == other.total_bytes_read &&
             self.total_bytes_written == other.total_bytes_written &&
             self.encryptor == other.encryptor &&
-            self.decryptor == other.decryptor &&
-            self.opcode == other.opcode &&
-            self.waiting == other.waiting &&
-            self.read_buffer == other.read_buffer
+            self.decryptor == other.decryptor
     }
 }
 
 impl GameClient {
-    pub fn new(stream: TcpStream) -> Self {
-        Self {
-            stream: Some(stream),
-            inbound: Packet::new(BUFFER_SIZE),
-            outbound: Packet::new(BUFFER_SIZE), // Increase initial size from 1 to BUFFER_SIZE
-            state: ConnectionState::New,
+    /// Hands `stream` over to a reader and a writer task, must be called from inside the tokio runtime.
+    pub fn spawn(stream: TcpStream) -> std::io::Result<Self> {
+        let peer_addr = stream.peer_addr()?;
+        let _ = stream.set_nodelay(true);
+        let (read_half, write_half) = stream.into_split();
+
+        let (inbound_tx, inbound_rx) = channel(MAX_PENDING_INBOUND);
+        let (outbound_tx, outbound_rx) = unbounded_channel();
+        let pending_outbound = Arc::new(AtomicUsize::new(0));
+        let closed = Arc::new(AtomicBool::new(false));
+
+        let reader = tokio::spawn(Self::read_loop(read_half, inbound_tx, Arc::clone(&closed)));
+        tokio::spawn(Self::write_loop(write_half, outbound_rx, Arc::clone(&pending_outbound), Arc::clone(&closed)));
+
+        Ok(Self {
+            socket: Some(GameSocket {
+                peer_addr,
+                inbound: inbound_rx,
+                outbound: outbound_tx,
+                pending_outbound,
+                closed,
+                reader: reader.abort_handle(),
+            }),
+            inbound: Packet::new(1),
+            outbound: Packet::new(BUFFER_SIZE),
+            state: ConnectionState::Connected,
             total_bytes_read: 0,
             total_bytes_written: 0,
             encryptor: None,
             decryptor: None,
-            opcode: 0,
-            waiting: 0,
-            read_buffer: vec![0u8; BUFFER_SIZE],
-            nonblocking: false,
-        }
+        })
     }
 
     pub fn new_dummy() -> Self {
         Self {
-            stream: None,
+            socket: None,
             inbound: Packet::new(1),
             outbound: Packet::new(1),
             state: ConnectionState::Null,
@@ -90,128 +198,96 @@ impl GameClient {
             total_bytes_written: 0,
             encryptor: None,
             decryptor: None,
-            opcode: 0,
-            waiting: 0,
-            read_buffer: Vec::with_capacity(1),
-            nonblocking: false,
         }
     }
 
-    /// Read data from stream into inbound packet.
-    #[inline]
-    pub fn read_packet(&mut self) -> Result<usize, std::io::Error> {
-        if self.stream.is_none() {
-            return Err(std::io::Error::new(ErrorKind::NotConnected, "No connection"));
-        }
+    async fn read_loop(mut stream: OwnedReadHalf, inbound: Sender<InboundPacket>, closed: Arc<AtomicBool>) {
+        let mut framer = PacketFramer::new();
+        let mut buffer = vec![0u8; BUFFER_SIZE];
+
+        'read: loop {
+            let bytes_read = match stream.read(&mut buffer).await {
+                Ok(0) => break,
+                Ok(bytes_read) => bytes_read,
+                Err(e) => {
+                    debug!("Error reading from client: {}", e);
+                    break;
+                }
+            };
 
-        self.inbound.position = 0;
-
-        // Directly read into the buffer
-        let bytes_read = self.stream.as_mut().unwrap().read(&mut self.read_buffer)?;
-
-        if bytes_read > 0 {
-            // Clear existing data and ensure capacity
-            self.inbound.data.clear();
-            self.inbound.data.reserve(bytes_read);
-
-            // Use unsafe for performance in critical section
-            unsafe {
-                // Set length to match read bytes and copy directly
-                self.inbound.data.set_len(bytes_read);
-                std::ptr::copy_nonoverlapping(
-                    self.read_buffer.as_ptr(),
-                    self.inbound.data.as_mut_ptr(),
-                    bytes_read
-                );
-            }
+            framer.push(&buffer[0..bytes_read]);
 
-            self.total_bytes_read += bytes_read;
+            loop {
+                match framer.next_packet() {
+                    Ok(Some(packet)) => match inbound.try_send(packet) {
+                        Ok(()) => {}
+                        Err(TrySendError::Full(_)) => {
+                            debug!("Dropping client with {} packets waiting for the tick", MAX_PENDING_INBOUND);
+                            break 'read;
+                        }
+                        // The tick side went away, nobody is left to read these.
+                        Err(TrySendError::Closed(_)) => break 'read,
+                    },
+                    Ok(None) => break,
+                    Err(e) => {
+                        debug!("{}", e);
+                        break 'read;
+                    }
+                }
+            }
         }
 
-        Ok(bytes_read)
+        closed.store(true, Ordering::Release);
     }
 
-    /// Read data with specified size from stream into inbound packet.
-    #[inline]
-    pub fn read_packet_with_size(&mut self, size: usize) -> Result<usize, std::io::Error> {
-        if self.stream.is_none() {
-            return Err(std::io::Error::new(ErrorKind::NotConnected, "No connection"));
-        }
+    async fn write_loop(mut stream: OwnedWriteHalf, mut outbound: UnboundedReceiver<Vec<u8>>, pending_outbound: Arc<AtomicUsize>, closed: Arc<AtomicBool>) {
+        while let Some(bytes) = outbound.recv().await {
+            let result = stream.write_all(&bytes).await;
+            pending_outbound.fetch_sub(bytes.len(), Ordering::AcqRel);
 
-        self.inbound.position = 0;
-
-        // Ensure buffer is large enough
-        if self.read_buffer.len() < size {
-            self.read_buffer.resize(size, 0);
+            if let Err(e) = result {
+                debug!("Error writing to client: {}", e);
+                break;
+            }
         }
 
-        // Read exact number of bytes if possible
-        match self.stream.as_mut().unwrap().read_exact(&mut self.read_buffer[0..size]) {
-            Ok(_) => {
-                // Clear and ensure capacity for inbound data
-                self.inbound.data.clear();
-                self.inbound.data.reserve(size);
-
-                // Use unsafe for performance in critical section
-                unsafe {
-                    // Set length to match size and copy directly
-                    self.inbound.data.set_len(size);
-                    std::ptr::copy_nonoverlapping(
-                        self.read_buffer.as_ptr(),
-                        self.inbound.data.as_mut_ptr(),
-                        size
-                    );
-                }
-
-                self.total_bytes_read += size;
-                Ok(size)
-            },
-            Err(e) => {
-                // Handle partial reads
-                if e.kind() == ErrorKind::UnexpectedEof {
-                    // Try a normal read for whatever bytes are available
-                    let bytes_read = self.stream.as_mut().unwrap().read(&mut self.read_buffer[0..size])?;
-
-                    if bytes_read > 0 {
-                        self.inbound.data.clear();
-                        self.inbound.data.reserve(bytes_read);
-
-                        unsafe {
-                            self.inbound.data.set_len(bytes_read);
-                            std::ptr::copy_nonoverlapping(
-                                self.read_buffer.as_ptr(),
-                                self.inbound.data.as_mut_ptr(),
-                                bytes_read
-                            );
-                        }
-
-                        self.total_bytes_read += bytes_read;
-                    }
+        closed.store(true, Ordering::Release);
+        let _ = stream.shutdown().await;
+    }
 
-                    Ok(bytes_read)
-                } else {
-                    Err(e)
-                }
-            }
-        }
+    /// Next packet framed by the reader task, never waits on the socket.
+    #[inline]
+    pub fn poll_packet(&mut self) -> Option<InboundPacket> {
+        let packet = self.socket.as_mut()?.inbound.try_recv().ok()?;
+        self.total_bytes_read += packet.data.len() + 1;
+        Some(packet)
     }
 
-    /// Write data from outbound [Packet] to stream
+    /// Hands the outbound [Packet] to the writer task, a client that can't keep up gets dropped
+    /// instead of stalling the tick.
     #[inline]
     pub fn write_packet(&mut self) -> Result<usize, std::io::Error> {
-        if self.stream.is_none() {
-            return Err(std::io::Error::new(ErrorKind::NotConnected, "No connection"));
-        }
+        let socket = match &self.socket {
+            Some(socket) => socket,
+            None => return Err(std::io::Error::new(ErrorKind::NotConnected, "No connection")),
+        };
 
         // Skip if nothing to write
         if self.outbound.position == 0 {
             return Ok(0);
         }
 
-        // Use write_all for more reliable writing
         let bytes_to_write = self.outbound.position;
-        self.stream.as_mut().unwrap().write_all(&self.outbound.data[0..bytes_to_write])?;
-        self.stream.as_mut().unwrap().flush()?;
+        let pending = socket.pending_outbound.fetch_add(bytes_to_write, Ordering::AcqRel) + bytes_to_write;
+        if pending > MAX_PENDING_OUTBOUND {
+            self.shutdown();
+            return Err(std::io::Error::new(ErrorKind::WouldBlock, "Outbound backlog exceeded"));
+        }
+
+        if socket.outbound.send(self.outbound.data[0..bytes_to_write].to_vec()).is_err() {
+            self.shutdown();
+            return Err(std::io::Error::new(ErrorKind::BrokenPipe, "Writer closed"));
+        }
 
         // Reset outbound packet for next use but maintain capacity
         self.outbound.position = 0;
@@ -233,57 +309,27 @@ impl GameClient {
         &mut self.outbound
     }
 
-    pub fn peer_addr(&self) -> std::io::Result<std::net::SocketAddr> {
-        match &self.stream {
-            Some(stream) => stream.peer_addr(),
+    pub fn peer_addr(&self) -> std::io::Result<SocketAddr> {
+        match &self.socket {
+            Some(socket) => Ok(socket.peer_addr),
             None => Err(std::io::Error::new(ErrorKind::NotConnected, "No connection"))
         }
     }
 
+    /// Stops reading straight away, whatever was already queued is still flushed before the writer closes.
     pub fn shutdown(&mut self) {
-        if let Some(stream) = &self.stream {
-            let _ = stream.shutdown(Shutdown::Both);
+        if let Some(socket) = self.socket.take() {
+            socket.reader.abort();
+            socket.closed.store(true, Ordering::Release);
         }
-        self.stream = None;
+        self.state = ConnectionState::Closed;
     }
 
     #[inline]
     pub fn is_connection_active(&self) -> bool {
-        match &self.stream {
+        match &self.socket {
             None => false,
-            Some(stream) => {
-                let mut buf = [0; 1];
-
-                // Only toggle nonblocking if needed
-                let need_toggle = !self.nonblocking;
-                let mut was_toggled = false;
-
-                // Set nonblocking temporarily if needed
-                if need_toggle {
-                    was_toggled = stream.set_nonblocking(true).is_ok();
-                }
-
-                let result = match stream.peek(&mut buf) {
-                    Ok(0) => false, // Connection closed (EOF)
-                    Ok(_) => true,  // Data available
-                    Err(e) => {
-                        match e.kind() {
-                            ErrorKind::WouldBlock => true, // No data but connection is open
-                            ErrorKind::ConnectionReset |
-                            ErrorKind::ConnectionAborted |
-                            ErrorKind::BrokenPipe => false,
-                            _ => true, // Other errors might be temporary
-                        }
-                    }
-                };
-
-                // Restore original blocking mode if needed
-                if need_toggle && was_toggled {
-                    let _ = stream.set_nonblocking(false);
-                }
-
-                result
-            }
+            Some(socket) => !socket.closed.load(Ordering::Acquire),
         }
     }
 
@@ -292,144 +338,4 @@ impl GameClient {
     pub fn take_ownership(connection: &mut Option<GameClient>) -> GameClient {
         connection.take().unwrap_or_else(|| GameClient::new_dummy())
     }
-
-    // Optimized method to take just the stream from a connection
-    #[inline]
-    pub fn take_stream(&mut self) -> Option<TcpStream> {
-        self.stream.take()
-    }
-
-    // Add a stream to a dummy connection
-    #[inline]
-    pub fn with_stream(mut self, stream: TcpStream) -> Self {
-        self.stream = Some(stream);
-        self.state = ConnectionState::New;
-        self
-    }
-
-    #[inline]
-    pub fn has_available(&mut self, required_bytes: usize) -> Result<bool, std::io::Error> {
-        if self.stream.is_none() {
-            return Err(std::io::Error::new(ErrorKind::NotConnected, "No connection"));
-        }
-
-        // Cache stream reference
-        let stream = self.stream.as_ref().unwrap();
-
-        // Only toggle nonblocking if needed (caching state)
-        let was_nonblocking;
-        if !self.nonblocking {
-            was_nonblocking = false;
-            match stream.set_nonblocking(true) {
-                Ok(_) => (),
-                Err(_) => return Err(std::io::Error::new(ErrorKind::Other, "Failed to set nonblocking")),
-            }
-        } else {
-            was_nonblocking = true;
-        }
-
-        // Use a stack-allocated buffer for small peek requests
-        let mut stack_buf = [0u8; 16];
-        let peek_result = if required_bytes <= stack_buf.len() {
-            // Use stack buffer for small requests
-            stream.peek(&mut stack_buf[0..required_bytes])
-        } else {
-            // For larger requests, ensure our read buffer is large enough
-            if self.read_buffer.len() < required_bytes {
-                self.read_buffer.resize(required_bytes, 0);
-            }
-            stream.peek(&mut self.read_buffer[0..required_bytes])
-        };
-
-        // Process the peek result
-        let result = match peek_result {
-            Ok(n) => n >= required_bytes, // Return true if we have enough bytes
-            Err(e) if e.kind() == ErrorKind::WouldBlock => false, // No data available
-            Err(e) => {
-                // Restore blocking state if needed
-                if !was_nonblocking {
-                    let _ = stream.set_nonblocking(false);
-                    self.nonblocking = false;
-                }
-                return Err(e);
-            }
-        };
-
-        // Restore blocking state if needed
-        if !was_nonblocking {
-            let _ = stream.set_nonblocking(false);
-            self.nonblocking = false;
-        }
-
-        Ok(result)
-    }
-
-    // Method to toggle and maintain nonblocking state
-    pub fn set_nonblocking(&mut self, nonblocking: bool) -> Result<(), std::io::Error> {
-        if self.stream.is_none() {
-            return Err(std::io::Error::new(ErrorKind::NotConnected, "No connection"));
-        }
-
-        // Only change if state is different
-        if self.nonblocking != nonblocking {
-            self.stream.as_ref().unwrap().set_nonblocking(nonblocking)?;
-            self.nonblocking = nonblocking;
-        }
-
-        Ok(())
-    }
-
-    // Batch write multiple buffers to reduce system calls
-    pub fn write_multiple(&mut self, buffers: &[&[u8]]) -> Result<usize, std::io::Error> {
-        if self.stream.is_none() {
-            return Err(std::io::Error::new(ErrorKind::NotConnected, "No connection"));
-        }
-
-        let mut total_written = 0;
-
-        // Combine small buffers into a single write
-        for buffer in buffers {
-            // Add to outbound buffer
-            let start_pos = self.outbound.position;
-            let buffer_len = buffer.len();
-            let end_pos = start_pos + buffer_len;
-
-            // Ensure capacity
-            if end_pos > self.outbound.data.len() {
-                self.outbound.data.resize(end_pos, 0);
-            }
-
-            // Copy buffer
-            self.outbound.data[start_pos..end_pos].copy_from_slice(buffer);
-            self.outbound.position = end_pos;
-
-            total_written += buffer_len;
-        }
-
-        // Write combined data
-        self.write_packet()?;
-
-        Ok(total_written)
-    }
-
-    // Directly write a buffer without copying to outbound first (for large buffers)
-    pub fn write_direct(&mut self, buffer: &[u8]) -> Result<usize, std::io::Error> {
-        if self.stream.is_none() {
-            return Err(std::io::Error::new(ErrorKind::NotConnected, "No connection"));
-        }
-
-        // For large buffers, bypass the outbound packet
-        if buffer.len() > BUFFER_SIZE {
-            let bytes_written = self.stream.as_mut().unwrap().write(buffer)?;
-            self.total_bytes_written += bytes_written;
-            return Ok(bytes_written);
-        }
-
-        // For smaller buffers, use normal path
-        self.outbound.data.clear();
-        self.outbound.data.extend_from_slice(buffer);
-        self.outbound.position = buffer.len();
-
-        self.write_packet()
-    }
-}
\ No newline at end of file
+}
@@ -0,0 +1,121 @@
+use std::time::Duration;
+use tokio::io::{AsyncReadExt, AsyncWriteExt};
+use tokio::net::{TcpListener, TcpStream};
+use crate::game_connection::{FrameError, GameClient, InboundPacket, PacketFramer, MAX_PENDING_INBOUND};
+use crate::io::client::protocol::client_protocol::ClientProtocol;
+
+const IF_BUTTON: u8 = ClientProtocol::IF_BUTTON.id.0 as u8;
+const NO_TIMEOUT: u8 = ClientProtocol::NO_TIMEOUT.id.0 as u8;
+
+#[test]
+fn test_framer_waits_for_complete_packet() {
+    let mut framer = PacketFramer::new();
+
+    framer.push(&[IF_BUTTON, 0, 1, 0]);
+    assert_eq!(framer.next_packet(), Ok(None));
+
+    framer.push(&[2, 0, 3, NO_TIMEOUT]);
+    assert_eq!(framer.next_packet(), Ok(Some(InboundPacket { opcode: IF_BUTTON, data: vec![0, 1, 0, 2, 0, 3] })));
+    assert_eq!(framer.next_packet(), Ok(Some(InboundPacket { opcode: NO_TIMEOUT, data: vec![] })));
+    assert_eq!(framer.next_packet(), Ok(None));
+    assert_eq!(framer.buffered(), 0);
+}
+
+#[test]
+fn test_framer_rejects_unknown_opcode() {
+    let mut framer = PacketFramer::new();
+    framer.push(&[255]);
+
+    assert_eq!(framer.next_packet(), Err(FrameError::UnknownOpcode(255)));
+}
+
+async fn connected_pair() -> (GameClient, TcpStream) {
+    let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+    let remote = TcpStream::connect(listener.local_addr().unwrap()).await.unwrap();
+    let (stream, _) = listener.accept().await.unwrap();
+
+    (GameClient::spawn(stream).unwrap(), remote)
+}
+
+async fn poll_packet(client: &mut GameClient) -> InboundPacket {
+    for _ in 0..200 {
+        if let Some(packet) = client.poll_packet() {
+            return packet;
+        }
+        tokio::time::sleep(Duration::from_millis(5)).await;
+    }
+    panic!("No packet arrived");
+}
+
+#[tokio::test]
+async fn test_reader_task_frames_packets() {
+    let (mut client, mut remote) = connected_pair().await;
+
+    assert!(client.poll_packet().is_none());
+
+    remote.write_all(&[NO_TIMEOUT, IF_BUTTON, 0, 1]).await.unwrap();
+    assert_eq!(poll_packet(&mut client).await, InboundPacket { opcode: NO_TIMEOUT, data: vec![] });
+
+    remote.write_all(&[0, 2, 0, 3]).await.unwrap();
+    assert_eq!(poll_packet(&mut client).await, InboundPacket { opcode: IF_BUTTON, data: vec![0, 1, 0, 2, 0, 3] });
+}
+
+#[tokio::test]
+async fn test_writer_task_flushes_outbound() {
+    let (mut client, mut remote) = connected_pair().await;
+
+    client.outbound.p1(1);
+    client.outbound.p2(515);
+    assert_eq!(client.write_packet().unwrap(), 3);
+    assert_eq!(client.outbound.position, 0);
+
+    let mut received = [0u8; 3];
+    remote.read_exact(&mut received).await.unwrap();
+    assert_eq!(received, [1, 2, 3]);
+}
+
+#[tokio::test]
+async fn test_shutdown_closes_socket() {
+    let (mut client, mut remote) = connected_pair().await;
+
+    client.outbound.p1(7);
+    client.write_packet().unwrap();
+    client.shutdown();
+    assert!(!client.is_connection_active());
+
+    let mut received = Vec::new();
+    remote.read_to_end(&mut received).await.unwrap();
+    assert_eq!(received, vec![7]);
+}
+
+#[tokio::test]
+async fn test_remote_close_marks_connection_inactive() {
+    let (client, remote) = connected_pair().await;
+    drop(remote);
+
+    for _ in 0..200 {
+        if !client.is_connection_active() {
+            return;
+        }
+        tokio::time::sleep(Duration::from_millis(5)).await;
+    }
+    panic!("Connection still active after remote closed");
+}
+
+#[tokio::test]
+async fn test_inbound_flood_drops_client() {
+    let (mut client, mut remote) = connected_pair().await;
+
+    // Nothing polls while the client floods, the queue fills and the reader gives up on it.
+    remote.write_all(&vec![NO_TIMEOUT; MAX_PENDING_INBOUND + 1]).await.unwrap();
+
+    for _ in 0..200 {
+        if !client.is_connection_active() {
+            let queued = std::iter::from_fn(|| client.poll_packet()).count();
+            assert_eq!(queued, MAX_PENDING_INBOUND);
+            return;
+        }
+        tokio::time::sleep(Duration::from_millis(5)).await;
+    }
+    panic!("Connection still active after flooding the inbound queue");
+}
@@ -0,0 +1,20 @@
+use crate::io::client::codec::message_decoder::MessageDecoder;
+use crate::io::client::model::if_button::IfButtonMessage;
+use crate::io::client::protocol::client_protocol::ClientProtocol;
+use crate::io::packet::Packet;
+
+pub struct IfButtonDecoder;
+
+impl MessageDecoder for IfButtonDecoder {
+    type Message = IfButtonMessage;
+
+    fn protocol(&self) -> &ClientProtocol {
+        &ClientProtocol::IF_BUTTON
+    }
+
+    fn decode(&self, packet: &mut Packet, _length: usize) -> Box<Self::Message> {
+        let component = packet.g4();
+        let slot = packet.g2();
+        Box::new(IfButtonMessage { component, slot })
+    }
+}
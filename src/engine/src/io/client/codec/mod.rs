@@ -2,4 +2,8 @@ pub mod message_decoder;
 pub mod window_status_decoder;
 pub mod verification_decoder;
 pub mod event_camera_position_decoder;
-pub mod event_applet_focus_decoder;
\ No newline at end of file
+pub mod event_applet_focus_decoder;
+pub mod if_button_decoder;
+pub mod transmitvar_verifyid_decoder;
+pub mod resume_pausebutton_decoder;
+pub mod resume_p_countdialog_decoder;
\ No newline at end of file
@@ -0,0 +1,20 @@
+use crate::io::client::codec::message_decoder::MessageDecoder;
+use crate::io::client::model::resume_pausebutton::ResumePauseButtonMessage;
+use crate::io::client::protocol::client_protocol::ClientProtocol;
+use crate::io::packet::Packet;
+
+pub struct ResumePauseButtonDecoder;
+
+impl MessageDecoder for ResumePauseButtonDecoder {
+    type Message = ResumePauseButtonMessage;
+
+    fn protocol(&self) -> &ClientProtocol {
+        &ClientProtocol::RESUME_PAUSEBUTTON
+    }
+
+    fn decode(&self, packet: &mut Packet, _length: usize) -> Box<Self::Message> {
+        let component = packet.g4();
+        let slot = packet.g2();
+        Box::new(ResumePauseButtonMessage { component, slot })
+    }
+}
@@ -0,0 +1,19 @@
+use crate::io::client::codec::message_decoder::MessageDecoder;
+use crate::io::client::model::transmitvar_verifyid::TransmitVarVerifyIdMessage;
+use crate::io::client::protocol::client_protocol::ClientProtocol;
+use crate::io::packet::Packet;
+
+pub struct TransmitVarVerifyIdDecoder;
+
+impl MessageDecoder for TransmitVarVerifyIdDecoder {
+    type Message = TransmitVarVerifyIdMessage;
+
+    fn protocol(&self) -> &ClientProtocol {
+        &ClientProtocol::TRANSMITVAR_VERIFYID
+    }
+
+    fn decode(&self, packet: &mut Packet, _length: usize) -> Box<Self::Message> {
+        let verify_id = packet.g2();
+        Box::new(TransmitVarVerifyIdMessage { verify_id })
+    }
+}
@@ -0,0 +1,38 @@
+use log::debug;
+use crate::entity::player::Player;
+use crate::io::client::handler::message_handler::MessageHandler;
+use crate::io::client::model::if_button::IfButtonMessage;
+use crate::script::script_provider::ScriptProvider;
+use crate::script::script_runner::ScriptRunner;
+use crate::script::server_trigger_types::ServerTriggerTypes;
+
+pub struct IfButtonHandler;
+
+impl MessageHandler for IfButtonHandler {
+    type Message = IfButtonMessage;
+
+    fn handle(&self, message: &Self::Message, player: &mut Player) -> bool {
+        // The client echoes the last verify id it received, anything else was clicked on an interface state we've since replaced.
+        if player.client_verify_id != player.get_verify_id() {
+            debug!("Ignoring stale if_button {} (verify id {} != {})", message.component, player.client_verify_id, player.get_verify_id());
+            return false;
+        }
+
+        if !player.interfaces.is_open(message.interface_id()) {
+            debug!("Ignoring if_button {} for interface {} which is not open", message.component, message.interface_id());
+            return false;
+        }
+
+        player.last_com = message.component;
+        player.last_slot = message.slot as i32;
+
+        if let Some(trigger) = ScriptProvider::get_by_trigger_specific(ServerTriggerTypes::IF_BUTTON, message.component, -1) {
+            let script = ScriptRunner::init(trigger, Some(player.as_entity_type()), None, None);
+            player.queue_script(script, true);
+        } else if cfg!(debug_assertions) {
+            debug!("Unhandled if_button: {}:{}", message.interface_id(), message.component & 0xFFFF);
+        }
+
+        true
+    }
+}
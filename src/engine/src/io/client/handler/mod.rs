@@ -1,3 +1,7 @@
 pub mod message_handler;
 pub mod window_status_handler;
-pub mod verification_handler;
\ No newline at end of file
+pub mod verification_handler;
+pub mod if_button_handler;
+pub mod transmitvar_verifyid_handler;
+pub mod resume_pausebutton_handler;
+pub mod resume_p_countdialog_handler;
\ No newline at end of file
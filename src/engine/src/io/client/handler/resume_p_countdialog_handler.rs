@@ -0,0 +1,16 @@
+use crate::entity::player::Player;
+use crate::io::client::handler::message_handler::MessageHandler;
+use crate::io::client::model::resume_p_countdialog::ResumePCountDialogMessage;
+use crate::script::script_state::ScriptState;
+
+pub struct ResumePCountDialogHandler;
+
+impl MessageHandler for ResumePCountDialogHandler {
+    type Message = ResumePCountDialogMessage;
+
+    fn handle(&self, message: &Self::Message, player: &mut Player) -> bool {
+        player.last_int = message.input;
+
+        player.queue_resume(ScriptState::COUNTDIALOG, Some(message.input))
+    }
+}
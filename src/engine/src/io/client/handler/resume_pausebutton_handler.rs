@@ -0,0 +1,23 @@
+use log::debug;
+use crate::entity::player::Player;
+use crate::io::client::handler::message_handler::MessageHandler;
+use crate::io::client::model::resume_pausebutton::ResumePauseButtonMessage;
+use crate::script::script_state::ScriptState;
+
+pub struct ResumePauseButtonHandler;
+
+impl MessageHandler for ResumePauseButtonHandler {
+    type Message = ResumePauseButtonMessage;
+
+    fn handle(&self, message: &Self::Message, player: &mut Player) -> bool {
+        if !player.interfaces.is_open(message.interface_id()) {
+            debug!("Ignoring resume_pausebutton {} for interface {} which is not open", message.component, message.interface_id());
+            return false;
+        }
+
+        player.last_com = message.component;
+        player.last_slot = message.slot as i32;
+
+        player.queue_resume(ScriptState::PAUSEBUTTON, Some(message.component))
+    }
+}
@@ -0,0 +1,14 @@
+use crate::entity::player::Player;
+use crate::io::client::handler::message_handler::MessageHandler;
+use crate::io::client::model::transmitvar_verifyid::TransmitVarVerifyIdMessage;
+
+pub struct TransmitVarVerifyIdHandler;
+
+impl MessageHandler for TransmitVarVerifyIdHandler {
+    type Message = TransmitVarVerifyIdMessage;
+
+    fn handle(&self, message: &Self::Message, player: &mut Player) -> bool {
+        player.client_verify_id = message.verify_id;
+        true
+    }
+}
@@ -1,5 +1,5 @@
-mod incoming_message;
+pub mod incoming_message;
 mod handler;
 mod codec;
-mod model;
+pub mod model;
 pub mod protocol;
\ No newline at end of file
@@ -0,0 +1,25 @@
+use std::any::Any;
+use crate::io::client::incoming_message::IncomingMessage;
+use crate::io::client::protocol::client_protocol_category::ClientProtocolCategory;
+
+pub struct IfButtonMessage {
+    pub(crate) component: i32,
+    pub(crate) slot: u16,
+}
+
+impl IfButtonMessage {
+    #[inline]
+    pub fn interface_id(&self) -> u32 {
+        (self.component as u32) >> 16
+    }
+}
+
+impl IncomingMessage for IfButtonMessage {
+    fn category(&self) -> ClientProtocolCategory {
+        ClientProtocolCategory::USER_EVENT
+    }
+
+    fn as_any(&self) -> &dyn Any {
+        self
+    }
+}
@@ -0,0 +1,17 @@
+use std::any::Any;
+use crate::io::client::incoming_message::IncomingMessage;
+use crate::io::client::protocol::client_protocol_category::ClientProtocolCategory;
+
+pub struct ResumePCountDialogMessage {
+    pub(crate) input: i32,
+}
+
+impl IncomingMessage for ResumePCountDialogMessage {
+    fn category(&self) -> ClientProtocolCategory {
+        ClientProtocolCategory::USER_EVENT
+    }
+
+    fn as_any(&self) -> &dyn Any {
+        self
+    }
+}
@@ -0,0 +1,25 @@
+use std::any::Any;
+use crate::io::client::incoming_message::IncomingMessage;
+use crate::io::client::protocol::client_protocol_category::ClientProtocolCategory;
+
+pub struct ResumePauseButtonMessage {
+    pub(crate) component: i32,
+    pub(crate) slot: u16,
+}
+
+impl ResumePauseButtonMessage {
+    #[inline]
+    pub fn interface_id(&self) -> u32 {
+        (self.component as u32) >> 16
+    }
+}
+
+impl IncomingMessage for ResumePauseButtonMessage {
+    fn category(&self) -> ClientProtocolCategory {
+        ClientProtocolCategory::USER_EVENT
+    }
+
+    fn as_any(&self) -> &dyn Any {
+        self
+    }
+}
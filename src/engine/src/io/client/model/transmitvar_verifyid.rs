@@ -0,0 +1,17 @@
+use std::any::Any;
+use crate::io::client::incoming_message::IncomingMessage;
+use crate::io::client::protocol::client_protocol_category::ClientProtocolCategory;
+
+pub struct TransmitVarVerifyIdMessage {
+    pub(crate) verify_id: u16,
+}
+
+impl IncomingMessage for TransmitVarVerifyIdMessage {
+    fn category(&self) -> ClientProtocolCategory {
+        ClientProtocolCategory::CLIENT_EVENT
+    }
+
+    fn as_any(&self) -> &dyn Any {
+        self
+    }
+}
@@ -13,12 +13,15 @@ pub struct ClientProtocol {
 impl ClientProtocol {
     pub const EVENT_APPLET_FOCUS: Self = ClientProtocol { id: ProtocolId(22), length: 1 };
     pub const EVENT_CAMERA_POSITION: Self = ClientProtocol { id: ProtocolId(21), length: 4 };
+    pub const IF_BUTTON: Self = ClientProtocol { id: ProtocolId(155), length: 6 };
     pub const EVENT_MOUSE_CLICK: Self = ClientProtocol { id: ProtocolId(75), length: 6 };
     pub const MAP_REBUILD_COMPLETE: Self = ClientProtocol { id: ProtocolId(110), length: 0 };
     pub const NO_TIMEOUT: Self = ClientProtocol { id: ProtocolId(93), length: 0 };
     pub const VERIFICATION: Self = ClientProtocol { id: ProtocolId(20), length: 4 };
     pub const WINDOW_STATUS: Self = ClientProtocol { id: ProtocolId(243), length: 6 };
     pub const TRANSMITVAR_VERIFYID: Self = ClientProtocol { id: ProtocolId(177), length: 2 };
+    pub const RESUME_PAUSEBUTTON: Self = ClientProtocol { id: ProtocolId(132), length: 6 };
+    pub const RESUME_P_COUNTDIALOG: Self = ClientProtocol { id: ProtocolId(23), length: 4 };
 }
 
 lazy_static! {
@@ -26,12 +29,15 @@ lazy_static! {
         let protocols = [
             ClientProtocol::EVENT_APPLET_FOCUS,
             ClientProtocol::EVENT_CAMERA_POSITION,
+            ClientProtocol::IF_BUTTON,
             ClientProtocol::EVENT_MOUSE_CLICK,
             ClientProtocol::MAP_REBUILD_COMPLETE,
             ClientProtocol::NO_TIMEOUT,
             ClientProtocol::VERIFICATION,
             ClientProtocol::WINDOW_STATUS,
             ClientProtocol::TRANSMITVAR_VERIFYID,
+            ClientProtocol::RESUME_PAUSEBUTTON,
+            ClientProtocol::RESUME_P_COUNTDIALOG,
         ];
         
         let mut map = HashMap::new();
@@ -5,10 +5,18 @@ use lazy_static::lazy_static;
 use crate::entity::player::Player;
 use crate::io::client::codec::event_applet_focus_decoder::EventAppletFocusDecoder;
 use crate::io::client::codec::event_camera_position_decoder::EventCameraPositionDecoder;
+use crate::io::client::codec::if_button_decoder::IfButtonDecoder;
 use crate::io::client::codec::message_decoder::MessageDecoder;
+use crate::io::client::codec::resume_p_countdialog_decoder::ResumePCountDialogDecoder;
+use crate::io::client::codec::resume_pausebutton_decoder::ResumePauseButtonDecoder;
+use crate::io::client::codec::transmitvar_verifyid_decoder::TransmitVarVerifyIdDecoder;
 use crate::io::client::codec::verification_decoder::VerificationDecoder;
 use crate::io::client::codec::window_status_decoder::WindowStatusDecoder;
+use crate::io::client::handler::if_button_handler::IfButtonHandler;
 use crate::io::client::handler::message_handler::MessageHandler;
+use crate::io::client::handler::resume_p_countdialog_handler::ResumePCountDialogHandler;
+use crate::io::client::handler::resume_pausebutton_handler::ResumePauseButtonHandler;
+use crate::io::client::handler::transmitvar_verifyid_handler::TransmitVarVerifyIdHandler;
 use crate::io::client::handler::verification_handler::VerificationHandler;
 use crate::io::client::handler::window_status_handler::WindowStatusHandler;
 use crate::io::client::incoming_message::IncomingMessage;
@@ -169,6 +177,10 @@ impl ClientProtocolRepository {
         register_protocol!(VerificationDecoder, VerificationHandler);
         register_protocol!(EventCameraPositionDecoder);
         register_protocol!(EventAppletFocusDecoder);
+        register_protocol!(TransmitVarVerifyIdDecoder, TransmitVarVerifyIdHandler);
+        register_protocol!(IfButtonDecoder, IfButtonHandler);
+        register_protocol!(ResumePauseButtonDecoder, ResumePauseButtonHandler);
+        register_protocol!(ResumePCountDialogDecoder, ResumePCountDialogHandler);
 
         repository
     }
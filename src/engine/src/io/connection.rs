@@ -39,6 +39,17 @@ impl Connection {
         Ok(bytes_read)
     }
 
+    /// Read exactly `size` bytes from stream into inbound packet
+    pub async fn read_packet_with_size(&mut self, size: usize) -> Result<usize, Error> {
+        self.inbound.position = 0;
+        self.inbound.data.clear();
+        self.inbound.data.resize(size, 0);
+
+        self.stream.read_exact(&mut self.inbound.data).await?;
+
+        Ok(size)
+    }
+
     /// Write data from outbound packet to stream
     pub async fn write_packet(&mut self) -> Result<usize, Error> {
         let bytes_written = self.stream.write(&self.outbound.data[0..self.outbound.position]).await?;
@@ -0,0 +1,90 @@
+use std::collections::HashMap;
+use std::net::IpAddr;
+use std::sync::{Arc, Mutex};
+use constants::js5_out::js5_out;
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum LimitExceeded {
+    ServerFull,
+    IpLimit,
+}
+
+impl LimitExceeded {
+    /// JS5 handshake response telling the client why it was turned away.
+    pub fn status(&self) -> i32 {
+        match self {
+            LimitExceeded::ServerFull => js5_out::SERVER_FULL,
+            LimitExceeded::IpLimit => js5_out::IP_LIMIT,
+        }
+    }
+}
+
+#[derive(Debug, Default)]
+struct Connections {
+    total: usize,
+    per_ip: HashMap<IpAddr, usize>,
+}
+
+/// Counts open connections of a server, overall and per address.
+///
+/// Loopback addresses are only held to the global cap, every client that comes in through the
+/// proxy shows up as one of them.
+#[derive(Debug)]
+pub struct ConnectionLimiter {
+    max_connections: usize,
+    max_per_ip: usize,
+    connections: Mutex<Connections>,
+}
+
+impl ConnectionLimiter {
+    pub fn new(max_connections: usize, max_per_ip: usize) -> Arc<ConnectionLimiter> {
+        Arc::new(ConnectionLimiter {
+            max_connections,
+            max_per_ip,
+            connections: Mutex::new(Connections::default()),
+        })
+    }
+
+    /// Takes a slot for `ip`, it is given back when the guard is dropped.
+    pub fn acquire(self: &Arc<Self>, ip: IpAddr) -> Result<ConnectionGuard, LimitExceeded> {
+        let mut connections = self.connections.lock().unwrap();
+        if connections.total >= self.max_connections {
+            return Err(LimitExceeded::ServerFull);
+        }
+
+        if !ip.is_loopback() {
+            let count = connections.per_ip.entry(ip).or_insert(0);
+            if *count >= self.max_per_ip {
+                return Err(LimitExceeded::IpLimit);
+            }
+            *count += 1;
+        }
+        connections.total += 1;
+
+        Ok(ConnectionGuard { limiter: Arc::clone(self), ip })
+    }
+
+    pub fn connections(&self) -> usize {
+        self.connections.lock().unwrap().total
+    }
+}
+
+#[derive(Debug)]
+pub struct ConnectionGuard {
+    limiter: Arc<ConnectionLimiter>,
+    ip: IpAddr,
+}
+
+impl Drop for ConnectionGuard {
+    fn drop(&mut self) {
+        let mut connections = self.limiter.connections.lock().unwrap();
+        connections.total -= 1;
+
+        if let Some(count) = connections.per_ip.get_mut(&self.ip) {
+            *count -= 1;
+            if *count == 0 {
+                connections.per_ip.remove(&self.ip);
+            }
+        }
+    }
+}
@@ -0,0 +1,51 @@
+use std::net::{IpAddr, Ipv4Addr};
+use constants::js5_out::js5_out;
+use crate::io::connection_limiter::{ConnectionLimiter, LimitExceeded};
+
+fn ip(last: u8) -> IpAddr {
+    IpAddr::V4(Ipv4Addr::new(10, 0, 0, last))
+}
+
+#[test]
+fn test_per_ip_limit() {
+    let limiter = ConnectionLimiter::new(10, 2);
+
+    let first = limiter.acquire(ip(1)).unwrap();
+    let _second = limiter.acquire(ip(1)).unwrap();
+    assert_eq!(limiter.acquire(ip(1)).unwrap_err(), LimitExceeded::IpLimit);
+
+    // Other addresses are unaffected.
+    let _other = limiter.acquire(ip(2)).unwrap();
+
+    drop(first);
+    assert!(limiter.acquire(ip(1)).is_ok());
+}
+
+#[test]
+fn test_global_limit() {
+    let limiter = ConnectionLimiter::new(2, 5);
+
+    let first = limiter.acquire(ip(1)).unwrap();
+    let _second = limiter.acquire(ip(2)).unwrap();
+    assert_eq!(limiter.acquire(ip(3)).unwrap_err(), LimitExceeded::ServerFull);
+    assert_eq!(limiter.connections(), 2);
+
+    drop(first);
+    assert_eq!(limiter.connections(), 1);
+    assert!(limiter.acquire(ip(3)).is_ok());
+}
+
+#[test]
+fn test_loopback_exempt_from_ip_limit() {
+    let limiter = ConnectionLimiter::new(3, 1);
+    let loopback = IpAddr::V4(Ipv4Addr::LOCALHOST);
+
+    let _guards: Vec<_> = (0..3).map(|_| limiter.acquire(loopback).unwrap()).collect();
+    assert_eq!(limiter.acquire(loopback).unwrap_err(), LimitExceeded::ServerFull);
+}
+
+#[test]
+fn test_status_codes() {
+    assert_eq!(LimitExceeded::ServerFull.status(), js5_out::SERVER_FULL);
+    assert_eq!(LimitExceeded::IpLimit.status(), js5_out::IP_LIMIT);
+}
@@ -23,11 +23,12 @@ impl CRC {
         CRC { table }
     }
     
-    pub fn get_crc(&self, src: &Vec<u8>, offset: usize, length: usize) -> i32 {
-        let mut crc: i32 = 0;
-        for i in offset..length {
-            crc = (crc >> 8) ^ (self.table[((crc ^ src[i] as i32) & 0xFF) as usize]);
+    /// CRC-32 of `src[offset..length]`, the same checksum the JS5 indexes store.
+    pub fn get_crc(&self, src: &[u8], offset: usize, length: usize) -> i32 {
+        let mut crc: u32 = 0xFFFFFFFF;
+        for byte in &src[offset..length] {
+            crc = (crc >> 8) ^ (self.table[((crc ^ *byte as u32) & 0xFF) as usize] as u32);
         }
-        !crc
+        !crc as i32
     }
 }
\ No newline at end of file
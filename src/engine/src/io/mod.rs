@@ -1,9 +1,12 @@
 pub mod client;
 pub mod server;
 pub mod connection;
+pub mod connection_limiter;
+mod connection_limiter_tests;
 pub mod packet;
 pub mod client_state;
 pub mod crc;
 mod packet_tests;
 pub mod rsa;
-pub mod isaac;
\ No newline at end of file
+pub mod isaac;
+mod rsa_tests;
\ No newline at end of file
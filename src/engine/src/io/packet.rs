@@ -5,6 +5,33 @@ pub struct Packet {
     pub bit_position: usize,
 }
 
+/// What the client makes of bytes 128..160, the rest of CP1252 matches Latin-1. The five bytes
+/// CP1252 leaves undefined are '\0' here and read as '?'.
+const CP1252: [char; 32] = [
+    '\u{20AC}', '\0', '\u{201A}', '\u{0192}', '\u{201E}', '\u{2026}', '\u{2020}', '\u{2021}',
+    '\u{02C6}', '\u{2030}', '\u{0160}', '\u{2039}', '\u{0152}', '\0', '\u{017D}', '\0',
+    '\0', '\u{2018}', '\u{2019}', '\u{201C}', '\u{201D}', '\u{2022}', '\u{2013}', '\u{2014}',
+    '\u{02DC}', '\u{2122}', '\u{0161}', '\u{203A}', '\u{0153}', '\0', '\u{017E}', '\u{0178}',
+];
+
+/// The CP1252 byte for `c`, `None` when the client has no way to show it.
+pub fn cp1252_byte(c: char) -> Option<u8> {
+    match c as u32 {
+        1..=127 | 160..=255 => Some(c as u32 as u8),
+        _ => CP1252.iter().position(|&mapped| mapped == c && c != '\0').map(|index| 128 + index as u8),
+    }
+}
+
+fn cp1252_char(byte: u8) -> char {
+    match byte {
+        128..=159 => match CP1252[(byte - 128) as usize] {
+            '\0' => '?',
+            c => c,
+        },
+        _ => byte as char,
+    }
+}
+
 // Helper function for branch prediction optimization
 macro_rules! likely {
     ($expr:expr) => {
@@ -226,10 +253,11 @@ impl Packet {
 
     #[inline(always)]
     pub fn pjstr(&mut self, str: &str, terminator: u8) {
+        // One CP1252 byte per char, the inverse of 'gjstr'. Like the client, anything CP1252 can't hold becomes '?'.
         let mut len = 0;
 
-        for &b in str.as_bytes() {
-            if b != 0 {
+        for c in str.chars() {
+            if c != '\0' {
                 len += 1;
             }
         }
@@ -240,9 +268,9 @@ impl Packet {
         }
 
         let mut idx = self.position;
-        for &b in str.as_bytes() {
-            if b != 0 {
-                self.data[idx] = b;
+        for c in str.chars() {
+            if c != '\0' {
+                self.data[idx] = cp1252_byte(c).unwrap_or(b'?');
                 idx += 1;
             }
         }
@@ -501,7 +529,7 @@ impl Packet {
             .copied()
             .filter(|&b| b != 0);
         
-        let decoded: String = filtered.map(|b| b as char).collect();
+        let decoded: String = filtered.map(cp1252_char).collect();
         
         decoded
     }
@@ -54,4 +54,17 @@ fn test_g2() {
 fn test_g4() {
     let mut packet = Packet::from(vec![0x01, 0x02, 0x03, 0x04]);
     assert_eq!(packet.g4(), 0x01020304);
-}
\ No newline at end of file
+}
+
+#[test]
+fn test_jstr_is_cp1252() {
+    let mut packet = Packet::from(Vec::new());
+    packet.pjstr("\u{20AC}5 caf\u{E9} \u{6F22}", 0);
+    assert_eq!(packet.data, vec![0x80, b'5', b' ', b'c', b'a', b'f', 0xE9, b' ', b'?', 0]);
+
+    packet.position = 0;
+    assert_eq!(packet.gjstr(), "\u{20AC}5 caf\u{E9} ?");
+
+    // 0x81 is undefined in CP1252.
+    assert_eq!(Packet::from(vec![0x81, 0x9F, 0]).gjstr(), "?\u{178}");
+}
@@ -1,14 +1,320 @@
 pub mod rsa {
-    use num_bigint::BigInt;
+    use std::fmt;
+    use std::fs;
+    use std::io::{ErrorKind, Write};
+    use std::path::Path;
+    use std::sync::OnceLock;
+    use log::{info, warn};
+    use num_bigint::{BigInt, BigUint, Sign};
+    use rand::Rng;
     use constants::rsa::title_protocol::{SERVER_EXPONENT, SERVER_MODULUS};
     use crate::io::packet::Packet;
-    
-    pub fn decrypt_rsa_block(mut packet: Packet, packet_length: usize) -> Packet {
-        let rsa_bytes_vec = packet.gbytes(packet_length);
-        let rsa_bytes = BigInt::from_bytes_be(num_bigint::Sign::Plus, &rsa_bytes_vec);
-        let decrypted_byes = rsa_bytes.modpow(&*SERVER_EXPONENT, &*SERVER_MODULUS);
-        let (_, bytes) = decrypted_byes.to_bytes_be();
-        
-        Packet::from(bytes)
-    }
-}
\ No newline at end of file
+
+    /// First byte of every login block the client encrypts.
+    pub const LOGIN_BLOCK_MAGIC: u8 = 10;
+    /// Public exponent handed to the client alongside the modulus.
+    pub const PUBLIC_EXPONENT: u32 = 65537;
+    pub const SUPPORTED_KEY_SIZES: [u64; 2] = [512, 1024];
+
+    /// Rounds of Miller-Rabin per candidate, a composite survives with at most 4^-rounds chance.
+    const MILLER_RABIN_ROUNDS: usize = 40;
+    const SMALL_PRIMES: [u32; 24] = [3, 5, 7, 11, 13, 17, 19, 23, 29, 31, 37, 41, 43, 47, 53, 59, 61, 67, 71, 73, 79, 83, 89, 97];
+
+    #[derive(Debug, Clone, PartialEq)]
+    pub enum RsaError {
+        /// The block is empty or bigger than the modulus, it can't have come from our key.
+        InvalidLength(usize),
+        /// Decrypting gave something that doesn't start with [`LOGIN_BLOCK_MAGIC`], usually a client
+        /// patched with a different modulus.
+        BadBlock(u8),
+        UnsupportedKeySize(u64),
+        InvalidKeyFile(String),
+        Io(String),
+        /// [`load_key`] hasn't run or failed, logins can't be decrypted.
+        NotLoaded,
+    }
+
+    impl fmt::Display for RsaError {
+        fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+            match self {
+                RsaError::InvalidLength(length) => write!(f, "RSA block of {} bytes does not fit the key", length),
+                RsaError::BadBlock(magic) => write!(f, "RSA block decrypted to magic {} instead of {}, is the client using the right modulus?", magic, LOGIN_BLOCK_MAGIC),
+                RsaError::UnsupportedKeySize(bits) => write!(f, "Unsupported key size {}, expected one of {:?}", bits, SUPPORTED_KEY_SIZES),
+                RsaError::InvalidKeyFile(reason) => write!(f, "Invalid key file: {}", reason),
+                RsaError::Io(reason) => write!(f, "{}", reason),
+                RsaError::NotLoaded => write!(f, "No RSA key loaded"),
+            }
+        }
+    }
+
+    impl std::error::Error for RsaError {}
+
+    /// The server half of the login key, the private exponent and the modulus.
+    #[derive(Debug, Clone, PartialEq)]
+    pub struct RsaKey {
+        pub modulus: BigInt,
+        pub exponent: BigInt,
+    }
+
+    impl RsaKey {
+        /// The key shipped with the repository, matches the modulus in the unpatched client.
+        pub fn builtin() -> RsaKey {
+            RsaKey {
+                modulus: (*SERVER_MODULUS).clone(),
+                exponent: (*SERVER_EXPONENT).clone(),
+            }
+        }
+
+        pub fn decrypt_block(&self, mut packet: Packet, packet_length: usize) -> Result<Packet, RsaError> {
+            let max_length = self.modulus.bits().div_ceil(8) as usize;
+            if packet_length == 0 || packet_length > max_length || packet.remaining() < packet_length as i32 {
+                return Err(RsaError::InvalidLength(packet_length));
+            }
+
+            let rsa_bytes_vec = packet.gbytes(packet_length);
+            let rsa_bytes = BigInt::from_bytes_be(Sign::Plus, &rsa_bytes_vec);
+            let decrypted_bytes = rsa_bytes.modpow(&self.exponent, &self.modulus);
+            let (_, bytes) = decrypted_bytes.to_bytes_be();
+
+            Ok(Packet::from(bytes))
+        }
+
+        /// Decrypts a login block and checks its magic, the returned packet is positioned after it.
+        pub fn decrypt_login_block(&self, packet: Packet, packet_length: usize) -> Result<Packet, RsaError> {
+            let mut decrypted = self.decrypt_block(packet, packet_length)?;
+
+            let magic = decrypted.g1();
+            if magic != LOGIN_BLOCK_MAGIC {
+                return Err(RsaError::BadBlock(magic));
+            }
+
+            Ok(decrypted)
+        }
+
+        /// Reads a key written by [`RsaKeyPair::write`], `modulus=` and `exponent=` lines in decimal.
+        pub fn parse(contents: &str) -> Result<RsaKey, RsaError> {
+            let mut modulus = None;
+            let mut exponent = None;
+
+            for line in contents.lines().map(str::trim) {
+                if line.is_empty() || line.starts_with('#') {
+                    continue;
+                }
+
+                let (name, value) = line.split_once('=')
+                    .ok_or_else(|| RsaError::InvalidKeyFile(format!("Expected name=value, got {:?}", line)))?;
+                let value = BigInt::parse_bytes(value.trim().as_bytes(), 10)
+                    .ok_or_else(|| RsaError::InvalidKeyFile(format!("{} is not a decimal number", name.trim())))?;
+
+                match name.trim() {
+                    "modulus" => modulus = Some(value),
+                    "exponent" => exponent = Some(value),
+                    other => return Err(RsaError::InvalidKeyFile(format!("Unknown entry {}", other))),
+                }
+            }
+
+            match (modulus, exponent) {
+                (Some(modulus), Some(exponent)) => Ok(RsaKey { modulus, exponent }),
+                _ => Err(RsaError::InvalidKeyFile("Missing modulus or exponent".to_string())),
+            }
+        }
+
+        pub fn read(path: &str) -> Result<RsaKey, RsaError> {
+            let contents = fs::read_to_string(path)
+                .map_err(|e| RsaError::Io(format!("Failed to read {}: {}", path, e)))?;
+            Self::parse(&contents)
+        }
+    }
+
+    #[derive(Debug, Clone, PartialEq)]
+    pub struct RsaKeyPair {
+        pub modulus: BigUint,
+        pub public_exponent: BigUint,
+        pub private_exponent: BigUint,
+    }
+
+    impl RsaKeyPair {
+        pub fn generate<R: Rng>(bits: u64, rng: &mut R) -> Result<RsaKeyPair, RsaError> {
+            if !SUPPORTED_KEY_SIZES.contains(&bits) {
+                return Err(RsaError::UnsupportedKeySize(bits));
+            }
+
+            let public_exponent = BigUint::from(PUBLIC_EXPONENT);
+            let one = BigUint::from(1u32);
+
+            loop {
+                let p = generate_prime(bits / 2, rng);
+                let q = generate_prime(bits / 2, rng);
+                if p == q {
+                    continue;
+                }
+
+                let modulus = &p * &q;
+                if modulus.bits() != bits {
+                    continue;
+                }
+
+                let totient = (&p - &one) * (&q - &one);
+                // Only fails when e divides the totient, pick new primes.
+                if let Some(private_exponent) = public_exponent.modinv(&totient) {
+                    return Ok(RsaKeyPair { modulus, public_exponent, private_exponent });
+                }
+            }
+        }
+
+        pub fn private_key(&self) -> RsaKey {
+            RsaKey {
+                modulus: BigInt::from(self.modulus.clone()),
+                exponent: BigInt::from(self.private_exponent.clone()),
+            }
+        }
+
+        /// What the client does with the login block, only used to check a key works.
+        pub fn encrypt(&self, block: &[u8]) -> Vec<u8> {
+            BigUint::from_bytes_be(block).modpow(&self.public_exponent, &self.modulus).to_bytes_be()
+        }
+
+        /// Writes `private.key` for the server and `public.key` with the values to patch into the client.
+        pub fn write(&self, dir: &str) -> Result<(), RsaError> {
+            fs::create_dir_all(dir).map_err(|e| RsaError::Io(format!("Failed to create {}: {}", dir, e)))?;
+
+            let private = format!(
+                "# Login RSA private key, keep this out of version control.\nmodulus={}\nexponent={}\n",
+                self.modulus, self.private_exponent
+            );
+            let public = format!(
+                "# Patch these into the client's login RSA modulus and exponent.\nmodulus={}\nexponent={}\n",
+                self.modulus, self.public_exponent
+            );
+
+            // Only the private key needs to stay unreadable to other users.
+            for (name, contents, mode) in [("private.key", private, 0o600), ("public.key", public, 0o644)] {
+                let path = Path::new(dir).join(name);
+                write_file(&path, &contents, mode).map_err(|e| RsaError::Io(format!("Failed to write {}: {}", path.display(), e)))?;
+            }
+            Ok(())
+        }
+    }
+
+    fn write_file(path: &Path, contents: &str, mode: u32) -> std::io::Result<()> {
+        let mut options = fs::OpenOptions::new();
+        options.write(true).create(true).truncate(true);
+        #[cfg(unix)]
+        {
+            use std::os::unix::fs::{OpenOptionsExt, PermissionsExt};
+            options.mode(mode);
+            // The mode only applies when the file is created, an existing key keeps its old permissions otherwise.
+            if path.exists() {
+                fs::set_permissions(path, fs::Permissions::from_mode(mode))?;
+            }
+        }
+        #[cfg(not(unix))]
+        let _ = mode;
+        options.open(path)?.write_all(contents.as_bytes())
+    }
+
+    /// Random odd number with exactly `bits` bits, the top two set so two of them multiply to twice the size.
+    fn random_candidate<R: Rng>(bits: u64, rng: &mut R) -> BigUint {
+        let mut bytes = vec![0u8; bits.div_ceil(8) as usize];
+        rng.fill(&mut bytes[..]);
+
+        let excess = bytes.len() as u64 * 8 - bits;
+        bytes[0] &= 0xFF >> excess;
+        bytes[0] |= 0xC0 >> excess;
+        let last = bytes.len() - 1;
+        bytes[last] |= 1;
+
+        BigUint::from_bytes_be(&bytes)
+    }
+
+    fn generate_prime<R: Rng>(bits: u64, rng: &mut R) -> BigUint {
+        loop {
+            let candidate = random_candidate(bits, rng);
+            if is_probable_prime(&candidate, MILLER_RABIN_ROUNDS, rng) {
+                return candidate;
+            }
+        }
+    }
+
+    /// Miller-Rabin with `rounds` random bases, trial division by small primes first.
+    pub fn is_probable_prime<R: Rng>(n: &BigUint, rounds: usize, rng: &mut R) -> bool {
+        let two = BigUint::from(2u32);
+        if *n < two {
+            return false;
+        }
+
+        for small in SMALL_PRIMES.iter().copied().chain(std::iter::once(2)) {
+            let small = BigUint::from(small);
+            if *n == small {
+                return true;
+            }
+            if (n % &small) == BigUint::ZERO {
+                return false;
+            }
+        }
+
+        let one = BigUint::from(1u32);
+        let n_minus_one = n - &one;
+
+        // n - 1 = d * 2^s with d odd
+        let s = n_minus_one.trailing_zeros().unwrap_or(0);
+        let d = &n_minus_one >> s;
+
+        let byte_length = n.bits().div_ceil(8) as usize;
+        let mut bytes = vec![0u8; byte_length];
+
+        'witness: for _ in 0..rounds {
+            // Base in [2, n - 2]
+            rng.fill(&mut bytes[..]);
+            let base = BigUint::from_bytes_be(&bytes) % (n - 3u32) + &two;
+
+            let mut x = base.modpow(&d, n);
+            if x == one || x == n_minus_one {
+                continue;
+            }
+
+            for _ in 1..s {
+                x = x.modpow(&two, n);
+                if x == n_minus_one {
+                    continue 'witness;
+                }
+            }
+
+            return false;
+        }
+
+        true
+    }
+
+    static KEY: OnceLock<RsaKey> = OnceLock::new();
+
+    /// Reads the login key at `path`, the built-in key is only used when the file doesn't exist.
+    /// A key that is there but can't be read or parsed is an error, never silently replaced.
+    pub fn resolve_key(path: &str) -> Result<RsaKey, RsaError> {
+        match fs::metadata(path) {
+            Ok(_) => {
+                info!("Loading RSA key from {}", path);
+                RsaKey::read(path)
+            }
+            Err(e) if e.kind() == ErrorKind::NotFound => {
+                warn!("No RSA key at {}, using the built-in key. Generate one with `engine keygen`.", path);
+                Ok(RsaKey::builtin())
+            }
+            Err(e) => Err(RsaError::Io(format!("Failed to read {}: {}", path, e))),
+        }
+    }
+
+    /// Loads the key every login is decrypted with, see [`resolve_key`].
+    pub fn load_key(path: &str) -> Result<(), RsaError> {
+        let key = resolve_key(path)?;
+
+        if KEY.set(key).is_err() {
+            warn!("RSA key already loaded, ignoring {}", path);
+        }
+        Ok(())
+    }
+
+    pub fn decrypt_login_block(packet: Packet, packet_length: usize) -> Result<Packet, RsaError> {
+        KEY.get().ok_or(RsaError::NotLoaded)?.decrypt_login_block(packet, packet_length)
+    }
+}
@@ -0,0 +1,118 @@
+use num_bigint::BigUint;
+use rand::rngs::StdRng;
+use rand::SeedableRng;
+use crate::io::packet::Packet;
+use crate::io::rsa::rsa::{is_probable_prime, resolve_key, RsaError, RsaKey, RsaKeyPair, LOGIN_BLOCK_MAGIC};
+
+fn login_packet(keypair: &RsaKeyPair, block: &[u8]) -> (Packet, usize) {
+    let encrypted = keypair.encrypt(block);
+    let length = encrypted.len();
+    (Packet::from(encrypted), length)
+}
+
+#[test]
+fn test_miller_rabin() {
+    let mut rng = StdRng::seed_from_u64(0);
+
+    for prime in [2u32, 3, 97, 101, 7919, 65537, 2147483647] {
+        assert!(is_probable_prime(&BigUint::from(prime), 20, &mut rng), "{} is prime", prime);
+    }
+
+    // 561 and 41041 are Carmichael numbers, 10403 = 101 * 103.
+    for composite in [0u32, 1, 4, 561, 10403, 41041, 2147483649] {
+        assert!(!is_probable_prime(&BigUint::from(composite), 20, &mut rng), "{} is composite", composite);
+    }
+}
+
+#[test]
+fn test_generated_key_decrypts_login_block() {
+    let mut rng = StdRng::seed_from_u64(530);
+    let keypair = RsaKeyPair::generate(512, &mut rng).unwrap();
+    assert_eq!(keypair.modulus.bits(), 512);
+
+    let (packet, length) = login_packet(&keypair, &[LOGIN_BLOCK_MAGIC, 1, 2, 3, 4]);
+    let mut decrypted = keypair.private_key().decrypt_login_block(packet, length).unwrap();
+
+    assert_eq!(decrypted.g1(), 1);
+    assert_eq!(decrypted.g1(), 2);
+}
+
+#[test]
+fn test_bad_block_is_an_error() {
+    let mut rng = StdRng::seed_from_u64(530);
+    let keypair = RsaKeyPair::generate(512, &mut rng).unwrap();
+
+    let (packet, length) = login_packet(&keypair, &[11, 1, 2, 3, 4]);
+    assert_eq!(keypair.private_key().decrypt_login_block(packet, length), Err(RsaError::BadBlock(11)));
+
+    let (packet, _) = login_packet(&keypair, &[LOGIN_BLOCK_MAGIC, 1]);
+    assert_eq!(keypair.private_key().decrypt_login_block(packet, 0), Err(RsaError::InvalidLength(0)));
+    assert_eq!(keypair.private_key().decrypt_login_block(Packet::from(vec![1; 65]), 65), Err(RsaError::InvalidLength(65)));
+}
+
+#[test]
+fn test_unsupported_key_size() {
+    let mut rng = StdRng::seed_from_u64(0);
+    assert_eq!(RsaKeyPair::generate(256, &mut rng), Err(RsaError::UnsupportedKeySize(256)));
+}
+
+#[test]
+fn test_key_files_round_trip() {
+    let mut rng = StdRng::seed_from_u64(1);
+    let keypair = RsaKeyPair::generate(512, &mut rng).unwrap();
+
+    let dir = std::env::temp_dir().join(format!("rsa_tests_{}", std::process::id()));
+    let dir = dir.to_string_lossy().to_string();
+    keypair.write(&dir).unwrap();
+
+    let private = RsaKey::read(&format!("{}/private.key", dir)).unwrap();
+    let public = RsaKey::read(&format!("{}/public.key", dir)).unwrap();
+    std::fs::remove_dir_all(&dir).unwrap();
+
+    assert_eq!(private, keypair.private_key());
+    assert_eq!(public.modulus, private.modulus);
+    assert_eq!(public.exponent.to_string(), "65537");
+}
+
+#[test]
+fn test_invalid_key_file() {
+    assert!(matches!(RsaKey::parse("modulus=12"), Err(RsaError::InvalidKeyFile(_))));
+    assert!(matches!(RsaKey::parse("modulus=abc\nexponent=3"), Err(RsaError::InvalidKeyFile(_))));
+    assert_eq!(RsaKey::parse("# comment\nmodulus=33\nexponent=7\n").unwrap().modulus.to_string(), "33");
+}
+
+#[test]
+fn test_resolve_key() {
+    let dir = std::env::temp_dir().join(format!("rsa_resolve_tests_{}", std::process::id()));
+    std::fs::create_dir_all(&dir).unwrap();
+    let path = dir.join("private.key").to_string_lossy().to_string();
+
+    assert_eq!(resolve_key(&path), Ok(RsaKey::builtin()));
+
+    std::fs::write(&path, "modulus=12\n").unwrap();
+    let corrupt = resolve_key(&path);
+    std::fs::remove_dir_all(&dir).unwrap();
+
+    assert!(matches!(corrupt, Err(RsaError::InvalidKeyFile(_))));
+}
+
+#[cfg(unix)]
+#[test]
+fn test_private_key_permissions() {
+    use std::os::unix::fs::PermissionsExt;
+
+    let mut rng = StdRng::seed_from_u64(2);
+    let keypair = RsaKeyPair::generate(512, &mut rng).unwrap();
+
+    let dir = std::env::temp_dir().join(format!("rsa_mode_tests_{}", std::process::id()));
+    std::fs::create_dir_all(&dir).unwrap();
+    // A key left over from before is tightened as well.
+    std::fs::write(dir.join("private.key"), "").unwrap();
+    std::fs::set_permissions(dir.join("private.key"), std::fs::Permissions::from_mode(0o644)).unwrap();
+    keypair.write(&dir.to_string_lossy()).unwrap();
+
+    let mode = std::fs::metadata(dir.join("private.key")).unwrap().permissions().mode();
+    std::fs::remove_dir_all(&dir).unwrap();
+
+    assert_eq!(mode & 0o777, 0o600);
+}
@@ -0,0 +1,23 @@
+use crate::io::packet::Packet;
+use crate::io::server::codec::message_encoder::MessageEncoder;
+use crate::io::server::model::if_closesub::If_CloseSub;
+use crate::io::server::protocol::server_protocol::ServerProtocol;
+
+pub struct If_CloseSub_Encoder;
+
+impl If_CloseSub_Encoder {
+    #[inline]
+    pub fn new() -> Self { If_CloseSub_Encoder }
+}
+
+impl MessageEncoder<If_CloseSub> for If_CloseSub_Encoder {
+    #[inline]
+    fn protocol(&self) -> ServerProtocol { ServerProtocol::IF_CLOSESUB }
+
+    fn encode(&self, packet: &mut Packet, message: If_CloseSub) {
+        let component_pointer = message.window_id << 16 | message.component_id;
+
+        packet.p2(message.verify_id as i32);
+        packet.p4(component_pointer as i32);
+    }
+}
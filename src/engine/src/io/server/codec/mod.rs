@@ -2,4 +2,6 @@ pub mod message_encoder;
 pub mod rebuild_normal_encoder;
 pub mod if_opentop_encoder;
 pub mod if_opensub_encoder;
-pub mod message_game_encoder;
\ No newline at end of file
+pub mod message_game_encoder;
+pub mod if_closesub_encoder;
+pub mod run_clientscript_encoder;
\ No newline at end of file
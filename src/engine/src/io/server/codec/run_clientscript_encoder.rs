@@ -0,0 +1,40 @@
+use crate::entity::entity_queue_request::ScriptArgument;
+use crate::io::packet::Packet;
+use crate::io::server::codec::message_encoder::MessageEncoder;
+use crate::io::server::model::run_clientscript::RunClientScript;
+use crate::io::server::protocol::server_protocol::ServerProtocol;
+
+pub struct RunClientScriptEncoder;
+
+impl RunClientScriptEncoder {
+    #[inline]
+    pub fn new() -> Self { RunClientScriptEncoder }
+}
+
+impl MessageEncoder<RunClientScript> for RunClientScriptEncoder {
+    #[inline]
+    fn protocol(&self) -> ServerProtocol { ServerProtocol::RUNCLIENTSCRIPT }
+
+    fn encode(&self, packet: &mut Packet, message: RunClientScript) {
+        let types: String = message.args.iter().map(|arg| match arg {
+            ScriptArgument::Number(_) => 'i',
+            ScriptArgument::String(_) => 's',
+        }).collect();
+
+        let mut temporary_packet = Packet::from(Vec::new());
+        temporary_packet.p2(message.verify_id as i32);
+        temporary_packet.pjstr(&types, 0);
+
+        // The client reads the arguments back to front.
+        for arg in message.args.iter().rev() {
+            match arg {
+                ScriptArgument::Number(value) => temporary_packet.p4(*value),
+                ScriptArgument::String(value) => temporary_packet.pjstr(value, 0),
+            }
+        }
+        temporary_packet.p4(message.script_id);
+
+        packet.p2(temporary_packet.position as i32);
+        packet.pbytes(&temporary_packet.data, 0, temporary_packet.position);
+    }
+}
\ No newline at end of file
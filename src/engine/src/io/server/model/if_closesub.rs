@@ -0,0 +1,16 @@
+#[derive(Debug, Clone, PartialEq)]
+pub struct If_CloseSub {
+    pub(crate) window_id: u32,
+    pub(crate) component_id: u32,
+    pub(crate) verify_id: u16,
+}
+
+impl If_CloseSub {
+    pub fn new(window_id: u32, component_id: u32, verify_id: u16) -> If_CloseSub {
+        If_CloseSub {
+            window_id,
+            component_id,
+            verify_id,
+        }
+    }
+}
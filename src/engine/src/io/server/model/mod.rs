@@ -1,4 +1,6 @@
 pub mod rebuild_normal;
 pub mod if_opentop;
 pub mod if_opensub;
-pub mod message_game;
\ No newline at end of file
+pub mod message_game;
+pub mod if_closesub;
+pub mod run_clientscript;
\ No newline at end of file
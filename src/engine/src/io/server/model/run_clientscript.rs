@@ -0,0 +1,18 @@
+use crate::entity::entity_queue_request::ScriptArgument;
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct RunClientScript {
+    pub(crate) script_id: i32,
+    pub(crate) args: Vec<ScriptArgument>,
+    pub(crate) verify_id: u16,
+}
+
+impl RunClientScript {
+    pub fn new(script_id: i32, args: Vec<ScriptArgument>, verify_id: u16) -> RunClientScript {
+        RunClientScript {
+            script_id,
+            args,
+            verify_id,
+        }
+    }
+}
\ No newline at end of file
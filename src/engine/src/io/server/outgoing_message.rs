@@ -1,5 +1,6 @@
 use crate::entity::player::Player;
 use crate::io::packet::Packet;
+use crate::io::server::model::if_closesub::If_CloseSub;
 use crate::io::server::model::if_opensub::If_OpenSub;
 use crate::io::server::model::if_opentop::If_OpenTop;
 use crate::io::server::model::rebuild_normal::RebuildNormal;
@@ -8,6 +9,7 @@ use crate::io::server::protocol::server_protocol_priority::ServerProtocolPriorit
 use crate::io::server::protocol::server_protocol_repository::ServerProtocolRepository;
 use std::fmt::Debug;
 use crate::io::server::model::message_game::Message_Game;
+use crate::io::server::model::run_clientscript::RunClientScript;
 
 pub trait OutgoingMessage: Debug + Send + PartialEq {
     fn priority(&self) -> ServerProtocolPriority;
@@ -48,7 +50,9 @@ macro_rules! define_outgoing_messages {
                     // Encode message directly
                     if let Some(encoder) = player.get_server_protocol_repository().get_encoder(self) {
                         encoder.encode(&mut player.client.outbound, self.clone());
-                        player.client.write_packet().expect("Write packet failed");
+                        if let Err(e) = player.client.write_packet() {
+                            log::debug!("Failed to write packet: {}", e);
+                        }
                     }
                 }
             }
@@ -114,5 +118,7 @@ define_outgoing_messages!(
     (RebuildNormal, RebuildNormal, ServerProtocolPriority::IMMEDIATE),
     (MessageGame, Message_Game, ServerProtocolPriority::IMMEDIATE),
     (IfOpenTop, If_OpenTop, ServerProtocolPriority::BUFFERED),
-    (IfOpenSub, If_OpenSub, ServerProtocolPriority::BUFFERED)
+    (IfOpenSub, If_OpenSub, ServerProtocolPriority::BUFFERED),
+    (IfCloseSub, If_CloseSub, ServerProtocolPriority::BUFFERED),
+    (RunClientScript, RunClientScript, ServerProtocolPriority::BUFFERED)
 );
\ No newline at end of file
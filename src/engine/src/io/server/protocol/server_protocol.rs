@@ -23,6 +23,8 @@ impl ServerProtocol {
     // Interfaces
     pub const IF_OPENTOP: ServerProtocol = ServerProtocol::new(145, 5);
     pub const IF_OPENSUB: ServerProtocol = ServerProtocol::new(155, 9);
+    pub const IF_CLOSESUB: ServerProtocol = ServerProtocol::new(149, 6);
+    pub const RUNCLIENTSCRIPT: ServerProtocol = ServerProtocol::new(115, -2);
     
     // Social
     pub const MESSAGE_GAME: ServerProtocol = ServerProtocol::new(70, -1);
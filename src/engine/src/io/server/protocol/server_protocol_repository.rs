@@ -1,14 +1,18 @@
 use std::any::{Any, TypeId};
 use fnv::FnvHashMap;
+use crate::io::server::codec::if_closesub_encoder::If_CloseSub_Encoder;
 use crate::io::server::codec::if_opensub_encoder::If_OpenSub_Encoder;
 use crate::io::server::codec::if_opentop_encoder::If_OpenTop_Encoder;
 use crate::io::server::codec::message_encoder::MessageEncoder;
 use crate::io::server::codec::message_game_encoder::Message_Game_Encoder;
 use crate::io::server::codec::rebuild_normal_encoder::RebuildNormalEncoder;
+use crate::io::server::codec::run_clientscript_encoder::RunClientScriptEncoder;
+use crate::io::server::model::if_closesub::If_CloseSub;
 use crate::io::server::model::if_opensub::If_OpenSub;
 use crate::io::server::model::if_opentop::If_OpenTop;
 use crate::io::server::model::message_game::Message_Game;
 use crate::io::server::model::rebuild_normal::RebuildNormal;
+use crate::io::server::model::run_clientscript::RunClientScript;
 use crate::io::server::outgoing_message::OutgoingMessage;
 use crate::io::server::protocol::server_protocol::ServerProtocol;
 
@@ -27,6 +31,8 @@ impl ServerProtocolRepository {
             .with::<RebuildNormal>(RebuildNormalEncoder::new())
             .with::<If_OpenTop>(If_OpenTop_Encoder::new())
             .with::<If_OpenSub>(If_OpenSub_Encoder::new())
+            .with::<If_CloseSub>(If_CloseSub_Encoder::new())
+            .with::<RunClientScript>(RunClientScriptEncoder::new())
             .with::<Message_Game>(Message_Game_Encoder::new())
             .build()
     }
@@ -2,7 +2,11 @@ pub mod engine;
 pub mod entity;
 pub mod grid;
 mod engine_stat;
+mod engine_tests;
 mod game_connection;
+mod game_connection_tests;
 pub mod io;
 pub mod util;
-pub mod script;
\ No newline at end of file
+pub mod script;
+pub mod simulation;
+mod simulation_tests;
\ No newline at end of file
@@ -1,11 +1,202 @@
+use std::net::TcpListener;
+use std::path::Path;
+use config::ServerConfig;
 use engine::engine::Engine;
+use engine::io::rsa::rsa::RsaKeyPair;
+use engine::util::cache::cache_verify::verify_cache;
+use engine::util::cache::obj_packer::pack_objs_to_overlay;
+use engine::util::pack_file::{pack_all, revalidate_pack};
+use engine::util::runescript_compiler::{compile_scripts, prepare_compiler};
+use engine::util::symbols::generate_server_symbols;
+use log::{error, info, warn};
+
+const USAGE: &str = "\
+Usage: engine [command]
+
+Commands:
+  run                               Start the world (default)
+  keygen [--bits 512|1024] [--out <dir>]
+                                    Generate a login RSA keypair, defaults to 1024 bits in the directory of paths.rsa_key
+  cache verify [--out <file>]       Check every group against its index and XTEA key, writes a JSON report to stdout or <file>
+  cache pack-objs [--in <file>]     Re-encode the objs in <file>, defaults to src/scripts/_unpack/all.obj under paths.data,
+                                    and write the changed groups to paths.overlay
+  compile                           Compile the scripts with the [compiler] settings, offline uses the pinned jar as is
+  pack [--check] [--orphans]        Give every config and script name under src of paths.data an id and rewrite the
+                                    .pack files, --check only reports, --orphans lists ids whose name is gone";
 
 fn main() {
     if std::env::var_os("RUST_LOG").is_none() {
         std::env::set_var("RUST_LOG", "debug");
     }
     env_logger::init();
-    
-    Engine::init();
-    Engine::get().start(true);
-}
\ No newline at end of file
+
+    let config = match config::init() {
+        Ok(config) => config,
+        Err(e) => {
+            error!("{}", e);
+            std::process::exit(1);
+        }
+    };
+
+    let args: Vec<String> = std::env::args().skip(1).collect();
+    let result = match args.first().map(String::as_str) {
+        None | Some("run") => run(config),
+        Some("keygen") => keygen(config, &args[1..]),
+        Some("cache") => match args.get(1).map(String::as_str) {
+            Some("verify") => cache_verify(config, &args[2..]),
+            Some("pack-objs") => cache_pack_objs(config, &args[2..]),
+            _ => Err(format!("Expected a cache command\n\n{}", USAGE)),
+        },
+        Some("compile") => compile(config),
+        Some("pack") => pack(config, &args[1..]),
+        Some("help") | Some("--help") | Some("-h") => {
+            println!("{}", USAGE);
+            Ok(())
+        }
+        Some(other) => Err(format!("Unknown command {:?}\n\n{}", other, USAGE)),
+    };
+
+    if let Err(e) = result {
+        error!("{}", e);
+        std::process::exit(1);
+    }
+}
+
+fn run(config: &ServerConfig) -> Result<(), String> {
+    let listen_addr = &config.network.game;
+    let listener = TcpListener::bind(listen_addr)
+        .map_err(|e| format!("Failed to bind to {}: {}", listen_addr, e))?;
+
+    let mut engine = Engine::new();
+    engine.start(Some(listener), true)
+}
+
+fn keygen(config: &ServerConfig, args: &[String]) -> Result<(), String> {
+    let mut bits = 1024;
+    let mut out = Path::new(&config.paths.rsa_key)
+        .parent()
+        .map(|dir| dir.to_string_lossy().to_string())
+        .unwrap_or_else(|| ".".to_string());
+
+    let mut args = args.iter();
+    while let Some(arg) = args.next() {
+        let value = args.next().ok_or_else(|| format!("Missing value for {}\n\n{}", arg, USAGE))?;
+        match arg.as_str() {
+            "--bits" => bits = value.parse().map_err(|_| format!("Invalid key size {:?}", value))?,
+            "--out" => out = value.clone(),
+            _ => return Err(format!("Unknown option {:?}\n\n{}", arg, USAGE)),
+        }
+    }
+
+    info!("Generating {} bit RSA keypair.", bits);
+    let keypair = RsaKeyPair::generate(bits, &mut rand::rng()).map_err(|e| e.to_string())?;
+    keypair.write(&out).map_err(|e| e.to_string())?;
+
+    info!("Wrote {}/private.key for the server and {}/public.key for the client.", out, out);
+    Ok(())
+}
+
+fn cache_verify(config: &ServerConfig, args: &[String]) -> Result<(), String> {
+    let mut out = None;
+
+    let mut args = args.iter();
+    while let Some(arg) = args.next() {
+        let value = args.next().ok_or_else(|| format!("Missing value for {}\n\n{}", arg, USAGE))?;
+        match arg.as_str() {
+            "--out" => out = Some(value.clone()),
+            _ => return Err(format!("Unknown option {:?}\n\n{}", arg, USAGE)),
+        }
+    }
+
+    let keys = cache::xtea::read_xtea_keys_by_group().unwrap_or_else(|e| {
+        warn!("No XTEA keys loaded, encrypted map groups will be reported as missing keys: {}", e);
+        Default::default()
+    });
+
+    info!("Verifying cache at {}", config.paths.cache);
+    let report = verify_cache(&config.paths.cache, &keys).map_err(|e| e.to_string())?;
+    let json = serde_json::to_string_pretty(&report).map_err(|e| e.to_string())?;
+
+    match out {
+        Some(path) => std::fs::write(&path, json).map_err(|e| format!("Failed to write {}: {}", path, e))?,
+        None => println!("{}", json),
+    }
+
+    info!(
+        "Checked {} groups in {} archives: {} broken, {} missing XTEA keys, {} mismatches.",
+        report.groups, report.archives, report.broken.len(), report.missing_xtea_keys.len(), report.mismatches.len()
+    );
+
+    if report.is_ok() {
+        Ok(())
+    } else {
+        Err("Cache verification failed".to_string())
+    }
+}
+
+fn cache_pack_objs(config: &ServerConfig, args: &[String]) -> Result<(), String> {
+    let mut src = format!("{}/src/scripts/_unpack/all.obj", config.paths.data);
+
+    let mut args = args.iter();
+    while let Some(arg) = args.next() {
+        let value = args.next().ok_or_else(|| format!("Missing value for {}\n\n{}", arg, USAGE))?;
+        match arg.as_str() {
+            "--in" => src = value.clone(),
+            _ => return Err(format!("Unknown option {:?}\n\n{}", arg, USAGE)),
+        }
+    }
+
+    info!("Packing objs from {}", src);
+    let written = pack_objs_to_overlay(Path::new(&src), &config.paths.cache, Path::new(&config.paths.overlay))
+        .map_err(|e| e.to_string())?;
+
+    info!("Wrote {} changed obj groups to {}, the cache picks them up on the next start.", written, config.paths.overlay);
+    Ok(())
+}
+
+fn compile(config: &ServerConfig) -> Result<(), String> {
+    // The compiler reads the script ids and command symbols
+    revalidate_pack();
+    generate_server_symbols();
+
+    prepare_compiler(&config.compiler).map_err(|e| e.to_string())?;
+    compile_scripts(&config.compiler).map_err(|e| e.to_string())
+}
+
+fn pack(config: &ServerConfig, args: &[String]) -> Result<(), String> {
+    let mut check = false;
+    let mut list_orphans = false;
+
+    for arg in args {
+        match arg.as_str() {
+            "--check" => check = true,
+            "--orphans" => list_orphans = true,
+            _ => return Err(format!("Unknown option {:?}\n\n{}", arg, USAGE)),
+        }
+    }
+
+    let reports = pack_all(&config.paths.data, !check)?;
+
+    let mut added = 0;
+    for report in &reports {
+        for (id, name) in &report.added {
+            info!("New {} {} is id {}", report.type_name, name, id);
+        }
+        if !report.orphans.is_empty() {
+            warn!("{}.pack keeps {} ids whose names are gone from the source.", report.type_name, report.orphans.len());
+        }
+        if list_orphans {
+            for (id, name) in &report.orphans {
+                info!("Orphan {} {}={}", report.type_name, id, name);
+            }
+        }
+        added += report.added.len();
+    }
+
+    if check && added > 0 {
+        return Err(format!("{} names have no id yet, run pack to assign them", added));
+    }
+
+    info!("Checked {} pack types, {} new ids.", reports.len(), added);
+    Ok(())
+}
@@ -0,0 +1,113 @@
+use criterion::{black_box, criterion_group, criterion_main, Criterion};
+use engine::engine::Engine;
+use engine::io::packet::Packet;
+use engine::script::script_file::{is_large_operand, ScriptFile};
+use engine::script::script_opcode::ScriptOpcode;
+use engine::script::script_opcode::ScriptOpcode::*;
+use engine::script::script_runner::ScriptRunner;
+use engine::script::script_state::ScriptState;
+
+const ITERATIONS: i32 = 10_000;
+
+/// The compiled form of a proc that loops `ITERATIONS` times:
+/// `while ($i < ITERATIONS) { $sum = calc($sum + $i % 7); $i = calc($i + 1); } return($sum);`
+fn loop_script() -> ScriptFile {
+    let instructions: [(ScriptOpcode, i32); 16] = [
+        (PUSH_INT_LOCAL, 0),
+        (PUSH_CONSTANT_INT, ITERATIONS),
+        (BRANCH_GREATER_THAN_OR_EQUALS, 11),
+        (PUSH_INT_LOCAL, 1),
+        (PUSH_INT_LOCAL, 0),
+        (PUSH_CONSTANT_INT, 7),
+        (MODULO, 0),
+        (ADD, 0),
+        (POP_INT_LOCAL, 1),
+        (PUSH_INT_LOCAL, 0),
+        (PUSH_CONSTANT_INT, 1),
+        (ADD, 0),
+        (POP_INT_LOCAL, 0),
+        (BRANCH, -14),
+        (PUSH_INT_LOCAL, 1),
+        (RETURN, 0),
+    ];
+
+    let mut packet = Packet::from(Vec::new());
+    packet.pjstr("[proc,bench_loop]", 0);
+    packet.pjstr("bench.rs2", 0);
+    packet.p4(0);
+    packet.p1(0);
+    packet.p2(0);
+
+    for (opcode, operand) in instructions {
+        packet.p2(opcode as i32);
+        if is_large_operand(opcode as i32) {
+            packet.p4(operand);
+        } else {
+            packet.p1(operand);
+        }
+    }
+
+    packet.p4(instructions.len() as i32);
+    packet.p2(2);
+    packet.p2(0);
+    packet.p2(0);
+    packet.p2(0);
+    packet.p1(0);
+    packet.p2(1);
+
+    let script = ScriptFile::decode(0, packet).expect("bench script decodes");
+    ScriptRunner::validate(&script).expect("bench script is valid");
+    script
+}
+
+/// The interpreter loop before the jump table, which hashed every opcode to find its handler.
+fn execute_hashed(state: &mut ScriptState, engine: &mut Engine) {
+    let handlers = ScriptRunner::get_handlers();
+
+    while state.execution == ScriptState::RUNNING {
+        state.opcount += 1;
+        state.pc += 1;
+
+        if state.pc >= state.script.opcodes.len() as i32 {
+            state.execution = ScriptState::FINISHED;
+            break;
+        }
+
+        let opcode = state.script.opcodes[state.pc as usize] as i32;
+        match handlers.get(&opcode) {
+            Some(handler) => handler(state, engine),
+            None => state.execution = ScriptState::ABORTED,
+        }
+    }
+}
+
+fn benchmark_script_loop(c: &mut Criterion) {
+    let script = loop_script();
+    let mut engine = Engine::new();
+    let expected: i32 = (0..ITERATIONS).map(|i| i % 7).sum();
+
+    let mut group = c.benchmark_group("script_loop");
+
+    group.bench_function("hashmap_dispatch", |b| {
+        b.iter(|| {
+            let mut state = ScriptRunner::init(script.clone(), None, None, None);
+            execute_hashed(&mut state, &mut engine);
+            assert_eq!(state.int_stack[0], expected);
+            black_box(state.opcount)
+        });
+    });
+
+    group.bench_function("table_dispatch", |b| {
+        b.iter(|| {
+            let mut state = ScriptRunner::init(script.clone(), None, None, None);
+            ScriptRunner::execute(&mut state, &mut engine, false, true);
+            assert_eq!(state.int_stack[0], expected);
+            black_box(state.opcount)
+        });
+    });
+
+    group.finish();
+}
+
+criterion_group!(benches, benchmark_script_loop);
+criterion_main!(benches);
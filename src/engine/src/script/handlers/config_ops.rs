@@ -0,0 +1,92 @@
+use crate::engine::Engine;
+use crate::script::script_opcode::ScriptOpcode;
+use crate::script::script_runner::CommandHandlers;
+use crate::script::script_state::ScriptState;
+use crate::util::cache::config::enum_type::EnumTypeProvider;
+use crate::util::cache::config::param_type::ParamTypeProvider;
+use crate::util::cache::config::seq_type::SeqTypeProvider;
+use crate::util::cache::config::struct_type::StructTypeProvider;
+use crate::util::cache::param_helper::ParamValue;
+use std::collections::HashMap;
+use std::sync::OnceLock;
+
+pub fn get_config_ops() -> &'static CommandHandlers {
+    static HANDLERS: OnceLock<CommandHandlers> = OnceLock::new();
+
+    HANDLERS.get_or_init(|| {
+        let mut handlers: CommandHandlers = HashMap::with_capacity(8);
+
+        handlers.insert(
+            ScriptOpcode::ENUM as i32,
+            |state: &mut ScriptState, _engine: &mut Engine| {
+                let key = state.pop_int();
+                let enum_id = state.pop_int();
+                let outputtype = state.pop_int();
+                let inputtype = state.pop_int();
+
+                let Some(enum_type) = EnumTypeProvider::get(enum_id) else {
+                    return state.abort(&format!("Invalid enum: {}", enum_id));
+                };
+                if enum_type.inputtype as i32 != inputtype || enum_type.outputtype as i32 != outputtype {
+                    return state.abort(&format!(
+                        "Enum {} maps {} to {}, not {} to {}",
+                        enum_id, enum_type.inputtype as char, enum_type.outputtype as char, inputtype as u8 as char, outputtype as u8 as char
+                    ));
+                }
+
+                push_value(state, enum_type.get(key));
+            }
+        );
+
+        handlers.insert(
+            ScriptOpcode::ENUM_GETOUTPUTCOUNT as i32,
+            |state: &mut ScriptState, _engine: &mut Engine| {
+                let enum_id = state.pop_int();
+
+                match EnumTypeProvider::get(enum_id) {
+                    Some(enum_type) => state.push_int(enum_type.output_count() as i32),
+                    None => state.abort(&format!("Invalid enum: {}", enum_id)),
+                }
+            }
+        );
+
+        handlers.insert(
+            ScriptOpcode::STRUCT_PARAM as i32,
+            |state: &mut ScriptState, _engine: &mut Engine| {
+                let param_id = state.pop_int();
+                let struct_id = state.pop_int();
+
+                let Some(param) = ParamTypeProvider::get(param_id) else {
+                    return state.abort(&format!("Invalid param: {}", param_id));
+                };
+                let Some(struct_type) = StructTypeProvider::get(struct_id) else {
+                    return state.abort(&format!("Invalid struct: {}", struct_id));
+                };
+
+                push_value(state, struct_type.param(param));
+            }
+        );
+
+        handlers.insert(
+            ScriptOpcode::SEQLENGTH as i32,
+            |state: &mut ScriptState, _engine: &mut Engine| {
+                let seq = state.pop_int();
+
+                match SeqTypeProvider::get(seq) {
+                    Some(seq_type) => state.push_int(seq_type.duration_ticks() as i32),
+                    None => state.abort(&format!("Invalid seq: {}", seq)),
+                }
+            }
+        );
+
+        handlers
+    })
+}
+
+/// Strings go on the string stack, everything else on the int stack.
+fn push_value(state: &mut ScriptState, value: ParamValue) {
+    match value {
+        ParamValue::String(value) => state.push_string(value),
+        ParamValue::Integer(value) => state.push_int(value),
+    }
+}
@@ -1,12 +1,19 @@
+use crate::engine::Engine;
 use crate::script::script_opcode::ScriptOpcode;
 use crate::script::script_runner::CommandHandlers;
 use crate::script::script_state::ScriptState;
 use std::collections::HashMap;
 use std::sync::OnceLock;
-use log::error;
 use crate::script::script_file::ScriptFile;
 use crate::script::script_provider::ScriptProvider;
 
+/// Scripts can't nest procs deeper than this.
+const MAX_FRAMES: usize = 50;
+/// The largest array DEFINE_ARRAY may create.
+const MAX_ARRAY_SIZE: i32 = 5000;
+/// The type char of an int array, every other type defaults its elements to -1.
+const ARRAY_TYPE_INT: i32 = 'i' as i32;
+
 pub fn get_core_ops() -> &'static CommandHandlers {
     static HANDLERS: OnceLock<CommandHandlers> = OnceLock::new();
 
@@ -15,61 +22,28 @@ pub fn get_core_ops() -> &'static CommandHandlers {
 
         handlers.insert(
             ScriptOpcode::PUSH_CONSTANT_INT as i32,
-            |state: &mut ScriptState| {
+            |state: &mut ScriptState, _engine: &mut Engine| {
                 state.push_int(state.get_int_operand())
             }
         );
         
-        handlers.insert(
-            ScriptOpcode::PUSH_VARP as i32,
-            |state: &mut ScriptState| {
-                error!("Unimplemented");
-            }
-        );
-        
-        handlers.insert(
-            ScriptOpcode::POP_VARP as i32,
-            |state: &mut ScriptState| {
-                error!("Unimplemented");
-            }
-        );
-
         handlers.insert(
             ScriptOpcode::PUSH_CONSTANT_STRING as i32,
-            |state: &mut ScriptState| {
-                match state.get_string_operand().parse() {
-                    Ok(str_value) => state.push_string(str_value),
-                    Err(e) => {
-                        error!("Failed to parse string operand: {:?}", e);
-                        state.push_string(String::new());
-                    }
-                }
+            |state: &mut ScriptState, _engine: &mut Engine| {
+                state.push_string(state.get_string_operand().to_string());
             }
         );
         
-        handlers.insert(
-            ScriptOpcode::PUSH_VARN as i32,
-            |state: &mut ScriptState| {
-                error!("Unimplemented");
-            }
-        );
-        
-        handlers.insert(
-            ScriptOpcode::POP_VARN as i32,
-            |state: &mut ScriptState| {
-                error!("Unimplemented");
-            }
-        );
         handlers.insert(
             ScriptOpcode::BRANCH as i32,
-            |state: &mut ScriptState| {
+            |state: &mut ScriptState, _engine: &mut Engine| {
                 state.pc += state.get_int_operand();
             }
         );
         
         handlers.insert(
             ScriptOpcode::BRANCH_NOT as i32,
-            |state: &mut ScriptState| {
+            |state: &mut ScriptState, _engine: &mut Engine| {
                 let b = state.pop_int();
                 let a = state.pop_int();
                 
@@ -81,7 +55,7 @@ pub fn get_core_ops() -> &'static CommandHandlers {
         
         handlers.insert(
             ScriptOpcode::BRANCH_EQUALS as i32,
-            |state: &mut ScriptState| {
+            |state: &mut ScriptState, _engine: &mut Engine| {
                 let b = state.pop_int();
                 let a = state.pop_int();
                 
@@ -93,7 +67,7 @@ pub fn get_core_ops() -> &'static CommandHandlers {
         
         handlers.insert(
             ScriptOpcode::BRANCH_LESS_THAN as i32,
-            |state: &mut ScriptState| {
+            |state: &mut ScriptState, _engine: &mut Engine| {
                 let b = state.pop_int();
                 let a = state.pop_int();
                 
@@ -105,7 +79,7 @@ pub fn get_core_ops() -> &'static CommandHandlers {
         
         handlers.insert(
             ScriptOpcode::BRANCH_GREATER_THAN as i32,
-            |state: &mut ScriptState| {
+            |state: &mut ScriptState, _engine: &mut Engine| {
                 let b = state.pop_int();
                 let a = state.pop_int();
                 
@@ -115,23 +89,9 @@ pub fn get_core_ops() -> &'static CommandHandlers {
             }
         );
         
-        handlers.insert(
-            ScriptOpcode::PUSH_VARS as i32,
-            |state: &mut ScriptState| {
-                error!("Unimplemented");
-            }
-        );
-        
-        handlers.insert(
-            ScriptOpcode::POP_VARS as i32,
-            |state: &mut ScriptState| {
-                error!("Unimplemented");
-            }
-        );
-
         handlers.insert(
             ScriptOpcode::RETURN as i32,
-            |state: &mut ScriptState| {
+            |state: &mut ScriptState, _engine: &mut Engine| {
                 if state.fp == 0 {
                     state.execution = ScriptState::FINISHED;
                     return;
@@ -142,67 +102,41 @@ pub fn get_core_ops() -> &'static CommandHandlers {
 
         handlers.insert(
             ScriptOpcode::GOSUB as i32,
-            |state: &mut ScriptState| {
-                if state.fp >= 50 {
-                    error!("Stack overflow");
-                }
-
-                let proc: Option<ScriptFile> = ScriptProvider::get(state.pop_int() as usize);
-                if let Some(proc) = proc {
-                    state.gosub_frame(proc)
-                } else {
-                    error!("Unable to find proc: {:?}", proc);
-                }
+            |state: &mut ScriptState, _engine: &mut Engine| {
+                let id = state.pop_int();
+                gosub(state, id);
             }
         );
 
         handlers.insert(
             ScriptOpcode::JUMP as i32,
-            |state: &mut ScriptState| {
-                let label: Option<ScriptFile> = ScriptProvider::get(state.pop_int() as usize);
-                if label.is_some() {
-                    error!("Unable to find label: {:?}", label);
-                }
-                state.goto_frame(label.unwrap());
+            |state: &mut ScriptState, _engine: &mut Engine| {
+                let id = state.pop_int();
+                jump(state, id);
             }
         );
         
         handlers.insert(
             ScriptOpcode::SWITCH as i32,
-            |state: &mut ScriptState| {
-                /*let key = state.pop_int();
-                let operand = state.get_int_operand() as usize;
-                let table: Option<SwitchTable> = &state.script.switch_tables[operand];
-                
-                if table.is_none() {
-                    return;
+            |state: &mut ScriptState, _engine: &mut Engine| {
+                let key = state.pop_int();
+                let operand = state.get_int_operand();
+
+                let offset = match state.script.switch_tables.get(operand as usize) {
+                    Some(table) => table.get(key),
+                    None => return state.abort(&format!("Invalid switch table: {}", operand)),
+                };
+
+                // A key without a case falls through to the instruction after the switch.
+                if let Some(offset) = offset {
+                    state.pc += offset;
                 }
-                
-                let result = table.unwrap().get(&key);
-                if let Some(result) = result {
-                    state.pc += result.unwrap();
-                }*/
-                error!("Unimplemented");
-            }
-        );
-        
-        handlers.insert(
-            ScriptOpcode::PUSH_VARBIT as i32,
-            |state: &mut ScriptState| {
-                error!("Unimplemented");
-            }
-        );
-        
-        handlers.insert(
-            ScriptOpcode::POP_VARBIT as i32,
-            |state: &mut ScriptState| {
-                error!("Unimplemented");
             }
         );
         
         handlers.insert(
             ScriptOpcode::BRANCH_LESS_THAN_OR_EQUALS as i32,
-            |state: &mut ScriptState| {
+            |state: &mut ScriptState, _engine: &mut Engine| {
                 let b = state.pop_int();
                 let a = state.pop_int();
                 
@@ -214,7 +148,7 @@ pub fn get_core_ops() -> &'static CommandHandlers {
         
         handlers.insert(
             ScriptOpcode::BRANCH_GREATER_THAN_OR_EQUALS as i32,
-            |state: &mut ScriptState| {
+            |state: &mut ScriptState, _engine: &mut Engine| {
                 let b = state.pop_int();
                 let a = state.pop_int();
                 
@@ -226,123 +160,149 @@ pub fn get_core_ops() -> &'static CommandHandlers {
 
         handlers.insert(
             ScriptOpcode::PUSH_INT_LOCAL as i32,
-            |state: &mut ScriptState| {
+            |state: &mut ScriptState, _engine: &mut Engine| {
                 state.push_int(state.int_locals[state.get_int_operand() as usize])
             }
         );
 
         handlers.insert(
             ScriptOpcode::POP_INT_LOCAL as i32,
-            |state: &mut ScriptState| {
+            |state: &mut ScriptState, _engine: &mut Engine| {
                 let operand = state.get_int_operand() as usize; 
                 state.int_locals[operand] = state.pop_int();
             }
         );
 
-        /*handlers.insert(
+        handlers.insert(
             ScriptOpcode::PUSH_STRING_LOCAL as i32,
-            |state: &mut ScriptState| {
-                state.push_string(std::mem::replace(&mut state.string_locals[state.get_int_operand() as usize], String::new()));
+            |state: &mut ScriptState, _engine: &mut Engine| {
+                state.push_string(state.string_locals[state.get_int_operand() as usize].clone())
             }
-        );*/
+        );
         
-        /*handlers.insert(
+        handlers.insert(
             ScriptOpcode::POP_STRING_LOCAL as i32,
-            |state: &mut ScriptState| {
-                state.string_locals[state.get_int_operand() as usize] = state.pop_string();
+            |state: &mut ScriptState, _engine: &mut Engine| {
+                let operand = state.get_int_operand() as usize;
+                state.string_locals[operand] = state.pop_string();
             }
-        );*/
+        );
         
         handlers.insert(
             ScriptOpcode::JOIN_STRING as i32,
-            |state: &mut ScriptState| {
-                let count = state.get_int_operand();
-                
-                let mut strings = Vec::with_capacity(count as usize);
-                for _i in 0..count {
-                    strings.push(state.pop_string());
-                }
-                strings.reverse();
-                state.push_string(strings.join(""));
+            |state: &mut ScriptState, _engine: &mut Engine| {
+                let count = state.get_int_operand() as usize;
+                let strings = state.pop_strings(count);
+                state.push_string(strings.concat());
             }
         );
 
         handlers.insert(
             ScriptOpcode::POP_INT_DISCARD as i32,
-            |state: &mut ScriptState| {
+            |state: &mut ScriptState, _engine: &mut Engine| {
                 state.isp -= 1
             }
         );
 
         handlers.insert(
             ScriptOpcode::POP_STRING_DISCARD as i32,
-            |state: &mut ScriptState| {
+            |state: &mut ScriptState, _engine: &mut Engine| {
                 state.ssp -= 1
             }
         );
         
         handlers.insert(
             ScriptOpcode::GOSUB_WITH_PARAMS as i32,
-            |state: &mut ScriptState| {
-                if state.fp >= 50 {
-                    error!("Stack overflow");
-                }
-
-                let proc: Option<ScriptFile> = ScriptProvider::get(state.get_int_operand() as usize);
-                if let Some(proc) = proc {
-                    state.gosub_frame(proc)
-                } else {
-                    error!("Unable to find proc: {:?}", proc);
-                }
+            |state: &mut ScriptState, _engine: &mut Engine| {
+                gosub(state, state.get_int_operand());
             }
         );
         
         handlers.insert(
             ScriptOpcode::JUMP_WITH_PARAMS as i32,
-            |state: &mut ScriptState| {
-                let label: Option<ScriptFile> = ScriptProvider::get(state.get_int_operand() as usize);
-                if label.is_some() {
-                    error!("Unable to find label: {:?}", label);
-                }
-                state.goto_frame(label.unwrap());
-            }
-        );
-        
-        handlers.insert(
-            ScriptOpcode::PUSH_VARC_INT as i32,
-            |state: &mut ScriptState| {
-                error!("Unimplemented");
-            }
-        );
-        
-        handlers.insert(
-            ScriptOpcode::POP_VARC_INT as i32,
-            |state: &mut ScriptState| {
-                error!("Unimplemented");
+            |state: &mut ScriptState, _engine: &mut Engine| {
+                jump(state, state.get_int_operand());
             }
         );
         
         handlers.insert(
             ScriptOpcode::DEFINE_ARRAY as i32,
-            |state: &mut ScriptState| {
-                error!("Unimplemented");
+            |state: &mut ScriptState, _engine: &mut Engine| {
+                let operand = state.get_int_operand();
+                let id = (operand >> 16) as usize;
+                let array_type = operand & 0xFFFF;
+                let size = state.pop_int();
+
+                if !(0..=MAX_ARRAY_SIZE).contains(&size) {
+                    return state.abort(&format!("Invalid array size: {}", size));
+                }
+
+                let default = if array_type == ARRAY_TYPE_INT { 0 } else { -1 };
+                if state.int_arrays.len() <= id {
+                    state.int_arrays.resize(id + 1, Vec::new());
+                }
+                state.int_arrays[id] = vec![default; size as usize];
             }
         );
         
         handlers.insert(
             ScriptOpcode::PUSH_ARRAY_INT as i32,
-            |state: &mut ScriptState| {
-                error!("Unimplemented");
+            |state: &mut ScriptState, _engine: &mut Engine| {
+                let id = state.get_int_operand();
+                let index = state.pop_int();
+
+                match array_index(state, id, index) {
+                    Ok((id, index)) => state.push_int(state.int_arrays[id][index]),
+                    Err(err) => state.abort(&err),
+                }
             }
         );
         
         handlers.insert(
             ScriptOpcode::POP_ARRAY_INT as i32,
-            |state: &mut ScriptState| {
-                error!("Unimplemented");
+            |state: &mut ScriptState, _engine: &mut Engine| {
+                let id = state.get_int_operand();
+                let value = state.pop_int();
+                let index = state.pop_int();
+
+                match array_index(state, id, index) {
+                    Ok((id, index)) => state.int_arrays[id][index] = value,
+                    Err(err) => state.abort(&err),
+                }
             }
         );
         
         handlers
     })
+}
+
+/// Calls proc `id`, its arguments are taken from the stack.
+fn gosub(state: &mut ScriptState, id: i32) {
+    if state.fp >= MAX_FRAMES {
+        return state.abort("Stack overflow");
+    }
+
+    let proc: Option<ScriptFile> = ScriptProvider::get(id as usize);
+    match proc {
+        Some(proc) => state.gosub_frame(proc),
+        None => state.abort(&format!("Unable to find proc: {}", id)),
+    }
+}
+
+/// Jumps to label `id`, which never returns to the current script.
+fn jump(state: &mut ScriptState, id: i32) {
+    let label: Option<ScriptFile> = ScriptProvider::get(id as usize);
+    match label {
+        Some(label) => state.goto_frame(label),
+        None => state.abort(&format!("Unable to find label: {}", id)),
+    }
+}
+
+/// Checks `index` is within array `id`, both as indices into `int_arrays`.
+fn array_index(state: &ScriptState, id: i32, index: i32) -> Result<(usize, usize), String> {
+    let array = state.int_arrays.get(id as usize).ok_or_else(|| format!("Array {} is not defined", id))?;
+    if index < 0 || index as usize >= array.len() {
+        return Err(format!("Array index {} out of bounds for size {}", index, array.len()));
+    }
+    Ok((id as usize, index as usize))
 }
\ No newline at end of file
@@ -1,74 +1,76 @@
 use std::collections::HashMap;
-use std::mem::offset_of;
 use std::sync::OnceLock;
+use lazy_static::lazy_static;
+use rand::Rng;
+use crate::engine::Engine;
 use crate::script::script_opcode::ScriptOpcode;
 use crate::script::script_runner::CommandHandlers;
 use crate::script::script_state::ScriptState;
-use crate::util::bits::{bitcount, clear_bit_range, set_bit_range, MASK};
+use crate::util::bits::{bit_mask, bitcount, clear_bit_range, range_mask, set_bit_range};
 use crate::util::trig::Trig;
 
+lazy_static! {
+    static ref TRIG: Trig = Trig::new();
+}
+
 pub fn get_math_ops() -> &'static CommandHandlers {
     static HANDLERS: OnceLock<CommandHandlers> = OnceLock::new();
 
     HANDLERS.get_or_init(|| {
-        let mut handlers: CommandHandlers = HashMap::with_capacity(28);
+        let mut handlers: CommandHandlers = HashMap::with_capacity(29);
 
         handlers.insert(
             ScriptOpcode::ADD as i32,
-            |state: &mut ScriptState| {
+            |state: &mut ScriptState, _engine: &mut Engine| {
                 let b = state.pop_int();
                 let a = state.pop_int();
 
-                state.push_int(a + b);
+                state.push_int(a.wrapping_add(b));
             }
         );
 
         handlers.insert(
             ScriptOpcode::SUB as i32,
-            |state: &mut ScriptState| {
+            |state: &mut ScriptState, _engine: &mut Engine| {
                 let b = state.pop_int();
                 let a = state.pop_int();
 
-                state.push_int(a - b);
+                state.push_int(a.wrapping_sub(b));
             }
         );
 
         handlers.insert(
             ScriptOpcode::MULTIPLY as i32,
-            |state: &mut ScriptState| {
+            |state: &mut ScriptState, _engine: &mut Engine| {
                 let b = state.pop_int();
                 let a = state.pop_int();
 
-                state.push_int(a * b);
+                state.push_int(a.wrapping_mul(b));
             }
         );
 
         handlers.insert(
             ScriptOpcode::DIVIDE as i32,
-            |state: &mut ScriptState| {
+            |state: &mut ScriptState, _engine: &mut Engine| {
                 let b = state.pop_int();
                 let a = state.pop_int();
 
-                state.push_int(a / b);
+                if b == 0 {
+                    return state.abort("Division by zero");
+                }
+                state.push_int(a.wrapping_div(b));
             }
         );
 
         handlers.insert(
             ScriptOpcode::RANDOM as i32,
-            |state: &mut ScriptState| {
-                use rand::rngs::ThreadRng;
-                use rand::Rng;
-
-                thread_local! {
-                    static RNG: std::cell::RefCell<ThreadRng> = std::cell::RefCell::new(rand::thread_rng());
-                }
-
+            |state: &mut ScriptState, engine: &mut Engine| {
                 let a = state.pop_int();
 
                 let random_value = if a <= 0 {
                     0
                 } else {
-                    RNG.with(|rng| rng.borrow_mut().random_range(0..a))
+                    engine.rng.random_range(0..a)
                 };
 
                 state.push_int(random_value);
@@ -77,20 +79,13 @@ pub fn get_math_ops() -> &'static CommandHandlers {
 
         handlers.insert(
             ScriptOpcode::RANDOMINC as i32,
-            |state: &mut ScriptState| {
-                use rand::rngs::ThreadRng;
-                use rand::Rng;
-
-                thread_local! {
-                    static RNG: std::cell::RefCell<ThreadRng> = std::cell::RefCell::new(rand::thread_rng());
-                }
-
+            |state: &mut ScriptState, engine: &mut Engine| {
                 let a = state.pop_int();
 
                 let random_value = if a < 0 {
                     0
                 } else {
-                    RNG.with(|rng| rng.borrow_mut().gen_range(0..=a))
+                    engine.rng.random_range(0..=a)
                 };
 
                 state.push_int(random_value);
@@ -99,7 +94,7 @@ pub fn get_math_ops() -> &'static CommandHandlers {
 
         handlers.insert(
             ScriptOpcode::INTERPOLATE as i32,
-            |state: &mut ScriptState| {
+            |state: &mut ScriptState, _engine: &mut Engine| {
                 let x = state.pop_int();
                 let x1 = state.pop_int();
                 let x0 = state.pop_int();
@@ -110,9 +105,9 @@ pub fn get_math_ops() -> &'static CommandHandlers {
                 let lerp = if x1 == x0 {
                     y0
                 } else {
-                    // Calculate linear interpolation
-                    let slope = (y1 - y0) / (x1 / x0);
-                    (slope * (x - x0)) + y0
+                    // The slope is floored before scaling, the same as the original engine.
+                    let slope = (y1.wrapping_sub(y0) as f64 / x1.wrapping_sub(x0) as f64).floor() as i32;
+                    slope.wrapping_mul(x.wrapping_sub(x0)).wrapping_add(y0)
                 };
 
                 state.push_int(lerp);
@@ -121,11 +116,11 @@ pub fn get_math_ops() -> &'static CommandHandlers {
 
         handlers.insert(
             ScriptOpcode::ADDPERCENT as i32,
-            |state: &mut ScriptState| {
+            |state: &mut ScriptState, _engine: &mut Engine| {
                 let percent = state.pop_int();
                 let num = state.pop_int();
 
-                let result = ((num * percent) / 100) + num;
+                let result = (num.wrapping_mul(percent) / 100).wrapping_add(num);
 
                 state.push_int(result);
             }
@@ -133,46 +128,60 @@ pub fn get_math_ops() -> &'static CommandHandlers {
 
         handlers.insert(
             ScriptOpcode::SETBIT as i32,
-            |state: &mut ScriptState| {
+            |state: &mut ScriptState, _engine: &mut Engine| {
                 let bit = state.pop_int();
                 let value = state.pop_int();
 
-                state.push_int(value | (1 << bit));
+                let Some(mask) = bit_mask(bit) else {
+                    return state.abort(&format!("Invalid bit: {}", bit));
+                };
+                state.push_int(value | mask);
             }
         );
 
         handlers.insert(
             ScriptOpcode::CLEARBIT as i32,
-            |state: &mut ScriptState| {
+            |state: &mut ScriptState, _engine: &mut Engine| {
                 let bit = state.pop_int();
                 let value = state.pop_int();
 
-                state.push_int(value & !(1 << bit));
+                let Some(mask) = bit_mask(bit) else {
+                    return state.abort(&format!("Invalid bit: {}", bit));
+                };
+                state.push_int(value & !mask);
             }
         );
 
         handlers.insert(
             ScriptOpcode::TESTBIT as i32,
-            |state: &mut ScriptState| {
+            |state: &mut ScriptState, _engine: &mut Engine| {
                 let bit = state.pop_int();
                 let value = state.pop_int();
 
+                let Some(mask) = bit_mask(bit) else {
+                    return state.abort(&format!("Invalid bit: {}", bit));
+                };
                 // Test if the bit is set, return 1 if set, 0 if not
-                state.push_int(if (value & (1 << bit)) != 0 { 1 } else { 0 });
+                state.push_int(if (value & mask) != 0 { 1 } else { 0 });
             }
         );
 
         handlers.insert(
             ScriptOpcode::MODULO as i32,
-            |state: &mut ScriptState| {
-                let values = state.pop_ints(2);
-                state.push_int(values[0] % values[1]);
+            |state: &mut ScriptState, _engine: &mut Engine| {
+                let b = state.pop_int();
+                let a = state.pop_int();
+
+                if b == 0 {
+                    return state.abort("Division by zero");
+                }
+                state.push_int(a.wrapping_rem(b));
             }
         );
 
         handlers.insert(
             ScriptOpcode::POW as i32,
-            |state: &mut ScriptState| {
+            |state: &mut ScriptState, _engine: &mut Engine| {
                 let exponent = state.pop_int();
                 let base = state.pop_int();
 
@@ -183,7 +192,7 @@ pub fn get_math_ops() -> &'static CommandHandlers {
 
         handlers.insert(
             ScriptOpcode::INVPOW as i32,
-            |state: &mut ScriptState| {
+            |state: &mut ScriptState, _engine: &mut Engine| {
                 let n2 = state.pop_int();
                 let n1 = state.pop_int();
 
@@ -194,7 +203,7 @@ pub fn get_math_ops() -> &'static CommandHandlers {
                         1 => state.push_int(n1),
                         2 => state.push_int((n1 as f64).sqrt() as i32),
                         3 => state.push_int((n1 as f64).cbrt() as i32),
-                        4 => state.push_int((n1 as f64).sqrt().cbrt() as i32),
+                        4 => state.push_int((n1 as f64).sqrt().sqrt() as i32),
 
                         _ => {
                             let result = (n1 as f64).powf(1.0 / n2 as f64) as i32;
@@ -207,47 +216,57 @@ pub fn get_math_ops() -> &'static CommandHandlers {
 
         handlers.insert(
             ScriptOpcode::AND as i32,
-            |state: &mut ScriptState| {
-                let values = state.pop_ints(2);
-                state.push_int(values[0] & values[1]);
+            |state: &mut ScriptState, _engine: &mut Engine| {
+                let b = state.pop_int();
+                let a = state.pop_int();
+                state.push_int(a & b);
             }
         );
 
         handlers.insert(
             ScriptOpcode::OR as i32,
-            |state: &mut ScriptState| {
-                let values = state.pop_ints(2);
-                state.push_int(values[0] | values[1]);
+            |state: &mut ScriptState, _engine: &mut Engine| {
+                let b = state.pop_int();
+                let a = state.pop_int();
+                state.push_int(a | b);
             }
         );
 
         handlers.insert(
             ScriptOpcode::MIN as i32,
-            |state: &mut ScriptState| {
-                let values = state.pop_ints(2);
-                state.push_int(std::cmp::min(values[0], values[1]));
+            |state: &mut ScriptState, _engine: &mut Engine| {
+                let b = state.pop_int();
+                let a = state.pop_int();
+                state.push_int(std::cmp::min(a, b));
             }
         );
 
         handlers.insert(
             ScriptOpcode::MAX as i32,
-            |state: &mut ScriptState| {
-                let values = state.pop_ints(2);
-                state.push_int(std::cmp::max(values[0], values[1]));
+            |state: &mut ScriptState, _engine: &mut Engine| {
+                let b = state.pop_int();
+                let a = state.pop_int();
+                state.push_int(std::cmp::max(a, b));
             }
         );
 
         handlers.insert(
             ScriptOpcode::SCALE as i32,
-            |state: &mut ScriptState| {
+            |state: &mut ScriptState, _engine: &mut Engine| {
                 let values = state.pop_ints(3);
-                state.push_int((values[0] * values[1]) / values[2]);
+                let (a, b, c) = (values[0] as i64, values[1] as i64, values[2] as i64);
+
+                // Scales c by a/b, the product can't overflow before it's divided.
+                if b == 0 {
+                    return state.abort("Division by zero");
+                }
+                state.push_int((a * c / b) as i32);
             }
         );
 
         handlers.insert(
             ScriptOpcode::BITCOUNT as i32,
-            |state: &mut ScriptState| {
+            |state: &mut ScriptState, _engine: &mut Engine| {
                 let value = state.pop_int();
                 state.push_int(bitcount(value));
             }
@@ -255,36 +274,51 @@ pub fn get_math_ops() -> &'static CommandHandlers {
 
         handlers.insert(
             ScriptOpcode::TOGGLEBIT as i32,
-            |state: &mut ScriptState| {
-                let values = state.pop_ints(2);
-                state.push_int(values[0] ^ (1 << values[1]));
+            |state: &mut ScriptState, _engine: &mut Engine| {
+                let bit = state.pop_int();
+                let value = state.pop_int();
+
+                let Some(mask) = bit_mask(bit) else {
+                    return state.abort(&format!("Invalid bit: {}", bit));
+                };
+                state.push_int(value ^ mask);
             }
         );
 
         handlers.insert(
             ScriptOpcode::SETBIT_RANGE as i32,
-            |state: &mut ScriptState| {
+            |state: &mut ScriptState, _engine: &mut Engine| {
                 let values = state.pop_ints(3);
-                state.push_int(set_bit_range(values[0], values[1], values[2]));
+                match set_bit_range(values[0], values[1], values[2]) {
+                    Some(value) => state.push_int(value),
+                    None => state.abort(&format!("Invalid bit range: {}-{}", values[1], values[2])),
+                }
             }
         );
 
         handlers.insert(
-            ScriptOpcode::CLEARBIT as i32,
-            |state: &mut ScriptState| {
+            ScriptOpcode::CLEARBIT_RANGE as i32,
+            |state: &mut ScriptState, _engine: &mut Engine| {
                 let values = state.pop_ints(3);
-                state.push_int(clear_bit_range(values[0], values[1], values[2]));
+                match clear_bit_range(values[0], values[1], values[2]) {
+                    Some(value) => state.push_int(value),
+                    None => state.abort(&format!("Invalid bit range: {}-{}", values[1], values[2])),
+                }
             }
         );
 
         handlers.insert(
             ScriptOpcode::GETBIT_RANGE as i32,
-            |state: &mut ScriptState| {
+            |state: &mut ScriptState, _engine: &mut Engine| {
                 let values = state.pop_ints(3);
-                let a = 31 - values[2];
+                let (num, start_bit, end_bit) = (values[0], values[1], values[2]);
+                if range_mask(start_bit, end_bit).is_none() {
+                    return state.abort(&format!("Invalid bit range: {}-{}", start_bit, end_bit));
+                }
+                let a = 31 - end_bit;
 
-                let shifted_left = (values[0] << a) as u32;
-                let result = (shifted_left >> (values[1] + a)) as i32;
+                let shifted_left = (num << a) as u32;
+                let result = (shifted_left >> (start_bit + a)) as i32;
 
                 state.push_int(result);
             }
@@ -292,48 +326,48 @@ pub fn get_math_ops() -> &'static CommandHandlers {
 
         handlers.insert(
             ScriptOpcode::SETBIT_RANGE_TOINT as i32,
-            |state: &mut ScriptState| {
+            |state: &mut ScriptState, _engine: &mut Engine| {
                 let values = state.pop_ints(4);
-                let cleared_bit_range = clear_bit_range(values[0], values[2], values[3]);
-                let max_value = MASK[(values[3] - values[2] + 1) as usize];
-                let mut assign_value = values[1];
-                if values[1] > max_value {
-                    assign_value = max_value;
-                }
-                state.push_int(cleared_bit_range | (assign_value << values[2]));
+                let (num, value, start_bit, end_bit) = (values[0], values[1], values[2], values[3]);
+
+                let (Some(cleared_bit_range), Some(max_value)) = (clear_bit_range(num, start_bit, end_bit), range_mask(start_bit, end_bit)) else {
+                    return state.abort(&format!("Invalid bit range: {}-{}", start_bit, end_bit));
+                };
+                let assign_value = std::cmp::min(value, max_value);
+                state.push_int(cleared_bit_range | (assign_value << start_bit));
             }
         );
 
         handlers.insert(
             ScriptOpcode::SIN_DEG as i32,
-            |state: &mut ScriptState| {
+            |state: &mut ScriptState, _engine: &mut Engine| {
                 let value = state.pop_int();
-                //state.push_int(Trig::sin(value));
-                // TODO
+                state.push_int(TRIG.sin(value));
             }
         );
         
         handlers.insert(
             ScriptOpcode::COS_DEG as i32,
-            |state: &mut ScriptState| {
+            |state: &mut ScriptState, _engine: &mut Engine| {
                 let value = state.pop_int();
-                // TODO
+                state.push_int(TRIG.cos(value));
             }
         );
         
         handlers.insert(
             ScriptOpcode::ATAN2_DEG as i32,
-            |state: &mut ScriptState| {
-                let value = state.pop_int();
-                // TODO
+            |state: &mut ScriptState, _engine: &mut Engine| {
+                let x = state.pop_int();
+                let y = state.pop_int();
+                state.push_int(Trig::atan2(y, x));
             }
         );
 
         handlers.insert(
             ScriptOpcode::ABS as i32,
-            |state: &mut ScriptState| {
+            |state: &mut ScriptState, _engine: &mut Engine| {
                 let value = state.pop_int();
-                state.push_int(value.abs());
+                state.push_int(value.wrapping_abs());
             }
         );
 
@@ -1,3 +1,8 @@
 pub mod player_ops;
 pub mod core_ops;
-mod math_ops;
\ No newline at end of file
+pub mod math_ops;
+pub mod server_ops;
+pub mod npc_ops;
+pub mod config_ops;
+pub mod string_ops;
+mod ops_tests;
\ No newline at end of file
@@ -0,0 +1,74 @@
+use crate::script::script_opcode::ScriptOpcode;
+use crate::script::script_runner::CommandHandlers;
+use crate::script::script_state::ScriptState;
+use std::collections::HashMap;
+use std::sync::OnceLock;
+use crate::engine::Engine;
+use crate::util::cache::config::seq_type::SeqTypeProvider;
+use crate::util::cache::config::spot_anim_type::SpotAnimTypeProvider;
+
+pub fn get_npc_ops() -> &'static CommandHandlers {
+    static HANDLERS: OnceLock<CommandHandlers> = OnceLock::new();
+
+    HANDLERS.get_or_init(|| {
+        let mut handlers: CommandHandlers = HashMap::with_capacity(64); // TODO - update as need be
+
+        handlers.insert(
+            ScriptOpcode::NPC_ANIM as i32,
+            |state: &mut ScriptState, engine: &mut Engine| {
+                let delay = state.pop_int();
+                let seq = state.pop_int();
+
+                let seq_type = if seq == -1 {
+                    None
+                } else {
+                    match SeqTypeProvider::get(seq) {
+                        Some(seq_type) => Some(seq_type),
+                        None => return state.abort(&format!("Invalid seq: {}", seq)),
+                    }
+                };
+                let npc = match state.get_active_npc(engine) {
+                    Ok(npc) => npc,
+                    Err(err) => return state.abort(&err),
+                };
+                npc.pathing_entity.anim(seq_type, delay);
+            }
+        );
+
+        handlers.insert(
+            ScriptOpcode::NPC_DELAY as i32,
+            |state: &mut ScriptState, engine: &mut Engine| {
+                let delay = state.pop_int();
+                let current_tick = engine.current_tick;
+                let npc = match state.get_active_npc(engine) {
+                    Ok(npc) => npc,
+                    Err(err) => return state.abort(&err),
+                };
+
+                npc.pathing_entity.delayed = true;
+                npc.pathing_entity.delayed_until = current_tick + 1 + delay;
+                state.execution = ScriptState::NPC_SUSPENDED;
+            }
+        );
+
+        handlers.insert(
+            ScriptOpcode::SPOTANIM_NPC as i32,
+            |state: &mut ScriptState, engine: &mut Engine| {
+                let delay = state.pop_int();
+                let height = state.pop_int();
+                let spotanim = state.pop_int();
+
+                let Some(spotanim_type) = SpotAnimTypeProvider::get(spotanim) else {
+                    return state.abort(&format!("Invalid spotanim: {}", spotanim));
+                };
+                let npc = match state.get_active_npc(engine) {
+                    Ok(npc) => npc,
+                    Err(err) => return state.abort(&err),
+                };
+                npc.pathing_entity.spotanim(spotanim, spotanim_type.seq_type(), height, delay);
+            }
+        );
+
+        handlers
+    })
+}
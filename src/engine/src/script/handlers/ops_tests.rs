@@ -0,0 +1,375 @@
+use crate::engine::Engine;
+use crate::entity::block_walk::BlockWalk;
+use crate::entity::entity_lifecycle::EntityLifeCycle;
+use crate::entity::entity_type::EntityType;
+use crate::entity::move_restrict::MoveRestrict;
+use crate::entity::npc::NPC;
+use crate::entity::pathing_entity::PathingEntity;
+use crate::entity::player::Player;
+use crate::grid::coord_grid::CoordGrid;
+use crate::script::script_file::{ScriptFile, SwitchTable};
+use crate::script::script_opcode::ScriptOpcode;
+use crate::script::script_opcode::ScriptOpcode::*;
+use crate::script::script_runner::ScriptRunner;
+use crate::script::script_state::ScriptState;
+
+/// `(opcode, int operand, string operand)` instructions.
+type Instructions<'a> = &'a [(ScriptOpcode, i32, &'a str)];
+
+/// A proc made of `instructions`.
+fn script(instructions: Instructions) -> ScriptFile {
+    let mut script = ScriptFile::new(0);
+    script.info.lookup_key = 0;
+    script.int_local_count = 1;
+    script.string_local_count = 1;
+
+    for &(opcode, int_operand, string_operand) in instructions {
+        script.opcodes.push(opcode);
+        script.int_operands.push(int_operand);
+        script.string_operands.push(string_operand.to_string());
+    }
+    script
+}
+
+fn run(script: ScriptFile, engine: &mut Engine, self_entity: Option<EntityType>) -> ScriptState {
+    let mut state = ScriptRunner::init(script, self_entity, None, None);
+    ScriptRunner::execute(&mut state, engine, false, false);
+    state
+}
+
+/// Pushes `ints` and `strings` as constants, then runs `opcode`.
+fn call(opcode: ScriptOpcode, ints: &[i32], strings: &[&str]) -> ScriptState {
+    let mut instructions: Vec<(ScriptOpcode, i32, &str)> = Vec::new();
+    instructions.extend(ints.iter().map(|&int| (PUSH_CONSTANT_INT, int, "")));
+    instructions.extend(strings.iter().map(|&string| (PUSH_CONSTANT_STRING, 0, string)));
+    instructions.push((opcode, 0, ""));
+
+    run(script(&instructions), &mut Engine::new(), None)
+}
+
+fn ints(state: &ScriptState) -> &[i32] {
+    &state.int_stack[..state.isp]
+}
+
+fn strings(state: &ScriptState) -> &[String] {
+    &state.string_stack[..state.ssp]
+}
+
+#[test]
+fn test_math_ops() {
+    let cases: &[(ScriptOpcode, &[i32], i32)] = &[
+        (ADD, &[2, 3], 5),
+        (ADD, &[i32::MAX, 1], i32::MIN),
+        (SUB, &[2, 3], -1),
+        (MULTIPLY, &[6, 7], 42),
+        (DIVIDE, &[7, 2], 3),
+        (DIVIDE, &[-7, 2], -3),
+        (RANDOM, &[0], 0),
+        (RANDOM, &[1], 0),
+        (RANDOMINC, &[0], 0),
+        (INTERPOLATE, &[0, 100, 0, 10, 5], 50),
+        (INTERPOLATE, &[0, 10, 0, 4, 2], 4),
+        (INTERPOLATE, &[7, 10, 3, 3, 5], 7),
+        (INTERPOLATE, &[i32::MIN, i32::MAX, i32::MIN, i32::MAX, 0], 0),
+        (SETBIT, &[0, 31], i32::MIN),
+        (GETBIT_RANGE, &[-1, 0, 31], -1),
+        (ADDPERCENT, &[200, 10], 220),
+        (SETBIT, &[0, 3], 8),
+        (CLEARBIT, &[15, 0], 14),
+        (TESTBIT, &[8, 3], 1),
+        (TESTBIT, &[8, 2], 0),
+        (MODULO, &[7, 3], 1),
+        (POW, &[2, 10], 1024),
+        (INVPOW, &[81, 2], 9),
+        (INVPOW, &[27, 3], 3),
+        (INVPOW, &[16, 4], 2),
+        (INVPOW, &[0, 2], 0),
+        (AND, &[12, 10], 8),
+        (OR, &[12, 10], 14),
+        (MIN, &[3, -4], -4),
+        (MAX, &[3, -4], 3),
+        (SCALE, &[1, 2, 100], 50),
+        (SCALE, &[3, 4, 1_000_000_000], 750_000_000),
+        (BITCOUNT, &[0xFF], 8),
+        (TOGGLEBIT, &[5, 0], 4),
+        (SETBIT_RANGE, &[0, 4, 7], 0xF0),
+        (CLEARBIT_RANGE, &[0xFF, 4, 7], 0x0F),
+        (GETBIT_RANGE, &[0xF0, 4, 7], 0xF),
+        (SETBIT_RANGE_TOINT, &[0xFF, 3, 4, 7], 0x3F),
+        (SETBIT_RANGE_TOINT, &[0, 100, 0, 3], 0xF),
+        (SIN_DEG, &[0], 0),
+        (SIN_DEG, &[4096], 16384),
+        (COS_DEG, &[0], 16384),
+        (COS_DEG, &[8192], -16384),
+        (ATAN2_DEG, &[1, 0], 4096),
+        (ABS, &[-5], 5),
+    ];
+
+    for (opcode, args, expected) in cases {
+        let state = call(*opcode, args, &[]);
+        assert_eq!(state.execution, ScriptState::FINISHED, "{:?}{:?}", opcode, args);
+        assert_eq!(ints(&state), &[*expected], "{:?}{:?}", opcode, args);
+    }
+}
+
+#[test]
+fn test_math_aborts() {
+    let cases: &[(ScriptOpcode, &[i32])] = &[
+        (DIVIDE, &[1, 0]),
+        (MODULO, &[1, 0]),
+        (SCALE, &[1, 0, 1]),
+        (SETBIT, &[0, 32]),
+        (SETBIT, &[0, -1]),
+        (CLEARBIT, &[0, 32]),
+        (TESTBIT, &[0, 40]),
+        (TOGGLEBIT, &[0, -5]),
+        (SETBIT_RANGE, &[0, 7, 4]),
+        (SETBIT_RANGE, &[0, 0, 32]),
+        (CLEARBIT_RANGE, &[0, -1, 3]),
+        (GETBIT_RANGE, &[0, 0, 32]),
+        (GETBIT_RANGE, &[0, 5, 4]),
+        (SETBIT_RANGE_TOINT, &[0, 1, 4, 3]),
+        (SETBIT_RANGE_TOINT, &[0, 1, 0, 40]),
+    ];
+
+    for (opcode, args) in cases {
+        assert_eq!(call(*opcode, args, &[]).execution, ScriptState::ABORTED, "{:?}{:?}", opcode, args);
+    }
+}
+
+#[test]
+fn test_string_ops() {
+    let cases: &[(ScriptOpcode, &[i32], &[&str], &str)] = &[
+        (APPEND_NUM, &[5], &["x"], "x5"),
+        (APPEND, &[], &["foo", "bar"], "foobar"),
+        (APPEND_SIGNNUM, &[5], &["x"], "x+5"),
+        (APPEND_SIGNNUM, &[-5], &["x"], "x-5"),
+        (APPEND_SIGNNUM, &[0], &["x"], "x+0"),
+        (LOWERCASE, &[], &["HeLLo"], "hello"),
+        (TOSTRING, &[-12], &[], "-12"),
+        (TEXT_SWITCH, &[1], &["a", "b"], "a"),
+        (TEXT_SWITCH, &[0], &["a", "b"], "b"),
+        (APPEND_CHAR, &[33], &["hi"], "hi!"),
+        (SUBSTRING, &[1, 3], &["abcdef"], "bc"),
+        (SUBSTRING, &[3, 1], &["abcdef"], "bc"),
+        (SUBSTRING, &[-2, 100], &["abcdef"], "abcdef"),
+    ];
+
+    for (opcode, args, texts, expected) in cases {
+        let state = call(*opcode, args, texts);
+        assert_eq!(state.execution, ScriptState::FINISHED, "{:?}{:?}{:?}", opcode, args, texts);
+        assert_eq!(strings(&state), &[expected.to_string()], "{:?}{:?}{:?}", opcode, args, texts);
+        assert!(ints(&state).is_empty());
+    }
+}
+
+#[test]
+fn test_string_int_ops() {
+    let cases: &[(ScriptOpcode, &[i32], &[&str], i32)] = &[
+        (COMPARE, &[], &["a", "b"], -1),
+        (COMPARE, &[], &["abc", "ab"], 1),
+        (COMPARE, &[], &["x", "x"], 0),
+        (STRING_LENGTH, &[], &["hello"], 5),
+        (STRING_LENGTH, &[], &[""], 0),
+        (STRING_INDEXOF_CHAR, &[99], &["abc"], 2),
+        (STRING_INDEXOF_CHAR, &[122], &["abc"], -1),
+        (STRING_INDEXOF_STRING, &[], &["abcabc", "ca"], 2),
+        (STRING_INDEXOF_STRING, &[], &["abc", "z"], -1),
+    ];
+
+    for (opcode, args, texts, expected) in cases {
+        let state = call(*opcode, args, texts);
+        assert_eq!(state.execution, ScriptState::FINISHED, "{:?}{:?}{:?}", opcode, args, texts);
+        assert_eq!(ints(&state), &[*expected], "{:?}{:?}{:?}", opcode, args, texts);
+        assert!(strings(&state).is_empty());
+    }
+}
+
+#[test]
+fn test_text_gender() {
+    let cases: &[(u8, &str)] = &[(0, "sir"), (1, "madam")];
+
+    for (gender, expected) in cases {
+        let mut engine = Engine::new();
+        engine.players.set(1, Player::new_dummy(CoordGrid { coord: 0 }, *gender, 1)).unwrap();
+
+        let text_gender = script(&[(PUSH_CONSTANT_STRING, 0, "sir"), (PUSH_CONSTANT_STRING, 0, "madam"), (TEXT_GENDER, 0, "")]);
+        let state = run(text_gender, &mut engine, Some(EntityType::Player(1)));
+        assert_eq!(strings(&state), &[expected.to_string()], "gender {}", gender);
+    }
+
+    // Without an active player there's no one to pick the text for.
+    assert_eq!(call(TEXT_GENDER, &[], &["sir", "madam"]).execution, ScriptState::ABORTED);
+}
+
+#[test]
+fn test_branches() {
+    let cases: &[(ScriptOpcode, i32, i32, bool)] = &[
+        (BRANCH_NOT, 1, 2, true),
+        (BRANCH_NOT, 2, 2, false),
+        (BRANCH_EQUALS, 2, 2, true),
+        (BRANCH_EQUALS, 1, 2, false),
+        (BRANCH_LESS_THAN, 1, 2, true),
+        (BRANCH_LESS_THAN, 2, 2, false),
+        (BRANCH_GREATER_THAN, 3, 2, true),
+        (BRANCH_GREATER_THAN, 2, 2, false),
+        (BRANCH_LESS_THAN_OR_EQUALS, 2, 2, true),
+        (BRANCH_LESS_THAN_OR_EQUALS, 3, 2, false),
+        (BRANCH_GREATER_THAN_OR_EQUALS, 2, 2, true),
+        (BRANCH_GREATER_THAN_OR_EQUALS, 1, 2, false),
+    ];
+
+    for (opcode, a, b, taken) in cases {
+        let branch = script(&[(PUSH_CONSTANT_INT, *a, ""), (PUSH_CONSTANT_INT, *b, ""), (*opcode, 1, ""), (PUSH_CONSTANT_INT, 7, "")]);
+        let state = run(branch, &mut Engine::new(), None);
+        let expected: &[i32] = if *taken { &[] } else { &[7] };
+        assert_eq!(ints(&state), expected, "{:?} {} {}", opcode, a, b);
+    }
+}
+
+#[test]
+fn test_loop() {
+    // while ($i < 10) { $i = calc($i + 1); }
+    let counter = script(&[
+        (PUSH_INT_LOCAL, 0, ""),
+        (PUSH_CONSTANT_INT, 10, ""),
+        (BRANCH_GREATER_THAN_OR_EQUALS, 5, ""),
+        (PUSH_INT_LOCAL, 0, ""),
+        (PUSH_CONSTANT_INT, 1, ""),
+        (ADD, 0, ""),
+        (POP_INT_LOCAL, 0, ""),
+        (BRANCH, -8, ""),
+        (PUSH_INT_LOCAL, 0, ""),
+        (RETURN, 0, ""),
+    ]);
+
+    let state = run(counter, &mut Engine::new(), None);
+    assert_eq!(state.execution, ScriptState::FINISHED);
+    assert_eq!(ints(&state), &[10]);
+}
+
+#[test]
+fn test_stack_ops() {
+    let cases: &[(Instructions, &[i32], &[&str])] = &[
+        (&[(PUSH_CONSTANT_INT, 4, ""), (POP_INT_LOCAL, 0, ""), (PUSH_INT_LOCAL, 0, ""), (PUSH_INT_LOCAL, 0, "")], &[4, 4], &[]),
+        (&[(PUSH_CONSTANT_STRING, 0, "a"), (POP_STRING_LOCAL, 0, ""), (PUSH_STRING_LOCAL, 0, ""), (PUSH_STRING_LOCAL, 0, "")], &[], &["a", "a"]),
+        (&[(PUSH_CONSTANT_STRING, 0, "a"), (PUSH_CONSTANT_STRING, 0, "b"), (PUSH_CONSTANT_STRING, 0, "c"), (JOIN_STRING, 3, "")], &[], &["abc"]),
+        (&[(PUSH_CONSTANT_STRING, 0, "a"), (PUSH_CONSTANT_STRING, 0, "b"), (JOIN_STRING, 1, "")], &[], &["a", "b"]),
+        (&[(PUSH_CONSTANT_INT, 1, ""), (PUSH_CONSTANT_INT, 2, ""), (POP_INT_DISCARD, 0, "")], &[1], &[]),
+        (&[(PUSH_CONSTANT_STRING, 0, "a"), (PUSH_CONSTANT_STRING, 0, "b"), (POP_STRING_DISCARD, 0, "")], &[], &["a"]),
+        (&[(PUSH_CONSTANT_INT, 1, ""), (RETURN, 0, ""), (PUSH_CONSTANT_INT, 2, "")], &[1], &[]),
+    ];
+
+    for (instructions, expected_ints, expected_strings) in cases {
+        let state = run(script(instructions), &mut Engine::new(), None);
+        assert_eq!(state.execution, ScriptState::FINISHED, "{:?}", instructions);
+        assert_eq!(ints(&state), *expected_ints, "{:?}", instructions);
+        assert_eq!(strings(&state), expected_strings.iter().map(|s| s.to_string()).collect::<Vec<_>>(), "{:?}", instructions);
+    }
+}
+
+#[test]
+fn test_switch() {
+    let cases: &[(i32, i32)] = &[(1, 100), (2, 200), (3, 300), (4, 300)];
+
+    for (key, expected) in cases {
+        let mut switch = script(&[
+            (PUSH_CONSTANT_INT, *key, ""),
+            (SWITCH, 0, ""),
+            (PUSH_CONSTANT_INT, 300, ""),
+            (RETURN, 0, ""),
+            (PUSH_CONSTANT_INT, 100, ""),
+            (RETURN, 0, ""),
+            (PUSH_CONSTANT_INT, 200, ""),
+        ]);
+        switch.switch_tables.push(SwitchTable::new(vec![(2, 4), (1, 2)]));
+
+        let state = run(switch, &mut Engine::new(), None);
+        assert_eq!(ints(&state), &[*expected], "case {}", key);
+    }
+
+    let missing_table = script(&[(PUSH_CONSTANT_INT, 1, ""), (SWITCH, 1, "")]);
+    assert_eq!(run(missing_table, &mut Engine::new(), None).execution, ScriptState::ABORTED);
+}
+
+#[test]
+fn test_arrays() {
+    let int_array = (1 << 16) | 'i' as i32;
+    let obj_array = 'o' as i32;
+
+    let cases: &[(Instructions, &[i32])] = &[
+        // Int arrays start out zeroed, stored values can be read back.
+        (&[
+            (PUSH_CONSTANT_INT, 3, ""), (DEFINE_ARRAY, int_array, ""),
+            (PUSH_CONSTANT_INT, 2, ""), (PUSH_CONSTANT_INT, 42, ""), (POP_ARRAY_INT, 1, ""),
+            (PUSH_CONSTANT_INT, 2, ""), (PUSH_ARRAY_INT, 1, ""),
+            (PUSH_CONSTANT_INT, 0, ""), (PUSH_ARRAY_INT, 1, ""),
+        ], &[42, 0]),
+        // Any other type starts out null.
+        (&[
+            (PUSH_CONSTANT_INT, 1, ""), (DEFINE_ARRAY, obj_array, ""),
+            (PUSH_CONSTANT_INT, 0, ""), (PUSH_ARRAY_INT, 0, ""),
+        ], &[-1]),
+    ];
+
+    for (instructions, expected) in cases {
+        let state = run(script(instructions), &mut Engine::new(), None);
+        assert_eq!(state.execution, ScriptState::FINISHED, "{:?}", instructions);
+        assert_eq!(ints(&state), *expected, "{:?}", instructions);
+    }
+
+    let aborts: &[Instructions] = &[
+        &[(PUSH_CONSTANT_INT, 5001, ""), (DEFINE_ARRAY, int_array, "")],
+        &[(PUSH_CONSTANT_INT, -1, ""), (DEFINE_ARRAY, int_array, "")],
+        &[(PUSH_CONSTANT_INT, 0, ""), (PUSH_ARRAY_INT, 0, "")],
+        &[(PUSH_CONSTANT_INT, 3, ""), (DEFINE_ARRAY, obj_array, ""), (PUSH_CONSTANT_INT, 3, ""), (PUSH_ARRAY_INT, 0, "")],
+        &[(PUSH_CONSTANT_INT, 3, ""), (DEFINE_ARRAY, obj_array, ""), (PUSH_CONSTANT_INT, -1, ""), (PUSH_CONSTANT_INT, 1, ""), (POP_ARRAY_INT, 0, "")],
+    ];
+
+    for instructions in aborts {
+        assert_eq!(run(script(instructions), &mut Engine::new(), None).execution, ScriptState::ABORTED, "{:?}", instructions);
+    }
+}
+
+#[test]
+fn test_npc_anim_ops() {
+    let mut engine = Engine::new();
+    engine.npcs.set(1, NPC::new(CoordGrid { coord: 0 }, 1, 1, EntityLifeCycle::FOREVER, 1, 0, MoveRestrict::Normal, BlockWalk::Npc)).unwrap();
+
+    let state = run(script(&[(PUSH_CONSTANT_INT, -1, ""), (PUSH_CONSTANT_INT, 0, ""), (NPC_ANIM, 0, "")]), &mut engine, Some(EntityType::NPC(1)));
+    assert_eq!(state.execution, ScriptState::FINISHED);
+    let npc = engine.npcs.get(1).unwrap();
+    assert_eq!((npc.pathing_entity.masks, npc.pathing_entity.anim_id), (PathingEntity::MASK_ANIM, -1));
+
+    // No seqs or spotanims are loaded without a cache.
+    let aborts: &[Instructions] = &[
+        &[(PUSH_CONSTANT_INT, 5, ""), (PUSH_CONSTANT_INT, 0, ""), (NPC_ANIM, 0, "")],
+        &[(PUSH_CONSTANT_INT, 5, ""), (PUSH_CONSTANT_INT, 0, ""), (PUSH_CONSTANT_INT, 0, ""), (SPOTANIM_NPC, 0, "")],
+    ];
+
+    for instructions in aborts {
+        assert_eq!(run(script(instructions), &mut engine, Some(EntityType::NPC(1))).execution, ScriptState::ABORTED, "{:?}", instructions);
+    }
+}
+
+#[test]
+fn test_missing_scripts_abort() {
+    let cases: &[Instructions] = &[
+        &[(GOSUB_WITH_PARAMS, 99999, "")],
+        &[(JUMP_WITH_PARAMS, 99999, "")],
+        &[(PUSH_CONSTANT_INT, 99999, ""), (GOSUB, 0, "")],
+        &[(PUSH_CONSTANT_INT, 99999, ""), (JUMP, 0, "")],
+    ];
+
+    for instructions in cases {
+        assert_eq!(run(script(instructions), &mut Engine::new(), None).execution, ScriptState::ABORTED, "{:?}", instructions);
+    }
+}
+
+#[test]
+fn test_gosub_stack_overflow() {
+    let mut state = ScriptRunner::init(script(&[(GOSUB_WITH_PARAMS, 0, "")]), None, None, None);
+    state.fp = 50;
+
+    assert_eq!(ScriptRunner::execute(&mut state, &mut Engine::new(), false, false), ScriptState::ABORTED);
+}
@@ -4,7 +4,10 @@ use crate::script::script_state::ScriptState;
 use std::collections::HashMap;
 use std::sync::OnceLock;
 use crate::engine::Engine;
+use crate::entity::interface_tree::ModalType;
 use crate::io::server::model::message_game::Message_Game;
+use crate::util::cache::config::seq_type::SeqTypeProvider;
+use crate::util::cache::config::spot_anim_type::SpotAnimTypeProvider;
 
 pub fn get_player_ops() -> &'static CommandHandlers {
     static HANDLERS: OnceLock<CommandHandlers> = OnceLock::new();
@@ -14,13 +17,133 @@ pub fn get_player_ops() -> &'static CommandHandlers {
 
         handlers.insert(
             ScriptOpcode::MES as i32,
-            |state: &mut ScriptState| {
-                let pid = state.get_active_player().expect("No active player found").get_pid();
-                let player = Engine::get().players.get_mut(pid).expect(format!("No player found for PID: {}", pid).as_str());
+            |state: &mut ScriptState, engine: &mut Engine| {
+                let player = match state.get_active_player(engine) {
+                    Ok(player) => player,
+                    Err(err) => return state.abort(&err),
+                };
                 player.write(Message_Game::new(state.pop_string()));
             }
         );
 
+        handlers.insert(
+            ScriptOpcode::ANIM as i32,
+            |state: &mut ScriptState, engine: &mut Engine| {
+                let delay = state.pop_int();
+                let seq = state.pop_int();
+
+                let seq_type = if seq == -1 {
+                    None
+                } else {
+                    match SeqTypeProvider::get(seq) {
+                        Some(seq_type) => Some(seq_type),
+                        None => return state.abort(&format!("Invalid seq: {}", seq)),
+                    }
+                };
+                let player = match state.get_active_player(engine) {
+                    Ok(player) => player,
+                    Err(err) => return state.abort(&err),
+                };
+                player.pathing_entity.anim(seq_type, delay);
+            }
+        );
+
+        handlers.insert(
+            ScriptOpcode::SPOTANIM_PL as i32,
+            |state: &mut ScriptState, engine: &mut Engine| {
+                let delay = state.pop_int();
+                let height = state.pop_int();
+                let spotanim = state.pop_int();
+
+                let Some(spotanim_type) = SpotAnimTypeProvider::get(spotanim) else {
+                    return state.abort(&format!("Invalid spotanim: {}", spotanim));
+                };
+                let player = match state.get_active_player(engine) {
+                    Ok(player) => player,
+                    Err(err) => return state.abort(&err),
+                };
+                player.pathing_entity.spotanim(spotanim, spotanim_type.seq_type(), height, delay);
+            }
+        );
+
+        handlers.insert(
+            ScriptOpcode::IF_CLOSE as i32,
+            |state: &mut ScriptState, engine: &mut Engine| {
+                let player = match state.get_protected_active_player(engine) {
+                    Ok(player) => player,
+                    Err(err) => return state.abort(&err),
+                };
+                player.close_modal();
+            }
+        );
+
+        handlers.insert(
+            ScriptOpcode::IF_OPENCHAT as i32,
+            |state: &mut ScriptState, engine: &mut Engine| {
+                let interface_id = state.pop_int();
+                let player = match state.get_protected_active_player(engine) {
+                    Ok(player) => player,
+                    Err(err) => return state.abort(&err),
+                };
+                player.open_modal(ModalType::Chat, interface_id as u32);
+            }
+        );
+
+        handlers.insert(
+            ScriptOpcode::IF_OPENMAIN as i32,
+            |state: &mut ScriptState, engine: &mut Engine| {
+                let interface_id = state.pop_int();
+                let player = match state.get_protected_active_player(engine) {
+                    Ok(player) => player,
+                    Err(err) => return state.abort(&err),
+                };
+                player.open_modal(ModalType::Main, interface_id as u32);
+            }
+        );
+
+        handlers.insert(
+            ScriptOpcode::P_COUNTDIALOG as i32,
+            |state: &mut ScriptState, engine: &mut Engine| {
+                let player = match state.get_protected_active_player(engine) {
+                    Ok(player) => player,
+                    Err(err) => return state.abort(&err),
+                };
+
+                // Resumed by RESUME_P_COUNTDIALOG with the entered amount on the stack.
+                player.open_count_dialog("Enter amount:");
+                state.execution = ScriptState::COUNTDIALOG;
+            }
+        );
+
+        handlers.insert(
+            ScriptOpcode::P_DELAY as i32,
+            |state: &mut ScriptState, engine: &mut Engine| {
+                let delay = state.pop_int();
+                let current_tick = engine.current_tick;
+                let player = match state.get_protected_active_player(engine) {
+                    Ok(player) => player,
+                    Err(err) => return state.abort(&err),
+                };
+
+                player.pathing_entity.delayed = true;
+                player.pathing_entity.delayed_until = current_tick + 1 + delay;
+                state.execution = ScriptState::SUSPENDED;
+            }
+        );
+
+        handlers.insert(
+            ScriptOpcode::P_PAUSEBUTTON as i32,
+            |state: &mut ScriptState, engine: &mut Engine| {
+                if let Err(err) = state.get_protected_active_player(engine) {
+                    return state.abort(&err);
+                }
+
+                // Resumed by RESUME_PAUSEBUTTON with the clicked component on the stack. The button
+                // belongs to the dialog the script already opened, so the client needs no prompt.
+                state.execution = ScriptState::PAUSEBUTTON;
+            }
+        );
+
         handlers  
     })
-}
\ No newline at end of file
+}
@@ -0,0 +1,24 @@
+use crate::engine::Engine;
+use crate::script::script_opcode::ScriptOpcode;
+use crate::script::script_runner::CommandHandlers;
+use crate::script::script_state::ScriptState;
+use std::collections::HashMap;
+use std::sync::OnceLock;
+
+pub fn get_server_ops() -> &'static CommandHandlers {
+    static HANDLERS: OnceLock<CommandHandlers> = OnceLock::new();
+
+    HANDLERS.get_or_init(|| {
+        let mut handlers: CommandHandlers = HashMap::with_capacity(64); // TODO - update as need be
+
+        handlers.insert(
+            ScriptOpcode::WORLD_DELAY as i32,
+            |state: &mut ScriptState, _engine: &mut Engine| {
+                // The delay is left on the stack, it's popped when the script is queued on the world.
+                state.execution = ScriptState::WORLD_SUSPENDED;
+            }
+        );
+
+        handlers
+    })
+}
@@ -0,0 +1,176 @@
+use std::collections::HashMap;
+use std::sync::OnceLock;
+use crate::engine::Engine;
+use crate::script::script_opcode::ScriptOpcode;
+use crate::script::script_runner::CommandHandlers;
+use crate::script::script_state::ScriptState;
+
+pub fn get_string_ops() -> &'static CommandHandlers {
+    static HANDLERS: OnceLock<CommandHandlers> = OnceLock::new();
+
+    HANDLERS.get_or_init(|| {
+        let mut handlers: CommandHandlers = HashMap::with_capacity(13);
+
+        handlers.insert(
+            ScriptOpcode::APPEND_NUM as i32,
+            |state: &mut ScriptState, _engine: &mut Engine| {
+                let text = state.pop_string();
+                let num = state.pop_int();
+
+                state.push_string(format!("{}{}", text, num));
+            }
+        );
+
+        handlers.insert(
+            ScriptOpcode::APPEND as i32,
+            |state: &mut ScriptState, _engine: &mut Engine| {
+                let strings = state.pop_strings(2);
+                state.push_string(strings.concat());
+            }
+        );
+
+        handlers.insert(
+            ScriptOpcode::APPEND_SIGNNUM as i32,
+            |state: &mut ScriptState, _engine: &mut Engine| {
+                let text = state.pop_string();
+                let num = state.pop_int();
+
+                state.push_string(format!("{}{:+}", text, num));
+            }
+        );
+
+        handlers.insert(
+            ScriptOpcode::LOWERCASE as i32,
+            |state: &mut ScriptState, _engine: &mut Engine| {
+                let text = state.pop_string();
+                state.push_string(text.to_lowercase());
+            }
+        );
+
+        handlers.insert(
+            ScriptOpcode::TEXT_GENDER as i32,
+            |state: &mut ScriptState, engine: &mut Engine| {
+                let mut strings = state.pop_strings(2);
+                let player = match state.get_active_player(engine) {
+                    Ok(player) => player,
+                    Err(err) => return state.abort(&err),
+                };
+
+                let female = strings.pop().unwrap_or_default();
+                let male = strings.pop().unwrap_or_default();
+                state.push_string(if player.gender == 0 { male } else { female });
+            }
+        );
+
+        handlers.insert(
+            ScriptOpcode::TOSTRING as i32,
+            |state: &mut ScriptState, _engine: &mut Engine| {
+                let value = state.pop_int();
+                state.push_string(value.to_string());
+            }
+        );
+
+        handlers.insert(
+            ScriptOpcode::COMPARE as i32,
+            |state: &mut ScriptState, _engine: &mut Engine| {
+                let strings = state.pop_strings(2);
+                state.push_int(compare(&strings[0], &strings[1]));
+            }
+        );
+
+        handlers.insert(
+            ScriptOpcode::TEXT_SWITCH as i32,
+            |state: &mut ScriptState, _engine: &mut Engine| {
+                let value = state.pop_int();
+                let mut strings = state.pop_strings(2);
+
+                let second = strings.pop().unwrap_or_default();
+                let first = strings.pop().unwrap_or_default();
+                state.push_string(if value == 1 { first } else { second });
+            }
+        );
+
+        handlers.insert(
+            ScriptOpcode::APPEND_CHAR as i32,
+            |state: &mut ScriptState, _engine: &mut Engine| {
+                let mut text = state.pop_string();
+                let value = state.pop_int();
+
+                match char::from_u32(value as u32) {
+                    Some(char) => {
+                        text.push(char);
+                        state.push_string(text);
+                    }
+                    None => state.abort(&format!("Invalid char: {}", value)),
+                }
+            }
+        );
+
+        handlers.insert(
+            ScriptOpcode::STRING_LENGTH as i32,
+            |state: &mut ScriptState, _engine: &mut Engine| {
+                let text = state.pop_string();
+                state.push_int(text.chars().count() as i32);
+            }
+        );
+
+        handlers.insert(
+            ScriptOpcode::SUBSTRING as i32,
+            |state: &mut ScriptState, _engine: &mut Engine| {
+                let text = state.pop_string();
+                let end = state.pop_int();
+                let start = state.pop_int();
+
+                state.push_string(substring(&text, start, end));
+            }
+        );
+
+        handlers.insert(
+            ScriptOpcode::STRING_INDEXOF_CHAR as i32,
+            |state: &mut ScriptState, _engine: &mut Engine| {
+                let text = state.pop_string();
+                let value = state.pop_int();
+
+                let index = char::from_u32(value as u32)
+                    .and_then(|find| text.chars().position(|char| char == find))
+                    .map_or(-1, |index| index as i32);
+                state.push_int(index);
+            }
+        );
+
+        handlers.insert(
+            ScriptOpcode::STRING_INDEXOF_STRING as i32,
+            |state: &mut ScriptState, _engine: &mut Engine| {
+                let strings = state.pop_strings(2);
+                let (text, find) = (&strings[0], &strings[1]);
+
+                // Indexes count chars, not the bytes `find` returns.
+                let index = text.find(find.as_str()).map_or(-1, |byte| text[..byte].chars().count() as i32);
+                state.push_int(index);
+            }
+        );
+
+        handlers
+    })
+}
+
+/// Compares like Java's `String.compareTo`, the first differing char or else the length decides.
+fn compare(a: &str, b: &str) -> i32 {
+    for (x, y) in a.chars().zip(b.chars()) {
+        if x != y {
+            return x as i32 - y as i32;
+        }
+    }
+
+    a.chars().count() as i32 - b.chars().count() as i32
+}
+
+/// The chars from `start` up to `end`, both clamped to the text and swapped when reversed.
+fn substring(text: &str, start: i32, end: i32) -> String {
+    let length = text.chars().count() as i32;
+    let start = start.clamp(0, length);
+    let end = end.clamp(0, length);
+    let (from, to) = if start > end { (end, start) } else { (start, end) };
+
+    text.chars().skip(from as usize).take((to - from) as usize).collect()
+}
\ No newline at end of file
@@ -6,4 +6,7 @@ pub mod server_trigger_types;
 mod handlers;
 pub mod script_state;
 pub mod script_pointer;
-pub mod script_runner;
\ No newline at end of file
+pub mod script_runner;
+mod script_opcode_pointers_tests;
+mod script_file_tests;
+mod script_runner_tests;
\ No newline at end of file
@@ -1,6 +1,4 @@
-use std::collections::HashMap;
 use std::path::Path;
-use log::{debug, error};
 use crate::io::packet::Packet;
 use crate::script::script_opcode::ScriptOpcode;
 
@@ -14,7 +12,29 @@ pub struct ScriptInfo {
     lines: Vec<i32>,
 }
 
-pub type SwitchTable = HashMap<i32, Option<i32>>;
+/// The cases of a switch as `(key, offset)` pairs sorted by key, so a lookup is a binary search.
+#[derive(Debug, Clone, PartialEq, Default)]
+pub struct SwitchTable {
+    cases: Vec<(i32, i32)>,
+}
+
+impl SwitchTable {
+    pub fn new(mut cases: Vec<(i32, i32)>) -> Self {
+        cases.sort_unstable_by_key(|&(key, _)| key);
+        cases.dedup_by_key(|&mut (key, _)| key);
+        SwitchTable { cases }
+    }
+
+    /// The pc offset of the case for `key`, `None` falls through to the next instruction.
+    #[inline(always)]
+    pub fn get(&self, key: i32) -> Option<i32> {
+        self.cases.binary_search_by_key(&key, |&(key, _)| key).ok().map(|index| self.cases[index].1)
+    }
+
+    pub fn offsets(&self) -> impl Iterator<Item = i32> + '_ {
+        self.cases.iter().map(|&(_, offset)| offset)
+    }
+}
 
 pub fn is_large_operand(opcode: i32) -> bool {
     if opcode > 100 {
@@ -39,7 +59,7 @@ pub struct ScriptFile {
     pub string_local_count: i32,
     pub int_arg_count: i32,
     pub string_arg_count: i32,
-    pub switch_tables: Vec<Option<SwitchTable>>,
+    pub switch_tables: Vec<SwitchTable>,
     pub opcodes: Vec<ScriptOpcode>,
     pub int_operands: Vec<i32>,
     pub string_operands: Vec<String>,
@@ -100,18 +120,18 @@ impl ScriptFile {
         self.info.lines.last().copied().unwrap_or(0)
     }
     
-    pub fn decode(id: usize, mut packet: Packet) -> ScriptFile {
+    pub fn decode(id: usize, mut packet: Packet) -> Result<ScriptFile, String> {
         let length = packet.len();
         if length < 16 {
-            error!("Invalid script file (minimum length).");
+            return Err("Invalid script file (minimum length).".to_string());
         }
         
         packet.position = length - 2;
         let trailer_length = packet.g2() as usize;
-        let trailer_position = length - trailer_length - 12 - 2;
+        let trailer_position = length.saturating_sub(trailer_length + 12 + 2);
         
         if trailer_position == 0 || trailer_position >= length {
-            error!("Invalid script file (trailer position).");
+            return Err("Invalid script file (trailer position).".to_string());
         }
         
         packet.position = trailer_position;
@@ -127,15 +147,15 @@ impl ScriptFile {
         let switches = packet.g1();
         for _i in 0..switches {
             let count = packet.g2();
-            let mut table: SwitchTable = SwitchTable::new();
+            let mut cases = Vec::with_capacity(count as usize);
             
             for _j in 0..count {
                 let key = packet.g4();
                 let offset = packet.g4();
-                table.insert(key, Some(offset));
+                cases.push((key, offset));
             }
             
-            script.switch_tables.push(Some(table));
+            script.switch_tables.push(SwitchTable::new(cases));
         }
         
         packet.position = 0;
@@ -154,23 +174,25 @@ impl ScriptFile {
             script.info.lines.push(packet.g4());
         }
         
-        let mut instruction = 0;
+        // Every instruction gets a slot in both operand lists so they can be indexed by pc.
         while trailer_position > packet.position {
             let opcode = packet.g2();
             
             if opcode == ScriptOpcode::PUSH_CONSTANT_STRING as u16 {
-                let test = packet.gjstr();
-                script.string_operands.push(test);
+                script.int_operands.push(0);
+                script.string_operands.push(packet.gjstr());
             } else if is_large_operand(opcode as i32) {
                 script.int_operands.push(packet.g4());
+                script.string_operands.push(String::new());
             } else {
                 script.int_operands.push(packet.g1() as i32);
+                script.string_operands.push(String::new());
             }
             
-            script.opcodes.insert(instruction, ScriptOpcode::try_from(opcode as i32).expect("Invalid opcode"));
-            instruction += 1;
+            let pc = script.opcodes.len();
+            script.opcodes.push(ScriptOpcode::try_from(opcode as i32).map_err(|_| format!("Invalid opcode {} at pc {}", opcode, pc))?);
         }
         
-        script
+        Ok(script)
     }
 }
\ No newline at end of file
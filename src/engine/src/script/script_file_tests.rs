@@ -0,0 +1,47 @@
+use crate::io::packet::Packet;
+use crate::script::script_file::ScriptFile;
+use crate::script::script_opcode::ScriptOpcode;
+
+/// `[proc,test]` pushing a string and an int, with one switch table.
+fn encoded_script() -> Vec<u8> {
+    let mut packet = Packet::from(Vec::new());
+    packet.pjstr("[proc,test]", 0);
+    packet.pjstr("test.rs2", 0);
+    packet.p4(-1);
+    packet.p1(0);
+    packet.p2(0);
+
+    packet.p2(ScriptOpcode::PUSH_CONSTANT_STRING as i32); packet.pjstr("hi", 0);
+    packet.p2(ScriptOpcode::PUSH_CONSTANT_INT as i32); packet.p4(5);
+    packet.p2(ScriptOpcode::RETURN as i32); packet.p1(0);
+
+    packet.p4(3);
+    packet.p2(1); packet.p2(2); packet.p2(0); packet.p2(0);
+    packet.p1(1); packet.p2(1); packet.p4(7); packet.p4(2);
+    packet.p2(11);
+    packet.data
+}
+
+#[test]
+fn test_operands_are_indexed_by_pc() {
+    let script = ScriptFile::decode(4, Packet::from(encoded_script())).unwrap();
+
+    assert_eq!(script.name(), "[proc,test]");
+    assert_eq!(script.opcodes, vec![ScriptOpcode::PUSH_CONSTANT_STRING, ScriptOpcode::PUSH_CONSTANT_INT, ScriptOpcode::RETURN]);
+    assert_eq!(script.int_operands, vec![0, 5, 0]);
+    assert_eq!(script.string_operands, vec!["hi", "", ""]);
+    assert_eq!((script.int_local_count, script.string_local_count), (1, 2));
+    assert_eq!(script.switch_tables[0].get(7), Some(2));
+    assert_eq!(script.switch_tables[0].get(8), None);
+}
+
+#[test]
+fn test_invalid_opcode() {
+    let mut data = encoded_script();
+    // Turns the RETURN after the two pushes into 48, which no opcode uses.
+    let pc = data.iter().position(|&byte| byte == ScriptOpcode::RETURN as u8).unwrap();
+    data[pc] = 48;
+
+    let err = ScriptFile::decode(4, Packet::from(data)).unwrap_err();
+    assert_eq!(err, "Invalid opcode 48 at pc 2");
+}
@@ -42,7 +42,7 @@ pub enum ScriptOpcode {
     POP_ARRAY_INT = 46,
 
     // Server ops (1000-1999)
-    COORDX = 10000,
+    COORDX = 1000,
     COORDY = 1001,
     COORDZ = 1002,
     DISTANCE = 1003,
@@ -145,14 +145,38 @@ pub enum ScriptOpcode {
     LAST_USEITEM = 2063,
     LAST_USESLOT = 2064,
     LONGQUEUE = 2065,
-    MES = 1000,
+    MES = 2066,
+    P_COUNTDIALOG = 2072,
+    P_DELAY = 2073,
+    P_PAUSEBUTTON = 2085,
+    SPOTANIM_PL = 2098,
+
+    // Npc ops (2500-2999)
+    NPC_ANIM = 2501,
+    NPC_DELAY = 2507,
+    SPOTANIM_NPC = 2529,
     
+    // String ops (4100-4199)
+    APPEND_NUM = 4100,
+    APPEND,
+    APPEND_SIGNNUM,
+    LOWERCASE,
+    TEXT_GENDER,
+    TOSTRING,
+    COMPARE,
+    TEXT_SWITCH,
+    APPEND_CHAR,
+    STRING_LENGTH,
+    SUBSTRING,
+    STRING_INDEXOF_CHAR,
+    STRING_INDEXOF_STRING,
+
     // Enum ops (4400-4499)
     ENUM = 4400,
     ENUM_GETOUTPUTCOUNT,
     
     // Math ops (4600-4699)
-    ADD = 400,
+    ADD = 4600,
     SUB,
     MULTIPLY,
     DIVIDE,
@@ -1,7 +1,7 @@
 use std::collections::HashMap;
 use crate::script::script_opcode::ScriptOpcode;
 
-const POINTER_GROUP_FIND: [&str; 5] = ["find_player", "find_npc", "find_loc", "fond_obj", "find_db"];
+const POINTER_GROUP_FIND: [&str; 5] = ["find_player", "find_npc", "find_loc", "find_obj", "find_db"];
 
 #[derive(Debug, Default)]
 pub struct ScriptOpcodePointers {
@@ -19,32 +19,32 @@ impl ScriptOpcodePointers {
         ScriptOpcodePointers::default()
     }
 
-    fn require(mut self, require: &[&str]) -> Self {
+    fn require<const N: usize>(mut self, require: [&str; N]) -> Self {
         self.require = Some(require.iter().map(|&s| s.to_string()).collect());
         self
     }
 
-    fn require2(mut self, require2: &[&str]) -> Self {
+    fn require2<const N: usize>(mut self, require2: [&str; N]) -> Self {
         self.require2 = Some(require2.iter().map(|&s| s.to_string()).collect());
         self
     }
 
-    fn set(mut self, set: &[&str]) -> Self {
+    fn set<const N: usize>(mut self, set: [&str; N]) -> Self {
         self.set = Some(set.iter().map(|&s| s.to_string()).collect());
         self
     }
 
-    fn set2(mut self, set2: &[&str]) -> Self {
+    fn set2<const N: usize>(mut self, set2: [&str; N]) -> Self {
         self.set2 = Some(set2.iter().map(|&s| s.to_string()).collect());
         self
     }
 
-    fn corrupt(mut self, corrupt: &[&str]) -> Self {
+    fn corrupt<const N: usize>(mut self, corrupt: [&str; N]) -> Self {
         self.corrupt = Some(corrupt.iter().map(|&s| s.to_string()).collect());
         self
     }
 
-    fn corrupt2(mut self, corrupt2: &[&str]) -> Self {
+    fn corrupt2<const N: usize>(mut self, corrupt2: [&str; N]) -> Self {
         self.corrupt2 = Some(corrupt2.iter().map(|&s| s.to_string()).collect());
         self
     }
@@ -56,11 +56,11 @@ impl ScriptOpcodePointers {
 }
 
 macro_rules! script_opcode {
-    ($name:expr, { $( $field:ident : [ $( $value:expr ),* ] ),* $(,)? }) => {
+    ($name:expr, { $( $field:ident : $value:expr ),* $(,)? }) => {
         (
             $name,
             ScriptOpcodePointers::new()
-                $(.$field(&[$($value),*]))*
+                $(.$field($value))*
         )
     };
 }
@@ -68,9 +68,207 @@ pub fn initialize_script_opcode_pointers() -> HashMap<ScriptOpcode, ScriptOpcode
     let mut script_opcode_pointers: HashMap<ScriptOpcode, ScriptOpcodePointers> = HashMap::new();
 
     let opcodes = vec![
+        // Core language ops (0-99)
+        script_opcode!(ScriptOpcode::PUSH_CONSTANT_INT, {}),
+        script_opcode!(ScriptOpcode::PUSH_VARP, { require: ["active_player"], require2: ["active_player2"] }),
+        script_opcode!(ScriptOpcode::POP_VARP, { require: ["p_active_player"], require2: ["p_active_player2"] }),
+        script_opcode!(ScriptOpcode::PUSH_CONSTANT_STRING, {}),
+        script_opcode!(ScriptOpcode::PUSH_VARN, { require: ["active_npc"], require2: ["active_npc2"] }),
+        script_opcode!(ScriptOpcode::POP_VARN, { require: ["active_npc"], require2: ["active_npc2"] }),
+        script_opcode!(ScriptOpcode::BRANCH, {}),
+        script_opcode!(ScriptOpcode::BRANCH_NOT, {}),
+        script_opcode!(ScriptOpcode::BRANCH_EQUALS, {}),
+        script_opcode!(ScriptOpcode::BRANCH_LESS_THAN, {}),
+        script_opcode!(ScriptOpcode::BRANCH_GREATER_THAN, {}),
+        script_opcode!(ScriptOpcode::PUSH_VARS, {}),
+        script_opcode!(ScriptOpcode::POP_VARS, {}),
+        script_opcode!(ScriptOpcode::RETURN, {}),
+        script_opcode!(ScriptOpcode::GOSUB, {}),
+        script_opcode!(ScriptOpcode::JUMP, {}),
+        script_opcode!(ScriptOpcode::SWITCH, {}),
+        script_opcode!(ScriptOpcode::PUSH_VARBIT, { require: ["active_player"], require2: ["active_player2"] }),
+        script_opcode!(ScriptOpcode::POP_VARBIT, { require: ["p_active_player"], require2: ["p_active_player2"] }),
+        script_opcode!(ScriptOpcode::BRANCH_LESS_THAN_OR_EQUALS, {}),
+        script_opcode!(ScriptOpcode::BRANCH_GREATER_THAN_OR_EQUALS, {}),
+        script_opcode!(ScriptOpcode::PUSH_INT_LOCAL, {}),
+        script_opcode!(ScriptOpcode::POP_INT_LOCAL, {}),
+        script_opcode!(ScriptOpcode::PUSH_STRING_LOCAL, {}),
+        script_opcode!(ScriptOpcode::POP_STRING_LOCAL, {}),
+        script_opcode!(ScriptOpcode::JOIN_STRING, {}),
+        script_opcode!(ScriptOpcode::POP_INT_DISCARD, {}),
+        script_opcode!(ScriptOpcode::POP_STRING_DISCARD, {}),
+        script_opcode!(ScriptOpcode::GOSUB_WITH_PARAMS, {}),
+        script_opcode!(ScriptOpcode::JUMP_WITH_PARAMS, {}),
+        script_opcode!(ScriptOpcode::PUSH_VARC_INT, {}),
+        script_opcode!(ScriptOpcode::POP_VARC_INT, {}),
+        script_opcode!(ScriptOpcode::DEFINE_ARRAY, {}),
+        script_opcode!(ScriptOpcode::PUSH_ARRAY_INT, {}),
+        script_opcode!(ScriptOpcode::POP_ARRAY_INT, {}),
+
+        // Server ops (1000-1999)
+        script_opcode!(ScriptOpcode::COORDX, {}),
+        script_opcode!(ScriptOpcode::COORDY, {}),
+        script_opcode!(ScriptOpcode::COORDZ, {}),
+        script_opcode!(ScriptOpcode::DISTANCE, {}),
+        script_opcode!(ScriptOpcode::HUNTALL, {}),
+        script_opcode!(ScriptOpcode::HUNTNEXT, { set: ["active_player"], set2: ["active_player2"], conditional: true }),
+        script_opcode!(ScriptOpcode::INZONE, {}),
+        script_opcode!(ScriptOpcode::LINEOFSIGHT, {}),
+        script_opcode!(ScriptOpcode::LINEOFWALK, {}),
+        script_opcode!(ScriptOpcode::MAP_BLOCKED, {}),
+        script_opcode!(ScriptOpcode::MAP_INDOORS, {}),
+        script_opcode!(ScriptOpcode::MAP_CLOCK, {}),
+        script_opcode!(ScriptOpcode::MAP_LOCADDUNSAFE, {}),
+        script_opcode!(ScriptOpcode::MAP_MEMBERS, {}),
+        script_opcode!(ScriptOpcode::MAP_PLAYERCOUNT, {}),
+        script_opcode!(ScriptOpcode::MAP_FINDSQUARE, {}),
+        script_opcode!(ScriptOpcode::MOVECOORD, {}),
+        script_opcode!(ScriptOpcode::PLAYERCOUNT, {}),
+        script_opcode!(ScriptOpcode::PROJANIM_MAP, {}),
+        script_opcode!(ScriptOpcode::PROJANIM_NPC, {}),
+        script_opcode!(ScriptOpcode::PROJANIM_PL, {}),
+        script_opcode!(ScriptOpcode::SEQLENGTH, {}),
+        script_opcode!(ScriptOpcode::SPLIT_GET, {}),
+        script_opcode!(ScriptOpcode::SPLIT_INIT, {}),
+        script_opcode!(ScriptOpcode::SPLIT_LINECOUNT, {}),
+        script_opcode!(ScriptOpcode::SPLIT_PAGECOUNT, {}),
+        script_opcode!(ScriptOpcode::SPOTANIM_MAP, {}),
+        script_opcode!(ScriptOpcode::STAT_RANDOM, {}),
+        script_opcode!(ScriptOpcode::STRUCT_PARAM, {}),
+        script_opcode!(ScriptOpcode::WORLD_DELAY, { corrupt: POINTER_GROUP_FIND }),
+        script_opcode!(ScriptOpcode::NPCCOUNT, {}),
+        script_opcode!(ScriptOpcode::ZONECOUNT, {}),
+        script_opcode!(ScriptOpcode::LOCCOUNT, {}),
+        script_opcode!(ScriptOpcode::OBJCOUNT, {}),
+        script_opcode!(ScriptOpcode::MAP_MULTIWAY, {}),
+
+        // Player ops (2000-2499)
         script_opcode!(ScriptOpcode::ALLOWDESIGN, { require: ["active_player"] }),
         script_opcode!(ScriptOpcode::ANIM, { require: ["active_player"], require2: ["active_player2"] }),
+        script_opcode!(ScriptOpcode::BAS_READYANIM, { require: ["active_player"], require2: ["active_player2"] }),
+        script_opcode!(ScriptOpcode::BAS_RUNNING, { require: ["active_player"], require2: ["active_player2"] }),
+        script_opcode!(ScriptOpcode::BAS_TURNONSPOT, { require: ["active_player"], require2: ["active_player2"] }),
+        script_opcode!(ScriptOpcode::BAS_WALK_B, { require: ["active_player"], require2: ["active_player2"] }),
+        script_opcode!(ScriptOpcode::BAS_WALK_F, { require: ["active_player"], require2: ["active_player2"] }),
+        script_opcode!(ScriptOpcode::BAS_WALK_L, { require: ["active_player"], require2: ["active_player2"] }),
+        script_opcode!(ScriptOpcode::BAS_WALK_R, { require: ["active_player"], require2: ["active_player2"] }),
+        script_opcode!(ScriptOpcode::BUFFER_FULL, { require: ["active_player"], require2: ["active_player2"] }),
+        script_opcode!(ScriptOpcode::BUILDAPPEARANCE, { require: ["p_active_player"], require2: ["p_active_player2"] }),
+        script_opcode!(ScriptOpcode::BUSY, { require: ["active_player"], require2: ["active_player2"] }),
+        script_opcode!(ScriptOpcode::CAM_LOOKAT, { require: ["p_active_player"], require2: ["p_active_player2"] }),
+        script_opcode!(ScriptOpcode::CAM_MOVETO, { require: ["p_active_player"], require2: ["p_active_player2"] }),
+        script_opcode!(ScriptOpcode::CAM_RESET, { require: ["p_active_player"], require2: ["p_active_player2"] }),
+        script_opcode!(ScriptOpcode::CAM_SHAKE, { require: ["p_active_player"], require2: ["p_active_player2"] }),
+        script_opcode!(ScriptOpcode::CLEARQUEUE, { require: ["p_active_player"], require2: ["p_active_player2"] }),
+        script_opcode!(ScriptOpcode::CLEARSOFTTIME, { require: ["p_active_player"], require2: ["p_active_player2"] }),
+        script_opcode!(ScriptOpcode::CLEARTIMER, { require: ["p_active_player"], require2: ["p_active_player2"] }),
+        script_opcode!(ScriptOpcode::GETTIMER, { require: ["active_player"], require2: ["active_player2"] }),
+        script_opcode!(ScriptOpcode::COORD, { require: ["active_player"], require2: ["active_player2"] }),
+        script_opcode!(ScriptOpcode::DAMAGE, { require: ["p_active_player"], require2: ["p_active_player2"] }),
+        script_opcode!(ScriptOpcode::DISPLAYNAME, { require: ["active_player"], require2: ["active_player2"] }),
+        script_opcode!(ScriptOpcode::FACESQUARE, { require: ["active_player"], require2: ["active_player2"] }),
+        script_opcode!(ScriptOpcode::FINDUID, { set: ["p_active_player"], set2: ["p_active_player2"], conditional: true }),
+        script_opcode!(ScriptOpcode::GENDER, { require: ["active_player"], require2: ["active_player2"] }),
+        script_opcode!(ScriptOpcode::GETQUEUE, { require: ["active_player"], require2: ["active_player2"] }),
+        script_opcode!(ScriptOpcode::STAT_ADVANCE, { require: ["p_active_player"], require2: ["p_active_player2"] }),
+        script_opcode!(ScriptOpcode::HEADICONS_GET, { require: ["active_player"], require2: ["active_player2"] }),
+        script_opcode!(ScriptOpcode::HEADICONS_SET, { require: ["p_active_player"], require2: ["p_active_player2"] }),
+        script_opcode!(ScriptOpcode::HEALENERGY, { require: ["p_active_player"], require2: ["p_active_player2"] }),
+        script_opcode!(ScriptOpcode::HINT_COORD, { require: ["p_active_player"], require2: ["p_active_player2"] }),
+        script_opcode!(ScriptOpcode::HINT_NPC, { require: ["p_active_player"], require2: ["p_active_player2"] }),
+        script_opcode!(ScriptOpcode::HINT_PLAYER, { require: ["p_active_player"], require2: ["p_active_player2"] }),
+        script_opcode!(ScriptOpcode::HINT_STOP, { require: ["p_active_player"], require2: ["p_active_player2"] }),
+        script_opcode!(ScriptOpcode::IF_CLOSE, { require: ["p_active_player"], require2: ["p_active_player2"] }),
+        script_opcode!(ScriptOpcode::TUT_CLOSE, { require: ["p_active_player"], require2: ["p_active_player2"] }),
+        script_opcode!(ScriptOpcode::IF_MULTIZONE, { require: ["p_active_player"], require2: ["p_active_player2"] }),
+        script_opcode!(ScriptOpcode::IF_OPENCHAT, { require: ["p_active_player"], require2: ["p_active_player2"] }),
+        script_opcode!(ScriptOpcode::TUT_OPEN, { require: ["p_active_player"], require2: ["p_active_player2"] }),
+        script_opcode!(ScriptOpcode::IF_OPENMAIN, { require: ["p_active_player"], require2: ["p_active_player2"] }),
+        script_opcode!(ScriptOpcode::IF_OPENOVERLAY, { require: ["p_active_player"], require2: ["p_active_player2"] }),
+        script_opcode!(ScriptOpcode::IF_OPENSIDE, { require: ["p_active_player"], require2: ["p_active_player2"] }),
+        script_opcode!(ScriptOpcode::IF_SETANIM, { require: ["p_active_player"], require2: ["p_active_player2"] }),
+        script_opcode!(ScriptOpcode::IF_SETCOLOUR, { require: ["p_active_player"], require2: ["p_active_player2"] }),
+        script_opcode!(ScriptOpcode::IF_SETHIDE, { require: ["p_active_player"], require2: ["p_active_player2"] }),
+        script_opcode!(ScriptOpcode::IF_SETMODEL, { require: ["p_active_player"], require2: ["p_active_player2"] }),
+        script_opcode!(ScriptOpcode::IF_SETRECOL, { require: ["p_active_player"], require2: ["p_active_player2"] }),
+        script_opcode!(ScriptOpcode::IF_SETNPCHEAD, { require: ["p_active_player"], require2: ["p_active_player2"] }),
+        script_opcode!(ScriptOpcode::IF_SETOBJECT, { require: ["p_active_player"], require2: ["p_active_player2"] }),
+        script_opcode!(ScriptOpcode::IF_SETPLAYERHEAD, { require: ["p_active_player"], require2: ["p_active_player2"] }),
+        script_opcode!(ScriptOpcode::IF_SETPOSITION, { require: ["p_active_player"], require2: ["p_active_player2"] }),
+        script_opcode!(ScriptOpcode::IF_SETRESUMEBUTTONS, { require: ["p_active_player"], require2: ["p_active_player2"] }),
+        script_opcode!(ScriptOpcode::IF_SETTAB, { require: ["p_active_player"], require2: ["p_active_player2"] }),
+        script_opcode!(ScriptOpcode::IF_SETTABACTIVE, { require: ["p_active_player"], require2: ["p_active_player2"] }),
+        script_opcode!(ScriptOpcode::TUT_FLASH, { require: ["p_active_player"], require2: ["p_active_player2"] }),
+        script_opcode!(ScriptOpcode::IF_SETTEXT, { require: ["p_active_player"], require2: ["p_active_player2"] }),
+        script_opcode!(ScriptOpcode::LAST_LOGIN_INFO, { require: ["active_player"], require2: ["active_player2"] }),
+        script_opcode!(ScriptOpcode::LAST_COM, { require: ["active_player"], require2: ["active_player2"] }),
+        script_opcode!(ScriptOpcode::LAST_INT, { require: ["active_player"], require2: ["active_player2"] }),
+        script_opcode!(ScriptOpcode::LAST_ITEM, { require: ["active_player"], require2: ["active_player2"] }),
+        script_opcode!(ScriptOpcode::LAST_SLOT, { require: ["active_player"], require2: ["active_player2"] }),
+        script_opcode!(ScriptOpcode::LAST_TARGETSLOT, { require: ["active_player"], require2: ["active_player2"] }),
+        script_opcode!(ScriptOpcode::LAST_USEITEM, { require: ["active_player"], require2: ["active_player2"] }),
+        script_opcode!(ScriptOpcode::LAST_USESLOT, { require: ["active_player"], require2: ["active_player2"] }),
+        script_opcode!(ScriptOpcode::LONGQUEUE, { require: ["p_active_player"], require2: ["p_active_player2"] }),
         script_opcode!(ScriptOpcode::MES, { require: ["active_player"], require2: ["active_player2"] }),
+        script_opcode!(ScriptOpcode::P_COUNTDIALOG, { require: ["p_active_player"], corrupt: POINTER_GROUP_FIND }),
+        script_opcode!(ScriptOpcode::P_DELAY, { require: ["p_active_player"], corrupt: POINTER_GROUP_FIND }),
+        script_opcode!(ScriptOpcode::P_PAUSEBUTTON, { require: ["p_active_player"], corrupt: POINTER_GROUP_FIND }),
+        script_opcode!(ScriptOpcode::SPOTANIM_PL, { require: ["active_player"], require2: ["active_player2"] }),
+
+        // Npc ops (2500-2999)
+        script_opcode!(ScriptOpcode::NPC_ANIM, { require: ["active_npc"], require2: ["active_npc2"] }),
+        script_opcode!(ScriptOpcode::NPC_DELAY, { require: ["active_npc"], require2: ["active_npc2"], corrupt: POINTER_GROUP_FIND }),
+        script_opcode!(ScriptOpcode::SPOTANIM_NPC, { require: ["active_npc"], require2: ["active_npc2"] }),
+
+        // String ops (4100-4199)
+        script_opcode!(ScriptOpcode::APPEND_NUM, {}),
+        script_opcode!(ScriptOpcode::APPEND, {}),
+        script_opcode!(ScriptOpcode::APPEND_SIGNNUM, {}),
+        script_opcode!(ScriptOpcode::LOWERCASE, {}),
+        script_opcode!(ScriptOpcode::TEXT_GENDER, { require: ["active_player"], require2: ["active_player2"] }),
+        script_opcode!(ScriptOpcode::TOSTRING, {}),
+        script_opcode!(ScriptOpcode::COMPARE, {}),
+        script_opcode!(ScriptOpcode::TEXT_SWITCH, {}),
+        script_opcode!(ScriptOpcode::APPEND_CHAR, {}),
+        script_opcode!(ScriptOpcode::STRING_LENGTH, {}),
+        script_opcode!(ScriptOpcode::SUBSTRING, {}),
+        script_opcode!(ScriptOpcode::STRING_INDEXOF_CHAR, {}),
+        script_opcode!(ScriptOpcode::STRING_INDEXOF_STRING, {}),
+
+        // Enum ops (4400-4499)
+        script_opcode!(ScriptOpcode::ENUM, {}),
+        script_opcode!(ScriptOpcode::ENUM_GETOUTPUTCOUNT, {}),
+
+        // Math ops (4600-4699)
+        script_opcode!(ScriptOpcode::ADD, {}),
+        script_opcode!(ScriptOpcode::SUB, {}),
+        script_opcode!(ScriptOpcode::MULTIPLY, {}),
+        script_opcode!(ScriptOpcode::DIVIDE, {}),
+        script_opcode!(ScriptOpcode::RANDOM, {}),
+        script_opcode!(ScriptOpcode::RANDOMINC, {}),
+        script_opcode!(ScriptOpcode::INTERPOLATE, {}),
+        script_opcode!(ScriptOpcode::ADDPERCENT, {}),
+        script_opcode!(ScriptOpcode::SETBIT, {}),
+        script_opcode!(ScriptOpcode::CLEARBIT, {}),
+        script_opcode!(ScriptOpcode::TESTBIT, {}),
+        script_opcode!(ScriptOpcode::MODULO, {}),
+        script_opcode!(ScriptOpcode::POW, {}),
+        script_opcode!(ScriptOpcode::INVPOW, {}),
+        script_opcode!(ScriptOpcode::AND, {}),
+        script_opcode!(ScriptOpcode::OR, {}),
+        script_opcode!(ScriptOpcode::MIN, {}),
+        script_opcode!(ScriptOpcode::MAX, {}),
+        script_opcode!(ScriptOpcode::SCALE, {}),
+        script_opcode!(ScriptOpcode::BITCOUNT, {}),
+        script_opcode!(ScriptOpcode::TOGGLEBIT, {}),
+        script_opcode!(ScriptOpcode::SETBIT_RANGE, {}),
+        script_opcode!(ScriptOpcode::CLEARBIT_RANGE, {}),
+        script_opcode!(ScriptOpcode::GETBIT_RANGE, {}),
+        script_opcode!(ScriptOpcode::SETBIT_RANGE_TOINT, {}),
+        script_opcode!(ScriptOpcode::SIN_DEG, {}),
+        script_opcode!(ScriptOpcode::COS_DEG, {}),
+        script_opcode!(ScriptOpcode::ATAN2_DEG, {}),
+        script_opcode!(ScriptOpcode::ABS, {}),
     ];
     
     for (name, opcode) in opcodes {
@@ -0,0 +1,39 @@
+use strum::IntoEnumIterator;
+use crate::script::script_opcode::ScriptOpcode;
+use crate::script::script_opcode_pointers::initialize_script_opcode_pointers;
+use crate::util::symbols::command_symbols;
+
+/// The columns after the opcode of `command` in commands.sym.
+fn command_line(symbols: &str, command: &str) -> Vec<String> {
+    let line = symbols
+        .lines()
+        .find(|line| line.split('\t').nth(1) == Some(command))
+        .unwrap_or_else(|| panic!("{} is missing from commands.sym", command));
+    line.split('\t').skip(1).map(String::from).collect()
+}
+
+#[test]
+fn test_every_opcode_has_pointers() {
+    let pointers = initialize_script_opcode_pointers();
+
+    let missing: Vec<ScriptOpcode> = ScriptOpcode::iter().filter(|opcode| !pointers.contains_key(opcode)).collect();
+    assert!(missing.is_empty(), "No pointer entry for {:?}", missing);
+    assert_eq!(pointers.len(), ScriptOpcode::iter().count());
+}
+
+#[test]
+fn test_command_symbols() {
+    let symbols = command_symbols();
+
+    assert_eq!(symbols.lines().count(), ScriptOpcode::iter().count());
+    for line in symbols.lines() {
+        assert_eq!(line.split('\t').count(), 8, "{}", line);
+    }
+
+    assert_eq!(command_line(&symbols, "mes"), vec!["mes", "active_player", "none", "none", "false", "true", "active_player2"]);
+    assert_eq!(command_line(&symbols, "p_delay"), vec![
+        "p_delay", "p_active_player", "find_player,find_npc,find_loc,find_obj,find_db", "none", "false", "false", "none",
+    ]);
+    assert_eq!(command_line(&symbols, "huntnext"), vec!["huntnext", "none", "none", "active_player", "true", "true", "none"]);
+    assert_eq!(command_line(&symbols, "add"), vec!["add", "none", "none", "none", "false", "false", "none"]);
+}
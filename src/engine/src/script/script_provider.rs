@@ -4,6 +4,7 @@ use std::sync::OnceLock;
 use log::{debug, error};
 use crate::io::packet::Packet;
 use crate::script::script_file::ScriptFile;
+use crate::script::script_runner::ScriptRunner;
 use crate::script::server_trigger_types::ServerTriggerTypes;
 
 // Static storage using OnceLock for thread safety
@@ -24,10 +25,11 @@ impl ScriptProvider {
     }
 
     pub fn load() -> u32 {
-        let dat_path = "./data/pack/server/script.dat";
-        let idx_path = "./data/pack/server/script.idx";
+        let data_path = &config::get().paths.data;
+        let dat_path = format!("{}/pack/server/script.dat", data_path);
+        let idx_path = format!("{}/pack/server/script.idx", data_path);
 
-        match (Packet::io(dat_path.parse().unwrap()), Packet::io(idx_path.parse().unwrap())) {
+        match (Packet::io(dat_path), Packet::io(idx_path)) {
             (Ok(dat), Ok(idx)) => {
                 let count = Self::parse(dat, idx);
                 debug!("Loaded {} scripts", count);
@@ -68,7 +70,13 @@ impl ScriptProvider {
 
             match (|| {
                 let bytes = dat.gbytes(size as usize);
-                let script = ScriptFile::decode(id as usize, Packet::from(bytes));
+                let script = ScriptFile::decode(id as usize, Packet::from(bytes))?;
+
+                // A script the interpreter can't run is left out, so triggers and gosubs never reach it.
+                if let Err(err) = ScriptRunner::validate(&script) {
+                    error!("Script {} is not loaded: {}", script.name(), err);
+                    return Ok(());
+                }
 
                 if script.info.lookup_key != -1 {
                     script_lookup.insert(script.info.lookup_key as usize, script.clone());
@@ -97,10 +105,13 @@ impl ScriptProvider {
         loaded
     }
 
+    /// The script with id `id`, procs and labels are only reachable this way since they have no lookup key.
     #[inline]
     pub fn get(id: usize) -> Option<ScriptFile> {
-        let lookup = SCRIPT_LOOKUP.get()?;
-        lookup.get(&id).cloned()
+        // Scripts are stored in id order, but empty entries in the index are skipped.
+        let scripts = SCRIPTS.get()?;
+        let index = scripts.binary_search_by_key(&(id as i64), |script| script.id as i64).ok()?;
+        scripts.get(index).cloned()
     }
     
     #[inline]
@@ -2,20 +2,30 @@ use std::collections::HashMap;
 use std::sync::OnceLock;
 use std::time::Instant;
 use log::{debug, error};
+use crate::engine::Engine;
 use crate::entity::entity_queue_request::ScriptArgument;
 use crate::entity::entity_type::EntityType;
+use crate::script::handlers::config_ops::get_config_ops;
 use crate::script::handlers::core_ops::get_core_ops;
+use crate::script::handlers::math_ops::get_math_ops;
+use crate::script::handlers::npc_ops::get_npc_ops;
 use crate::script::handlers::player_ops::get_player_ops;
+use crate::script::handlers::server_ops::get_server_ops;
+use crate::script::handlers::string_ops::get_string_ops;
 use crate::script::script_file::ScriptFile;
+use crate::script::script_opcode::ScriptOpcode;
 use crate::script::script_pointer::ScriptPointer;
 use crate::script::script_state::ScriptState;
 
-// Function type for script handlers - requires Send+Sync for thread safety
-pub type CommandHandler = fn(&mut ScriptState);
+// Function type for script handlers, the engine is handed in so handlers can resolve and change the world.
+pub type CommandHandler = fn(&mut ScriptState, &mut Engine);
 
 // Map of opcode numbers to handler functions
 pub type CommandHandlers = HashMap<i32, CommandHandler>;
 
+// Handlers indexed by opcode number, opcodes without a handler are None.
+pub type CommandTable = Vec<Option<CommandHandler>>;
+
 pub struct ScriptRunner;
 
 pub const OP_LIMIT: i32 = 500_000;
@@ -35,10 +45,87 @@ impl ScriptRunner {
                 handlers.insert(*key, *func);
             }
 
+            for (key, func) in get_server_ops().iter() {
+                handlers.insert(*key, *func);
+            }
+
+            for (key, func) in get_npc_ops().iter() {
+                handlers.insert(*key, *func);
+            }
+
+            for (key, func) in get_config_ops().iter() {
+                handlers.insert(*key, *func);
+            }
+
+            for (key, func) in get_string_ops().iter() {
+                handlers.insert(*key, *func);
+            }
+
+            for (key, func) in get_math_ops().iter() {
+                handlers.insert(*key, *func);
+            }
+
             handlers
         })
     }
 
+    /// The handlers as a dense table, which the interpreter indexes by opcode instead of hashing it.
+    pub fn get_handler_table() -> &'static [Option<CommandHandler>] {
+        static TABLE: OnceLock<CommandTable> = OnceLock::new();
+
+        TABLE.get_or_init(|| {
+            let handlers = Self::get_handlers();
+            let size = handlers.keys().max().map_or(0, |&max| max as usize + 1);
+
+            let mut table: CommandTable = vec![None; size];
+            for (key, func) in handlers.iter() {
+                table[*key as usize] = Some(*func);
+            }
+
+            table
+        })
+    }
+
+    /// Checks once on load what the interpreter relies on: every opcode has a handler, every
+    /// switch has a table, and every branch or case lands inside the script.
+    pub fn validate(script: &ScriptFile) -> Result<(), String> {
+        let table = Self::get_handler_table();
+        let length = script.opcodes.len() as i32;
+        let in_bounds = |pc: usize, offset: i32| (-1..length).contains(&(pc as i32 + offset));
+
+        for (pc, &opcode) in script.opcodes.iter().enumerate() {
+            if table.get(opcode as usize).copied().flatten().is_none() {
+                return Err(format!("Unimplemented opcode {:?} at pc {}", opcode, pc));
+            }
+
+            let operand = script.int_operands.get(pc).copied().unwrap_or(0);
+            match opcode {
+                ScriptOpcode::BRANCH
+                | ScriptOpcode::BRANCH_NOT
+                | ScriptOpcode::BRANCH_EQUALS
+                | ScriptOpcode::BRANCH_LESS_THAN
+                | ScriptOpcode::BRANCH_GREATER_THAN
+                | ScriptOpcode::BRANCH_LESS_THAN_OR_EQUALS
+                | ScriptOpcode::BRANCH_GREATER_THAN_OR_EQUALS if !in_bounds(pc, operand) => {
+                    return Err(format!("Branch at pc {} jumps out of the script", pc));
+                }
+
+                ScriptOpcode::SWITCH => {
+                    let Some(switch) = script.switch_tables.get(operand as usize) else {
+                        return Err(format!("Switch at pc {} has no table {}", pc, operand));
+                    };
+                    if switch.offsets().any(|offset| !in_bounds(pc, offset)) {
+                        return Err(format!("Switch at pc {} jumps out of the script", pc));
+                    }
+                }
+
+                _ => {}
+            }
+        }
+
+        Ok(())
+    }
+
     #[inline]
     pub fn init(
         script: ScriptFile,
@@ -54,27 +141,24 @@ impl ScriptRunner {
 
         if let Some(self_ent) = self_entity {
             state.self_entity = Some(self_ent);
-            
-            unsafe {
-                match &state.self_entity {
-                    Some(EntityType::Player(player)) => {
-                        state.active_player = Some(player.clone());
-                        state.pointer_add(ScriptPointer::ActivePlayer);
-                    },
-                    Some(EntityType::NPC(npc)) => {
-                        state.active_npc = Some(npc.clone());
-                        state.pointer_add(ScriptPointer::ActiveNpc);
-                    },
-                    Some(EntityType::Loc(loc)) => {
-                        state.active_loc = Some(loc.clone());
-                        state.pointer_add(ScriptPointer::ActiveLoc);
-                    },
-                    Some(EntityType::Obj(obj)) => {
-                        state.active_obj = Some(obj.clone());
-                        state.pointer_add(ScriptPointer::ActiveObj);
-                    },
-                    _ => {}
-                }
+
+            match self_ent {
+                EntityType::Player(pid) => {
+                    state.active_player = Some(pid);
+                    state.pointer_add(ScriptPointer::ActivePlayer);
+                },
+                EntityType::NPC(nid) => {
+                    state.active_npc = Some(nid);
+                    state.pointer_add(ScriptPointer::ActiveNpc);
+                },
+                EntityType::Loc(uid) => {
+                    state.active_loc = Some(uid);
+                    state.pointer_add(ScriptPointer::ActiveLoc);
+                },
+                EntityType::Obj(uid) => {
+                    state.active_obj = Some(uid);
+                    state.pointer_add(ScriptPointer::ActiveObj);
+                },
             }
         }
 
@@ -86,44 +170,42 @@ impl ScriptRunner {
                 (Some(EntityType::Obj(_)), EntityType::Obj(_)) => true,
                 _ => false
             };
-            
-            unsafe {
-                match target {
-                    EntityType::Player(player) => {
-                        if has_same_type {
-                            state.active_player2 = Some(player.clone());
-                            state.pointer_add(ScriptPointer::ActivePlayer2);
-                        } else {
-                            state.active_player = Some(player.clone());
-                            state.pointer_add(ScriptPointer::ActivePlayer);
-                        }
-                    },
-                    EntityType::NPC(npc) => {
-                        if has_same_type {
-                            state.active_npc2 = Some(npc.clone());
-                            state.pointer_add(ScriptPointer::ActiveNpc2);
-                        } else {
-                            state.active_npc = Some(npc.clone());
-                            state.pointer_add(ScriptPointer::ActiveNpc);
-                        }
-                    },
-                    EntityType::Loc(loc) => {
-                        if has_same_type {
-                            state.active_loc2 = Some(loc.clone());
-                            state.pointer_add(ScriptPointer::ActiveLoc2);
-                        } else {
-                            state.active_loc = Some(loc.clone());
-                            state.pointer_add(ScriptPointer::ActiveLoc);
-                        }
-                    },
-                    EntityType::Obj(obj) => {
-                        if has_same_type {
-                            state.active_obj2 = Some(obj.clone());
-                            state.pointer_add(ScriptPointer::ActiveObj2);
-                        } else {
-                            state.active_obj = Some(obj.clone());
-                            state.pointer_add(ScriptPointer::ActiveObj);
-                        }
+
+            match target {
+                EntityType::Player(pid) => {
+                    if has_same_type {
+                        state.active_player2 = Some(pid);
+                        state.pointer_add(ScriptPointer::ActivePlayer2);
+                    } else {
+                        state.active_player = Some(pid);
+                        state.pointer_add(ScriptPointer::ActivePlayer);
+                    }
+                },
+                EntityType::NPC(nid) => {
+                    if has_same_type {
+                        state.active_npc2 = Some(nid);
+                        state.pointer_add(ScriptPointer::ActiveNpc2);
+                    } else {
+                        state.active_npc = Some(nid);
+                        state.pointer_add(ScriptPointer::ActiveNpc);
+                    }
+                },
+                EntityType::Loc(uid) => {
+                    if has_same_type {
+                        state.active_loc2 = Some(uid);
+                        state.pointer_add(ScriptPointer::ActiveLoc2);
+                    } else {
+                        state.active_loc = Some(uid);
+                        state.pointer_add(ScriptPointer::ActiveLoc);
+                    }
+                },
+                EntityType::Obj(uid) => {
+                    if has_same_type {
+                        state.active_obj2 = Some(uid);
+                        state.pointer_add(ScriptPointer::ActiveObj2);
+                    } else {
+                        state.active_obj = Some(uid);
+                        state.pointer_add(ScriptPointer::ActiveObj);
                     }
                 }
             }
@@ -135,6 +217,7 @@ impl ScriptRunner {
     #[inline(always)]
     pub fn execute(
         state: &mut ScriptState,
+        engine: &mut Engine,
         reset: bool,
         benchmark: bool,
     ) -> i32 {
@@ -151,7 +234,7 @@ impl ScriptRunner {
         #[cfg(feature = "profiling")]
         let start = if benchmark { Some(Instant::now()) } else { None };
 
-        let handlers = Self::get_handlers();
+        let handlers = Self::get_handler_table();
         
         while state.execution == ScriptState::RUNNING {
             state.opcount += 1;
@@ -168,26 +251,17 @@ impl ScriptRunner {
                 break;
             }
 
-            let opcode = state.script.opcodes[state.pc as usize] as i32;
+            let opcode = state.script.opcodes[state.pc as usize];
 
-            #[cfg(debug_assertions)]
-            {
-                if let Some(handler) = handlers.get(&opcode) {
-                    let handler_fn = *handler;
-                    handler_fn(state);
-                } else {
-                    error!("Unknown opcode: {}", opcode);
+            // Loaded scripts are validated, this only catches scripts built some other way.
+            match handlers.get(opcode as usize) {
+                Some(Some(handler)) => handler(state, engine),
+                _ => {
+                    error!("Unknown opcode: {:?}", opcode);
                     state.execution = ScriptState::ABORTED;
                     break;
                 }
             }
-
-            #[cfg(not(debug_assertions))]
-            {
-                // SAFETY: All opcodes are validated at compile-time
-                let handler_fn = unsafe { *handlers.get(&opcode).unwrap_unchecked() };
-                handler_fn(state);
-            }
         }
 
         #[cfg(feature = "profiling")]
@@ -215,11 +289,11 @@ impl ScriptRunner {
     }
 
     #[inline(always)]
-    pub fn execute_opcode(state: &mut ScriptState, opcode: i32) -> Result<(), String> {
-        let handlers = Self::get_handlers();
+    pub fn execute_opcode(state: &mut ScriptState, engine: &mut Engine, opcode: i32) -> Result<(), String> {
+        let handlers = Self::get_handler_table();
 
-        if let Some(handler) = handlers.get(&opcode) {
-            handler(state);
+        if let Some(Some(handler)) = usize::try_from(opcode).ok().and_then(|opcode| handlers.get(opcode)) {
+            handler(state, engine);
             Ok(())
         } else {
             Err(format!("Unknown opcode: {}", opcode))
@@ -0,0 +1,90 @@
+use crate::engine::Engine;
+use crate::io::packet::Packet;
+use crate::script::script_file::{ScriptFile, SwitchTable};
+use crate::script::script_opcode::ScriptOpcode;
+use crate::script::script_opcode::ScriptOpcode::*;
+use crate::script::script_runner::ScriptRunner;
+use crate::script::script_state::ScriptState;
+
+/// `(opcode, int operand)` instructions.
+type Instructions<'a> = &'a [(ScriptOpcode, i32)];
+
+fn script(instructions: Instructions, switch_tables: Vec<SwitchTable>) -> ScriptFile {
+    let mut script = ScriptFile::new(0);
+    script.info.lookup_key = 0;
+    script.switch_tables = switch_tables;
+
+    for &(opcode, operand) in instructions {
+        script.opcodes.push(opcode);
+        script.int_operands.push(operand);
+        script.string_operands.push(String::new());
+    }
+    script
+}
+
+#[test]
+fn test_handler_table() {
+    let handlers = ScriptRunner::get_handlers();
+    let table = ScriptRunner::get_handler_table();
+
+    assert_eq!(table.iter().flatten().count(), handlers.len());
+    for key in handlers.keys() {
+        assert!(table[*key as usize].is_some(), "{:?}", ScriptOpcode::try_from(*key));
+    }
+    assert!(table[COORDX as usize].is_none());
+}
+
+#[test]
+fn test_validate() {
+    let cases: &[(Instructions, Vec<SwitchTable>, Result<(), &str>)] = &[
+        (&[(PUSH_CONSTANT_INT, 1), (BRANCH, -2)], vec![], Ok(())),
+        (&[(BRANCH, 1), (RETURN, 0)], vec![], Ok(())),
+        (&[(PUSH_CONSTANT_INT, 1), (SWITCH, 0), (RETURN, 0)], vec![SwitchTable::new(vec![(1, 1), (2, 0)])], Ok(())),
+        (&[(PUSH_CONSTANT_INT, 0), (COORDX, 0)], vec![], Err("Unimplemented opcode COORDX at pc 1")),
+        (&[(BRANCH, 2), (RETURN, 0)], vec![], Err("Branch at pc 0 jumps out of the script")),
+        (&[(PUSH_CONSTANT_INT, 1), (PUSH_CONSTANT_INT, 2), (BRANCH_EQUALS, -4)], vec![], Err("Branch at pc 2 jumps out of the script")),
+        (&[(PUSH_CONSTANT_INT, 1), (SWITCH, 0)], vec![], Err("Switch at pc 1 has no table 0")),
+        (&[(PUSH_CONSTANT_INT, 1), (SWITCH, 0)], vec![SwitchTable::new(vec![(1, 1)])], Err("Switch at pc 1 jumps out of the script")),
+    ];
+
+    for (instructions, switch_tables, expected) in cases {
+        let result = ScriptRunner::validate(&script(instructions, switch_tables.clone()));
+        assert_eq!(result, expected.map_err(String::from), "{:?}", instructions);
+    }
+}
+
+#[test]
+fn test_unknown_opcode_aborts() {
+    let mut state = ScriptRunner::init(script(&[(COORDX, 0)], vec![]), None, None, None);
+    let mut engine = Engine::new();
+
+    assert_eq!(ScriptRunner::execute(&mut state, &mut engine, false, false), ScriptState::ABORTED);
+    assert!(ScriptRunner::execute_opcode(&mut state, &mut engine, COORDX as i32).is_err());
+    assert!(ScriptRunner::execute_opcode(&mut state, &mut engine, -1).is_err());
+}
+
+#[test]
+fn test_unimplemented_var_ops_fail_validation() {
+    let mut packet = Packet::from(Vec::new());
+    packet.pjstr("[proc,varp]", 0);
+    packet.pjstr("varp.rs2", 0);
+    packet.p4(-1);
+    packet.p1(0);
+    packet.p2(0);
+
+    packet.p2(PUSH_VARP as i32); packet.p4(12);
+    packet.p2(RETURN as i32); packet.p1(0);
+
+    packet.p4(2);
+    packet.p2(0); packet.p2(0); packet.p2(0); packet.p2(0);
+    packet.p1(0);
+    packet.p2(1);
+
+    // Vars aren't stored anywhere yet, a script reading one must never load.
+    let decoded = ScriptFile::decode(0, packet).unwrap();
+    assert_eq!(ScriptRunner::validate(&decoded), Err("Unimplemented opcode PUSH_VARP at pc 0".to_string()));
+
+    for opcode in [PUSH_VARP, POP_VARP, PUSH_VARN, POP_VARN, PUSH_VARS, POP_VARS, PUSH_VARBIT, POP_VARBIT, PUSH_VARC_INT, POP_VARC_INT] {
+        assert!(ScriptRunner::validate(&script(&[(opcode, 0)], vec![])).is_err(), "{:?}", opcode);
+    }
+}
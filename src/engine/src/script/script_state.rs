@@ -1,10 +1,10 @@
 use std::sync::Arc;
+use log::error;
 use reqwest::header::SERVER;
 use crate::entity::entity_queue_request::ScriptArgument;
 use crate::entity::entity_type::EntityType;
-use crate::entity::loc::Loc;
+use crate::engine::Engine;
 use crate::entity::npc::NPC;
-use crate::entity::obj::Obj;
 use crate::entity::player::Player;
 use crate::script::script_file::ScriptFile;
 use crate::script::script_pointer::ScriptPointer;
@@ -15,7 +15,8 @@ pub struct GosubStackFrame {
     pub script: Arc<ScriptFile>,
     pub pc: i32,
     pub int_locals: Vec<i32>,
-    pub string_locals: Vec<String>
+    pub string_locals: Vec<String>,
+    pub int_arrays: Vec<Vec<i32>>
 }
 
 #[derive(Clone, PartialEq)]
@@ -42,16 +43,22 @@ pub struct ScriptState {
     pub ssp: usize,
     pub int_locals: Vec<i32>,
     pub string_locals: Vec<String>,
+    /// Arrays defined by the running script, indexed by array id.
+    pub int_arrays: Vec<Vec<i32>>,
     pub pointers: i32,
     pub self_entity: Option<EntityType>,
-    pub active_player: Option<Player>,
-    pub active_player2: Option<Player>,
-    pub active_npc: Option<NPC>,
-    pub active_npc2: Option<NPC>,
-    pub active_loc: Option<Loc>,
-    pub active_loc2: Option<Loc>,
-    pub active_obj: Option<Obj>,
-    pub active_obj2: Option<Obj>,
+    /// Pid of the active player.
+    pub active_player: Option<usize>,
+    pub active_player2: Option<usize>,
+    /// Nid of the active npc.
+    pub active_npc: Option<usize>,
+    pub active_npc2: Option<usize>,
+    /// Uid of the active loc.
+    pub active_loc: Option<u64>,
+    pub active_loc2: Option<u64>,
+    /// Uid of the active obj.
+    pub active_obj: Option<u64>,
+    pub active_obj2: Option<u64>,
     pub split_pages: Vec<Vec<String>>,
     pub split_mesanim: i32
 }
@@ -82,6 +89,10 @@ impl ScriptState {
             }
         }
 
+        // Arguments fill the first locals, the rest start out empty.
+        int_locals.resize(int_locals.len().max(script.int_local_count as usize), 0);
+        string_locals.resize(string_locals.len().max(script.string_local_count as usize), String::new());
+
         let arc_script = Arc::new(script);
         let trigger = ServerTriggerTypes::try_from(arc_script.info.lookup_key & 0xFF).unwrap();
 
@@ -102,6 +113,7 @@ impl ScriptState {
             ssp: 0,
             int_locals,
             string_locals,
+            int_arrays: Vec::new(),
             pointers: 0,
             self_entity: None,
             active_player: None,
@@ -155,25 +167,62 @@ impl ScriptState {
             .join(", ")
     }
 
-    pub fn get_active_player(&mut self) -> Result<&mut Player, String> {
-        match if self.get_int_operand() == 0 {
-            &mut self.active_player
+    /// Resolves the active player through the engine, the operand selects the primary or secondary player.
+    pub fn get_active_player<'a>(&self, engine: &'a mut Engine) -> Result<&'a mut Player, String> {
+        let (pointer, pid) = if self.get_int_operand() == 0 {
+            (ScriptPointer::ActivePlayer, self.active_player)
         } else {
-            &mut self.active_player2
-        } {
-            Some(player) => Ok(player),
-            None => Err("Player not found".to_string()),
-        }
+            (ScriptPointer::ActivePlayer2, self.active_player2)
+        };
+
+        self.pointer_check(&[pointer])?;
+
+        let pid = pid.ok_or_else(|| "Player not found".to_string())?;
+        engine.players.get_mut(pid).ok_or_else(|| format!("No player found for PID: {}", pid))
     }
 
-    pub fn set_active_player(&mut self, player: Player) {
+    /// Same as [`ScriptState::get_active_player`], but the script must hold protected access to the player.
+    pub fn get_protected_active_player<'a>(&self, engine: &'a mut Engine) -> Result<&'a mut Player, String> {
+        let pointer = if self.get_int_operand() == 0 {
+            ScriptPointer::ProtectedActivePlayer
+        } else {
+            ScriptPointer::ProtectedActivePlayer2
+        };
+
+        self.pointer_check(&[pointer])?;
+        self.get_active_player(engine)
+    }
+
+    pub fn set_active_player(&mut self, pid: usize) {
         if self.get_int_operand() == 0 {
-            self.active_player = Some(player);
+            self.active_player = Some(pid);
+            self.pointer_add(ScriptPointer::ActivePlayer);
         } else {
-            self.active_player2 = Some(player);
+            self.active_player2 = Some(pid);
+            self.pointer_add(ScriptPointer::ActivePlayer2);
         }
     }
 
+    /// Resolves the active npc through the engine, the operand selects the primary or secondary npc.
+    pub fn get_active_npc<'a>(&self, engine: &'a mut Engine) -> Result<&'a mut NPC, String> {
+        let (pointer, nid) = if self.get_int_operand() == 0 {
+            (ScriptPointer::ActiveNpc, self.active_npc)
+        } else {
+            (ScriptPointer::ActiveNpc2, self.active_npc2)
+        };
+
+        self.pointer_check(&[pointer])?;
+
+        let nid = nid.ok_or_else(|| "Npc not found".to_string())?;
+        engine.npcs.get_mut(nid).ok_or_else(|| format!("No npc found for NID: {}", nid))
+    }
+
+    /// Logs why a command couldn't run and stops the script.
+    pub fn abort(&mut self, reason: &str) {
+        error!("Script {} aborted: {}", self.script.name(), reason);
+        self.execution = Self::ABORTED;
+    }
+
     pub fn get_int_operand(&self) -> i32 {
         self.script.int_operands[self.pc as usize]
     }
@@ -187,18 +236,13 @@ impl ScriptState {
         self.int_stack.get(self.isp).copied().unwrap_or(0)
     }
 
+    /// Pops `amount` ints, returned in the order they were pushed.
     #[inline(always)]
     pub fn pop_ints(&mut self, amount: usize) -> Vec<i32> {
-        let mut result = Vec::with_capacity(amount);
-        let start_idx = self.isp.saturating_sub(1);
-        for i in (start_idx.saturating_sub(amount-1)..=start_idx).rev() {
-            if i < self.int_stack.len() {
-                result.push(self.int_stack[i]);
-            }
+        let mut result = vec![0; amount];
+        for i in (0..amount).rev() {
+            result[i] = self.pop_int();
         }
-
-        self.isp = self.isp.saturating_sub(amount);
-
         result
     }
 
@@ -231,8 +275,13 @@ impl ScriptState {
         }
     }
 
+    /// Pops `amount` strings, returned in the order they were pushed.
     pub fn pop_strings(&mut self, amount: usize) -> Vec<String> {
-        (0..amount).rev().map(|_| self.pop_string()).collect()
+        let mut result = vec![String::new(); amount];
+        for i in (0..amount).rev() {
+            result[i] = self.pop_string();
+        }
+        result
     }
 
     #[inline(always)]
@@ -257,6 +306,7 @@ impl ScriptState {
         self.script = frame.script.clone();
         self.int_locals = frame.int_locals.clone();
         self.string_locals = frame.string_locals.clone();
+        self.int_arrays = frame.int_arrays.clone();
     }
 
     pub fn gosub_frame(&mut self, proc: ScriptFile) {
@@ -266,6 +316,7 @@ impl ScriptState {
                 pc: 0,
                 int_locals: Vec::new(),
                 string_locals: Vec::new(),
+                int_arrays: Vec::new(),
             });
         }
 
@@ -282,6 +333,7 @@ impl ScriptState {
             pc: self.pc,
             int_locals: new_int_locals,
             string_locals: new_string_locals,
+            int_arrays: std::mem::take(&mut self.int_arrays),
         };
 
         self.fp += 1;
@@ -330,6 +382,7 @@ impl ScriptState {
         self.script = arc_script;
         self.int_locals = int_locals;
         self.string_locals = string_locals;
+        self.int_arrays.clear();
     }
     
     pub fn reset(&mut self) {
@@ -342,6 +395,7 @@ impl ScriptState {
         self.ssp = 0;
         self.int_locals.clear();
         self.string_locals.clear();
+        self.int_arrays.clear();
         self.pointers = 0;
     }
 }
\ No newline at end of file
@@ -0,0 +1,111 @@
+use std::collections::VecDeque;
+use std::sync::Arc;
+use crate::engine::Engine;
+use crate::entity::player::Player;
+use crate::grid::coord_grid::CoordGrid;
+use crate::io::client::incoming_message::IncomingMessage;
+use crate::io::client::protocol::client_protocol::{ClientProtocol, ProtocolId};
+use crate::io::server::outgoing_message::OutgoingMessageEnum;
+
+/// A client message that skipped the socket and the decoder.
+#[derive(Clone)]
+pub struct InjectedMessage {
+    pub protocol: ProtocolId,
+    pub message: Arc<dyn IncomingMessage + Send + Sync>,
+}
+
+impl PartialEq for InjectedMessage {
+    fn eq(&self, other: &Self) -> bool {
+        self.protocol == other.protocol && Arc::ptr_eq(&self.message, &other.message)
+    }
+}
+
+/// Socket replacement for headless players: whatever is queued in `inbound` is handled on the
+/// next tick, whatever the server writes ends up in `outgoing` instead of being encoded.
+#[derive(Clone, PartialEq, Default)]
+pub struct FakeClient {
+    pub inbound: VecDeque<InjectedMessage>,
+    pub outgoing: Vec<OutgoingMessageEnum>,
+}
+
+impl FakeClient {
+    pub fn new() -> FakeClient {
+        FakeClient::default()
+    }
+
+    pub fn send<M: IncomingMessage>(&mut self, protocol: &ClientProtocol, message: M) {
+        self.inbound.push_back(InjectedMessage { protocol: protocol.id, message: Arc::new(message) });
+    }
+
+    pub fn take_outgoing(&mut self) -> Vec<OutgoingMessageEnum> {
+        std::mem::take(&mut self.outgoing)
+    }
+}
+
+/// Drives an [`Engine`] one tick at a time without a listener or the wall clock, so the same seed
+/// and the same inputs always play out the same way.
+pub struct Simulation {
+    pub engine: Engine,
+}
+
+impl Simulation {
+    pub fn new(seed: u64) -> Simulation {
+        Simulation {
+            engine: Engine::with_seed(seed),
+        }
+    }
+
+    /// Logs in a player backed by a [`FakeClient`], returns its pid once the login tick has run.
+    pub fn login(&mut self, username: &str, coord: CoordGrid) -> Option<usize> {
+        let mut player = Player::new_dummy(coord, 0, 0);
+        player.username = username.to_string();
+        player.fake_client = Some(FakeClient::new());
+        self.engine.new_players.lock().unwrap().push(player);
+
+        self.step();
+
+        let mut pid = None;
+        self.engine.players.for_each(|player| {
+            if player.username == username {
+                pid = Some(player.get_pid());
+            }
+        });
+        pid
+    }
+
+    #[inline]
+    pub fn step(&mut self) {
+        self.engine.tick();
+    }
+
+    pub fn run(&mut self, ticks: usize) {
+        for _ in 0..ticks {
+            self.step();
+        }
+    }
+
+    #[inline]
+    pub fn current_tick(&self) -> i32 {
+        self.engine.current_tick
+    }
+
+    pub fn player(&mut self, pid: usize) -> Option<&mut Player> {
+        self.engine.players.get_mut(pid)
+    }
+
+    /// Queues `message` as if the player's client had sent it, it is handled on the next tick.
+    pub fn send<M: IncomingMessage>(&mut self, pid: usize, protocol: &ClientProtocol, message: M) {
+        match self.player(pid).and_then(|player| player.fake_client.as_mut()) {
+            Some(fake) => fake.send(protocol, message),
+            None => panic!("Player {} has no fake client", pid),
+        }
+    }
+
+    /// Everything written to the player since the last call.
+    pub fn outgoing(&mut self, pid: usize) -> Vec<OutgoingMessageEnum> {
+        self.player(pid)
+            .and_then(|player| player.fake_client.as_mut())
+            .map(FakeClient::take_outgoing)
+            .unwrap_or_default()
+    }
+}
@@ -0,0 +1,243 @@
+use crate::entity::entity_queue_request::ScriptArgument;
+use crate::entity::block_walk::BlockWalk;
+use crate::entity::entity_lifecycle::EntityLifeCycle;
+use crate::entity::entity_type::EntityType;
+use crate::entity::interface_tree::ModalType;
+use crate::entity::move_restrict::MoveRestrict;
+use crate::entity::npc::NPC;
+use crate::grid::coord_grid::CoordGrid;
+use crate::io::client::model::resume_p_countdialog::ResumePCountDialogMessage;
+use crate::io::client::model::resume_pausebutton::ResumePauseButtonMessage;
+use crate::io::client::model::transmitvar_verifyid::TransmitVarVerifyIdMessage;
+use crate::io::client::protocol::client_protocol::ClientProtocol;
+use crate::io::server::model::message_game::Message_Game;
+use crate::io::server::outgoing_message::OutgoingMessageEnum;
+use crate::script::script_file::ScriptFile;
+use crate::script::script_opcode::ScriptOpcode;
+use crate::script::script_opcode::ScriptOpcode::*;
+use crate::script::script_runner::ScriptRunner;
+use crate::script::script_state::ScriptState;
+use crate::simulation::Simulation;
+
+const DIALOG_INTERFACE: u32 = 241;
+
+fn script_file(instructions: &[(ScriptOpcode, i32, &str)]) -> ScriptFile {
+    let mut script = ScriptFile::new(0);
+    script.info.lookup_key = 0;
+    for &(opcode, int_operand, string_operand) in instructions {
+        script.opcodes.push(opcode);
+        script.int_operands.push(int_operand);
+        script.string_operands.push(string_operand.to_string());
+    }
+    script
+}
+
+/// Runs `instructions` for `pid` with protected access on the next tick, like a clicked op would.
+fn run_script(simulation: &mut Simulation, pid: usize, instructions: &[(ScriptOpcode, i32, &str)]) {
+    let state = ScriptRunner::init(script_file(instructions), Some(EntityType::Player(pid)), None, None);
+    simulation.player(pid).unwrap().queue_script(state, true);
+    simulation.step();
+}
+
+fn messages(simulation: &mut Simulation, pid: usize) -> Vec<Message_Game> {
+    simulation.outgoing(pid).into_iter().filter_map(|message| match message {
+        OutgoingMessageEnum::MessageGame(message) => Some(message),
+        _ => None,
+    }).collect()
+}
+
+fn waiting_on(simulation: &mut Simulation, pid: usize) -> Option<i32> {
+    simulation.player(pid).unwrap().active_script.as_ref().map(|script| script.execution)
+}
+
+#[test]
+fn test_login_captures_outgoing_messages() {
+    let mut simulation = Simulation::new(0);
+    let pid = simulation.login("tester", CoordGrid { coord: 0 }).expect("Player should log in");
+
+    let outgoing = simulation.outgoing(pid);
+
+    assert!(outgoing.iter().any(|message| matches!(message, OutgoingMessageEnum::RebuildNormal(_))));
+    assert!(outgoing.iter().any(|message| matches!(message, OutgoingMessageEnum::IfOpenTop(_))));
+    assert!(simulation.outgoing(pid).is_empty());
+}
+
+#[test]
+fn test_injected_message_is_handled_next_tick() {
+    let mut simulation = Simulation::new(0);
+    let pid = simulation.login("tester", CoordGrid { coord: 0 }).expect("Player should log in");
+
+    simulation.send(pid, &ClientProtocol::TRANSMITVAR_VERIFYID, TransmitVarVerifyIdMessage { verify_id: 7 });
+    assert_eq!(simulation.player(pid).unwrap().client_verify_id, 0);
+
+    simulation.step();
+    assert_eq!(simulation.player(pid).unwrap().client_verify_id, 7);
+}
+
+#[test]
+fn test_fake_clients_do_not_time_out() {
+    let mut simulation = Simulation::new(0);
+    let pid = simulation.login("tester", CoordGrid { coord: 0 }).expect("Player should log in");
+
+    simulation.run(250);
+
+    assert_eq!(simulation.current_tick(), 251);
+    assert!(simulation.player(pid).is_some());
+}
+
+/// Logs in, runs a script that rolls `random(1000000)` eight times and returns what it printed.
+fn scripted_rolls(seed: u64) -> Vec<Message_Game> {
+    let mut simulation = Simulation::new(seed);
+    let pid = simulation.login("tester", CoordGrid { coord: 0 }).expect("Player should log in");
+    simulation.run(10);
+    simulation.outgoing(pid);
+
+    let mut instructions = Vec::new();
+    for _ in 0..8 {
+        instructions.extend([(PUSH_CONSTANT_INT, 1_000_000, ""), (RANDOM, 0, ""), (TOSTRING, 0, ""), (MES, 0, "")]);
+    }
+    instructions.push((RETURN, 0, ""));
+    run_script(&mut simulation, pid, &instructions);
+
+    messages(&mut simulation, pid)
+}
+
+#[test]
+fn test_same_seed_rolls_the_same() {
+    let rolls = scripted_rolls(1234);
+    assert_eq!(rolls.len(), 8);
+    assert_eq!(scripted_rolls(1234), rolls);
+    assert_ne!(scripted_rolls(4321), rolls);
+}
+
+#[test]
+fn test_countdialog_prompts_and_resumes_with_input() {
+    let mut simulation = Simulation::new(0);
+    let pid = simulation.login("tester", CoordGrid { coord: 0 }).expect("Player should log in");
+    simulation.outgoing(pid);
+
+    run_script(&mut simulation, pid, &[(P_COUNTDIALOG, 0, ""), (TOSTRING, 0, ""), (MES, 0, ""), (RETURN, 0, "")]);
+
+    let prompts: Vec<_> = simulation.outgoing(pid).into_iter().filter_map(|message| match message {
+        OutgoingMessageEnum::RunClientScript(script) => Some(script),
+        _ => None,
+    }).collect();
+    assert_eq!(prompts.len(), 1);
+    assert_eq!(prompts[0].script_id, 108);
+    assert_eq!(prompts[0].args, vec![ScriptArgument::String("Enter amount:".to_string())]);
+    assert_eq!(waiting_on(&mut simulation, pid), Some(ScriptState::COUNTDIALOG));
+
+    simulation.send(pid, &ClientProtocol::RESUME_P_COUNTDIALOG, ResumePCountDialogMessage { input: 42 });
+    simulation.step();
+
+    assert_eq!(messages(&mut simulation, pid), vec![Message_Game::new("42".to_string())]);
+    assert_eq!(waiting_on(&mut simulation, pid), None);
+}
+
+#[test]
+fn test_pausebutton_resumes_on_click() {
+    let mut simulation = Simulation::new(0);
+    let pid = simulation.login("tester", CoordGrid { coord: 0 }).expect("Player should log in");
+    simulation.player(pid).unwrap().open_modal(ModalType::Chat, DIALOG_INTERFACE);
+
+    run_script(&mut simulation, pid, &[(P_PAUSEBUTTON, 0, ""), (PUSH_CONSTANT_STRING, 0, "continued"), (MES, 0, ""), (RETURN, 0, "")]);
+    assert_eq!(waiting_on(&mut simulation, pid), Some(ScriptState::PAUSEBUTTON));
+    assert!(messages(&mut simulation, pid).is_empty());
+
+    let component = (DIALOG_INTERFACE << 16 | 5) as i32;
+    simulation.send(pid, &ClientProtocol::RESUME_PAUSEBUTTON, ResumePauseButtonMessage { component, slot: 0 });
+    simulation.step();
+
+    assert_eq!(messages(&mut simulation, pid), vec![Message_Game::new("continued".to_string())]);
+    assert_eq!(waiting_on(&mut simulation, pid), None);
+    assert_eq!(simulation.player(pid).unwrap().last_com, component);
+}
+
+#[test]
+fn test_opening_a_modal_cancels_the_pause() {
+    let mut simulation = Simulation::new(0);
+    let pid = simulation.login("tester", CoordGrid { coord: 0 }).expect("Player should log in");
+    simulation.player(pid).unwrap().open_modal(ModalType::Chat, DIALOG_INTERFACE);
+
+    run_script(&mut simulation, pid, &[(P_PAUSEBUTTON, 0, ""), (PUSH_CONSTANT_STRING, 0, "continued"), (MES, 0, ""), (RETURN, 0, "")]);
+    assert_eq!(waiting_on(&mut simulation, pid), Some(ScriptState::PAUSEBUTTON));
+
+    simulation.player(pid).unwrap().open_modal(ModalType::Main, 100);
+    assert_eq!(waiting_on(&mut simulation, pid), None);
+
+    // A click that was already on its way finds nothing to resume.
+    let component = (DIALOG_INTERFACE << 16 | 5) as i32;
+    simulation.send(pid, &ClientProtocol::RESUME_PAUSEBUTTON, ResumePauseButtonMessage { component, slot: 0 });
+    simulation.step();
+
+    assert!(messages(&mut simulation, pid).is_empty());
+    assert!(simulation.player(pid).unwrap().interfaces.is_open(100));
+}
+
+#[test]
+fn test_world_delay_resumes_on_the_right_tick() {
+    let mut simulation = Simulation::new(0);
+    let pid = simulation.login("tester", CoordGrid { coord: 0 }).expect("Player should log in");
+    simulation.outgoing(pid);
+
+    // Queued after this tick's world phase, so world_delay(1) counts down on the next two before resuming.
+    run_script(&mut simulation, pid, &[(PUSH_CONSTANT_INT, 1, ""), (WORLD_DELAY, 0, ""), (PUSH_CONSTANT_STRING, 0, "resumed"), (MES, 0, ""), (RETURN, 0, "")]);
+    assert_eq!(simulation.engine.world_queue.len(), 1);
+
+    simulation.run(2);
+    assert!(messages(&mut simulation, pid).is_empty());
+
+    simulation.step();
+    assert_eq!(messages(&mut simulation, pid), vec![Message_Game::new("resumed".to_string())]);
+    assert!(simulation.engine.world_queue.is_empty());
+}
+
+#[test]
+fn test_world_script_delaying_itself_runs_every_tick() {
+    let mut simulation = Simulation::new(0);
+    let pid = simulation.login("tester", CoordGrid { coord: 0 }).expect("Player should log in");
+    simulation.outgoing(pid);
+
+    run_script(&mut simulation, pid, &[(PUSH_CONSTANT_STRING, 0, "tick"), (MES, 0, ""), (PUSH_CONSTANT_INT, 0, ""), (WORLD_DELAY, 0, ""), (BRANCH, -5, "")]);
+    assert_eq!(messages(&mut simulation, pid).len(), 1);
+
+    simulation.step();
+    assert!(messages(&mut simulation, pid).is_empty());
+
+    // From here on it's queued from the world phase itself.
+    for _ in 0..3 {
+        simulation.step();
+        assert_eq!(messages(&mut simulation, pid).len(), 1);
+    }
+}
+
+#[test]
+fn test_suspended_npc_script_resumes_after_its_delay() {
+    let mut simulation = Simulation::new(0);
+    let npc = NPC::new(CoordGrid { coord: 0 }, 1, 1, EntityLifeCycle::FOREVER, 1, 0, MoveRestrict::Normal, BlockWalk::Npc);
+    simulation.engine.npcs.set(1, npc).unwrap();
+
+    let script = script_file(&[(PUSH_CONSTANT_INT, 0, ""), (NPC_DELAY, 0, ""), (PUSH_CONSTANT_INT, 2, ""), (NPC_DELAY, 0, ""), (RETURN, 0, "")]);
+    let start = simulation.current_tick();
+    simulation.engine.execute_script(ScriptRunner::init(script, Some(EntityType::NPC(1)), None, None));
+
+    let suspended = |simulation: &mut Simulation| {
+        let npc = simulation.engine.npcs.get(1).unwrap();
+        npc.delayed() && npc.active_script.as_ref().is_some_and(|script| script.execution == ScriptState::NPC_SUSPENDED)
+    };
+    assert!(suspended(&mut simulation));
+    assert_eq!(simulation.engine.npcs.get(1).unwrap().pathing_entity.delayed_until, start + 1);
+
+    // npc_delay(0) resumes next tick and then waits on npc_delay(2).
+    simulation.run(2);
+    assert!(suspended(&mut simulation));
+    assert_eq!(simulation.engine.npcs.get(1).unwrap().pathing_entity.delayed_until, start + 4);
+
+    simulation.run(2);
+    assert!(suspended(&mut simulation));
+
+    simulation.step();
+    let npc = simulation.engine.npcs.get(1).unwrap();
+    assert!(!npc.delayed());
+    assert!(npc.active_script.is_none());
+}
@@ -12,26 +12,37 @@ pub fn bitcount(value: i32) -> i32 {
     ((n + (n >> 4) & 0x0f0f0f0f).wrapping_mul(0x01010101)) >> 24
 }
 
-/// Sets a range of bits from `start_bit` to `end_bit` (inclusive) to 1.
-pub fn set_bit_range(value: i32, start_bit: i32, end_bit: i32) -> i32 {
-    let mask = MASK[(end_bit - start_bit + 1) as usize];
-    value | (mask << start_bit)
+/// `1 << bit`, `None` unless `bit` is 0 to 31.
+pub fn bit_mask(bit: i32) -> Option<i32> {
+    u32::try_from(bit).ok().and_then(|bit| 1_i32.checked_shl(bit))
 }
 
-/// Clears a range of bits from `start_bit` to `end_bit` to 0.
-pub fn clear_bit_range(value: i32, start_bit: i32, end_bit: i32) -> i32 {
-    let mask = MASK[(end_bit - start_bit + 1) as usize];
-    value & !(mask << start_bit)
+/// A mask as wide as `start_bit` to `end_bit` (inclusive), not yet shifted into place. `None`
+/// unless `0 <= start_bit <= end_bit <= 31`.
+pub fn range_mask(start_bit: i32, end_bit: i32) -> Option<i32> {
+    if start_bit < 0 || start_bit > end_bit || end_bit > 31 {
+        return None;
+    }
+    Some(MASK[(end_bit - start_bit + 1) as usize])
+}
+
+/// Sets a range of bits from `start_bit` to `end_bit` (inclusive) to 1, `None` for an invalid range.
+pub fn set_bit_range(value: i32, start_bit: i32, end_bit: i32) -> Option<i32> {
+    range_mask(start_bit, end_bit).map(|mask| value | (mask << start_bit))
+}
+
+/// Clears a range of bits from `start_bit` to `end_bit` to 0, `None` for an invalid range.
+pub fn clear_bit_range(value: i32, start_bit: i32, end_bit: i32) -> Option<i32> {
+    range_mask(start_bit, end_bit).map(|mask| value & !(mask << start_bit))
 }
 
 /// Initialize the array of bit masks.
 fn init_mask_array() -> [i32; 33] {
     let mut data = [0; 33];
-    let mut incrementor = 2;
     
-    for i in 1..33 {
-        data[i] = (incrementor - 1);
-        incrementor += incrementor;
+    // MASK[32] wraps around to -1, all 32 bits set.
+    for (i, mask) in data.iter_mut().enumerate().skip(1) {
+        *mask = ((1_i64 << i) - 1) as i32;
     }
     
     data
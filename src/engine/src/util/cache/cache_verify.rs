@@ -0,0 +1,142 @@
+use std::collections::HashMap;
+use std::error::Error;
+use cache::version_trailer::VersionTrailer;
+use cache::xtea::XTEAKey;
+use constants::js5_archive::js5_archive;
+use rs2cache::Cache;
+use rs2cache::js5_compression::Js5Compression;
+use rs2cache::js5_index::Js5Index;
+use rs2cache::js5_masterindex::Js5MasterIndex;
+use rs2cache::store::ARCHIVESET;
+use serde::Serialize;
+use crate::io::crc::CRC;
+
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct GroupRef {
+    pub archive: u8,
+    pub group: u32,
+}
+
+/// A group that couldn't be read, decompressed or decrypted.
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct BrokenGroup {
+    pub archive: u8,
+    pub group: u32,
+    pub reason: String,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum MismatchKind {
+    Crc,
+    Version,
+}
+
+/// A group that reads fine but disagrees with its entry in the archive index.
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct Mismatch {
+    pub archive: u8,
+    pub group: u32,
+    pub kind: MismatchKind,
+    pub expected: u32,
+    /// `None` when there was nothing to compare, a container without a version trailer.
+    pub actual: Option<u32>,
+}
+
+#[derive(Debug, Clone, Default, PartialEq, Serialize)]
+pub struct VerifyReport {
+    pub archives: usize,
+    pub groups: usize,
+    pub broken: Vec<BrokenGroup>,
+    pub missing_xtea_keys: Vec<GroupRef>,
+    pub mismatches: Vec<Mismatch>,
+}
+
+impl VerifyReport {
+    pub fn is_ok(&self) -> bool {
+        self.broken.is_empty() && self.missing_xtea_keys.is_empty() && self.mismatches.is_empty()
+    }
+
+    fn broken(&mut self, archive: u8, group: u32, reason: String) {
+        self.broken.push(BrokenGroup { archive, group, reason });
+    }
+}
+
+/// Checks a raw container against its index entry, the CRC covers everything but the version trailer.
+pub fn check_container(crc: &CRC, archive: u8, group: u32, data: &[u8], expected_checksum: u32, expected_version: u16) -> Vec<Mismatch> {
+    let mut mismatches = Vec::new();
+
+    let mut container = data.to_vec();
+    let version = VersionTrailer::strip(&mut container);
+
+    let checksum = crc.get_crc(&container, 0, container.len()) as u32;
+    if checksum != expected_checksum {
+        mismatches.push(Mismatch { archive, group, kind: MismatchKind::Crc, expected: expected_checksum, actual: Some(checksum) });
+    }
+
+    if version != Some(expected_version) {
+        mismatches.push(Mismatch { archive, group, kind: MismatchKind::Version, expected: expected_version as u32, actual: version.map(u32::from) });
+    }
+
+    mismatches
+}
+
+fn read_index(cache: &Cache, archive: u8) -> Result<Js5Index, String> {
+    let compressed = cache.store.read(ARCHIVESET, archive as u32).map_err(|e| e.to_string())?;
+    let decompressed = Js5Compression::uncompress(compressed, None).map_err(|e| e.to_string())?;
+    Js5Index::read(decompressed).map_err(|e| format!("{:?}", e))
+}
+
+fn verify_group(cache: &Cache, crc: &CRC, keys: &HashMap<(u8, u16), XTEAKey>, archive: u8, group: u32, checksum: u32, version: u16, report: &mut VerifyReport) {
+    let data = match cache.store.read(archive, group) {
+        Ok(data) => data,
+        Err(e) => return report.broken(archive, group, format!("Unreadable: {}", e)),
+    };
+
+    report.mismatches.extend(check_container(crc, archive, group, &data, checksum, version));
+
+    let mut container = data;
+    VersionTrailer::strip(&mut container);
+
+    let key = keys.get(&(archive, group as u16)).filter(|key| !key.is_zero());
+    if let Err(e) = Js5Compression::uncompress(container, key.map(XTEAKey::to_array)) {
+        match key {
+            // Encrypted map groups only decompress with their key.
+            None if archive as u32 == js5_archive::MAPS => report.missing_xtea_keys.push(GroupRef { archive, group }),
+            None => report.broken(archive, group, format!("Failed to decompress: {}", e)),
+            Some(_) => report.broken(archive, group, format!("Failed to decrypt with its XTEA key: {}", e)),
+        }
+    }
+}
+
+/// Walks the master index and every archive index, reading, checking and decompressing every group.
+pub fn verify_cache(path: &str, keys: &HashMap<(u8, u16), XTEAKey>) -> Result<VerifyReport, Box<dyn Error>> {
+    let cache = Cache::open(path).map_err(|e| format!("Failed to open cache: {}", e))?;
+    let master_index = Js5MasterIndex::create(&cache.store);
+    let crc = CRC::new();
+
+    let mut report = VerifyReport::default();
+    for archive in 0..master_index.entries.len() {
+        let archive = archive as u8;
+        report.archives += 1;
+
+        let index = match read_index(&cache, archive) {
+            Ok(index) => index,
+            Err(e) => {
+                report.broken(ARCHIVESET, archive as u32, format!("Unreadable index: {}", e));
+                continue;
+            }
+        };
+
+        let mut groups: Vec<_> = index.groups.iter().collect();
+        groups.sort_unstable_by_key(|(id, _)| **id);
+
+        for (id, group) in groups {
+            report.groups += 1;
+            // The trailer only has room for the low 16 bits of the version.
+            verify_group(&cache, &crc, keys, archive, *id, group.checksum as u32, group.version as u16, &mut report);
+        }
+    }
+
+    Ok(report)
+}
@@ -0,0 +1,67 @@
+use crate::io::crc::CRC;
+use crate::util::cache::cache_verify::{check_container, Mismatch, MismatchKind, VerifyReport};
+
+/// Uncompressed container around `payload`, with `version` as its trailer.
+fn container(payload: &[u8], version: Option<u16>) -> Vec<u8> {
+    let mut data = vec![0];
+    data.extend_from_slice(&(payload.len() as u32).to_be_bytes());
+    data.extend_from_slice(payload);
+    if let Some(version) = version {
+        data.extend_from_slice(&version.to_be_bytes());
+    }
+    data
+}
+
+#[test]
+fn test_crc32() {
+    let crc = CRC::new();
+    assert_eq!(crc.get_crc(b"123456789", 0, 9) as u32, 0xCBF43926);
+    assert_eq!(crc.get_crc(b"xx123456789", 2, 11) as u32, 0xCBF43926);
+    assert_eq!(crc.get_crc(&[], 0, 0), 0);
+}
+
+#[test]
+fn test_matching_container() {
+    let crc = CRC::new();
+    let data = container(b"obj", Some(7));
+    let checksum = crc.get_crc(&data, 0, data.len() - 2) as u32;
+
+    assert!(check_container(&crc, 19, 3, &data, checksum, 7).is_empty());
+}
+
+#[test]
+fn test_crc_mismatch() {
+    let crc = CRC::new();
+    let data = container(b"obj", Some(7));
+    let checksum = crc.get_crc(&data, 0, data.len() - 2) as u32;
+
+    let mismatches = check_container(&crc, 19, 3, &data, checksum ^ 1, 7);
+    assert_eq!(mismatches, vec![Mismatch { archive: 19, group: 3, kind: MismatchKind::Crc, expected: checksum ^ 1, actual: Some(checksum) }]);
+}
+
+#[test]
+fn test_version_mismatch_and_missing_trailer() {
+    let crc = CRC::new();
+    let data = container(b"obj", Some(6));
+    let checksum = crc.get_crc(&data, 0, data.len() - 2) as u32;
+
+    let mismatches = check_container(&crc, 19, 3, &data, checksum, 7);
+    assert_eq!(mismatches, vec![Mismatch { archive: 19, group: 3, kind: MismatchKind::Version, expected: 7, actual: Some(6) }]);
+
+    // A container shorter than a trailer has no version at all.
+    let mismatches = check_container(&crc, 19, 3, &[0], 0, 7);
+    assert!(mismatches.contains(&Mismatch { archive: 19, group: 3, kind: MismatchKind::Version, expected: 7, actual: None }));
+}
+
+#[test]
+fn test_report_json() {
+    let mut report = VerifyReport { archives: 1, groups: 2, ..Default::default() };
+    assert!(report.is_ok());
+
+    report.mismatches.push(Mismatch { archive: 5, group: 9, kind: MismatchKind::Crc, expected: 1, actual: Some(2) });
+    assert!(!report.is_ok());
+    assert_eq!(
+        serde_json::to_string(&report).unwrap(),
+        r#"{"archives":1,"groups":2,"broken":[],"missing_xtea_keys":[],"mismatches":[{"archive":5,"group":9,"kind":"crc","expected":1,"actual":2}]}"#
+    );
+}
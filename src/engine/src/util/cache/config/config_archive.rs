@@ -0,0 +1,95 @@
+use std::error::Error;
+use std::sync::OnceLock;
+use cache::file_handler::get_group;
+use constants::js5_archive::js5_archive;
+use log::{debug, error};
+use rs2cache::js5_compression::Js5Compression;
+use rs2cache::js5_index::Js5Index;
+use rs2cache::store::ARCHIVESET;
+use crate::io::packet::Packet;
+use crate::util::cache::config::config_type::ConfigType;
+use crate::util::cache::js5_group::unpack_group;
+
+/// Every file of `archive` as `(group, file, data)`, sorted by id. Reads through the cache
+/// crate, so groups replaced in the overlay are what the server sees too.
+pub fn read_archive_files(archive: u32) -> Result<Vec<(u32, u32, Vec<u8>)>, Box<dyn Error>> {
+    let index = read_index(archive)?;
+
+    let mut groups: Vec<_> = index.groups.keys().copied().collect();
+    groups.sort_unstable();
+
+    let mut files = Vec::new();
+    for group in groups {
+        let data = read_group(&index, archive, group)?;
+        files.extend(data.into_iter().map(|(file, data)| (group, file, data)));
+    }
+
+    Ok(files)
+}
+
+/// Every file of one group as `(file, data)`, sorted by id.
+pub fn read_group_files(archive: u32, group: u32) -> Result<Vec<(u32, Vec<u8>)>, Box<dyn Error>> {
+    read_group(&read_index(archive)?, archive, group)
+}
+
+fn read_index(archive: u32) -> Result<Js5Index, Box<dyn Error>> {
+    let index = get_group(ARCHIVESET, archive as u16)?;
+    let index = Js5Compression::uncompress(index.to_vec(), None).map_err(|e| e.to_string())?;
+    Ok(Js5Index::read(index).map_err(|e| format!("Failed to read index {}: {:?}", archive, e))?)
+}
+
+fn read_group(index: &Js5Index, archive: u32, group: u32) -> Result<Vec<(u32, Vec<u8>)>, Box<dyn Error>> {
+    let entry = index.groups.get(&group).ok_or_else(|| format!("Archive {} has no group {}", archive, group))?;
+    let mut ids: Vec<u32> = entry.files.keys().map(|file| *file as u32).collect();
+    ids.sort_unstable();
+
+    let container = get_group(archive as u8, group as u16)?;
+    let data = Js5Compression::uncompress(container.to_vec(), None).map_err(|e| e.to_string())?;
+    let data = unpack_group(&data, ids.len()).map_err(|e| format!("Group {} of archive {}: {}", group, archive, e))?;
+
+    Ok(ids.into_iter().zip(data).collect())
+}
+
+/// Decodes a config archive where the id of a type is `group << group_bits | file`, indexed by
+/// id. Ids without a file are left as `new(id)`.
+pub fn decode_config_archive<T: ConfigType>(archive: u32, group_bits: u32, new: fn(u32) -> T) -> Result<Vec<T>, Box<dyn Error>> {
+    let files = read_archive_files(archive)?;
+    let count = files.last().map_or(0, |(group, file, _)| (group << group_bits | file) + 1);
+
+    let mut types: Vec<T> = (0..count).map(new).collect();
+    for (group, file, data) in files {
+        let id = group << group_bits | file;
+        types[id as usize].decode_type(&mut Packet::from(data), &mut Vec::new());
+    }
+
+    Ok(types)
+}
+
+/// Decodes a group of `js5_archive::CONFIG` where the id of a type is its file, indexed by id.
+pub fn decode_config_group<T: ConfigType>(group: u32, new: fn(u32) -> T) -> Result<Vec<T>, Box<dyn Error>> {
+    let files = read_group_files(js5_archive::CONFIG, group)?;
+    let count = files.last().map_or(0, |(file, _)| file + 1);
+
+    let mut types: Vec<T> = (0..count).map(new).collect();
+    for (file, data) in files {
+        types[file as usize].decode_type(&mut Packet::from(data), &mut Vec::new());
+    }
+
+    Ok(types)
+}
+
+/// Keeps `decoded` in `types` for the rest of the process, returns how many there are.
+pub fn store_types<T>(types: &OnceLock<Vec<T>>, name: &str, decoded: Result<Vec<T>, Box<dyn Error>>) -> u32 {
+    match decoded {
+        Ok(decoded) => {
+            let count = decoded.len() as u32;
+            types.get_or_init(|| decoded);
+            debug!("Loaded {} {} types", count, name);
+            count
+        }
+        Err(e) => {
+            error!("Failed to load {} types: {}", name, e);
+            0
+        }
+    }
+}
@@ -8,6 +8,9 @@ pub trait ConfigType {
     
     fn decode(&mut self, opcode: u8, dat: &mut Packet);
     
+    /// Writes the payload of `opcode`, the counterpart of [`ConfigType::decode`].
+    fn encode(&self, opcode: u8, dat: &mut Packet);
+    
     fn decode_type(&mut self, data: &mut Packet, opcode_order: &mut Vec<u8>) {
         while data.remaining() > 0 {
             let opcode = data.g1();
@@ -21,4 +24,28 @@ pub trait ConfigType {
             self.decode(opcode, data);
         }
     }
-}
\ No newline at end of file
+    
+    /// Encodes the opcodes in `opcode_order`, so a type decoded by [`ConfigType::decode_type`]
+    /// comes back byte for byte.
+    fn encode_type(&self, data: &mut Packet, opcode_order: &[u8]) {
+        for &opcode in opcode_order {
+            if opcode == 0 {
+                break;
+            }
+            
+            data.p1(opcode as i32);
+            self.encode(opcode, data);
+        }
+        
+        data.p1(0);
+    }
+}
+
+/// Reads a g2 where 65535 stands for -1.
+pub fn nullable(value: u16) -> i32 {
+    if value == 65535 {
+        -1
+    } else {
+        value as i32
+    }
+}
@@ -0,0 +1,15 @@
+use crate::io::packet::Packet;
+use crate::util::cache::config::config_type::ConfigType;
+
+/// Decodes `data` into `config`, returning it with the opcodes in the order they were read.
+pub(super) fn decode<T: ConfigType>(mut config: T, data: Vec<u8>) -> (T, Vec<u8>) {
+    let mut opcode_order = Vec::new();
+    config.decode_type(&mut Packet::from(data), &mut opcode_order);
+    (config, opcode_order)
+}
+
+pub(super) fn encode<T: ConfigType>(config: &T, opcode_order: &[u8]) -> Vec<u8> {
+    let mut packet = Packet::from(Vec::new());
+    config.encode_type(&mut packet, opcode_order);
+    packet.data
+}
\ No newline at end of file
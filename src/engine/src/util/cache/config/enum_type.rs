@@ -0,0 +1,158 @@
+use std::collections::HashMap;
+use std::sync::OnceLock;
+use constants::js5_archive::js5_archive;
+use log::error;
+use crate::io::packet::Packet;
+use crate::util::cache::config::config_archive::{decode_config_archive, store_types};
+use crate::util::cache::config::config_type::ConfigType;
+use crate::util::cache::param_helper::{ParamValue, TYPE_STRING};
+
+static ENUM_TYPES: OnceLock<Vec<EnumType>> = OnceLock::new();
+
+#[derive(Debug)]
+pub struct EnumType {
+    pub id: u32,
+    debugname: Option<String>,
+    /// Type chars of the keys and values, `s` for strings.
+    pub inputtype: u8,
+    pub outputtype: u8,
+    pub default_string: String,
+    pub default_int: i32,
+    /// Values in the order they were encoded.
+    values: Vec<(i32, ParamValue)>,
+    /// Index into `values` by key.
+    lookup: HashMap<i32, usize>,
+}
+
+impl EnumType {
+    pub fn new(id: u32) -> Self {
+        EnumType {
+            id,
+            debugname: None,
+            inputtype: 0,
+            outputtype: 0,
+            default_string: "null".to_string(),
+            default_int: 0,
+            values: Vec::new(),
+            lookup: HashMap::new(),
+        }
+    }
+
+    /// The value for `key`, the enum's default when it has none.
+    pub fn get(&self, key: i32) -> ParamValue {
+        match self.lookup.get(&key) {
+            Some(index) => self.values[*index].1.clone(),
+            None if self.outputtype == TYPE_STRING => ParamValue::String(self.default_string.clone()),
+            None => ParamValue::Integer(self.default_int),
+        }
+    }
+
+    /// Number of keys with a value.
+    pub fn output_count(&self) -> usize {
+        self.values.len()
+    }
+
+    fn decode_values(&mut self, packet: &mut Packet, string: bool) {
+        let count = packet.g2();
+        self.values.reserve(count as usize);
+
+        for _ in 0..count {
+            let key = packet.g4();
+            let value = if string {
+                ParamValue::String(packet.gjstr())
+            } else {
+                ParamValue::Integer(packet.g4())
+            };
+
+            self.lookup.insert(key, self.values.len());
+            self.values.push((key, value));
+        }
+    }
+}
+
+impl ConfigType for EnumType {
+    fn id(&self) -> u32 {
+        self.id
+    }
+
+    fn debugname(&self) -> Option<&String> {
+        self.debugname.as_ref()
+    }
+
+    fn set_debugname(&mut self, debugname: String) {
+        self.debugname = Some(debugname);
+    }
+
+    fn decode(&mut self, opcode: u8, packet: &mut Packet) {
+        match opcode {
+            1 => {
+                self.inputtype = packet.g1();
+            }
+
+            2 => {
+                self.outputtype = packet.g1();
+            }
+
+            3 => {
+                self.default_string = packet.gjstr();
+            }
+
+            4 => {
+                self.default_int = packet.g4();
+            }
+
+            5 | 6 => {
+                self.decode_values(packet, opcode == 5);
+            }
+
+            _ => {
+                error!("Unknown opcode: {}", opcode);
+            }
+        }
+    }
+
+    fn encode(&self, opcode: u8, packet: &mut Packet) {
+        match opcode {
+            1 => packet.p1(self.inputtype as i32),
+
+            2 => packet.p1(self.outputtype as i32),
+
+            3 => packet.pjstr(&self.default_string, 0),
+
+            4 => packet.p4(self.default_int),
+
+            5 | 6 => {
+                packet.p2(self.values.len() as i32);
+
+                for (key, value) in &self.values {
+                    packet.p4(*key);
+                    match value {
+                        ParamValue::String(value) => packet.pjstr(value, 0),
+                        ParamValue::Integer(value) => packet.p4(*value),
+                    }
+                }
+            }
+
+            _ => {
+                error!("Unknown opcode: {}", opcode);
+            }
+        }
+    }
+}
+
+pub struct EnumTypeProvider;
+
+impl EnumTypeProvider {
+    /// Decodes `js5_archive::CONFIG_ENUM`, 256 enums to a group.
+    pub fn load() -> u32 {
+        store_types(&ENUM_TYPES, "enum", decode_config_archive(js5_archive::CONFIG_ENUM, 8, EnumType::new))
+    }
+
+    pub fn get(id: i32) -> Option<&'static EnumType> {
+        ENUM_TYPES.get()?.get(usize::try_from(id).ok()?)
+    }
+
+    pub fn count() -> usize {
+        ENUM_TYPES.get().map_or(0, Vec::len)
+    }
+}
@@ -0,0 +1,71 @@
+use crate::io::packet::Packet;
+use crate::util::cache::config::config_type_tests::{decode, encode};
+use crate::util::cache::config::enum_type::EnumType;
+use crate::util::cache::param_helper::ParamValue;
+
+fn string_enum() -> Vec<u8> {
+    let mut packet = Packet::from(Vec::new());
+    packet.p1(1); packet.p1(b'i' as i32);
+    packet.p1(2); packet.p1(b's' as i32);
+    packet.p1(3); packet.pjstr("Unknown", 0);
+    packet.p1(5); packet.p2(2);
+    packet.p4(7); packet.pjstr("Attack", 0);
+    packet.p4(-1); packet.pjstr("None", 0);
+    packet.p1(0);
+    packet.data
+}
+
+fn int_enum() -> Vec<u8> {
+    let mut packet = Packet::from(Vec::new());
+    packet.p1(1); packet.p1(b'i' as i32);
+    packet.p1(2); packet.p1(b'o' as i32);
+    packet.p1(4); packet.p4(-1);
+    packet.p1(6); packet.p2(3);
+    packet.p4(2); packet.p4(995);
+    packet.p4(0); packet.p4(1038);
+    packet.p4(1); packet.p4(-50);
+    packet.p1(0);
+    packet.data
+}
+
+#[test]
+fn test_string_enum() {
+    let (enum_type, _) = decode(EnumType::new(3), string_enum());
+
+    assert_eq!(enum_type.inputtype, b'i');
+    assert_eq!(enum_type.outputtype, b's');
+    assert_eq!(enum_type.output_count(), 2);
+    assert_eq!(enum_type.get(7), ParamValue::String("Attack".to_string()));
+    assert_eq!(enum_type.get(-1), ParamValue::String("None".to_string()));
+    assert_eq!(enum_type.get(8), ParamValue::String("Unknown".to_string()));
+}
+
+#[test]
+fn test_int_enum() {
+    let (enum_type, _) = decode(EnumType::new(3), int_enum());
+
+    assert_eq!(enum_type.output_count(), 3);
+    assert_eq!(enum_type.get(0), ParamValue::Integer(1038));
+    assert_eq!(enum_type.get(1), ParamValue::Integer(-50));
+    assert_eq!(enum_type.get(2), ParamValue::Integer(995));
+    assert_eq!(enum_type.get(3), ParamValue::Integer(-1));
+}
+
+#[test]
+fn test_defaults_without_opcodes() {
+    let (enum_type, _) = decode(EnumType::new(3), vec![2, b's', 0]);
+    assert_eq!(enum_type.get(0), ParamValue::String("null".to_string()));
+
+    let (enum_type, _) = decode(EnumType::new(3), vec![0]);
+    assert_eq!(enum_type.output_count(), 0);
+    assert_eq!(enum_type.get(0), ParamValue::Integer(0));
+}
+
+#[test]
+fn test_round_trip() {
+    for data in [string_enum(), int_enum()] {
+        let (enum_type, opcode_order) = decode(EnumType::new(3), data.clone());
+
+        assert_eq!(encode(&enum_type, &opcode_order), data);
+    }
+}
@@ -0,0 +1,579 @@
+use std::sync::OnceLock;
+use constants::js5_archive::js5_archive;
+use log::error;
+use crate::io::packet::Packet;
+use crate::util::cache::config::config_archive::{decode_config_archive, store_types};
+use crate::util::cache::config::config_type::{nullable, ConfigType};
+use crate::util::cache::config::param_type::ParamType;
+use crate::util::cache::param_helper::{decode_params, encode_params, param_value, ParamValue, Params};
+
+static LOC_TYPES: OnceLock<Vec<LocType>> = OnceLock::new();
+
+/// Shape of a ground decor loc, the one shape that is interactive by default.
+pub const CENTREPIECE_STRAIGHT: u8 = 10;
+
+#[derive(Debug)]
+pub struct LocType {
+    pub id: u32,
+    debugname: Option<String>,
+    models: Vec<u16>,
+    /// Shape of each model, `None` when the models were given without shapes.
+    shapes: Option<Vec<u8>>,
+    pub name: String,
+    pub width: u8,
+    pub length: u8,
+    /// 0 walks through, 1 blocks movement only, 2 blocks movement and projectiles unless
+    /// `blockrange` is off.
+    pub blockwalk: u8,
+    pub blockrange: bool,
+    /// -1 derives it from the models and ops, see [`LocType::active`].
+    active: i8,
+    anim: i32,
+    wallwidth: u8,
+    ambient: i8,
+    contrast: i32,
+    op: Vec<String>,
+    recol_s: Vec<u16>,
+    recol_d: Vec<u16>,
+    retex_s: Vec<u16>,
+    retex_d: Vec<u16>,
+    recol_d_palette: Vec<i8>,
+    pub mapfunction: i32,
+    resizex: u16,
+    resizey: u16,
+    resizez: u16,
+    /// Bitmask of the sides an interaction may approach from, 0 for all of them.
+    pub forceapproach: u8,
+    xoff: i16,
+    yoff: i16,
+    zoff: i16,
+    pub forcedecor: bool,
+    pub breakroutefinding: bool,
+    raiseobject: i8,
+    pub multivarbit: i32,
+    pub multivarp: i32,
+    multiloc: Vec<i32>,
+    multiloc_default: i32,
+    bgsound: i32,
+    bgsoundrange: u8,
+    bgsoundmin: u16,
+    bgsoundmax: u16,
+    bgsounds: Vec<u16>,
+    hillskew_value: i32,
+    pub members: bool,
+    cursor1op: i8,
+    cursor1: i32,
+    cursor2op: i8,
+    cursor2: i32,
+    mapscene_angle: u8,
+    pub mapscene: i32,
+    params: Params,
+}
+
+impl LocType {
+    pub fn new(id: u32) -> Self {
+        LocType {
+            id,
+            debugname: None,
+            models: Vec::new(),
+            shapes: None,
+            name: "null".to_string(),
+            width: 1,
+            length: 1,
+            blockwalk: 2,
+            blockrange: true,
+            active: -1,
+            anim: -1,
+            wallwidth: 16,
+            ambient: 0,
+            contrast: 0,
+            op: vec!["".to_string(); 5],
+            recol_s: Vec::new(),
+            recol_d: Vec::new(),
+            retex_s: Vec::new(),
+            retex_d: Vec::new(),
+            recol_d_palette: Vec::new(),
+            mapfunction: -1,
+            resizex: 128,
+            resizey: 128,
+            resizez: 128,
+            forceapproach: 0,
+            xoff: 0,
+            yoff: 0,
+            zoff: 0,
+            forcedecor: false,
+            breakroutefinding: false,
+            raiseobject: -1,
+            multivarbit: -1,
+            multivarp: -1,
+            multiloc: Vec::new(),
+            multiloc_default: -1,
+            bgsound: -1,
+            bgsoundrange: 0,
+            bgsoundmin: 0,
+            bgsoundmax: 0,
+            bgsounds: Vec::new(),
+            hillskew_value: 0,
+            members: false,
+            cursor1op: -1,
+            cursor1: -1,
+            cursor2op: -1,
+            cursor2: -1,
+            mapscene_angle: 0,
+            mapscene: -1,
+            params: Params::default(),
+        }
+    }
+
+    /// Width and length on the map, swapped when the loc is turned a quarter.
+    pub fn size(&self, angle: u8) -> (u8, u8) {
+        if angle & 0x1 == 1 {
+            (self.length, self.width)
+        } else {
+            (self.width, self.length)
+        }
+    }
+
+    /// Whether the loc can be interacted with, ground decor with a model or anything with an op
+    /// is unless the type says otherwise.
+    pub fn active(&self) -> bool {
+        if self.active != -1 {
+            return self.active == 1;
+        }
+
+        let centrepiece = match &self.shapes {
+            Some(shapes) => shapes.first() == Some(&CENTREPIECE_STRAIGHT),
+            None => true,
+        };
+        (!self.models.is_empty() && centrepiece) || (0..5).any(|op| self.op(op).is_some())
+    }
+
+    /// Op `index` (0-4) as shown in the client, hidden ops are `None`.
+    pub fn op(&self, index: usize) -> Option<&str> {
+        let op = self.op.get(index)?;
+        if op.is_empty() || op.eq_ignore_ascii_case("hidden") {
+            None
+        } else {
+            Some(op)
+        }
+    }
+
+    /// Movement is blocked, `breakroutefinding` locs never block.
+    pub fn blocks_walk(&self) -> bool {
+        self.blockwalk != 0 && !self.breakroutefinding
+    }
+
+    /// Projectiles and line of sight are blocked.
+    pub fn blocks_range(&self) -> bool {
+        self.blockrange && self.blocks_walk()
+    }
+
+    /// Whether objs dropped on the loc are raised on top of it.
+    pub fn raises_objs(&self) -> bool {
+        match self.raiseobject {
+            -1 => self.blocks_walk(),
+            raiseobject => raiseobject != 0,
+        }
+    }
+
+    /// Whether the loc morphs into another one depending on a varbit or varp.
+    pub fn is_multiloc(&self) -> bool {
+        self.multivarbit != -1 || self.multivarp != -1
+    }
+
+    /// The loc this one shows as when its varbit or varp is `value`, `None` when it disappears.
+    pub fn multiloc(&self, value: i32) -> Option<u16> {
+        let id = usize::try_from(value)
+            .ok()
+            .and_then(|index| self.multiloc.get(index).copied())
+            .unwrap_or(self.multiloc_default);
+        u16::try_from(id).ok()
+    }
+
+    /// The value of `param`, its default when the loc doesn't set it.
+    pub fn param(&self, param: &ParamType) -> ParamValue {
+        param_value(&self.params, param)
+    }
+}
+
+impl ConfigType for LocType {
+    fn id(&self) -> u32 {
+        self.id
+    }
+
+    fn debugname(&self) -> Option<&String> {
+        self.debugname.as_ref()
+    }
+
+    fn set_debugname(&mut self, debugname: String) {
+        self.debugname = Some(debugname);
+    }
+
+    fn decode(&mut self, opcode: u8, packet: &mut Packet) {
+        match opcode {
+            1 => {
+                let count = packet.g1();
+                let mut shapes = Vec::with_capacity(count as usize);
+
+                for _ in 0..count {
+                    self.models.push(packet.g2());
+                    shapes.push(packet.g1());
+                }
+                self.shapes = Some(shapes);
+            }
+
+            2 => {
+                self.name = packet.gjstr();
+            }
+
+            5 => {
+                let count = packet.g1();
+
+                for _ in 0..count {
+                    self.models.push(packet.g2());
+                }
+                self.shapes = None;
+            }
+
+            14 => {
+                self.width = packet.g1();
+            }
+
+            15 => {
+                self.length = packet.g1();
+            }
+
+            17 => {
+                self.blockwalk = 0;
+                self.blockrange = false;
+            }
+
+            18 => {
+                self.blockrange = false;
+            }
+
+            19 => {
+                self.active = packet.g1() as i8;
+            }
+
+            24 => {
+                self.anim = nullable(packet.g2());
+            }
+
+            27 => {
+                self.blockwalk = 1;
+            }
+
+            28 => {
+                self.wallwidth = packet.g1();
+            }
+
+            29 => {
+                self.ambient = packet.g1b();
+            }
+
+            30..=34 => {
+                self.op[(opcode - 30) as usize] = packet.gjstr();
+            }
+
+            39 => {
+                self.contrast = (packet.g1b() as i32) * 25;
+            }
+
+            40 => {
+                let count = packet.g1();
+
+                for _ in 0..count {
+                    self.recol_s.push(packet.g2());
+                    self.recol_d.push(packet.g2());
+                }
+            }
+
+            41 => {
+                let count = packet.g1();
+
+                for _ in 0..count {
+                    self.retex_s.push(packet.g2());
+                    self.retex_d.push(packet.g2());
+                }
+            }
+
+            42 => {
+                let count = packet.g1();
+
+                for _ in 0..count {
+                    self.recol_d_palette.push(packet.g1b());
+                }
+            }
+
+            60 => {
+                self.mapfunction = packet.g2() as i32;
+            }
+
+            65 => {
+                self.resizex = packet.g2();
+            }
+
+            66 => {
+                self.resizey = packet.g2();
+            }
+
+            67 => {
+                self.resizez = packet.g2();
+            }
+
+            69 => {
+                self.forceapproach = packet.g1();
+            }
+
+            70 => {
+                self.xoff = packet.g2s();
+            }
+
+            71 => {
+                self.yoff = packet.g2s();
+            }
+
+            72 => {
+                self.zoff = packet.g2s();
+            }
+
+            73 => {
+                self.forcedecor = true;
+            }
+
+            74 => {
+                self.breakroutefinding = true;
+            }
+
+            75 => {
+                self.raiseobject = packet.g1() as i8;
+            }
+
+            77 | 92 => {
+                self.multivarbit = nullable(packet.g2());
+                self.multivarp = nullable(packet.g2());
+
+                if opcode == 92 {
+                    self.multiloc_default = nullable(packet.g2());
+                }
+
+                let count = packet.g1();
+                self.multiloc = (0..=count).map(|_| nullable(packet.g2())).collect();
+            }
+
+            78 => {
+                self.bgsound = packet.g2() as i32;
+                self.bgsoundrange = packet.g1();
+            }
+
+            79 => {
+                self.bgsoundmin = packet.g2();
+                self.bgsoundmax = packet.g2();
+                self.bgsoundrange = packet.g1();
+
+                let count = packet.g1();
+                self.bgsounds = (0..count).map(|_| packet.g2()).collect();
+            }
+
+            81 => {
+                self.hillskew_value = (packet.g1() as i32) * 256;
+            }
+
+            91 => {
+                self.members = true;
+            }
+
+            93 => {
+                self.hillskew_value = packet.g2() as i32;
+            }
+
+            99 => {
+                self.cursor1op = packet.g1() as i8;
+                self.cursor1 = packet.g2() as i32;
+            }
+
+            100 => {
+                self.cursor2op = packet.g1() as i8;
+                self.cursor2 = packet.g2() as i32;
+            }
+
+            101 => {
+                self.mapscene_angle = packet.g1();
+            }
+
+            102 => {
+                self.mapscene = packet.g2() as i32;
+            }
+
+            249 => {
+                self.params = decode_params(packet);
+            }
+
+            // Client-only flags, the opcode itself is all there is.
+            21 | 22 | 23 | 62 | 64 | 82 | 88 | 89 | 90 | 94 | 95 | 96 | 97 | 98 => {}
+
+            _ => {
+                error!("Unknown opcode: {}", opcode);
+            }
+        }
+    }
+
+    fn encode(&self, opcode: u8, packet: &mut Packet) {
+        match opcode {
+            1 => {
+                let shapes = self.shapes.as_deref().unwrap_or_default();
+                packet.p1(self.models.len() as i32);
+
+                for (i, model) in self.models.iter().enumerate() {
+                    packet.p2(*model as i32);
+                    packet.p1(shapes.get(i).copied().unwrap_or(CENTREPIECE_STRAIGHT) as i32);
+                }
+            }
+
+            2 => packet.pjstr(&self.name, 0),
+
+            5 => {
+                packet.p1(self.models.len() as i32);
+
+                for model in &self.models {
+                    packet.p2(*model as i32);
+                }
+            }
+
+            14 => packet.p1(self.width as i32),
+
+            15 => packet.p1(self.length as i32),
+
+            19 => packet.p1(self.active as i32),
+
+            24 => packet.p2(self.anim),
+
+            28 => packet.p1(self.wallwidth as i32),
+
+            29 => packet.p1(self.ambient as i32),
+
+            30..=34 => packet.pjstr(&self.op[(opcode - 30) as usize], 0),
+
+            39 => packet.p1(self.contrast / 25),
+
+            40 => {
+                packet.p1(self.recol_s.len() as i32);
+
+                for i in 0..self.recol_s.len() {
+                    packet.p2(self.recol_s[i] as i32);
+                    packet.p2(self.recol_d[i] as i32);
+                }
+            }
+
+            41 => {
+                packet.p1(self.retex_s.len() as i32);
+
+                for i in 0..self.retex_s.len() {
+                    packet.p2(self.retex_s[i] as i32);
+                    packet.p2(self.retex_d[i] as i32);
+                }
+            }
+
+            42 => {
+                packet.p1(self.recol_d_palette.len() as i32);
+
+                for palette in &self.recol_d_palette {
+                    packet.p1(*palette as i32);
+                }
+            }
+
+            60 => packet.p2(self.mapfunction),
+
+            65 => packet.p2(self.resizex as i32),
+
+            66 => packet.p2(self.resizey as i32),
+
+            67 => packet.p2(self.resizez as i32),
+
+            69 => packet.p1(self.forceapproach as i32),
+
+            70 => packet.p2(self.xoff as i32),
+
+            71 => packet.p2(self.yoff as i32),
+
+            72 => packet.p2(self.zoff as i32),
+
+            75 => packet.p1(self.raiseobject as i32),
+
+            77 | 92 => {
+                packet.p2(self.multivarbit);
+                packet.p2(self.multivarp);
+
+                if opcode == 92 {
+                    packet.p2(self.multiloc_default);
+                }
+
+                packet.p1(self.multiloc.len() as i32 - 1);
+                for multiloc in &self.multiloc {
+                    packet.p2(*multiloc);
+                }
+            }
+
+            78 => {
+                packet.p2(self.bgsound);
+                packet.p1(self.bgsoundrange as i32);
+            }
+
+            79 => {
+                packet.p2(self.bgsoundmin as i32);
+                packet.p2(self.bgsoundmax as i32);
+                packet.p1(self.bgsoundrange as i32);
+                packet.p1(self.bgsounds.len() as i32);
+
+                for bgsound in &self.bgsounds {
+                    packet.p2(*bgsound as i32);
+                }
+            }
+
+            81 => packet.p1(self.hillskew_value / 256),
+
+            93 => packet.p2(self.hillskew_value),
+
+            99 => {
+                packet.p1(self.cursor1op as i32);
+                packet.p2(self.cursor1);
+            }
+
+            100 => {
+                packet.p1(self.cursor2op as i32);
+                packet.p2(self.cursor2);
+            }
+
+            101 => packet.p1(self.mapscene_angle as i32),
+
+            102 => packet.p2(self.mapscene),
+
+            249 => encode_params(packet, &self.params),
+
+            17 | 18 | 21 | 22 | 23 | 27 | 62 | 64 | 73 | 74 | 82 | 88 | 89 | 90 | 91 | 94 | 95 | 96 | 97 | 98 => { /* Flag only. */ }
+
+            _ => {
+                error!("Unknown opcode: {}", opcode);
+            }
+        }
+    }
+}
+
+pub struct LocTypeProvider;
+
+impl LocTypeProvider {
+    /// Decodes `js5_archive::CONFIG_LOC`, 256 locs to a group. Call once at startup, before the
+    /// map is loaded.
+    pub fn load() -> u32 {
+        store_types(&LOC_TYPES, "loc", decode_config_archive(js5_archive::CONFIG_LOC, 8, LocType::new))
+    }
+
+    pub fn get(id: u16) -> Option<&'static LocType> {
+        LOC_TYPES.get()?.get(id as usize)
+    }
+
+    pub fn count() -> usize {
+        LOC_TYPES.get().map_or(0, Vec::len)
+    }
+}
@@ -0,0 +1,150 @@
+use crate::io::packet::Packet;
+use crate::util::cache::config::config_type_tests::{decode, encode};
+use crate::util::cache::config::loc_type::LocType;
+use crate::util::cache::config::param_type::ParamType;
+use crate::util::cache::param_helper::ParamValue;
+
+/// A door: two models, ops, a multiloc, sounds and params, in the order the cache stores them.
+fn encoded_loc() -> Vec<u8> {
+    let mut packet = Packet::from(Vec::new());
+    packet.p1(1); packet.p1(2); packet.p2(1500); packet.p1(0); packet.p2(1501); packet.p1(10);
+    packet.p1(2); packet.pjstr("Door", 0);
+    packet.p1(14); packet.p1(2);
+    packet.p1(15); packet.p1(3);
+    packet.p1(18);
+    packet.p1(19); packet.p1(1);
+    packet.p1(21);
+    packet.p1(24); packet.p2(-1);
+    packet.p1(27);
+    packet.p1(28); packet.p1(8);
+    packet.p1(29); packet.p1(-10);
+    packet.p1(30); packet.pjstr("Open", 0);
+    packet.p1(31); packet.pjstr("hidden", 0);
+    packet.p1(39); packet.p1(-2);
+    packet.p1(40); packet.p1(1); packet.p2(10); packet.p2(20);
+    packet.p1(41); packet.p1(1); packet.p2(30); packet.p2(40);
+    packet.p1(42); packet.p1(1); packet.p1(-4);
+    packet.p1(60); packet.p2(12);
+    packet.p1(65); packet.p2(130);
+    packet.p1(66); packet.p2(140);
+    packet.p1(67); packet.p2(150);
+    packet.p1(69); packet.p1(0b1101);
+    packet.p1(70); packet.p2(-16);
+    packet.p1(71); packet.p2(4);
+    packet.p1(72); packet.p2(-8);
+    packet.p1(73);
+    packet.p1(75); packet.p1(0);
+    packet.p1(92); packet.p2(1234); packet.p2(-1); packet.p2(1502); packet.p1(1); packet.p2(1500); packet.p2(-1);
+    packet.p1(78); packet.p2(55); packet.p1(6);
+    packet.p1(79); packet.p2(100); packet.p2(200); packet.p1(6); packet.p1(2); packet.p2(56); packet.p2(57);
+    packet.p1(81); packet.p1(3);
+    packet.p1(91);
+    packet.p1(97);
+    packet.p1(99); packet.p1(1); packet.p2(300);
+    packet.p1(100); packet.p1(255); packet.p2(-1);
+    packet.p1(101); packet.p1(2);
+    packet.p1(102); packet.p2(77);
+    packet.p1(249); packet.p1(2);
+    packet.p1(0); packet.p3(10); packet.p4(99);
+    packet.p1(1); packet.p3(11); packet.pjstr("creaks", 0);
+    packet.p1(0);
+    packet.data
+}
+
+#[test]
+fn test_decode_encode_round_trip() {
+    let data = encoded_loc();
+    let (loc, opcode_order) = decode(LocType::new(1), data.clone());
+
+    assert_eq!(encode(&loc, &opcode_order), data);
+}
+
+#[test]
+fn test_decode_properties() {
+    let (loc, _) = decode(LocType::new(1), encoded_loc());
+
+    assert_eq!(loc.name, "Door");
+    assert_eq!((loc.width, loc.length), (2, 3));
+    assert_eq!(loc.blockwalk, 1);
+    assert!(loc.blocks_walk());
+    assert!(!loc.blocks_range());
+    assert!(!loc.raises_objs());
+    assert!(loc.active());
+    assert_eq!(loc.op(0), Some("Open"));
+    assert_eq!(loc.op(1), None);
+    assert_eq!(loc.op(5), None);
+    assert_eq!(loc.forceapproach, 0b1101);
+    assert!(loc.forcedecor);
+    assert!(loc.members);
+    assert_eq!(loc.mapfunction, 12);
+    assert_eq!(loc.mapscene, 77);
+    assert_eq!(loc.param(&ParamType::new(10)), ParamValue::Integer(99));
+    assert_eq!(loc.param(&ParamType::new(11)), ParamValue::String("creaks".to_string()));
+    assert_eq!(loc.param(&ParamType::new(12)), ParamValue::Integer(0));
+}
+
+#[test]
+fn test_multiloc() {
+    let (loc, _) = decode(LocType::new(1), encoded_loc());
+
+    assert!(loc.is_multiloc());
+    assert_eq!(loc.multivarbit, 1234);
+    assert_eq!(loc.multivarp, -1);
+    assert_eq!(loc.multiloc(0), Some(1500));
+    assert_eq!(loc.multiloc(1), None);
+    // Out of range falls back to the default of opcode 92.
+    assert_eq!(loc.multiloc(2), Some(1502));
+    assert_eq!(loc.multiloc(-1), Some(1502));
+
+    let (loc, _) = decode(LocType::new(1), vec![77, 0xFF, 0xFF, 0, 9, 0, 0, 3, 0]);
+    assert!(loc.is_multiloc());
+    assert_eq!(loc.multivarp, 9);
+    assert_eq!(loc.multiloc(0), Some(3));
+    assert_eq!(loc.multiloc(1), None);
+}
+
+#[test]
+fn test_defaults() {
+    let (loc, _) = decode(LocType::new(1), vec![0]);
+
+    assert_eq!(loc.size(0), (1, 1));
+    assert!(loc.blocks_walk());
+    assert!(loc.blocks_range());
+    assert!(loc.raises_objs());
+    assert!(!loc.active());
+    assert!(!loc.is_multiloc());
+    assert_eq!(loc.mapscene, -1);
+}
+
+#[test]
+fn test_size_turns_with_angle() {
+    let (loc, _) = decode(LocType::new(1), vec![14, 2, 15, 3, 0]);
+
+    assert_eq!(loc.size(0), (2, 3));
+    assert_eq!(loc.size(1), (3, 2));
+    assert_eq!(loc.size(2), (2, 3));
+    assert_eq!(loc.size(3), (3, 2));
+}
+
+#[test]
+fn test_active() {
+    // Ground decor with a model is active, a wall isn't without ops.
+    assert!(decode(LocType::new(1), vec![1, 1, 0, 5, 10, 0]).0.active());
+    assert!(decode(LocType::new(1), vec![5, 1, 0, 5, 0]).0.active());
+    assert!(!decode(LocType::new(1), vec![1, 1, 0, 5, 0, 0]).0.active());
+    assert!(decode(LocType::new(1), vec![1, 1, 0, 5, 0, 30, b'O', b'p', b'e', b'n', 0, 0]).0.active());
+    assert!(!decode(LocType::new(1), vec![5, 1, 0, 5, 19, 0, 0]).0.active());
+}
+
+#[test]
+fn test_breakroutefinding_never_blocks() {
+    let (loc, _) = decode(LocType::new(1), vec![74, 0]);
+
+    assert!(loc.breakroutefinding);
+    assert!(!loc.blocks_walk());
+    assert!(!loc.blocks_range());
+
+    let (loc, _) = decode(LocType::new(1), vec![17, 0]);
+    assert!(!loc.blocks_walk());
+    assert!(!loc.raises_objs());
+}
@@ -1,2 +1,15 @@
 pub mod config_type;
+mod config_type_tests;
 pub mod obj_type;
+mod obj_type_tests;
+pub mod config_archive;
+pub mod loc_type;
+mod loc_type_tests;
+pub mod param_type;
+pub mod struct_type;
+pub mod enum_type;
+mod enum_type_tests;
+mod param_type_tests;
+pub mod seq_type;
+pub mod spot_anim_type;
+mod seq_type_tests;
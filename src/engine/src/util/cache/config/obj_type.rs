@@ -1,10 +1,15 @@
-use std::fs::File;
-use crate::io::packet::Packet;
-use crate::util::cache::config::config_type::ConfigType;
-use crate::util::cache::param_helper::{decode_params, ParamValue, Params};
+use std::io;
 use std::io::Write;
+use std::str::FromStr;
+use crate::io::packet::{cp1252_byte, Packet};
+use crate::util::cache::config::config_type::ConfigType;
+use crate::util::cache::config::param_type::ParamType;
+use crate::util::cache::param_helper::{decode_params, encode_params, param_value, ParamValue, Params};
 use log::{debug, error};
 
+/// An obj with the opcodes it was encoded with, in order.
+pub type ParsedObj = (ObjType, Vec<u8>);
+
 #[derive(Debug)]
 pub struct ObjType {
     pub id: u32,
@@ -124,6 +129,11 @@ impl ObjType {
             params: Params::default(),
         }
     }
+
+    /// The value of `param`, its default when the obj doesn't set it.
+    pub fn param(&self, param: &ParamType) -> ParamValue {
+        param_value(&self.params, param)
+    }
 }
 
 impl ConfigType for ObjType {
@@ -208,11 +218,11 @@ impl ConfigType for ObjType {
             }
             
             30 | 31 | 32 | 33 | 34 => {
-                self.op.insert((opcode - 30) as usize, packet.gjstr())
+                self.op[(opcode - 30) as usize] = packet.gjstr();
             }
             
             35 | 36 | 37 | 38 | 39 => {
-                self.iop.insert((opcode - 35) as usize, packet.gjstr())
+                self.iop[(opcode - 35) as usize] = packet.gjstr();
             }
             
             40 => {
@@ -287,13 +297,8 @@ impl ConfigType for ObjType {
             }
             
             100 | 101 | 102 | 103 | 104 | 105 | 106 | 107 | 108 | 109 => {
-                if self.countobj.is_none() {
-                    self.countobj = Some(Vec::with_capacity(10));
-                    self.countco = Some(Vec::with_capacity(10));
-                }
-                
-                self.countobj.as_mut().unwrap().insert((opcode - 100) as usize, packet.g2());
-                self.countco.as_mut().unwrap().insert((opcode - 100) as usize, packet.g2());
+                self.countobj.get_or_insert_with(|| vec![0; 10])[(opcode - 100) as usize] = packet.g2();
+                self.countco.get_or_insert_with(|| vec![0; 10])[(opcode - 100) as usize] = packet.g2();
             }
             
             110 => {
@@ -305,7 +310,7 @@ impl ConfigType for ObjType {
             }
             
             112 => {
-                self.resizex = packet.g2() as u32;
+                self.resizez = packet.g2() as u32;
             }
             
             113 => {
@@ -358,24 +363,146 @@ impl ConfigType for ObjType {
             }
         }
     }
+
+    fn encode(&self, opcode: u8, packet: &mut Packet) {
+        match opcode {
+            1 => packet.p2(self.model as i32),
+            
+            2 => packet.pjstr(self.name.as_deref().unwrap_or(""), 0),
+            
+            4 => packet.p2(self.zoom2d as i32),
+            
+            5 => packet.p2(self.xan2d as i32),
+            
+            6 => packet.p2(self.yan2d as i32),
+            
+            7 => packet.p2(self.xof2d),
+            
+            8 => packet.p2(self.yof2d),
+            
+            11 | 16 | 65 => { /* Flag only. */ }
+            
+            12 => packet.p4(self.cost),
+            
+            23 => packet.p2(self.manWear),
+            
+            24 => packet.p2(self.manWear2),
+            
+            25 => packet.p2(self.womanWear),
+            
+            26 => packet.p2(self.womanWear2),
+            
+            30..=34 => packet.pjstr(&self.op[(opcode - 30) as usize], 0),
+            
+            35..=39 => packet.pjstr(&self.iop[(opcode - 35) as usize], 0),
+            
+            40 => {
+                packet.p1(self.recol_s.len() as i32);
+                
+                for i in 0..self.recol_s.len() {
+                    packet.p2(self.recol_s[i] as i32);
+                    packet.p2(self.recol_d[i] as i32);
+                }
+            }
+            
+            41 => {
+                packet.p1(self.retex_s.len() as i32);
+                
+                for i in 0..self.retex_s.len() {
+                    packet.p2(self.retex_s[i] as i32);
+                    packet.p2(self.retex_d[i] as i32);
+                }
+            }
+            
+            42 => {
+                packet.p1(self.recol_d_palette.len() as i32);
+                
+                for palette in &self.recol_d_palette {
+                    packet.p1(*palette as i32);
+                }
+            }
+            
+            78 => packet.p2(self.manWear3),
+            
+            79 => packet.p2(self.womanWear3),
+            
+            90 => packet.p2(self.manHead),
+            
+            91 => packet.p2(self.womanHead),
+            
+            92 => packet.p2(self.manHead2),
+            
+            93 => packet.p2(self.womanHead2),
+            
+            95 => packet.p2(self.zand2d as i32),
+            
+            96 => packet.p1(self.dummyItem as i32),
+            
+            97 => packet.p2(self.certlink),
+            
+            98 => packet.p2(self.certtemplate),
+            
+            100..=109 => {
+                let index = (opcode - 100) as usize;
+                packet.p2(self.countobj.as_ref().map_or(0, |countobj| countobj[index]) as i32);
+                packet.p2(self.countco.as_ref().map_or(0, |countco| countco[index]) as i32);
+            }
+            
+            110 => packet.p2(self.resizex as i32),
+            
+            111 => packet.p2(self.resizey as i32),
+            
+            112 => packet.p2(self.resizez as i32),
+            
+            113 => packet.p1(self.ambient as i32),
+            
+            114 => packet.p1(self.contrast / 5),
+            
+            115 => packet.p1(self.team as i32),
+            
+            121 => packet.p2(self.lentlink),
+            
+            122 => packet.p2(self.lenttemplate),
+            
+            125 => {
+                packet.p1(self.manwearxoffset as i32);
+                packet.p1(self.manwearyoffset as i32);
+                packet.p1(self.manwearzoffset as i32);
+            }
+            
+            126 => {
+                packet.p1(self.womanwearxoffset as i32);
+                packet.p1(self.womanwearyoffset as i32);
+                packet.p1(self.womanwearzoffset as i32);
+            }
+            
+            127 => {
+                packet.p1(self.cursor1op as i32);
+                packet.p2(self.cursor1);
+            }
+            
+            128 => {
+                packet.p1(self.cursor2op as i32);
+                packet.p2(self.cursor2);
+            }
+            
+            249 => encode_params(packet, &self.params),
+            
+            _ => {
+                error!("Unknown opcode: {}", opcode);
+            }
+        }
+    }
 }
 
-pub fn write_obj(file: &mut File, obj: &mut ObjType, opcode_order: &mut Vec<u8>) {
+/// Writes `obj` in the `.obj` text format, one line per opcode in `opcode_order` so that
+/// [`parse_objs`] can re-encode it in the same order.
+pub fn write_obj<W: Write>(out: &mut W, obj: &ObjType, opcode_order: &[u8]) -> io::Result<()> {
     let mut buffer: Vec<String> = Vec::new();
 
     // TODO - check for debug name
     buffer.push(format!("[obj_{}]", obj.id));
 
-    // Name always written first if it exists.
-    if let Some(name) = &obj.name {
-        buffer.push(format!("name={}", name));
-    }
-
-    let mut model_index: Option<usize> = None;
-    let mut recol_index: Option<usize> = None;
-    let mut recol_palette_index: Option<usize> = None;
-    let mut retex_index: Option<usize> = None;
-
     for opcode in opcode_order.iter() {
         let dereferenced_opcode = *opcode;
 
@@ -384,12 +511,9 @@ pub fn write_obj(file: &mut File, obj: &mut ObjType, opcode_order: &mut Vec<u8>)
                 break;
             }
 
-            1 => {
-                let model_line = format!("model=model_{}_obj", obj.model);
-                model_index = Some(buffer.len()); // Save the index
-                buffer.push(model_line);            }
+            1 => buffer.push(format!("model=model_{}_obj", obj.model)),
 
-            2 => { /* Written separately. */ }
+            2 => buffer.push(format!("name={}", obj.name.as_deref().unwrap_or(""))),
 
             4 => buffer.push(format!("2dzoom={}", obj.zoom2d)),
 
@@ -415,76 +539,29 @@ pub fn write_obj(file: &mut File, obj: &mut ObjType, opcode_order: &mut Vec<u8>)
 
             26 => buffer.push(format!("womanwear2=model_{}_obj_wear", obj.womanWear2)),
 
-            30..=34 => {
-                if obj.op[(opcode - 30) as usize] != "" {
-                    buffer.push(format!(
-                        "op{}={}",
-                        opcode - 30,
-                        obj.iop[(opcode - 30) as usize]
-                    ))
-                }
-            }
+            30..=34 => buffer.push(format!("op{}={}", opcode - 30, obj.op[(opcode - 30) as usize])),
 
-            35..=39 => {
-                if obj.iop[(opcode - 35) as usize] != "" {
-                    buffer.push(format!(
-                        "iop{}={}",
-                        opcode - 35,
-                        obj.iop[(opcode - 35) as usize]
-                    ))
-                }
-            },
+            35..=39 => buffer.push(format!("iop{}={}", opcode - 35, obj.iop[(opcode - 35) as usize])),
 
             40 => {
-                if let Some(index) = model_index {
-                    let mut recol_lines = Vec::new();
-                    let recol_count = obj.recol_s.len();
-
-                    // TODO - RGB15 reversal to HSL remains.
-
-                    for i in 0..recol_count {
-                        recol_lines.push(format!("recol{}s={}", i + 1, obj.recol_s[i]));
-                        recol_lines.push(format!("recol{}d={}", i + 1, obj.recol_d[i]));
-                    }
-
-                    let insert_index = index + 1;
-                    let recol_len = recol_lines.len();
-
-                    buffer.splice(insert_index..insert_index, recol_lines);
-                    recol_index = Some(insert_index + recol_len - 1);
+                // TODO - RGB15 reversal to HSL remains.
+                for i in 0..obj.recol_s.len() {
+                    buffer.push(format!("recol{}s={}", i + 1, obj.recol_s[i]));
+                    buffer.push(format!("recol{}d={}", i + 1, obj.recol_d[i]));
                 }
             },
 
             41 => {
-                let index = recol_palette_index.or(recol_index).or(model_index);
-                let mut retex_lines = Vec::new();
-                let retex_count = obj.retex_s.len();
-
-                for i in 0..retex_count {
-                    retex_lines.push(format!("retex{}s={}", i + 1, obj.retex_s[i]));
-                    retex_lines.push(format!("retex{}d={}", i + 1, obj.retex_d[i]));
+                for i in 0..obj.retex_s.len() {
+                    buffer.push(format!("retex{}s={}", i + 1, obj.retex_s[i]));
+                    buffer.push(format!("retex{}d={}", i + 1, obj.retex_d[i]));
                 }
-
-                let insert_index = index.unwrap() + 1;
-                let retex_len = retex_lines.len();
-
-                buffer.splice(insert_index..insert_index, retex_lines);
-                retex_index = Some(insert_index + retex_len - 1);
             },
 
             42 => {
-                let index = recol_index.or(model_index);
-                let mut recol_palette_lines = Vec::new();
-                let recol_palette_count = obj.recol_d_palette.len();
-
-                for i in 0..recol_palette_count {
-                    recol_palette_lines.push(format!("recol_pal{}d={}", i + 1, obj.recol_d_palette[i]));
+                for i in 0..obj.recol_d_palette.len() {
+                    buffer.push(format!("recol_pal{}d={}", i + 1, obj.recol_d_palette[i]));
                 }
-                let insert_index = index.unwrap() + 1;
-                let recol_palette_len = recol_palette_lines.len();
-
-                buffer.splice(insert_index..insert_index, recol_palette_lines);
-                recol_index = Some(insert_index + recol_palette_len - 1);
             },
 
             65 => buffer.push("stockmarket=yes".to_string()),
@@ -505,19 +582,15 @@ pub fn write_obj(file: &mut File, obj: &mut ObjType, opcode_order: &mut Vec<u8>)
             
             96 => buffer.push(format!("dummyitem=dummy_obj_{}", obj.dummyItem)),
 
+            97 => buffer.push(format!("certlink=obj_{}", obj.certlink)),
+
+            98 => buffer.push(format!("certtemplate=obj_{}", obj.certtemplate)),
+
             100..=109 => buffer.push(format!(
                 "count{}=obj_{},{}",
                 opcode - 99,
-                if obj.countobj.is_some() {
-                    obj.countobj.as_ref().unwrap()[(opcode - 100) as usize]
-                } else {
-                    0
-                },
-                if obj.countco.is_some() {
-                    obj.countco.as_ref().unwrap()[(opcode - 100) as usize]
-                } else {
-                    0
-                }
+                obj.countobj.as_ref().map_or(0, |countobj| countobj[(opcode - 100) as usize]),
+                obj.countco.as_ref().map_or(0, |countco| countco[(opcode - 100) as usize])
             )),
             
             110 => buffer.push(format!("resizex={}", obj.resizex)),
@@ -531,6 +604,10 @@ pub fn write_obj(file: &mut File, obj: &mut ObjType, opcode_order: &mut Vec<u8>)
             114 => buffer.push(format!("contrast={}", obj.contrast)),
             
             115 => buffer.push(format!("team={}", obj.team)),
+
+            121 => buffer.push(format!("lentlink=obj_{}", obj.lentlink)),
+
+            122 => buffer.push(format!("lenttemplate=obj_{}", obj.lenttemplate)),
             
             125 => {
                 buffer.push(format!(
@@ -561,33 +638,238 @@ pub fn write_obj(file: &mut File, obj: &mut ObjType, opcode_order: &mut Vec<u8>)
             }
             
             249 => {
-                for param in obj.params.iter() {
-                    let value_str = match param.1 {
+                for (key, value) in obj.params.iter() {
+                    let value_str = match value {
+                        // Quoted when it would read back as an integer.
+                        ParamValue::String(s) if s.parse::<i32>().is_ok() || s.starts_with('"') => format!("\"{}\"", s),
                         ParamValue::String(s) => s.clone(),
                         ParamValue::Integer(i) => i.to_string(),
                     };
-                    buffer.push(format!("param=param_{},{}", param.0, value_str));
+                    buffer.push(format!("param=param_{},{}", key, value_str));
                 }
             }
             
             _ => {
-                if dereferenced_opcode != 97 && dereferenced_opcode != 98 && dereferenced_opcode != 121 && dereferenced_opcode != 122 {
-                    debug!("opcode={}", dereferenced_opcode);    
-                }
+                debug!("opcode={}", dereferenced_opcode);
             }
         }
     }
 
-    if obj.certlink == -1 && obj.stackable == false {
-        buffer.push("tradeable=no".parse().unwrap());
+    // Derived from the other properties, parse_objs skips it.
+    if obj.certlink == -1 && !obj.stackable {
+        buffer.push("tradeable=no".to_string());
     }
     
     // Verify buffer length, if single-line, it's an empty cert obj.
-    if !buffer.is_empty() && buffer.len() > 1 {
+    if buffer.len() > 1 {
         buffer.push("".to_string());
 
         for line in buffer {
-            writeln!(file, "{}", line).unwrap();
+            writeln!(out, "{}", line)?;
         }
     }
-}
\ No newline at end of file
+    
+    Ok(())
+}
+
+/// Parses the `.obj` text format written by [`write_obj`]. Every obj comes with the opcodes of
+/// its lines in the order they appear, which is the order [`ConfigType::encode_type`] writes.
+pub fn parse_objs(text: &str) -> Result<Vec<ParsedObj>, String> {
+    let mut objs: Vec<ParsedObj> = Vec::new();
+
+    for (index, line) in text.lines().enumerate() {
+        let line = line.trim_end_matches('\r');
+        if line.trim().is_empty() || line.starts_with("//") {
+            continue;
+        }
+
+        if let Some(header) = line.strip_prefix('[') {
+            let id = header.strip_suffix(']')
+                .and_then(|name| name.strip_prefix("obj_"))
+                .and_then(|id| id.parse::<u32>().ok())
+                .ok_or_else(|| format!("Line {}: expected [obj_<id>], got {:?}", index + 1, line))?;
+            objs.push((ObjType::new(id), Vec::new()));
+            continue;
+        }
+
+        let Some((obj, opcode_order)) = objs.last_mut() else {
+            return Err(format!("Line {}: {:?} is outside of an [obj_<id>] block", index + 1, line));
+        };
+        let (key, value) = line.split_once('=')
+            .ok_or_else(|| format!("Line {}: expected key=value, got {:?}", index + 1, line))?;
+
+        let opcode = parse_property(obj, key, value)
+            .map_err(|e| format!("Line {}: {} in {:?}", index + 1, e, line))?;
+        if let Some(opcode) = opcode {
+            if !opcode_order.contains(&opcode) {
+                opcode_order.push(opcode);
+            }
+        }
+    }
+
+    for (_, opcode_order) in objs.iter_mut() {
+        opcode_order.push(0);
+    }
+
+    Ok(objs)
+}
+
+/// Sets the property behind `key` and returns the opcode it belongs to, `None` for derived keys.
+fn parse_property(obj: &mut ObjType, key: &str, value: &str) -> Result<Option<u8>, String> {
+    if let Some(n) = indexed(key, "recol", "s") {
+        set_indexed(&mut obj.recol_s, n, number(value)?)?;
+        return Ok(Some(40));
+    }
+    if let Some(n) = indexed(key, "recol", "d") {
+        set_indexed(&mut obj.recol_d, n, number(value)?)?;
+        return Ok(Some(40));
+    }
+    if let Some(n) = indexed(key, "retex", "s") {
+        set_indexed(&mut obj.retex_s, n, number(value)?)?;
+        return Ok(Some(41));
+    }
+    if let Some(n) = indexed(key, "retex", "d") {
+        set_indexed(&mut obj.retex_d, n, number(value)?)?;
+        return Ok(Some(41));
+    }
+    if let Some(n) = indexed(key, "recol_pal", "d") {
+        set_indexed(&mut obj.recol_d_palette, n, number(value)?)?;
+        return Ok(Some(42));
+    }
+    if let Some(n) = indexed(key, "op", "").filter(|n| *n < 5) {
+        obj.op[n] = text(value)?;
+        return Ok(Some(30 + n as u8));
+    }
+    if let Some(n) = indexed(key, "iop", "").filter(|n| *n < 5) {
+        obj.iop[n] = text(value)?;
+        return Ok(Some(35 + n as u8));
+    }
+    if let Some(n) = indexed(key, "count", "").filter(|n| (1..=10).contains(n)) {
+        let (countobj, countco) = value.split_once(',').ok_or("expected obj_<id>,<count>")?;
+        obj.countobj.get_or_insert_with(|| vec![0; 10])[n - 1] = reference(countobj, "obj_")?;
+        obj.countco.get_or_insert_with(|| vec![0; 10])[n - 1] = number(countco)?;
+        return Ok(Some(99 + n as u8));
+    }
+
+    let opcode = match key {
+        "model" => { obj.model = reference(value, "model_")?; 1 }
+        "name" => { obj.name = Some(text(value)?); 2 }
+        "2dzoom" => { obj.zoom2d = number(value)?; 4 }
+        "2xanof" => { obj.xan2d = number(value)?; 5 }
+        "2yanof" => { obj.yan2d = number(value)?; 6 }
+        "2dxof" => { obj.xof2d = number(value)?; 7 }
+        "2dyof" => { obj.yof2d = number(value)?; 8 }
+        "stackable" => return flag(&mut obj.stackable, value, 11),
+        "cost" => { obj.cost = number(value)?; 12 }
+        "members" => return flag(&mut obj.members, value, 16),
+        "manwear" => { obj.manWear = reference(value, "model_")?; 23 }
+        "manwear2" => { obj.manWear2 = reference(value, "model_")?; 24 }
+        "womanwear" => { obj.womanWear = reference(value, "model_")?; 25 }
+        "womanwear2" => { obj.womanWear2 = reference(value, "model_")?; 26 }
+        "stockmarket" => return flag(&mut obj.stockmarket_yes, value, 65),
+        "manwear3" => { obj.manWear3 = reference(value, "model_")?; 78 }
+        "womanwear3" => { obj.womanWear3 = reference(value, "model_")?; 79 }
+        "manhead" => { obj.manHead = reference(value, "model_")?; 90 }
+        "womanhead" => { obj.womanHead = reference(value, "model_")?; 91 }
+        "manhead2" => { obj.manHead2 = reference(value, "model_")?; 92 }
+        "womanhead2" => { obj.womanHead2 = reference(value, "model_")?; 93 }
+        "2dzan" => { obj.zand2d = number(value)?; 95 }
+        "dummyitem" => { obj.dummyItem = reference(value, "dummy_obj_")?; 96 }
+        "certlink" => { obj.certlink = reference(value, "obj_")?; 97 }
+        "certtemplate" => { obj.certtemplate = reference(value, "obj_")?; 98 }
+        "resizex" => { obj.resizex = number(value)?; 110 }
+        "resizey" => { obj.resizey = number(value)?; 111 }
+        "resizez" => { obj.resizez = number(value)?; 112 }
+        "ambient" => { obj.ambient = number(value)?; 113 }
+        "contrast" => { obj.contrast = number(value)?; 114 }
+        "team" => { obj.team = number(value)?; 115 }
+        "lentlink" => { obj.lentlink = reference(value, "obj_")?; 121 }
+        "lenttemplate" => { obj.lenttemplate = reference(value, "obj_")?; 122 }
+        "manwearoffset" => {
+            (obj.manwearxoffset, obj.manwearyoffset, obj.manwearzoffset) = offsets(value)?;
+            125
+        }
+        "womanwearoffset" => {
+            (obj.womanwearxoffset, obj.womanwearyoffset, obj.womanwearzoffset) = offsets(value)?;
+            126
+        }
+        "cursor1op" => { obj.cursor1op = number(value)?; 127 }
+        "cursor1" => { obj.cursor1 = number(value)?; 127 }
+        "cursor2op" => { obj.cursor2op = number(value)?; 128 }
+        "cursor2" => { obj.cursor2 = number(value)?; 128 }
+        "param" => {
+            let (param, value) = value.split_once(',').ok_or("expected param_<id>,<value>")?;
+            let value = match value.strip_prefix('"').and_then(|value| value.strip_suffix('"')) {
+                Some(quoted) => ParamValue::String(text(quoted)?),
+                None => match value.parse() {
+                    Ok(value) => ParamValue::Integer(value),
+                    Err(_) => ParamValue::String(text(value)?),
+                },
+            };
+            obj.params.push((reference(param, "param_")?, value));
+            249
+        }
+        "tradeable" => return Ok(None),
+        _ => return Err(format!("unknown key {:?}", key)),
+    };
+
+    Ok(Some(opcode))
+}
+
+/// `n` of `<prefix><n><suffix>`.
+fn indexed(key: &str, prefix: &str, suffix: &str) -> Option<usize> {
+    key.strip_prefix(prefix)?.strip_suffix(suffix)?.parse().ok()
+}
+
+/// Sets the 1-based entry `n`, recol and retex lists are written in order.
+fn set_indexed<T: Default + Clone>(values: &mut Vec<T>, n: usize, value: T) -> Result<(), String> {
+    if n == 0 || n > 255 {
+        return Err(format!("index {} out of range", n));
+    }
+    if values.len() < n {
+        values.resize(n, T::default());
+    }
+    values[n - 1] = value;
+    Ok(())
+}
+
+fn number<T: FromStr>(value: &str) -> Result<T, String> {
+    value.trim().parse().map_err(|_| format!("invalid number {:?}", value))
+}
+
+/// The id in a reference like `model_123_obj`, `obj_123` or `obj_-1` for none.
+fn reference<T: FromStr>(value: &str, prefix: &str) -> Result<T, String> {
+    let id = value.strip_prefix(prefix).ok_or_else(|| format!("expected {}<id>, got {:?}", prefix, value))?;
+    let sign = if id.starts_with('-') { 1 } else { 0 };
+    let end = id[sign..].find(|c: char| !c.is_ascii_digit()).map_or(id.len(), |end| sign + end);
+    number(&id[..end])
+}
+
+/// A string the cache can store, the client only reads CP1252.
+fn text(value: &str) -> Result<String, String> {
+    match value.chars().find(|&c| cp1252_byte(c).is_none()) {
+        Some(c) => Err(format!("{:?} can't be encoded in CP1252", c)),
+        None => Ok(value.to_string()),
+    }
+}
+
+/// Flags are opcodes without a payload, `no` leaves the opcode out.
+fn flag(field: &mut bool, value: &str, opcode: u8) -> Result<Option<u8>, String> {
+    match value {
+        "yes" => {
+            *field = true;
+            Ok(Some(opcode))
+        }
+        "no" => {
+            *field = false;
+            Ok(None)
+        }
+        _ => Err(format!("expected yes or no, got {:?}", value)),
+    }
+}
+
+fn offsets(value: &str) -> Result<(i8, i8, i8), String> {
+    let mut parts = value.split(',');
+    let mut next = || number(parts.next().ok_or("expected x,y,z")?);
+    Ok((next()?, next()?, next()?))
+}
@@ -0,0 +1,163 @@
+use crate::io::packet::Packet;
+use crate::util::cache::config::config_type_tests::{decode, encode};
+use crate::util::cache::config::obj_type::{parse_objs, write_obj, ObjType};
+
+/// An obj using every opcode the decoder knows, in the shuffled order the cache stores them.
+fn encoded_obj() -> Vec<u8> {
+    let mut packet = Packet::from(Vec::new());
+    packet.p1(2); packet.pjstr("Dragon d\u{e9}fender", 0);
+    packet.p1(12); packet.p4(68000);
+    packet.p1(16);
+    packet.p1(1); packet.p2(2443);
+    packet.p1(40); packet.p1(2); packet.p2(11179); packet.p2(103); packet.p2(127); packet.p2(10820);
+    packet.p1(42); packet.p1(2); packet.p1(-3); packet.p1(7);
+    packet.p1(41); packet.p1(1); packet.p2(40); packet.p2(41);
+    packet.p1(7); packet.p2(-3);
+    packet.p1(8); packet.p2(10);
+    packet.p1(4); packet.p2(670);
+    packet.p1(6); packet.p2(1932);
+    packet.p1(5); packet.p2(440);
+    packet.p1(11);
+    packet.p1(65);
+    for opcode in [23, 24, 25, 26, 78, 79, 90, 91, 92, 93] {
+        packet.p1(opcode); packet.p2(1000 + opcode);
+    }
+    packet.p1(32); packet.pjstr("", 0);
+    packet.p1(30); packet.pjstr("Take", 0);
+    packet.p1(35); packet.pjstr("Wield", 0);
+    packet.p1(38); packet.pjstr("Drop", 0);
+    packet.p1(95); packet.p2(12);
+    packet.p1(96); packet.p1(2);
+    packet.p1(97); packet.p2(1);
+    packet.p1(98); packet.p2(799);
+    packet.p1(102); packet.p2(996); packet.p2(3);
+    packet.p1(100); packet.p2(995); packet.p2(2);
+    packet.p1(112); packet.p2(300);
+    packet.p1(110); packet.p2(130);
+    packet.p1(111); packet.p2(140);
+    packet.p1(113); packet.p1(-20);
+    packet.p1(114); packet.p1(-6);
+    packet.p1(115); packet.p1(9);
+    packet.p1(121); packet.p2(14000);
+    packet.p1(122); packet.p2(13215);
+    packet.p1(125); packet.p1(-1); packet.p1(2); packet.p1(-3);
+    packet.p1(126); packet.p1(4); packet.p1(-5); packet.p1(6);
+    packet.p1(127); packet.p1(1); packet.p2(200);
+    packet.p1(128); packet.p1(255); packet.p2(65535);
+    packet.p1(249); packet.p1(3);
+    packet.p1(0); packet.p3(59); packet.p4(-7);
+    packet.p1(1); packet.p3(60); packet.pjstr("Skill cape", 0);
+    packet.p1(1); packet.p3(61); packet.pjstr("42", 0);
+    packet.p1(0);
+    packet.data
+}
+
+fn export(obj: &ObjType, opcode_order: &[u8]) -> String {
+    let mut out = Vec::new();
+    write_obj(&mut out, obj, opcode_order).unwrap();
+    String::from_utf8(out).unwrap()
+}
+
+#[test]
+fn test_decode_encode_round_trip() {
+    let data = encoded_obj();
+    let (obj, opcode_order) = decode(ObjType::new(15), data.clone());
+
+    assert_eq!(encode(&obj, &opcode_order), data);
+}
+
+#[test]
+fn test_export_parse_round_trip() {
+    let data = encoded_obj();
+    let (obj, opcode_order) = decode(ObjType::new(15), data.clone());
+    let text = export(&obj, &opcode_order);
+
+    let objs = parse_objs(&text).unwrap();
+    assert_eq!(objs.len(), 1);
+
+    let (parsed, parsed_order) = &objs[0];
+    assert_eq!(parsed.id, 15);
+    assert_eq!(parsed_order, &opcode_order);
+    assert_eq!(encode(parsed, parsed_order), data);
+    assert_eq!(export(parsed, parsed_order), text);
+}
+
+#[test]
+fn test_export_follows_opcode_order() {
+    let (obj, opcode_order) = decode(ObjType::new(15), encoded_obj());
+    let text = export(&obj, &opcode_order);
+    let lines: Vec<&str> = text.lines().collect();
+
+    assert_eq!(&lines[..7], &[
+        "[obj_15]",
+        "name=Dragon d\u{e9}fender",
+        "cost=68000",
+        "members=yes",
+        "model=model_2443_obj",
+        "recol1s=11179",
+        "recol1d=103",
+    ]);
+    assert!(lines.contains(&"op2="));
+    assert!(lines.contains(&"op0=Take"));
+    assert!(lines.contains(&"iop3=Drop"));
+    assert!(lines.contains(&"count3=obj_996,3"));
+    assert!(lines.contains(&"certtemplate=obj_799"));
+    assert!(lines.contains(&"lentlink=obj_14000"));
+    assert!(lines.contains(&"contrast=-30"));
+    assert!(lines.contains(&"param=param_61,\"42\""));
+    assert!(!lines.contains(&"tradeable=no"));
+}
+
+#[test]
+fn test_resizez_is_not_resizex() {
+    let (obj, opcode_order) = decode(ObjType::new(1), vec![112, 1, 44, 0]);
+
+    assert_eq!(export(&obj, &opcode_order), "[obj_1]\nresizez=300\ntradeable=no\n\n");
+}
+
+#[test]
+fn test_parse_unpacked_obj() {
+    let text = "\
+[obj_16]
+name=Magic whistle
+cost=10
+members=yes
+model=model_2433_obj
+2dyof=-2
+2dzoom=490
+2yanof=1208
+2xanof=152
+iop0=Blow
+tradeable=no
+
+[obj_17]
+name=Grail bell
+stackable=no
+";
+    let objs = parse_objs(text).unwrap();
+
+    assert_eq!(objs.len(), 2);
+    assert_eq!(objs[0].1, vec![2, 12, 16, 1, 8, 4, 6, 5, 35, 0]);
+    assert_eq!(objs[1].1, vec![2, 0]);
+
+    let (obj, opcode_order) = &objs[0];
+    assert_eq!(export(obj, opcode_order), text.split("\n\n").next().unwrap().to_string() + "\n\n");
+}
+
+#[test]
+fn test_parse_errors() {
+    assert_eq!(parse_objs("name=Orphan").unwrap_err(), "Line 1: \"name=Orphan\" is outside of an [obj_<id>] block");
+    assert_eq!(parse_objs("[obj_1]\ncolour=red").unwrap_err(), "Line 2: unknown key \"colour\" in \"colour=red\"");
+    assert_eq!(parse_objs("[obj_1]\n\ncost=lots").unwrap_err(), "Line 3: invalid number \"lots\" in \"cost=lots\"");
+    assert!(parse_objs("[npc_1]").is_err());
+    assert_eq!(parse_objs("[obj_1]\nname=Wand \u{6F22}").unwrap_err(), "Line 2: '\u{6F22}' can't be encoded in CP1252 in \"name=Wand \u{6F22}\"");
+}
+
+#[test]
+fn test_parse_negative_reference_and_cp1252_text() {
+    let text = "[obj_1]\nname=\u{20AC}uro caf\u{E9}\ncertlink=obj_-1\nparam=param_5,\u{2122}\ntradeable=no\n\n";
+    let objs = parse_objs(text).unwrap();
+
+    let (obj, opcode_order) = &objs[0];
+    assert_eq!(export(obj, opcode_order), text);
+}
@@ -0,0 +1,117 @@
+use std::sync::OnceLock;
+use constants::js5_config_group::js5_config_group;
+use log::error;
+use crate::io::packet::Packet;
+use crate::util::cache::config::config_archive::{decode_config_group, store_types};
+use crate::util::cache::config::config_type::ConfigType;
+use crate::util::cache::param_helper::{ParamValue, TYPE_STRING};
+
+static PARAM_TYPES: OnceLock<Vec<ParamType>> = OnceLock::new();
+
+#[derive(Debug)]
+pub struct ParamType {
+    pub id: u32,
+    debugname: Option<String>,
+    /// Type char of the value, `s` for strings and anything else for ints.
+    pub type_char: u8,
+    pub default_int: i32,
+    pub default_string: String,
+    pub autodisable: bool,
+}
+
+impl ParamType {
+    pub fn new(id: u32) -> Self {
+        ParamType {
+            id,
+            debugname: None,
+            type_char: b'i',
+            default_int: 0,
+            default_string: "null".to_string(),
+            autodisable: true,
+        }
+    }
+
+    pub fn is_string(&self) -> bool {
+        self.type_char == TYPE_STRING
+    }
+
+    /// The value a holder without this param resolves to.
+    pub fn default_value(&self) -> ParamValue {
+        if self.is_string() {
+            ParamValue::String(self.default_string.clone())
+        } else {
+            ParamValue::Integer(self.default_int)
+        }
+    }
+}
+
+impl ConfigType for ParamType {
+    fn id(&self) -> u32 {
+        self.id
+    }
+
+    fn debugname(&self) -> Option<&String> {
+        self.debugname.as_ref()
+    }
+
+    fn set_debugname(&mut self, debugname: String) {
+        self.debugname = Some(debugname);
+    }
+
+    fn decode(&mut self, opcode: u8, packet: &mut Packet) {
+        match opcode {
+            1 => {
+                self.type_char = packet.g1();
+            }
+
+            2 => {
+                self.default_int = packet.g4();
+            }
+
+            4 => {
+                self.autodisable = false;
+            }
+
+            5 => {
+                self.default_string = packet.gjstr();
+            }
+
+            _ => {
+                error!("Unknown opcode: {}", opcode);
+            }
+        }
+    }
+
+    fn encode(&self, opcode: u8, packet: &mut Packet) {
+        match opcode {
+            1 => packet.p1(self.type_char as i32),
+
+            2 => packet.p4(self.default_int),
+
+            4 => { /* Flag only. */ }
+
+            5 => packet.pjstr(&self.default_string, 0),
+
+            _ => {
+                error!("Unknown opcode: {}", opcode);
+            }
+        }
+    }
+}
+
+pub struct ParamTypeProvider;
+
+impl ParamTypeProvider {
+    /// Decodes group `js5_config_group::PARAMTYPE` of the config archive.
+    pub fn load() -> u32 {
+        store_types(&PARAM_TYPES, "param", decode_config_group(js5_config_group::PARAMTYPE, ParamType::new))
+    }
+
+    pub fn get(id: i32) -> Option<&'static ParamType> {
+        PARAM_TYPES.get()?.get(usize::try_from(id).ok()?)
+    }
+
+    pub fn count() -> usize {
+        PARAM_TYPES.get().map_or(0, Vec::len)
+    }
+}
@@ -0,0 +1,70 @@
+use crate::io::packet::Packet;
+use crate::util::cache::config::config_type_tests::{decode, encode};
+use crate::util::cache::config::obj_type::parse_objs;
+use crate::util::cache::config::param_type::ParamType;
+use crate::util::cache::config::struct_type::StructType;
+use crate::util::cache::param_helper::ParamValue;
+
+fn secret_param() -> Vec<u8> {
+    let mut packet = Packet::from(Vec::new());
+    packet.p1(1); packet.p1(b's' as i32);
+    packet.p1(5); packet.pjstr("It's a secret.", 0);
+    packet.p1(4);
+    packet.p1(0);
+    packet.data
+}
+
+#[test]
+fn test_param_defaults() {
+    let (param, _) = decode(ParamType::new(1), vec![1, b'i', 2, 0, 0, 0, 25, 0]);
+    assert!(!param.is_string());
+    assert!(param.autodisable);
+    assert_eq!(param.default_value(), ParamValue::Integer(25));
+
+    let (param, _) = decode(ParamType::new(2), secret_param());
+    assert!(param.is_string());
+    assert!(!param.autodisable);
+    assert_eq!(param.default_value(), ParamValue::String("It's a secret.".to_string()));
+
+    let (param, _) = decode(ParamType::new(3), vec![1, b's', 0]);
+    assert_eq!(param.default_value(), ParamValue::String("null".to_string()));
+}
+
+#[test]
+fn test_param_round_trip() {
+    let data = secret_param();
+    let (param, opcode_order) = decode(ParamType::new(2), data.clone());
+
+    assert_eq!(encode(&param, &opcode_order), data);
+}
+
+#[test]
+fn test_struct_param_falls_back_to_default() {
+    let mut packet = Packet::from(Vec::new());
+    packet.p1(249); packet.p1(2);
+    packet.p1(0); packet.p3(1); packet.p4(500);
+    packet.p1(1); packet.p3(2); packet.pjstr("Shown", 0);
+    packet.p1(0);
+    let (struct_type, opcode_order) = decode(StructType::new(0), packet.data.clone());
+
+    let (int_param, _) = decode(ParamType::new(1), vec![2, 0, 0, 0, 25, 0]);
+    let (string_param, _) = decode(ParamType::new(2), secret_param());
+    let (missing_param, _) = decode(ParamType::new(3), vec![2, 0xFF, 0xFF, 0xFF, 0xFF, 0]);
+
+    assert_eq!(struct_type.param(&int_param), ParamValue::Integer(500));
+    assert_eq!(struct_type.param(&string_param), ParamValue::String("Shown".to_string()));
+    assert_eq!(struct_type.param(&missing_param), ParamValue::Integer(-1));
+    assert_eq!(encode(&struct_type, &opcode_order), packet.data);
+}
+
+#[test]
+fn test_obj_param_falls_back_to_default() {
+    let objs = parse_objs("[obj_4151]\nname=Abyssal whip\nparam=param_1,\"70\"\n").unwrap();
+    let (obj, _) = &objs[0];
+
+    let (string_param, _) = decode(ParamType::new(1), vec![1, b's', 0]);
+    let (int_param, _) = decode(ParamType::new(2), vec![2, 0, 0, 0, 3, 0]);
+
+    assert_eq!(obj.param(&string_param), ParamValue::String("70".to_string()));
+    assert_eq!(obj.param(&int_param), ParamValue::Integer(3));
+}
@@ -0,0 +1,281 @@
+use std::sync::OnceLock;
+use constants::js5_archive::js5_archive;
+use log::error;
+use crate::io::packet::Packet;
+use crate::util::cache::config::config_archive::{decode_config_archive, store_types};
+use crate::util::cache::config::config_type::{nullable, ConfigType};
+use crate::util::cache::param_helper::{decode_params, encode_params, Params};
+
+static SEQ_TYPES: OnceLock<Vec<SeqType>> = OnceLock::new();
+
+/// Client cycles (20ms) in a server tick (600ms).
+pub const CYCLES_PER_TICK: u32 = 30;
+
+#[derive(Debug)]
+pub struct SeqType {
+    pub id: u32,
+    debugname: Option<String>,
+    /// Length of each frame in client cycles.
+    pub delays: Vec<u16>,
+    /// Each frame as `group << 16 | file` of `js5_archive::ANIMS`.
+    frames: Vec<i32>,
+    pub replayoff: i32,
+    /// Labels of the frame base that keep playing the walk anim.
+    walkmerge: Vec<u8>,
+    pub stretches: bool,
+    /// A playing seq is only replaced by one of at least the same priority.
+    pub priority: u8,
+    pub righthand: i32,
+    pub lefthand: i32,
+    pub replaycount: u8,
+    /// -1 derives it from the walk-merge labels, see [`SeqType::preanim_move`].
+    preanim_move: i8,
+    postanim_move: i8,
+    pub replacemode: u8,
+    iframes: Vec<i32>,
+    sounds: Vec<i32>,
+    params: Params,
+}
+
+impl SeqType {
+    pub fn new(id: u32) -> Self {
+        SeqType {
+            id,
+            debugname: None,
+            delays: Vec::new(),
+            frames: Vec::new(),
+            replayoff: -1,
+            walkmerge: Vec::new(),
+            stretches: false,
+            priority: 5,
+            righthand: -1,
+            lefthand: -1,
+            replaycount: 99,
+            preanim_move: -1,
+            postanim_move: -1,
+            replacemode: 2,
+            iframes: Vec::new(),
+            sounds: Vec::new(),
+            params: Params::default(),
+        }
+    }
+
+    /// Length of one play through in client cycles.
+    pub fn duration(&self) -> u32 {
+        self.delays.iter().map(|&delay| delay as u32).sum()
+    }
+
+    /// Length of one play through in server ticks, rounded up.
+    pub fn duration_ticks(&self) -> u32 {
+        self.duration().div_ceil(CYCLES_PER_TICK)
+    }
+
+    /// Whether parts of the body keep playing the walk anim while the seq plays.
+    pub fn walk_merges(&self) -> bool {
+        !self.walkmerge.is_empty()
+    }
+
+    /// What happens when the entity moves before the seq has started, 0 delays the seq,
+    /// 1 plays it anyway and 2 merges it with the walk anim.
+    pub fn preanim_move(&self) -> u8 {
+        match self.preanim_move {
+            -1 if self.walk_merges() => 2,
+            -1 => 0,
+            preanim_move => preanim_move as u8,
+        }
+    }
+
+    /// Same as [`SeqType::preanim_move`] for moving while the seq plays.
+    pub fn postanim_move(&self) -> u8 {
+        match self.postanim_move {
+            -1 if self.walk_merges() => 2,
+            -1 => 0,
+            postanim_move => postanim_move as u8,
+        }
+    }
+}
+
+impl ConfigType for SeqType {
+    fn id(&self) -> u32 {
+        self.id
+    }
+
+    fn debugname(&self) -> Option<&String> {
+        self.debugname.as_ref()
+    }
+
+    fn set_debugname(&mut self, debugname: String) {
+        self.debugname = Some(debugname);
+    }
+
+    fn decode(&mut self, opcode: u8, packet: &mut Packet) {
+        match opcode {
+            1 => {
+                let count = packet.g2() as usize;
+
+                self.delays = (0..count).map(|_| packet.g2()).collect();
+                self.frames = (0..count).map(|_| packet.g2() as i32).collect();
+                for frame in &mut self.frames {
+                    *frame |= (packet.g2() as i32) << 16;
+                }
+            }
+
+            2 => {
+                self.replayoff = nullable(packet.g2());
+            }
+
+            3 => {
+                let count = packet.g1();
+
+                for _ in 0..count {
+                    self.walkmerge.push(packet.g1());
+                }
+            }
+
+            4 => {
+                self.stretches = true;
+            }
+
+            5 => {
+                self.priority = packet.g1();
+            }
+
+            6 => {
+                self.righthand = nullable(packet.g2());
+            }
+
+            7 => {
+                self.lefthand = nullable(packet.g2());
+            }
+
+            8 => {
+                self.replaycount = packet.g1();
+            }
+
+            9 => {
+                self.preanim_move = packet.g1() as i8;
+            }
+
+            10 => {
+                self.postanim_move = packet.g1() as i8;
+            }
+
+            11 => {
+                self.replacemode = packet.g1();
+            }
+
+            12 => {
+                let count = packet.g1() as usize;
+
+                self.iframes = (0..count).map(|_| packet.g2() as i32).collect();
+                for frame in &mut self.iframes {
+                    *frame |= (packet.g2() as i32) << 16;
+                }
+            }
+
+            13 => {
+                let count = packet.g2();
+
+                for _ in 0..count {
+                    self.sounds.push(packet.g3());
+                }
+            }
+
+            14..=16 => { /* Client-only flags. */ }
+
+            249 => {
+                self.params = decode_params(packet);
+            }
+
+            _ => {
+                error!("Unknown opcode: {}", opcode);
+            }
+        }
+    }
+
+    fn encode(&self, opcode: u8, packet: &mut Packet) {
+        match opcode {
+            1 => {
+                packet.p2(self.delays.len() as i32);
+
+                for &delay in &self.delays {
+                    packet.p2(delay as i32);
+                }
+                for &frame in &self.frames {
+                    packet.p2(frame & 0xffff);
+                }
+                for &frame in &self.frames {
+                    packet.p2(frame >> 16);
+                }
+            }
+
+            2 => packet.p2(self.replayoff),
+
+            3 => {
+                packet.p1(self.walkmerge.len() as i32);
+
+                for &label in &self.walkmerge {
+                    packet.p1(label as i32);
+                }
+            }
+
+            4 | 14..=16 => { /* Flag only. */ }
+
+            5 => packet.p1(self.priority as i32),
+
+            6 => packet.p2(self.righthand),
+
+            7 => packet.p2(self.lefthand),
+
+            8 => packet.p1(self.replaycount as i32),
+
+            9 => packet.p1(self.preanim_move as i32),
+
+            10 => packet.p1(self.postanim_move as i32),
+
+            11 => packet.p1(self.replacemode as i32),
+
+            12 => {
+                packet.p1(self.iframes.len() as i32);
+
+                for &frame in &self.iframes {
+                    packet.p2(frame & 0xffff);
+                }
+                for &frame in &self.iframes {
+                    packet.p2(frame >> 16);
+                }
+            }
+
+            13 => {
+                packet.p2(self.sounds.len() as i32);
+
+                for &sound in &self.sounds {
+                    packet.p3(sound);
+                }
+            }
+
+            249 => encode_params(packet, &self.params),
+
+            _ => {
+                error!("Unknown opcode: {}", opcode);
+            }
+        }
+    }
+}
+
+pub struct SeqTypeProvider;
+
+impl SeqTypeProvider {
+    /// Decodes `js5_archive::CONFIG_SEQ`, 128 seqs to a group.
+    pub fn load() -> u32 {
+        store_types(&SEQ_TYPES, "seq", decode_config_archive(js5_archive::CONFIG_SEQ, 7, SeqType::new))
+    }
+
+    pub fn get(id: i32) -> Option<&'static SeqType> {
+        SEQ_TYPES.get()?.get(usize::try_from(id).ok()?)
+    }
+
+    pub fn count() -> usize {
+        SEQ_TYPES.get().map_or(0, Vec::len)
+    }
+}
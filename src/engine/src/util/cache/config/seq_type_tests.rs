@@ -0,0 +1,166 @@
+use crate::entity::entity_lifecycle::EntityLifeCycle;
+use crate::entity::pathing_entity::PathingEntity;
+use crate::grid::coord_grid::CoordGrid;
+use crate::io::packet::Packet;
+use crate::util::cache::config::config_type_tests::{decode, encode};
+use crate::util::cache::config::seq_type::SeqType;
+use crate::util::cache::config::spot_anim_type::SpotAnimType;
+
+/// A three frame attack with walk-merge labels, sounds and params.
+fn encoded_seq() -> Vec<u8> {
+    let mut packet = Packet::from(Vec::new());
+    packet.p1(1); packet.p2(3);
+    packet.p2(4); packet.p2(6); packet.p2(25);
+    packet.p2(0); packet.p2(1); packet.p2(2);
+    packet.p2(400); packet.p2(400); packet.p2(401);
+    packet.p1(2); packet.p2(1);
+    packet.p1(3); packet.p1(2); packet.p1(5); packet.p1(9);
+    packet.p1(4);
+    packet.p1(5); packet.p1(8);
+    packet.p1(6); packet.p2(-1);
+    packet.p1(7); packet.p2(1205);
+    packet.p1(8); packet.p1(1);
+    packet.p1(10); packet.p1(1);
+    packet.p1(11); packet.p1(1);
+    packet.p1(12); packet.p1(1); packet.p2(3); packet.p2(402);
+    packet.p1(13); packet.p2(2); packet.p3(0x123456); packet.p3(-1);
+    packet.p1(14);
+    packet.p1(249); packet.p1(1); packet.p1(0); packet.p3(40); packet.p4(2);
+    packet.p1(0);
+    packet.data
+}
+
+fn seq(id: u32, priority: u8) -> SeqType {
+    let mut packet = Packet::from(Vec::new());
+    packet.p1(5); packet.p1(priority as i32);
+    packet.p1(0);
+    decode(SeqType::new(id), packet.data).0
+}
+
+fn entity() -> PathingEntity {
+    PathingEntity::new(CoordGrid::from(3222, 0, 3222), 1, 1, EntityLifeCycle::FOREVER)
+}
+
+#[test]
+fn test_decode_seq() {
+    let (seq, _) = decode(SeqType::new(422), encoded_seq());
+
+    assert_eq!(seq.delays, vec![4, 6, 25]);
+    assert_eq!(seq.replayoff, 1);
+    assert!(seq.stretches);
+    assert_eq!(seq.priority, 8);
+    assert_eq!(seq.righthand, -1);
+    assert_eq!(seq.lefthand, 1205);
+    assert_eq!(seq.replaycount, 1);
+    assert_eq!(seq.replacemode, 1);
+    assert!(seq.walk_merges());
+    assert_eq!(seq.preanim_move(), 2);
+    assert_eq!(seq.postanim_move(), 1);
+}
+
+#[test]
+fn test_seq_round_trip() {
+    let data = encoded_seq();
+    let (seq, opcode_order) = decode(SeqType::new(422), data.clone());
+
+    assert_eq!(encode(&seq, &opcode_order), data);
+}
+
+#[test]
+fn test_seq_defaults() {
+    let (seq, _) = decode(SeqType::new(0), vec![0]);
+
+    assert_eq!(seq.priority, 5);
+    assert_eq!(seq.replayoff, -1);
+    assert_eq!(seq.replaycount, 99);
+    assert_eq!(seq.replacemode, 2);
+    assert!(!seq.walk_merges());
+    assert_eq!(seq.preanim_move(), 0);
+    assert_eq!(seq.postanim_move(), 0);
+    assert_eq!(seq.duration_ticks(), 0);
+}
+
+#[test]
+fn test_duration_ticks() {
+    let (seq, _) = decode(SeqType::new(422), encoded_seq());
+
+    // 35 client cycles are 700ms, which the client only finishes during the second tick.
+    assert_eq!(seq.duration(), 35);
+    assert_eq!(seq.duration_ticks(), 2);
+
+    let mut packet = Packet::from(Vec::new());
+    packet.p1(1); packet.p2(2);
+    packet.p2(15); packet.p2(15);
+    packet.p2(0); packet.p2(1);
+    packet.p2(7); packet.p2(7);
+    packet.p1(0);
+    let (seq, _) = decode(SeqType::new(1), packet.data);
+    assert_eq!(seq.duration_ticks(), 1);
+}
+
+#[test]
+fn test_spotanim_round_trip() {
+    let mut packet = Packet::from(Vec::new());
+    packet.p1(1); packet.p2(2365);
+    packet.p1(2); packet.p2(711);
+    packet.p1(4); packet.p2(96);
+    packet.p1(5); packet.p2(96);
+    packet.p1(6); packet.p2(90);
+    packet.p1(7); packet.p1(30);
+    packet.p1(8); packet.p1(50);
+    packet.p1(9);
+    packet.p1(40); packet.p1(1); packet.p2(10); packet.p2(20);
+    packet.p1(41); packet.p1(1); packet.p2(30); packet.p2(40);
+    packet.p1(0);
+    let data = packet.data;
+
+    let (spotanim, opcode_order) = decode(SpotAnimType::new(90), data.clone());
+    assert_eq!(spotanim.seq, 711);
+    assert_eq!(encode(&spotanim, &opcode_order), data);
+
+    assert_eq!(SpotAnimType::new(91).seq, -1);
+}
+
+#[test]
+fn test_anim_priority() {
+    let mut entity = entity();
+    let high = seq(1, 10);
+    let low = seq(2, 1);
+    let same = seq(3, 10);
+
+    assert!(entity.anim(Some(&high), 0));
+    assert!(!entity.anim(Some(&low), 0));
+    assert_eq!(entity.anim_id, 1);
+
+    assert!(entity.anim(Some(&same), 5));
+    assert_eq!((entity.anim_id, entity.anim_delay), (3, 5));
+
+    // Stopping always goes through, after which anything may play.
+    assert!(entity.anim(None, 0));
+    assert_eq!(entity.anim_id, -1);
+    assert!(entity.anim(Some(&low), 0));
+    assert_eq!(entity.anim_id, 2);
+    assert_ne!(entity.masks & PathingEntity::MASK_ANIM, 0);
+
+    entity.reset_masks();
+    assert_eq!(entity.masks, 0);
+    assert_eq!(entity.anim_id, -1);
+    assert!(entity.anim(Some(&low), 0));
+}
+
+#[test]
+fn test_spotanim_priority() {
+    let mut entity = entity();
+    let high = seq(1, 10);
+    let low = seq(2, 1);
+
+    assert!(entity.spotanim(90, Some(&high), 92, 0));
+    assert!(!entity.spotanim(91, Some(&low), 0, 0));
+    assert!(!entity.spotanim(92, None, 0, 0));
+    assert_eq!((entity.spotanim_id, entity.spotanim_height), (90, 92));
+    assert_ne!(entity.masks & PathingEntity::MASK_SPOTANIM, 0);
+
+    entity.reset_masks();
+    assert!(entity.spotanim(91, Some(&low), 0, 30));
+    assert_eq!((entity.spotanim_id, entity.spotanim_delay), (91, 30));
+}
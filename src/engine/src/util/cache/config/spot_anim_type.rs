@@ -0,0 +1,181 @@
+use std::sync::OnceLock;
+use constants::js5_archive::js5_archive;
+use log::error;
+use crate::io::packet::Packet;
+use crate::util::cache::config::config_archive::{decode_config_archive, store_types};
+use crate::util::cache::config::config_type::{nullable, ConfigType};
+use crate::util::cache::config::seq_type::{SeqType, SeqTypeProvider};
+
+static SPOT_ANIM_TYPES: OnceLock<Vec<SpotAnimType>> = OnceLock::new();
+
+#[derive(Debug)]
+pub struct SpotAnimType {
+    pub id: u32,
+    debugname: Option<String>,
+    model: u16,
+    /// The seq the spotanim plays, -1 for a still model.
+    pub seq: i32,
+    resizeh: u16,
+    resizev: u16,
+    angle: u16,
+    ambient: u8,
+    contrast: u8,
+    recol_s: Vec<u16>,
+    recol_d: Vec<u16>,
+    retex_s: Vec<u16>,
+    retex_d: Vec<u16>,
+}
+
+impl SpotAnimType {
+    pub fn new(id: u32) -> Self {
+        SpotAnimType {
+            id,
+            debugname: None,
+            model: 0,
+            seq: -1,
+            resizeh: 128,
+            resizev: 128,
+            angle: 0,
+            ambient: 0,
+            contrast: 0,
+            recol_s: Vec::new(),
+            recol_d: Vec::new(),
+            retex_s: Vec::new(),
+            retex_d: Vec::new(),
+        }
+    }
+
+    /// The seq the spotanim plays, `None` for a still model or an unknown seq.
+    pub fn seq_type(&self) -> Option<&'static SeqType> {
+        SeqTypeProvider::get(self.seq)
+    }
+}
+
+impl ConfigType for SpotAnimType {
+    fn id(&self) -> u32 {
+        self.id
+    }
+
+    fn debugname(&self) -> Option<&String> {
+        self.debugname.as_ref()
+    }
+
+    fn set_debugname(&mut self, debugname: String) {
+        self.debugname = Some(debugname);
+    }
+
+    fn decode(&mut self, opcode: u8, packet: &mut Packet) {
+        match opcode {
+            1 => {
+                self.model = packet.g2();
+            }
+
+            2 => {
+                self.seq = nullable(packet.g2());
+            }
+
+            4 => {
+                self.resizeh = packet.g2();
+            }
+
+            5 => {
+                self.resizev = packet.g2();
+            }
+
+            6 => {
+                self.angle = packet.g2();
+            }
+
+            7 => {
+                self.ambient = packet.g1();
+            }
+
+            8 => {
+                self.contrast = packet.g1();
+            }
+
+            9 | 10 => { /* Client-only flags. */ }
+
+            40 => {
+                let count = packet.g1();
+
+                for _ in 0..count {
+                    self.recol_s.push(packet.g2());
+                    self.recol_d.push(packet.g2());
+                }
+            }
+
+            41 => {
+                let count = packet.g1();
+
+                for _ in 0..count {
+                    self.retex_s.push(packet.g2());
+                    self.retex_d.push(packet.g2());
+                }
+            }
+
+            _ => {
+                error!("Unknown opcode: {}", opcode);
+            }
+        }
+    }
+
+    fn encode(&self, opcode: u8, packet: &mut Packet) {
+        match opcode {
+            1 => packet.p2(self.model as i32),
+
+            2 => packet.p2(self.seq),
+
+            4 => packet.p2(self.resizeh as i32),
+
+            5 => packet.p2(self.resizev as i32),
+
+            6 => packet.p2(self.angle as i32),
+
+            7 => packet.p1(self.ambient as i32),
+
+            8 => packet.p1(self.contrast as i32),
+
+            9 | 10 => { /* Flag only. */ }
+
+            40 => {
+                packet.p1(self.recol_s.len() as i32);
+
+                for i in 0..self.recol_s.len() {
+                    packet.p2(self.recol_s[i] as i32);
+                    packet.p2(self.recol_d[i] as i32);
+                }
+            }
+
+            41 => {
+                packet.p1(self.retex_s.len() as i32);
+
+                for i in 0..self.retex_s.len() {
+                    packet.p2(self.retex_s[i] as i32);
+                    packet.p2(self.retex_d[i] as i32);
+                }
+            }
+
+            _ => {
+                error!("Unknown opcode: {}", opcode);
+            }
+        }
+    }
+}
+
+pub struct SpotAnimTypeProvider;
+
+impl SpotAnimTypeProvider {
+    /// Decodes `js5_archive::CONFIG_SPOT`, 256 spotanims to a group.
+    pub fn load() -> u32 {
+        store_types(&SPOT_ANIM_TYPES, "spotanim", decode_config_archive(js5_archive::CONFIG_SPOT, 8, SpotAnimType::new))
+    }
+
+    pub fn get(id: i32) -> Option<&'static SpotAnimType> {
+        SPOT_ANIM_TYPES.get()?.get(usize::try_from(id).ok()?)
+    }
+
+    pub fn count() -> usize {
+        SPOT_ANIM_TYPES.get().map_or(0, Vec::len)
+    }
+}
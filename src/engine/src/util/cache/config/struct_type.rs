@@ -0,0 +1,85 @@
+use std::sync::OnceLock;
+use constants::js5_config_group::js5_config_group;
+use log::error;
+use crate::io::packet::Packet;
+use crate::util::cache::config::config_archive::{decode_config_group, store_types};
+use crate::util::cache::config::config_type::ConfigType;
+use crate::util::cache::config::param_type::ParamType;
+use crate::util::cache::param_helper::{decode_params, encode_params, param_value, ParamValue, Params};
+
+static STRUCT_TYPES: OnceLock<Vec<StructType>> = OnceLock::new();
+
+#[derive(Debug)]
+pub struct StructType {
+    pub id: u32,
+    debugname: Option<String>,
+    params: Params,
+}
+
+impl StructType {
+    pub fn new(id: u32) -> Self {
+        StructType {
+            id,
+            debugname: None,
+            params: Params::default(),
+        }
+    }
+
+    /// The value of `param`, its default when the struct doesn't set it.
+    pub fn param(&self, param: &ParamType) -> ParamValue {
+        param_value(&self.params, param)
+    }
+}
+
+impl ConfigType for StructType {
+    fn id(&self) -> u32 {
+        self.id
+    }
+
+    fn debugname(&self) -> Option<&String> {
+        self.debugname.as_ref()
+    }
+
+    fn set_debugname(&mut self, debugname: String) {
+        self.debugname = Some(debugname);
+    }
+
+    fn decode(&mut self, opcode: u8, packet: &mut Packet) {
+        match opcode {
+            249 => {
+                self.params = decode_params(packet);
+            }
+
+            _ => {
+                error!("Unknown opcode: {}", opcode);
+            }
+        }
+    }
+
+    fn encode(&self, opcode: u8, packet: &mut Packet) {
+        match opcode {
+            249 => encode_params(packet, &self.params),
+
+            _ => {
+                error!("Unknown opcode: {}", opcode);
+            }
+        }
+    }
+}
+
+pub struct StructTypeProvider;
+
+impl StructTypeProvider {
+    /// Decodes group `js5_config_group::STRUCTTYPE` of the config archive.
+    pub fn load() -> u32 {
+        store_types(&STRUCT_TYPES, "struct", decode_config_group(js5_config_group::STRUCTTYPE, StructType::new))
+    }
+
+    pub fn get(id: i32) -> Option<&'static StructType> {
+        STRUCT_TYPES.get()?.get(usize::try_from(id).ok()?)
+    }
+
+    pub fn count() -> usize {
+        STRUCT_TYPES.get().map_or(0, Vec::len)
+    }
+}
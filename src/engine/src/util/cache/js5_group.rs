@@ -0,0 +1,39 @@
+use crate::io::packet::Packet;
+
+// Packed in the cache crate, the overlay builds groups from directories of files the same way.
+pub use cache::overlay::pack_group;
+
+/// Splits a group into its `count` files, in the order of their ids in the archive index.
+pub fn unpack_group(data: &[u8], count: usize) -> Result<Vec<Vec<u8>>, String> {
+    if count == 1 {
+        return Ok(vec![data.to_vec()]);
+    }
+
+    let Some((&chunks, rest)) = data.split_last() else {
+        return Err("Empty group".to_string());
+    };
+    let table_len = chunks as usize * count * 4;
+    if table_len > rest.len() {
+        return Err(format!("Group of {} bytes is too short for {} chunks of {} files", data.len(), chunks, count));
+    }
+
+    let (mut body, table) = rest.split_at(rest.len() - table_len);
+    let mut table = Packet::from(table.to_vec());
+    let mut files = vec![Vec::new(); count];
+
+    for _ in 0..chunks {
+        let mut size = 0;
+        for file in files.iter_mut() {
+            size += table.g4();
+            if size < 0 || size as usize > body.len() {
+                return Err(format!("Chunk of {} bytes runs past the end of the group", size));
+            }
+
+            let (chunk, next) = body.split_at(size as usize);
+            file.extend_from_slice(chunk);
+            body = next;
+        }
+    }
+
+    Ok(files)
+}
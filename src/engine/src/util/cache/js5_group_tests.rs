@@ -0,0 +1,44 @@
+use crate::util::cache::js5_group::{pack_group, unpack_group};
+
+#[test]
+fn test_pack_single_file_group() {
+    assert_eq!(pack_group(&[vec![1, 2, 0]]), vec![1, 2, 0]);
+    assert_eq!(unpack_group(&[1, 2, 0], 1).unwrap(), vec![vec![1, 2, 0]]);
+}
+
+#[test]
+fn test_pack_group() {
+    let files = vec![vec![1, 0], vec![0], vec![2, 3, 4, 0]];
+    let group = pack_group(&files);
+
+    assert_eq!(group, vec![
+        1, 0, 0, 2, 3, 4, 0,
+        0, 0, 0, 2,
+        0xFF, 0xFF, 0xFF, 0xFF,
+        0, 0, 0, 3,
+        1,
+    ]);
+    assert_eq!(unpack_group(&group, 3).unwrap(), files);
+}
+
+#[test]
+fn test_unpack_chunked_group() {
+    // Two files split over two chunks, sizes are deltas within each chunk.
+    let group = vec![
+        1, 2, 3, 4,
+        0, 0, 0, 1,
+        0, 0, 0, 0,
+        0, 0, 0, 1,
+        0, 0, 0, 0,
+        2,
+    ];
+
+    assert_eq!(unpack_group(&group, 2).unwrap(), vec![vec![1, 3], vec![2, 4]]);
+}
+
+#[test]
+fn test_unpack_truncated_group() {
+    assert!(unpack_group(&[], 2).is_err());
+    assert!(unpack_group(&[0, 0, 0, 9, 1], 1).is_ok());
+    assert!(unpack_group(&[0, 0, 0, 9, 0, 0, 0, 0, 1], 2).is_err());
+}
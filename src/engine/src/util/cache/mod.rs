@@ -1,3 +1,9 @@
 pub mod obj_unpacker;
-mod config;
-pub mod param_helper;
\ No newline at end of file
+pub mod config;
+pub mod param_helper;
+pub mod cache_verify;
+mod cache_verify_tests;
+pub mod obj_packer;
+mod obj_packer_tests;
+pub mod js5_group;
+mod js5_group_tests;
\ No newline at end of file
@@ -0,0 +1,98 @@
+use std::collections::BTreeMap;
+use std::error::Error;
+use std::fs;
+use std::path::Path;
+use constants::js5_archive::js5_archive;
+use log::debug;
+use rs2cache::Cache;
+use rs2cache::js5_compression::Js5Compression;
+use rs2cache::js5_index::Js5Index;
+use rs2cache::store::ARCHIVESET;
+use crate::io::packet::Packet;
+use crate::util::cache::config::config_type::ConfigType;
+use crate::util::cache::config::obj_type::{parse_objs, ParsedObj};
+use crate::util::cache::js5_group::pack_group;
+
+/// Objs per group of `js5_archive::CONFIG_OBJ`, an obj id is `group << 8 | file`.
+pub const OBJS_PER_GROUP: u32 = 256;
+
+/// Encodes a file for every obj id below `count`, ids missing from `objs` are empty types.
+pub fn encode_objs(objs: &[ParsedObj], count: u32) -> Result<Vec<Vec<u8>>, String> {
+    let mut files = vec![vec![0]; count as usize];
+
+    for (obj, opcode_order) in objs {
+        let file = files.get_mut(obj.id as usize).ok_or_else(|| {
+            format!("obj_{} is past the {} objs in the cache index", obj.id, count)
+        })?;
+
+        let mut packet = Packet::from(Vec::new());
+        obj.encode_type(&mut packet, opcode_order);
+        *file = packet.data;
+    }
+
+    Ok(files)
+}
+
+/// Rebuilds every group of the obj archive, keyed by group id.
+pub fn pack_objs(objs: &[ParsedObj], count: u32) -> Result<BTreeMap<u32, Vec<u8>>, String> {
+    let files = encode_objs(objs, count)?;
+
+    Ok(files
+        .chunks(OBJS_PER_GROUP as usize)
+        .enumerate()
+        .map(|(group, files)| (group as u32, pack_group(files)))
+        .collect())
+}
+
+/// Objs in the archive, every group but the last one is full.
+pub fn obj_count(index: &Js5Index) -> u32 {
+    index.groups
+        .iter()
+        .map(|(group, entry)| group * OBJS_PER_GROUP + entry.files.len() as u32)
+        .max()
+        .unwrap_or(0)
+}
+
+pub fn read_obj_index(cache: &Cache) -> Result<Js5Index, Box<dyn Error>> {
+    let compressed = cache.store.read(ARCHIVESET, js5_archive::CONFIG_OBJ).map_err(|e| e.to_string())?;
+    let decompressed = Js5Compression::uncompress(compressed, None).map_err(|e| e.to_string())?;
+    Js5Index::read(decompressed).map_err(|e| format!("Failed to read the obj index: {:?}", e).into())
+}
+
+pub fn read_obj_group(cache: &Cache, group: u32) -> Result<Vec<u8>, Box<dyn Error>> {
+    let compressed = cache.store.read(js5_archive::CONFIG_OBJ as u8, group).map_err(|e| e.to_string())?;
+    Ok(Js5Compression::uncompress(compressed, None).map_err(|e| e.to_string())?)
+}
+
+/// Packs the `.obj` file at `src` against the cache at `cache_path` and writes the groups that
+/// differ from the cache to `<overlay>/19/<group>`, returns how many were written. Groups that
+/// match the cache again are removed from the overlay.
+pub fn pack_objs_to_overlay(src: &Path, cache_path: &str, overlay: &Path) -> Result<usize, Box<dyn Error>> {
+    let text = fs::read_to_string(src).map_err(|e| format!("Failed to read {}: {}", src.display(), e))?;
+    let objs = parse_objs(&text).map_err(|e| format!("{}: {}", src.display(), e))?;
+
+    let cache = Cache::open(cache_path).map_err(|e| format!("Failed to open cache: {}", e))?;
+    let index = read_obj_index(&cache)?;
+    let groups = pack_objs(&objs, obj_count(&index))?;
+
+    let dir = overlay.join(js5_archive::CONFIG_OBJ.to_string());
+    fs::create_dir_all(&dir)?;
+
+    let mut written = 0;
+    for (group, data) in groups {
+        let path = dir.join(group.to_string());
+
+        if read_obj_group(&cache, group)? == data {
+            if path.exists() {
+                fs::remove_file(&path)?;
+            }
+            continue;
+        }
+
+        debug!("Obj group {} changed, writing {}", group, path.display());
+        fs::write(&path, data).map_err(|e| format!("Failed to write {}: {}", path.display(), e))?;
+        written += 1;
+    }
+
+    Ok(written)
+}
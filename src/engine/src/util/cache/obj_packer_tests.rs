@@ -0,0 +1,66 @@
+use std::fmt::Write;
+use rs2cache::Cache;
+use crate::io::packet::Packet;
+use crate::util::cache::config::config_type::ConfigType;
+use crate::util::cache::config::obj_type::{parse_objs, write_obj, ObjType};
+use crate::util::cache::js5_group::pack_group;
+use crate::util::cache::obj_packer::{encode_objs, obj_count, pack_objs, read_obj_group, read_obj_index, OBJS_PER_GROUP};
+
+#[test]
+fn test_encode_missing_objs_as_empty() {
+    let objs = parse_objs("[obj_1]\ncost=5\n").unwrap();
+    let files = encode_objs(&objs, 3).unwrap();
+
+    assert_eq!(files, vec![vec![0], vec![12, 0, 0, 0, 5, 0], vec![0]]);
+    assert_eq!(encode_objs(&objs, 1).unwrap_err(), "obj_1 is past the 1 objs in the cache index");
+}
+
+#[test]
+fn test_pack_objs_by_group() {
+    let objs = parse_objs("[obj_257]\nmembers=yes\n").unwrap();
+    let groups = pack_objs(&objs, OBJS_PER_GROUP + 2).unwrap();
+
+    assert_eq!(groups.len(), 2);
+    assert_eq!(groups[&0], pack_group(&vec![vec![0]; OBJS_PER_GROUP as usize]));
+    assert_eq!(groups[&1], pack_group(&[vec![0], vec![16, 0]]));
+}
+
+/// Decodes, exports, parses and re-packs the whole obj archive of the cache at `paths.cache`.
+/// Run it with `cargo test -- --ignored` once a cache is in place.
+#[test]
+#[ignore = "needs a cache at paths.cache"]
+fn test_obj_archive_round_trip() {
+    let cache_path = &config::get().paths.cache;
+    let mut cache = Cache::open(cache_path).unwrap_or_else(|e| panic!("No cache at {}: {}", cache_path, e));
+    let index = read_obj_index(&cache).unwrap();
+    let count = obj_count(&index);
+
+    let mut files = Vec::with_capacity(count as usize);
+    let mut text = String::new();
+    for id in 0..count {
+        let data = cache.read(19, id / OBJS_PER_GROUP, (id % OBJS_PER_GROUP) as u16, None).unwrap();
+
+        let mut obj = ObjType::new(id);
+        let mut opcode_order = Vec::new();
+        obj.decode_type(&mut Packet::from(data.clone()), &mut opcode_order);
+
+        let mut encoded = Packet::from(Vec::new());
+        obj.encode_type(&mut encoded, &opcode_order);
+        assert_eq!(encoded.data, data, "obj_{} doesn't re-encode", id);
+
+        let mut out = Vec::new();
+        write_obj(&mut out, &obj, &opcode_order).unwrap();
+        write!(text, "{}", String::from_utf8(out).unwrap()).unwrap();
+        files.push(data);
+    }
+
+    let objs = parse_objs(&text).unwrap();
+    let encoded = encode_objs(&objs, count).unwrap();
+    for (id, (encoded, data)) in encoded.iter().zip(&files).enumerate() {
+        assert_eq!(encoded, data, "obj_{} doesn't survive export and parse", id);
+    }
+
+    for (group, data) in pack_objs(&objs, count).unwrap() {
+        assert_eq!(data, read_obj_group(&cache, group).unwrap(), "obj group {} doesn't repack", group);
+    }
+}
@@ -6,9 +6,10 @@ use rs2cache::js5_index::Js5Index;
 use crate::io::packet::Packet;
 use crate::util::cache::config::config_type::ConfigType;
 use crate::util::cache::config::obj_type::{write_obj, ObjType};
+use crate::util::cache::obj_packer::OBJS_PER_GROUP;
 
 pub fn unpack_objs() {
-    let cache_path = "../../src/cacheLocal";
+    let cache_path = &config::get().paths.cache;
 
     let mut cache = Cache::open(cache_path).unwrap();
     let archive_id = 19; // Config archive
@@ -16,18 +17,21 @@ pub fn unpack_objs() {
     let js5_index_decompressed = Js5Compression::uncompress(js5_index_compressed, None).unwrap();
     let js5_index = Js5Index::read(js5_index_decompressed).unwrap();
 
-    let mut file = File::create("../../../data/src/scripts/_unpack/all.obj").unwrap();
+    let mut file = File::create(format!("{}/src/scripts/_unpack/all.obj", config::get().paths.data)).unwrap();
     
     let mut obj_count = 0;
     for i in 0..js5_index.groups.len() {
-        for (j, _) in js5_index.groups.get(&(i as u32)).unwrap().files.iter() {
-            let mut obj = ObjType::new(obj_count);
+        let mut files: Vec<_> = js5_index.groups.get(&(i as u32)).unwrap().files.keys().collect();
+        files.sort_unstable();
+        
+        for j in files {
+            let mut obj = ObjType::new(i as u32 * OBJS_PER_GROUP + *j as u32);
             let mut opcode_order: Vec<u8> = Vec::new();
             obj.decode_type(&mut Packet::from(cache.read(19, i as u32, *j as u16, None).unwrap()), &mut opcode_order);
             
             obj_count += 1;
-            write_obj(&mut file, &mut obj, &mut opcode_order);
+            write_obj(&mut file, &obj, &opcode_order).unwrap();
         }
     }
-    debug!("Parsed: {:?} 'obj' entries.", obj_count + 1);
-}
\ No newline at end of file
+    debug!("Parsed: {:?} 'obj' entries.", obj_count);
+}
@@ -1,18 +1,21 @@
-use std::collections::HashMap;
 use crate::io::packet::Packet;
+use crate::util::cache::config::param_type::ParamType;
 
-#[derive(Debug)]
+/// Type char of strings in params and enums, every other type is stored as an int.
+pub const TYPE_STRING: u8 = b's';
+
+#[derive(Debug, Clone, PartialEq)]
 pub enum ParamValue {
     String(String),
     Integer(i32),
 }
 
-pub type Params = HashMap<i32, ParamValue>;
+/// Params in the order they were encoded, re-encoding has to keep it.
+pub type Params = Vec<(i32, ParamValue)>;
 
 pub fn decode_params(packet: &mut Packet) -> Params {
     let count = packet.g1();
-    // TODO - bitCeil handling for capacity
-    let mut params: Params = HashMap::with_capacity(count as usize);
+    let mut params: Params = Vec::with_capacity(count as usize);
     
     for _ in 0..count {
         let is_string = packet.g1() == 1;
@@ -24,8 +27,35 @@ pub fn decode_params(packet: &mut Packet) -> Params {
             ParamValue::Integer(packet.g4())
         };
         
-        params.insert(key, value);
+        params.push((key, value));
     }
     
     params
-}
\ No newline at end of file
+}
+
+pub fn encode_params(packet: &mut Packet, params: &Params) {
+    packet.p1(params.len() as i32);
+    
+    for (key, value) in params {
+        match value {
+            ParamValue::String(value) => {
+                packet.p1(1);
+                packet.p3(*key);
+                packet.pjstr(value, 0);
+            }
+            ParamValue::Integer(value) => {
+                packet.p1(0);
+                packet.p3(*key);
+                packet.p4(*value);
+            }
+        }
+    }
+}
+
+/// The value of `param` in `params`, the param's default when it isn't set.
+pub fn param_value(params: &Params, param: &ParamType) -> ParamValue {
+    params
+        .iter()
+        .find(|(key, _)| *key as u32 == param.id)
+        .map_or_else(|| param.default_value(), |(_, value)| value.clone())
+}
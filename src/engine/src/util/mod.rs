@@ -10,4 +10,6 @@ pub mod symbols;
 pub mod base37;
 pub mod cache;
 pub mod bits;
-pub mod trig;
\ No newline at end of file
+pub mod trig;
+mod pack_file_tests;
+mod runescript_compiler_tests;
\ No newline at end of file
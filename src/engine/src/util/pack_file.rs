@@ -1,13 +1,14 @@
-use std::collections::{HashMap, HashSet};
+use std::collections::{BTreeMap, HashMap, HashSet};
 use std::fs;
 use std::path::{Path, PathBuf};
 use std::sync::Mutex;
 use lazy_static::lazy_static;
-use log::debug;
-use crate::util::parse::{load_dir_ext_full, load_file};
+use log::{debug, error, info};
+use crate::util::parse::{list_files_ext, load_file, load_file_full, read_text_normalize};
 
+/// The pack type a validator keeps in sync with `data/src`.
 #[derive(Clone)]
-pub struct ValidatorArg;
+pub struct ValidatorArg(pub &'static PackType);
 
 type PackFileValidator = fn(&mut PackFile, &[ValidatorArg]) -> ();
 
@@ -39,8 +40,7 @@ impl PackFile {
             let args_clone = self.validator_args.clone();
             validator(self, &args_clone);
         } else {
-            // TODO - environment specification goes here.
-            let path = format!("./data/src/pack/{}.pack", self.type_name);
+            let path = format!("{}/src/pack/{}.pack", config::get().paths.data, self.type_name);
             self.load(&path);
         }
     }
@@ -55,7 +55,7 @@ impl PackFile {
         let lines = load_file(path);
         
         for (i, line) in lines.iter().enumerate() {
-            if line.is_empty() || !line.starts_with(|c: char| c.is_digit(10)) || !line.contains('=') {
+            if line.is_empty() || !line.starts_with(|c: char| c.is_ascii_digit()) || !line.contains('=') {
                 continue;
             }
             
@@ -90,7 +90,7 @@ impl PackFile {
             .join("\n") + "\n";
         
         fs::write(
-            format!("{}/pack/{}.pack", "./data/src", self.type_name),
+            format!("{}/src/pack/{}.pack", config::get().paths.data, self.type_name),
             content,
         ).expect("Unable to write pack file");
     }
@@ -115,88 +115,284 @@ impl PackFile {
     }
 }
 
-fn regenerate_script_pack(pack: &mut PackFile, _args: &[ValidatorArg]) {
-    debug!("regenerate_script_pack");
-    let path = format!("{}/pack/script.pack", "./data/src");
-    pack.load(&path);
-    
-    let names = crawl_config_names(".rs2", true);
-    for name in names {
-        if !pack.names.contains(&name) {
-            let max = pack.max;
-            pack.register(max, name);
-            pack.max += 1;
-        }
-    }
-    
-    pack.refresh_names();
-    pack.save();
+/// A source extension under `data/src` and the pack file that numbers its names.
+pub struct PackType {
+    pub name: &'static str,
+    pub ext: &'static str,
+    /// Scripts are named by their whole `[trigger,subject]` header, configs by the name inside
+    /// the brackets.
+    pub include_brackets: bool,
 }
 
+pub const PACK_TYPES: &[PackType] = &[
+    PackType { name: "category", ext: ".category", include_brackets: false },
+    PackType { name: "enum", ext: ".enum", include_brackets: false },
+    PackType { name: "flo", ext: ".flo", include_brackets: false },
+    PackType { name: "hunt", ext: ".hunt", include_brackets: false },
+    PackType { name: "idk", ext: ".idk", include_brackets: false },
+    PackType { name: "inv", ext: ".inv", include_brackets: false },
+    PackType { name: "loc", ext: ".loc", include_brackets: false },
+    PackType { name: "mesanim", ext: ".mesanim", include_brackets: false },
+    PackType { name: "npc", ext: ".npc", include_brackets: false },
+    PackType { name: "obj", ext: ".obj", include_brackets: false },
+    PackType { name: "param", ext: ".param", include_brackets: false },
+    PackType { name: "seq", ext: ".seq", include_brackets: false },
+    PackType { name: "spotanim", ext: ".spotanim", include_brackets: false },
+    PackType { name: "struct", ext: ".struct", include_brackets: false },
+    PackType { name: "varbit", ext: ".varbit", include_brackets: false },
+    PackType { name: "varp", ext: ".varp", include_brackets: false },
+    PackType { name: "script", ext: ".rs2", include_brackets: true },
+];
 
-lazy_static! {
-    pub static ref SCRIPT_PACK: Mutex<PackFile> = {
-        let empty_validator_args: Vec<ValidatorArg> = Vec::new();
-        Mutex::new(PackFile::new("script".to_string(), Some(regenerate_script_pack), empty_validator_args))
-    };
+pub fn pack_type(name: &str) -> Option<&'static PackType> {
+    PACK_TYPES.iter().find(|pack_type| pack_type.name == name)
 }
 
-pub fn revalidate_pack() {
-    SCRIPT_PACK.lock().unwrap().reload();
+/// What [`validate_pack_type`] found for one pack file.
+#[derive(Debug, Default)]
+pub struct PackReport {
+    pub type_name: String,
+    /// Names seen for the first time and the ids they were given.
+    pub added: Vec<(u32, String)>,
+    /// Ids whose name is gone from `data/src`. They stay in the pack so the id is never handed
+    /// out again, and come back if the name does.
+    pub orphans: Vec<(u32, String)>,
+    /// The pack after the new ids.
+    pub pack: BTreeMap<u32, String>,
 }
 
-pub fn crawl_config_names(ext: &str, include_brackets: bool) -> Vec<String> {
-    let mut names: Vec<String> = Vec::new();
-    let script_path = format!("{}/scripts", "./data/src");
+impl PackReport {
+    /// The pack file as written, one `id=name` line per id in id order.
+    pub fn content(&self) -> String {
+        self.pack.iter().map(|(id, name)| format!("{}={}\n", id, name)).collect()
+    }
+}
 
-    load_dir_ext_full(&script_path, ext, |lines, file| {
-        // Skip engine.rs2 file
-        if file == format!("{}/scripts/engine.rs2", "./data/src") {
-            return;
+/// Parses the `id=name` lines of a pack file, rejecting malformed lines, duplicate ids and
+/// duplicate names.
+pub fn parse_pack(path: &str, text: &str) -> Result<BTreeMap<u32, String>, String> {
+    let mut pack = BTreeMap::new();
+    let mut ids: HashMap<&str, u32> = HashMap::new();
+    let mut errors = Vec::new();
+
+    for (i, line) in text.lines().enumerate() {
+        let line = line.trim_end_matches('\r');
+        if line.is_empty() {
+            continue;
+        }
+
+        let Some((id, name)) = line.split_once('=') else {
+            errors.push(format!("{}:{}: Expected id=name, got {:?}", path, i + 1, line));
+            continue;
+        };
+        let Ok(id) = id.parse::<u32>() else {
+            errors.push(format!("{}:{}: Invalid id {:?}", path, i + 1, id));
+            continue;
+        };
+        if name.is_empty() {
+            errors.push(format!("{}:{}: Id {} has an empty name", path, i + 1, id));
+            continue;
+        }
+
+        if let Some(existing) = pack.insert(id, name.to_string()) {
+            errors.push(format!("{}:{}: Id {} is both {} and {}", path, i + 1, id, existing, name));
+        }
+        if let Some(existing) = ids.insert(name, id) {
+            errors.push(format!("{}:{}: Name {} is both id {} and {}", path, i + 1, name, existing, id));
         }
+    }
+
+    if errors.is_empty() {
+        Ok(pack)
+    } else {
+        Err(errors.join("\n"))
+    }
+}
+
+/// Every name of `pack_type` declared under `src_dir`, in file path order and then line order,
+/// so new ids come out the same on every machine. Fails on a name declared twice.
+pub fn crawl_names(src_dir: &str, pack_type: &PackType) -> Result<Vec<String>, String> {
+    let mut files = list_files_ext(src_dir, pack_type.ext);
+    files.sort();
+
+    let mut names = Vec::new();
+    let mut declared: HashMap<String, String> = HashMap::new();
+    let mut errors = Vec::new();
 
+    for file in &files {
+        // Engine commands are declared in engine.rs2, they are not scripts
+        if pack_type.ext == ".rs2" && file.ends_with("/scripts/engine.rs2") {
+            continue;
+        }
+
+        // Verify folders
+        let file_path = PathBuf::from(file);
+        let file_dir = file_path.parent()
+            .and_then(|p| p.file_name())
+            .map_or(String::new(), |n| n.to_string_lossy().to_string());
+
+        let parent_dir = file_path.parent()
+            .and_then(|p| p.parent())
+            .and_then(|p| p.file_name())
+            .map_or(String::new(), |n| n.to_string_lossy().to_string());
+
+        // _unpack holds dumps of the cache, like all.obj, that redeclare every name
+        if file_dir == "_unpack" {
+            continue;
+        }
+
+        // Skip directory verification for .flo files
+        if pack_type.ext != ".flo" {
+            // Verify scripts are in a scripts directory
+            if pack_type.ext == ".rs2" && file_dir != "scripts" && parent_dir != "scripts" {
+                errors.push(format!("Script file {} must be located inside a \"scripts\" directory.", file));
+                continue;
+            }
+            // Verify configs are in a configs directory
+            else if pack_type.ext != ".rs2" && file_dir != "configs" && parent_dir != "configs" {
+                errors.push(format!("Config file {} must be located inside a \"configs\" directory.", file));
+                continue;
+            }
+        }
+
+        let lines = load_file_full(file).map_err(|e| e.to_string())?;
         for line in lines {
-            if !line.starts_with("[") {
+            if !line.starts_with('[') {
                 continue;
             }
 
-            if let Some(closing_bracket_idx) = line.find("]") {
-                let mut name = line[0..closing_bracket_idx + 1].to_string();
+            let Some(closing_bracket_idx) = line.find(']') else {
+                continue;
+            };
+            let name = if pack_type.include_brackets {
+                line[0..closing_bracket_idx + 1].to_string()
+            } else {
+                line[1..closing_bracket_idx].to_string()
+            };
 
-                if !include_brackets {
-                    name = line[1..closing_bracket_idx].to_string();
-                }
+            if let Some(first) = declared.get(&name) {
+                errors.push(format!("Duplicate {} {} in {} and {}", pack_type.name, name, first, file));
+                continue;
+            }
 
-                // Verify folders
-                let file_path = PathBuf::from(file);
-                let file_dir = file_path.parent()
-                    .and_then(|p| p.file_name())
-                    .map_or(String::new(), |n| n.to_string_lossy().to_string());
-
-                let parent_dir = file_path.parent()
-                    .and_then(|p| p.parent())
-                    .and_then(|p| p.file_name())
-                    .map_or(String::new(), |n| n.to_string_lossy().to_string());
-
-                // Skip directory verification for _unpack or .flo files
-                if file_dir != "_unpack" && ext != ".flo" {
-                    // Verify scripts are in a scripts directory
-                    if ext == ".rs2" && file_dir != "scripts" && parent_dir != "scripts" {
-                        panic!("Script file {} must be located inside a \"scripts\" directory.", file);
-                    }
-                    // Verify configs are in a configs directory
-                    else if ext != ".rs2" && file_dir != "configs" && parent_dir != "configs" {
-                        panic!("Config file {} must be located inside a \"configs\" directory.", file);
-                    }
-                }
+            declared.insert(name.clone(), file.clone());
+            names.push(name);
+        }
+    }
+
+    if errors.is_empty() {
+        Ok(names)
+    } else {
+        Err(errors.join("\n"))
+    }
+}
+
+/// Brings the pack of `pack_type` in `pack_dir` up to date with the names under `src_dir`
+/// without writing it. Known names keep their id, new names get ids past the highest one in the
+/// pack in crawl order, and ids of removed names are reported as orphans but never reused.
+pub fn validate_pack_type(pack_dir: &str, src_dir: &str, pack_type: &PackType) -> Result<PackReport, String> {
+    let path = format!("{}/{}.pack", pack_dir, pack_type.name);
+    let mut pack = parse_pack(&path, &read_text_normalize(&path))?;
+    let names = crawl_names(src_dir, pack_type)?;
+
+    let known: HashSet<&String> = pack.values().collect();
+    let new_names: Vec<String> = names.iter().filter(|name| !known.contains(name)).cloned().collect();
+    let declared: HashSet<&String> = names.iter().collect();
+    let orphans: Vec<(u32, String)> = pack
+        .iter()
+        .filter(|(_, name)| !declared.contains(name))
+        .map(|(id, name)| (*id, name.clone()))
+        .collect();
+
+    let next = pack.last_key_value().map_or(0, |(&max, _)| max + 1);
+    let added: Vec<(u32, String)> = (next..).zip(new_names).collect();
+    pack.extend(added.iter().cloned());
+
+    Ok(PackReport {
+        type_name: pack_type.name.to_string(),
+        added,
+        orphans,
+        pack,
+    })
+}
+
+/// Validates every pack type of `data_dir` and, when `write` is set, rewrites the pack files that
+/// changed. Nothing is written unless every pack type validates.
+pub fn pack_all(data_dir: &str, write: bool) -> Result<Vec<PackReport>, String> {
+    let pack_dir = format!("{}/src/pack", data_dir);
+    let src_dir = format!("{}/src", data_dir);
+
+    let mut reports = Vec::new();
+    let mut errors = Vec::new();
+    for pack_type in PACK_TYPES {
+        match validate_pack_type(&pack_dir, &src_dir, pack_type) {
+            Ok(report) => reports.push(report),
+            Err(e) => errors.push(e),
+        }
+    }
+
+    if !errors.is_empty() {
+        return Err(errors.join("\n"));
+    }
+
+    if write {
+        for report in &reports {
+            write_pack(&pack_dir, report)?;
+        }
+    }
 
-                // Add name if it's not already in the list
-                if !names.contains(&name) {
-                    names.push(name);
+    Ok(reports)
+}
+
+/// Writes the pack of `report` unless the file already holds exactly that, returns whether it
+/// did. Types without any names don't get an empty pack file.
+pub fn write_pack(pack_dir: &str, report: &PackReport) -> Result<bool, String> {
+    let path = format!("{}/{}.pack", pack_dir, report.type_name);
+    let content = report.content();
+
+    let existing = fs::read_to_string(&path).ok();
+    if existing.as_deref() == Some(content.as_str()) || (existing.is_none() && report.pack.is_empty()) {
+        return Ok(false);
+    }
+
+    fs::create_dir_all(pack_dir).map_err(|e| format!("Failed to create {}: {}", pack_dir, e))?;
+    fs::write(&path, content).map_err(|e| format!("Failed to write {}: {}", path, e))?;
+    Ok(true)
+}
+
+/// Validator that syncs each pack type in `args` with `data/src` before the pack is loaded. A
+/// pack that fails to validate is loaded as it is.
+fn validate_pack(pack: &mut PackFile, args: &[ValidatorArg]) {
+    let data = &config::get().paths.data;
+    let pack_dir = format!("{}/src/pack", data);
+    let src_dir = format!("{}/src", data);
+
+    for ValidatorArg(pack_type) in args {
+        debug!("Validating {}.pack", pack_type.name);
+
+        match validate_pack_type(&pack_dir, &src_dir, pack_type) {
+            Ok(report) => {
+                for (id, name) in &report.added {
+                    info!("New {} {} is id {}", pack_type.name, name, id);
+                }
+                if let Err(e) = write_pack(&pack_dir, &report) {
+                    error!("{}", e);
                 }
             }
+            Err(e) => error!("{}.pack was not updated:\n{}", pack_type.name, e),
         }
-    });
+    }
 
-    names
-}
\ No newline at end of file
+    pack.load(&format!("{}/{}.pack", pack_dir, pack.type_name));
+}
+
+lazy_static! {
+    pub static ref SCRIPT_PACK: Mutex<PackFile> = {
+        let args = vec![ValidatorArg(pack_type("script").expect("script pack type"))];
+        Mutex::new(PackFile::new("script".to_string(), Some(validate_pack), args))
+    };
+}
+
+pub fn revalidate_pack() {
+    SCRIPT_PACK.lock().unwrap().reload();
+}
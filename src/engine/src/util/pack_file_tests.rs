@@ -0,0 +1,108 @@
+use std::fs;
+use crate::util::pack_file::{crawl_names, pack_type, parse_pack, validate_pack_type, write_pack};
+
+/// A fresh `data/src` under the temp dir with `files` in it, returns its path.
+fn src_dir(test: &str, files: &[(&str, &str)]) -> String {
+    let dir = std::env::temp_dir().join(format!("pack_file_tests_{}_{}", test, std::process::id()));
+    let _ = fs::remove_dir_all(&dir);
+
+    for (path, content) in files {
+        let path = dir.join(path);
+        fs::create_dir_all(path.parent().unwrap()).unwrap();
+        fs::write(path, content).unwrap();
+    }
+
+    dir.to_string_lossy().to_string()
+}
+
+#[test]
+fn test_parse_pack() {
+    let pack = parse_pack("obj.pack", "1=tool_kit\r\n0=dwarf_remains\n\n").unwrap();
+    assert_eq!(pack.into_iter().collect::<Vec<_>>(), vec![(0, "dwarf_remains".to_string()), (1, "tool_kit".to_string())]);
+
+    let err = parse_pack("obj.pack", "0=a\n0=b\n1=a\n2=\nthree=c\nd\n").unwrap_err();
+    assert_eq!(err.lines().collect::<Vec<_>>(), vec![
+        "obj.pack:2: Id 0 is both a and b",
+        "obj.pack:3: Name a is both id 0 and 1",
+        "obj.pack:4: Id 2 has an empty name",
+        "obj.pack:5: Invalid id \"three\"",
+        "obj.pack:6: Expected id=name, got \"d\"",
+    ]);
+}
+
+#[test]
+fn test_crawl_names() {
+    let src = src_dir("crawl", &[
+        ("scripts/engine.rs2", "[command,mes](string $message)\n"),
+        ("scripts/b/login.rs2", "[login,_]\nmes(\"Welcome\");\n// [proc,commented]\n"),
+        ("scripts/a/scripts/logout.rs2", "[logout,_]()(boolean)\nreturn(true);\n[proc,tidy]\n"),
+        ("scripts/b/configs/b.obj", "[bronze_axe]\nname=Bronze axe\n"),
+        ("scripts/a/configs/a.obj", "[abyssal_whip]\n[ashes]\n"),
+        ("scripts/_unpack/all.obj", "[abyssal_whip]\n[ashes]\n[bronze_axe]\n[unpacked_only]\n"),
+    ]);
+
+    let scripts = crawl_names(&src, pack_type("script").unwrap()).unwrap();
+    assert_eq!(scripts, vec!["[logout,_]", "[proc,tidy]", "[login,_]"]);
+
+    let objs = crawl_names(&src, pack_type("obj").unwrap()).unwrap();
+    assert_eq!(objs, vec!["abyssal_whip", "ashes", "bronze_axe"]);
+    fs::remove_dir_all(&src).unwrap();
+}
+
+#[test]
+fn test_crawl_errors() {
+    let src = src_dir("crawl_errors", &[
+        ("scripts/a/configs/a.obj", "[ashes]\n"),
+        ("scripts/b/configs/b.obj", "[ashes]\n"),
+        ("scripts/misplaced.obj", "[coins]\n"),
+    ]);
+
+    let err = crawl_names(&src, pack_type("obj").unwrap()).unwrap_err();
+    assert!(err.contains("Duplicate obj ashes in"), "{}", err);
+    assert!(err.contains("misplaced.obj must be located inside a \"configs\" directory"), "{}", err);
+    fs::remove_dir_all(&src).unwrap();
+}
+
+#[test]
+fn test_stable_ids() {
+    let src = src_dir("stable_ids", &[
+        ("pack/obj.pack", "0=dwarf_remains\n1=tool_kit\n3=ammo_mould\n"),
+        ("scripts/configs/a.obj", "[tool_kit]\n[bronze_axe]\n[dwarf_remains]\n[ashes]\n"),
+    ]);
+    let pack_dir = format!("{}/pack", src);
+    let obj = pack_type("obj").unwrap();
+
+    let report = validate_pack_type(&pack_dir, &src, obj).unwrap();
+    // ammo_mould is gone, but 3 stays taken and the gap at 2 is never filled.
+    assert_eq!(report.orphans, vec![(3, "ammo_mould".to_string())]);
+    assert_eq!(report.added, vec![(4, "bronze_axe".to_string()), (5, "ashes".to_string())]);
+    assert_eq!(report.content(), "0=dwarf_remains\n1=tool_kit\n3=ammo_mould\n4=bronze_axe\n5=ashes\n");
+
+    assert!(write_pack(&pack_dir, &report).unwrap());
+    assert!(!write_pack(&pack_dir, &report).unwrap());
+
+    // A second run finds nothing new and leaves the file alone.
+    let report = validate_pack_type(&pack_dir, &src, obj).unwrap();
+    assert!(report.added.is_empty());
+    assert!(!write_pack(&pack_dir, &report).unwrap());
+
+    // Types without names don't get a pack file.
+    let report = validate_pack_type(&pack_dir, &src, pack_type("npc").unwrap()).unwrap();
+    assert!(!write_pack(&pack_dir, &report).unwrap());
+    assert!(!std::path::Path::new(&format!("{}/npc.pack", pack_dir)).exists());
+    fs::remove_dir_all(&src).unwrap();
+}
+
+#[test]
+fn test_invalid_pack_is_not_rewritten() {
+    let src = src_dir("invalid_pack", &[
+        ("pack/obj.pack", "0=ashes\n1=ashes\n"),
+        ("scripts/configs/a.obj", "[ashes]\n[coins]\n"),
+    ]);
+    let pack_dir = format!("{}/pack", src);
+
+    let err = validate_pack_type(&pack_dir, &src, pack_type("obj").unwrap()).unwrap_err();
+    assert!(err.contains("Name ashes is both id 0 and 1"), "{}", err);
+    assert_eq!(fs::read_to_string(format!("{}/obj.pack", pack_dir)).unwrap(), "0=ashes\n1=ashes\n");
+    fs::remove_dir_all(&src).unwrap();
+}
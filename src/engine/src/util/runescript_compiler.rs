@@ -3,52 +3,143 @@ use std::fs;
 use std::fs::File;
 use std::io::Read;
 use std::path::Path;
-use log::{debug, info};
+use std::process::Command;
+use config::CompilerConfig;
+use log::{debug, info, warn};
 use reqwest::blocking;
 use sha2::Digest;
 use crate::script::script_provider::ScriptProvider;
 
-pub fn update_compiler() -> Result<bool, Box<dyn Error>> {
-    info!("Checking for compiler update.");
-    let mut needs_update = false;
-
-    if !Path::new("./RuneScriptCompiler.jar").exists() {
-       needs_update = true;
+/// Makes sure `compiler.jar` is the compiler to run. Offline the jar is only checked against the
+/// pinned `compiler.sha256`, otherwise it is updated from the GitHub release when it's missing or
+/// differs from the release.
+pub fn prepare_compiler(compiler: &CompilerConfig) -> Result<(), Box<dyn Error>> {
+    if compiler.offline {
+        verify_compiler(&compiler.jar, &compiler.sha256)?;
+        info!("Using pinned compiler {}.", compiler.jar);
+        Ok(())
     } else {
-        let mut file = File::open("./RuneScriptCompiler.jar")?;
-        let mut buffer = Vec::new();
-        file.read_to_end(&mut buffer)?;
+        update_compiler(&compiler.jar)
+    }
+}
 
-        let mut hasher = sha2::Sha256::new();
-        hasher.update(&buffer);
-        let shasum = format!("{:x}", hasher.finalize());
+/// Checks the jar at `jar` against the hex sha256 `expected`.
+pub fn verify_compiler(jar: &str, expected: &str) -> Result<(), Box<dyn Error>> {
+    if expected.is_empty() {
+        return Err("compiler.sha256 must pin the jar in offline mode".into());
+    }
+    if !Path::new(jar).exists() {
+        return Err(format!("Compiler {} not found, offline mode never downloads it", jar).into());
+    }
+
+    let shasum = sha256_file(jar)?;
+    if !shasum.eq_ignore_ascii_case(expected.trim()) {
+        return Err(format!("Compiler {} has sha256 {}, expected {}", jar, shasum, expected.trim()).into());
+    }
 
-        let sha_url = format!(
-            "https://github.com/LostCityRS/RuneScriptKt/releases/download/{}/RuneScriptCompiler.jar.sha256",
-            ScriptProvider::COMPILER_VERSION
-        );
+    Ok(())
+}
 
-        let sha_response = blocking::get(&sha_url)?;
-        let expected = sha_response.text()?[..64].to_string();
+/// Updates `jar` to the release pinned by [`ScriptProvider::COMPILER_VERSION`]. When GitHub can't be
+/// reached or sends something unexpected, the jar already on disk is kept and only a missing jar fails.
+pub fn update_compiler(jar: &str) -> Result<(), Box<dyn Error>> {
+    let release = format!("https://github.com/LostCityRS/RuneScriptKt/releases/download/{}", ScriptProvider::COMPILER_VERSION);
+    update_compiler_from(jar, &release)
+}
 
-        if shasum != expected {
-            needs_update = true;
+pub(crate) fn update_compiler_from(jar: &str, release: &str) -> Result<(), Box<dyn Error>> {
+    info!("Checking for compiler update.");
+
+    let result = fetch_sha256(release).and_then(|expected| {
+        if Path::new(jar).exists() && sha256_file(jar)? == expected {
+            return Ok(());
         }
-    }
 
-    if needs_update {
         info!("Updating compiler.");
-        let jar_url = format!(
-            "https://github.com/LostCityRS/RuneScriptKt/releases/download/{}/RuneScriptCompiler.jar",
-            ScriptProvider::COMPILER_VERSION
-        );
+        let jar_bytes = download_compiler(release, &expected)?;
+        fs::write(jar, jar_bytes)?;
+        Ok(())
+    });
+
+    match result {
+        Ok(()) => info!("Compiler is up to date."),
+        Err(e) if Path::new(jar).exists() => warn!("Failed to update the compiler, using {}: {}", jar, e),
+        Err(e) => return Err(format!("Compiler {} not found and could not be downloaded: {}", jar, e).into()),
+    }
+    Ok(())
+}
+
+fn fetch_sha256(release: &str) -> Result<String, Box<dyn Error>> {
+    let response = blocking::get(format!("{}/RuneScriptCompiler.jar.sha256", release))?.error_for_status()?;
+    parse_sha256(&response.text()?)
+}
 
-        let jar_response = blocking::get(&jar_url)?;
-        let jar_bytes = jar_response.bytes()?;
+/// Downloads the release jar and refuses it unless it hashes to `expected`.
+fn download_compiler(release: &str, expected: &str) -> Result<Vec<u8>, Box<dyn Error>> {
+    let response = blocking::get(format!("{}/RuneScriptCompiler.jar", release))?.error_for_status()?;
+    let jar_bytes = response.bytes()?.to_vec();
 
-        fs::write("RuneScriptCompiler.jar", jar_bytes)?;
+    let shasum = sha256_bytes(&jar_bytes);
+    if shasum != expected {
+        return Err(format!("Downloaded compiler has sha256 {}, expected {}", shasum, expected).into());
     }
+    Ok(jar_bytes)
+}
+
+/// The hex digest at the start of a `.sha256` file, which may be followed by a file name.
+pub(crate) fn parse_sha256(text: &str) -> Result<String, Box<dyn Error>> {
+    let digest = text.split_whitespace().next().unwrap_or("");
+    if digest.len() != 64 || !digest.chars().all(|c| c.is_ascii_hexdigit()) {
+        return Err(format!("Invalid sha256 {:?}", text.trim()).into());
+    }
+    Ok(digest.to_ascii_lowercase())
+}
+
+/// Runs the compiler on the sources named in `compiler.neptune`, which writes `script.dat` and
+/// `script.idx` to its binary output. Fails with the compiler output when it reports errors.
+pub fn compile_scripts(compiler: &CompilerConfig) -> Result<(), Box<dyn Error>> {
+    let neptune = Path::new(&compiler.neptune);
+    if !neptune.exists() {
+        return Err(format!("Compiler config {} not found", compiler.neptune).into());
+    }
+
+    let jar = fs::canonicalize(&compiler.jar)
+        .map_err(|e| format!("Compiler {} not found: {}", compiler.jar, e))?;
+    let dir = neptune.parent().filter(|dir| !dir.as_os_str().is_empty()).unwrap_or(Path::new("."));
+
+    info!("Compiling scripts with {}.", compiler.neptune);
+    let output = Command::new(&compiler.java)
+        .arg("-jar")
+        .arg(&jar)
+        .current_dir(dir)
+        .output()
+        .map_err(|e| format!("Failed to run {}: {}", compiler.java, e))?;
+
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    let stderr = String::from_utf8_lossy(&output.stderr);
+
+    if !output.status.success() {
+        let lines: Vec<&str> = stdout.lines().chain(stderr.lines()).filter(|line| !line.trim().is_empty()).collect();
+        return Err(format!("Compiler exited with {}:\n{}", output.status, lines.join("\n")).into());
+    }
+
+    for line in stdout.lines().chain(stderr.lines()) {
+        debug!("{}", line);
+    }
+    info!("Scripts compiled.");
+    Ok(())
+}
+
+fn sha256_file(path: &str) -> Result<String, Box<dyn Error>> {
+    let mut file = File::open(path)?;
+    let mut buffer = Vec::new();
+    file.read_to_end(&mut buffer)?;
+
+    Ok(sha256_bytes(&buffer))
+}
 
-    info!("Compiler is up to date.");
-    Ok(true)
+fn sha256_bytes(bytes: &[u8]) -> String {
+    let mut hasher = sha2::Sha256::new();
+    hasher.update(bytes);
+    format!("{:x}", hasher.finalize())
 }
\ No newline at end of file
@@ -0,0 +1,199 @@
+use std::fs;
+use std::io::{Read, Write};
+use std::net::TcpListener;
+use config::CompilerConfig;
+use crate::util::runescript_compiler::{compile_scripts, parse_sha256, prepare_compiler, update_compiler_from, verify_compiler};
+
+const JAR_SHA256: &str = "d2c6cf77ae5f94f752b1bb51027081935c2487ad68f670e6d230f41c93d5c547";
+
+/// A fresh directory with a fake jar and neptune.toml, returns the config pointing at it.
+fn compiler(test: &str) -> CompilerConfig {
+    let dir = std::env::temp_dir().join(format!("runescript_compiler_tests_{}_{}", test, std::process::id()));
+    let _ = fs::remove_dir_all(&dir);
+    fs::create_dir_all(&dir).unwrap();
+    fs::write(dir.join("RuneScriptCompiler.jar"), b"not really a jar").unwrap();
+    fs::write(dir.join("neptune.toml"), "sources = [\"data/src/scripts/\"]\n").unwrap();
+
+    CompilerConfig {
+        offline: true,
+        jar: dir.join("RuneScriptCompiler.jar").to_string_lossy().to_string(),
+        sha256: JAR_SHA256.to_string(),
+        neptune: dir.join("neptune.toml").to_string_lossy().to_string(),
+        ..CompilerConfig::default()
+    }
+}
+
+fn cleanup(compiler: &CompilerConfig) {
+    fs::remove_dir_all(std::path::Path::new(&compiler.neptune).parent().unwrap()).unwrap();
+}
+
+/// Serves `files` as a release over plain http, anything else is a 404. Returns the release url.
+fn fake_release(files: Vec<(&'static str, Vec<u8>)>) -> String {
+    let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+    let release = format!("http://{}/release", listener.local_addr().unwrap());
+
+    std::thread::spawn(move || {
+        for mut stream in listener.incoming().flatten() {
+            let mut request = Vec::new();
+            let mut buffer = [0; 1024];
+            while !request.windows(4).any(|window| window == b"\r\n\r\n") {
+                match stream.read(&mut buffer) {
+                    Ok(0) | Err(_) => break,
+                    Ok(n) => request.extend_from_slice(&buffer[..n]),
+                }
+            }
+
+            let request = String::from_utf8_lossy(&request);
+            let path = request.split_whitespace().nth(1).unwrap_or("");
+            let (status, body) = match files.iter().find(|(name, _)| path == format!("/release/{}", name)) {
+                Some((_, body)) => ("200 OK", body.clone()),
+                None => ("404 Not Found", Vec::new()),
+            };
+            let header = format!("HTTP/1.1 {}\r\nContent-Length: {}\r\nConnection: close\r\n\r\n", status, body.len());
+            let _ = stream.write_all(header.as_bytes()).and_then(|()| stream.write_all(&body));
+        }
+    });
+    release
+}
+
+/// A stand-in for java that prints `output` and exits with `status`.
+#[cfg(unix)]
+fn fake_java(compiler: &mut CompilerConfig, output: &str, status: i32) {
+    use std::os::unix::fs::PermissionsExt;
+
+    let path = std::path::Path::new(&compiler.neptune).with_file_name("java");
+    fs::write(&path, format!("#!/bin/sh\necho \"$@\" > args\necho '{}' >&2\nexit {}\n", output, status)).unwrap();
+    fs::set_permissions(&path, fs::Permissions::from_mode(0o755)).unwrap();
+    compiler.java = path.to_string_lossy().to_string();
+}
+
+#[test]
+fn test_offline_pinned_jar() {
+    let mut compiler = compiler("pinned");
+    assert!(prepare_compiler(&compiler).is_ok());
+    assert!(verify_compiler(&compiler.jar, &JAR_SHA256.to_uppercase()).is_ok());
+
+    compiler.sha256 = "0".repeat(64);
+    let err = prepare_compiler(&compiler).unwrap_err().to_string();
+    assert!(err.contains(&format!("has sha256 {}", JAR_SHA256)), "{}", err);
+
+    compiler.sha256 = String::new();
+    let err = prepare_compiler(&compiler).unwrap_err().to_string();
+    assert_eq!(err, "compiler.sha256 must pin the jar in offline mode");
+
+    cleanup(&compiler);
+}
+
+#[test]
+fn test_offline_missing_jar() {
+    let mut compiler = compiler("missing");
+    compiler.jar = format!("{}.missing", compiler.jar);
+
+    let err = prepare_compiler(&compiler).unwrap_err().to_string();
+    assert!(err.ends_with("offline mode never downloads it"), "{}", err);
+
+    cleanup(&compiler);
+}
+
+#[cfg(unix)]
+#[test]
+fn test_compile_success() {
+    let mut compiler = compiler("success");
+    fake_java(&mut compiler, "Compiled 2 scripts", 0);
+
+    assert!(compile_scripts(&compiler).is_ok());
+
+    // The compiler runs from the directory of neptune.toml with the jar as an absolute path.
+    let args = fs::read_to_string(std::path::Path::new(&compiler.neptune).with_file_name("args")).unwrap();
+    assert!(args.starts_with("-jar /"), "{}", args);
+    assert!(args.trim_end().ends_with("RuneScriptCompiler.jar"), "{}", args);
+
+    cleanup(&compiler);
+}
+
+#[cfg(unix)]
+#[test]
+fn test_compile_errors_are_surfaced() {
+    let mut compiler = compiler("errors");
+    fake_java(&mut compiler, "login.rs2:2:1: error: Unknown command mess", 1);
+
+    let err = compile_scripts(&compiler).unwrap_err().to_string();
+    assert!(err.starts_with("Compiler exited with exit status: 1"), "{}", err);
+    assert!(err.ends_with("login.rs2:2:1: error: Unknown command mess"), "{}", err);
+
+    cleanup(&compiler);
+}
+
+#[test]
+fn test_compile_without_java() {
+    let mut compiler = compiler("no_java");
+    compiler.java = format!("{}/no-such-java", std::env::temp_dir().to_string_lossy());
+
+    let err = compile_scripts(&compiler).unwrap_err().to_string();
+    assert!(err.starts_with("Failed to run"), "{}", err);
+
+    cleanup(&compiler);
+}
+
+#[test]
+fn test_parse_sha256() {
+    assert_eq!(parse_sha256(&format!("{}  RuneScriptCompiler.jar\n", JAR_SHA256.to_uppercase())).unwrap(), JAR_SHA256);
+    assert_eq!(parse_sha256(JAR_SHA256).unwrap(), JAR_SHA256);
+
+    for invalid in ["", "\n", "d2c6cf77", &JAR_SHA256.replace('d', "z"), "<html>Not Found</html>"] {
+        assert!(parse_sha256(invalid).is_err(), "{:?}", invalid);
+    }
+}
+
+#[test]
+fn test_update_downloads_release() {
+    let compiler = compiler("update");
+    fs::remove_file(&compiler.jar).unwrap();
+    let release = fake_release(vec![
+        ("RuneScriptCompiler.jar.sha256", format!("{}  RuneScriptCompiler.jar\n", JAR_SHA256).into_bytes()),
+        ("RuneScriptCompiler.jar", b"not really a jar".to_vec()),
+    ]);
+
+    assert!(update_compiler_from(&compiler.jar, &release).is_ok());
+    assert!(verify_compiler(&compiler.jar, JAR_SHA256).is_ok());
+
+    cleanup(&compiler);
+}
+
+#[test]
+fn test_update_rejects_tampered_jar() {
+    let compiler = compiler("tampered");
+    fs::write(&compiler.jar, b"the old jar").unwrap();
+    let release = fake_release(vec![
+        ("RuneScriptCompiler.jar.sha256", JAR_SHA256.as_bytes().to_vec()),
+        ("RuneScriptCompiler.jar", b"something else".to_vec()),
+    ]);
+
+    // The jar on disk is kept rather than replaced with one that doesn't match the release.
+    assert!(update_compiler_from(&compiler.jar, &release).is_ok());
+    assert_eq!(fs::read(&compiler.jar).unwrap(), b"the old jar");
+
+    fs::remove_file(&compiler.jar).unwrap();
+    let err = update_compiler_from(&compiler.jar, &release).unwrap_err().to_string();
+    assert!(err.contains("Downloaded compiler has sha256"), "{}", err);
+    assert!(!std::path::Path::new(&compiler.jar).exists());
+
+    cleanup(&compiler);
+}
+
+#[test]
+fn test_update_falls_back_to_existing_jar() {
+    let compiler = compiler("fallback");
+    let missing = fake_release(vec![]);
+
+    // A 404, an unreachable host, then a missing jar with nothing to fall back to.
+    assert!(update_compiler_from(&compiler.jar, &missing).is_ok());
+    assert!(update_compiler_from(&compiler.jar, "http://127.0.0.1:1/release").is_ok());
+    assert!(verify_compiler(&compiler.jar, JAR_SHA256).is_ok());
+
+    fs::remove_file(&compiler.jar).unwrap();
+    let err = update_compiler_from(&compiler.jar, &missing).unwrap_err().to_string();
+    assert!(err.contains("404"), "{}", err);
+
+    cleanup(&compiler);
+}
@@ -5,9 +5,10 @@ use crate::script::script_opcode_pointers::initialize_script_opcode_pointers;
 use crate::util::namemap::load_pack;
 
 pub fn generate_server_symbols() {
-    fs::create_dir_all("./data/symbols").expect("Failed to create symbols directory");
+    let data_path = &config::get().paths.data;
+    fs::create_dir_all(format!("{}/symbols", data_path)).expect("Failed to create symbols directory");
     
-    let scripts = load_pack("./data/src/pack/script.pack");
+    let scripts = load_pack(&format!("{}/src/pack/script.pack", data_path));
     let mut script_symbols = String::new();
     for (i, script) in scripts.iter().enumerate() {
         if !script.is_empty() {
@@ -15,24 +16,53 @@ pub fn generate_server_symbols() {
         }
     }
     
-    fs::write("./data/symbols/runescript.sym", script_symbols).expect("Failed to write to RuneScript symbols file");
+    fs::write(format!("{}/symbols/runescript.sym", data_path), script_symbols).expect("Failed to write to RuneScript symbols file");
     
+    fs::write(format!("{}/symbols/commands.sym", data_path), command_symbols()).expect("Failed to write to command symbols file");
+}
+
+/// One line per [`ScriptOpcode`] with the pointers the compiler checks when `check_pointers` is on:
+/// `opcode<tab>command<tab>require<tab>corrupt<tab>set<tab>conditional<tab>secondary<tab>secondaryRequire`.
+///
+/// Pointer lists are comma separated, `none` when empty. `conditional` is whether `set` only
+/// holds when the command returns true, `secondary` whether the command has a `.command` form
+/// for the secondary pointers, which requires `secondaryRequire`.
+pub fn command_symbols() -> String {
+    let pointers = initialize_script_opcode_pointers();
     let mut command_symbols = String::new();
-    
+
     for opcode in ScriptOpcode::iter() {
         let opcode_value = opcode as i32;
         let command_name = format!("{:?}", opcode).to_lowercase();
-
-        // format:
-        // opcode<tab>command<tab>require<tab>corrupt<tab>set<tab>conditional<tab>secondary<tab>secondaryRequire
-        
         let mut line = format!("{}\t{}", opcode_value, command_name);
-        
-        // TODO: pointers
-        
+
+        let Some(pointers) = pointers.get(&opcode) else {
+            panic!("Missing pointers for {:?}", opcode);
+        };
+        let secondary = pointers.require2.is_some() || pointers.set2.is_some() || pointers.corrupt2.is_some();
+
+        for column in [
+            pointer_list(&pointers.require),
+            pointer_list(&pointers.corrupt),
+            pointer_list(&pointers.set),
+            pointers.conditional.unwrap_or(false).to_string(),
+            secondary.to_string(),
+            pointer_list(&pointers.require2),
+        ] {
+            line.push('\t');
+            line.push_str(&column);
+        }
+
         line.push('\n');
         command_symbols.push_str(&line);
     }
-    // TODO - Disabled for now, waiting on fix to compiler to handle no-pointer setups.
-    //fs::write("./data/symbols/commands.sym", command_symbols).expect("Failed to write to command symbols file");
+
+    command_symbols
+}
+
+fn pointer_list(pointers: &Option<Vec<String>>) -> String {
+    match pointers {
+        Some(pointers) if !pointers.is_empty() => pointers.join(","),
+        _ => "none".to_string(),
+    }
 }
\ No newline at end of file
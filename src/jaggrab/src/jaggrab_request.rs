@@ -0,0 +1,117 @@
+use std::fmt;
+
+/// Longest request or header line read before the client is dropped.
+pub const MAX_LINE_LENGTH: usize = 1024;
+/// Header lines an HTTP client may send before it is dropped.
+pub const MAX_HEADERS: usize = 64;
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Protocol {
+    /// `JAGGRAB /path`, answered with the bare file.
+    Jaggrab,
+    /// `GET /path HTTP/1.x`, answered with a status line and headers.
+    Http,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct FileRequest {
+    pub protocol: Protocol,
+    pub path: String,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum RequestError {
+    Malformed(String),
+    UnsupportedMethod(String),
+}
+
+impl fmt::Display for RequestError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            RequestError::Malformed(line) => write!(f, "Malformed request line {:?}", line),
+            RequestError::UnsupportedMethod(method) => write!(f, "Unsupported method {}", method),
+        }
+    }
+}
+
+impl FileRequest {
+    /// Parses the first line of a request, the query string is dropped from the path.
+    pub fn parse(line: &str) -> Result<FileRequest, RequestError> {
+        let mut parts = line.split_whitespace();
+        let (Some(method), Some(target)) = (parts.next(), parts.next()) else {
+            return Err(RequestError::Malformed(line.to_string()));
+        };
+
+        let protocol = match method {
+            "JAGGRAB" => Protocol::Jaggrab,
+            "GET" => Protocol::Http,
+            _ => return Err(RequestError::UnsupportedMethod(method.to_string())),
+        };
+
+        if !target.starts_with('/') {
+            return Err(RequestError::Malformed(line.to_string()));
+        }
+
+        let path = target.split(['?', '#']).next().unwrap_or(target);
+        Ok(FileRequest { protocol, path: path.to_string() })
+    }
+}
+
+/// What a request path points at.
+#[derive(Debug, Clone, PartialEq)]
+pub enum Resource {
+    /// `/crc`, with anything after it ignored so clients can append a cache buster.
+    Crc,
+    /// `/cache/<archive>/<group>`, the group's container straight from the cache.
+    Group { archive: u8, group: u16 },
+    /// Any other single file name, served from the loader directory.
+    Loader(String),
+}
+
+impl Resource {
+    pub fn from_path(path: &str) -> Option<Resource> {
+        let path = path.strip_prefix('/')?;
+
+        if let Some(rest) = path.strip_prefix("crc") {
+            if rest.chars().all(|c| c.is_ascii_digit() || c == '-') {
+                return Some(Resource::Crc);
+            }
+        }
+
+        if let Some(rest) = path.strip_prefix("cache/") {
+            let (archive, group) = rest.split_once('/')?;
+            return Some(Resource::Group {
+                archive: archive.parse().ok()?,
+                group: group.parse().ok()?,
+            });
+        }
+
+        // Only plain file names, nothing that can leave the loader directory.
+        let valid = !path.is_empty()
+            && !path.starts_with('.')
+            && path.chars().all(|c| c.is_ascii_alphanumeric() || matches!(c, '.' | '_' | '-'));
+        if valid {
+            Some(Resource::Loader(path.to_string()))
+        } else {
+            None
+        }
+    }
+}
+
+/// Content type for a loader file, everything else is served as raw bytes.
+pub fn content_type(name: &str) -> &'static str {
+    match name.rsplit_once('.').map(|(_, extension)| extension) {
+        Some("jar") => "application/java-archive",
+        Some("html") | Some("htm") => "text/html",
+        Some("txt") => "text/plain",
+        _ => "application/octet-stream",
+    }
+}
+
+/// Status line and headers for an HTTP response, the body follows as is.
+pub fn http_header(status: u16, reason: &str, content_type: &str, length: usize) -> Vec<u8> {
+    format!(
+        "HTTP/1.1 {} {}\r\nContent-Type: {}\r\nContent-Length: {}\r\nConnection: close\r\n\r\n",
+        status, reason, content_type, length
+    ).into_bytes()
+}
@@ -0,0 +1,43 @@
+use crate::jaggrab_request::{content_type, http_header, FileRequest, Protocol, RequestError, Resource};
+
+#[test]
+fn test_parse_jaggrab_and_http() {
+    assert_eq!(FileRequest::parse("JAGGRAB /crc1234").unwrap(), FileRequest { protocol: Protocol::Jaggrab, path: "/crc1234".to_string() });
+    assert_eq!(FileRequest::parse("GET /loader.jar?v=2 HTTP/1.1").unwrap(), FileRequest { protocol: Protocol::Http, path: "/loader.jar".to_string() });
+}
+
+#[test]
+fn test_parse_rejects_bad_requests() {
+    assert_eq!(FileRequest::parse("POST /crc HTTP/1.1"), Err(RequestError::UnsupportedMethod("POST".to_string())));
+    assert!(matches!(FileRequest::parse("GET"), Err(RequestError::Malformed(_))));
+    assert!(matches!(FileRequest::parse("GET crc HTTP/1.1"), Err(RequestError::Malformed(_))));
+}
+
+#[test]
+fn test_resources() {
+    assert_eq!(Resource::from_path("/crc"), Some(Resource::Crc));
+    assert_eq!(Resource::from_path("/crc-1852361443"), Some(Resource::Crc));
+    assert_eq!(Resource::from_path("/cache/255/19"), Some(Resource::Group { archive: 255, group: 19 }));
+    assert_eq!(Resource::from_path("/cache/256/0"), None);
+    assert_eq!(Resource::from_path("/cache/2"), None);
+    assert_eq!(Resource::from_path("/loader_gl.jar"), Some(Resource::Loader("loader_gl.jar".to_string())));
+}
+
+#[test]
+fn test_loader_names_stay_in_directory() {
+    assert_eq!(Resource::from_path("/"), None);
+    assert_eq!(Resource::from_path("/../server.toml"), None);
+    assert_eq!(Resource::from_path("/.hidden"), None);
+    assert_eq!(Resource::from_path("/lib/jaggl.dll"), None);
+    assert_eq!(Resource::from_path("/%2e%2e"), None);
+}
+
+#[test]
+fn test_http_header() {
+    assert_eq!(content_type("runescape.jar"), "application/java-archive");
+    assert_eq!(content_type("crc"), "application/octet-stream");
+    assert_eq!(
+        String::from_utf8(http_header(200, "OK", "text/plain", 5)).unwrap(),
+        "HTTP/1.1 200 OK\r\nContent-Type: text/plain\r\nContent-Length: 5\r\nConnection: close\r\n\r\n"
+    );
+}
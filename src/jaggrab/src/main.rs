@@ -0,0 +1,182 @@
+mod jaggrab_request;
+
+#[cfg(test)]
+mod jaggrab_request_tests;
+
+use std::error::Error;
+use std::io::ErrorKind;
+use std::path::Path;
+use std::time::Duration;
+use cache::file_handler::{ensure_initialized, get_data, get_master_index};
+use engine::io::connection_limiter::{ConnectionGuard, ConnectionLimiter};
+use log::{debug, error, info};
+use tokio::io::{AsyncBufReadExt, AsyncReadExt, AsyncWriteExt, BufReader};
+use tokio::net::{TcpListener, TcpStream};
+use tokio::time::timeout;
+use crate::jaggrab_request::{content_type, http_header, FileRequest, Protocol, Resource, MAX_HEADERS, MAX_LINE_LENGTH};
+
+/// How long a client has to send its request line and headers.
+const REQUEST_TIMEOUT: Duration = Duration::from_secs(30);
+
+/// Reads one line without its line ending, `None` once the client has closed the connection.
+async fn read_line(reader: &mut BufReader<TcpStream>) -> Result<Option<String>, Box<dyn Error>> {
+    let mut line = String::new();
+    let read = (&mut *reader).take(MAX_LINE_LENGTH as u64).read_line(&mut line).await?;
+    if read == 0 {
+        return Ok(None);
+    }
+
+    if !line.ends_with('\n') {
+        return Err(format!("Request line longer than {} bytes", MAX_LINE_LENGTH).into());
+    }
+
+    Ok(Some(line.trim_end_matches(['\r', '\n']).to_string()))
+}
+
+/// Reads the request line and skips the headers after it, `None` once the client has closed the
+/// connection.
+async fn read_request(reader: &mut BufReader<TcpStream>) -> Result<Option<String>, Box<dyn Error>> {
+    let Some(line) = read_line(reader).await? else {
+        return Ok(None);
+    };
+
+    // Both end the request with an empty line, HTTP has its headers before it.
+    for _ in 0..MAX_HEADERS {
+        match read_line(reader).await? {
+            Some(header) if !header.is_empty() => continue,
+            _ => break,
+        }
+    }
+
+    Ok(Some(line))
+}
+
+/// Bytes and content type for `resource`, `None` when there is nothing to serve.
+fn load(resource: &Resource) -> Option<(Vec<u8>, &'static str)> {
+    let result = match resource {
+        Resource::Crc => get_master_index(),
+        Resource::Group { archive, group } => get_data(*archive, *group),
+        Resource::Loader(name) => {
+            let path = Path::new(&config::get().paths.loader).join(name);
+            match std::fs::read(&path) {
+                Ok(data) => return Some((data, content_type(name))),
+                Err(e) if e.kind() == ErrorKind::NotFound => return None,
+                Err(e) => Err(format!("Failed to read {}: {}", path.display(), e).into()),
+            }
+        }
+    };
+
+    match result {
+        Ok(data) => Some((data, "application/octet-stream")),
+        Err(e) => {
+            debug!("Unable to serve {:?}: {}", resource, e);
+            None
+        }
+    }
+}
+
+async fn handle_jaggrab_client(stream: TcpStream, _guard: ConnectionGuard) -> Result<(), Box<dyn Error>> {
+    let addr = stream.peer_addr()?;
+    debug!("New connection from: {}", addr);
+
+    let mut reader = BufReader::new(stream);
+    let line = match timeout(REQUEST_TIMEOUT, read_request(&mut reader)).await {
+        Err(_) => {
+            debug!("Timed out waiting on a request from {}", addr);
+            return Ok(());
+        },
+        Ok(result) => match result? {
+            Some(line) => line,
+            None => return Ok(()),
+        },
+    };
+
+    let request = match FileRequest::parse(&line) {
+        Ok(request) => request,
+        Err(e) => {
+            debug!("{} from {}", e, addr);
+            reader.get_mut().write_all(&http_header(400, "Bad Request", "text/plain", 0)).await?;
+            return Ok(());
+        }
+    };
+
+    debug!("{} requested {}", addr, request.path);
+    let file = Resource::from_path(&request.path).and_then(|resource| load(&resource));
+
+    let stream = reader.get_mut();
+    match (request.protocol, file) {
+        (Protocol::Jaggrab, Some((data, _))) => stream.write_all(&data).await?,
+        // JAGGRAB has no way to say not found, the client sees the connection close.
+        (Protocol::Jaggrab, None) => {}
+        (Protocol::Http, Some((data, content_type))) => {
+            stream.write_all(&http_header(200, "OK", content_type, data.len())).await?;
+            stream.write_all(&data).await?;
+        }
+        (Protocol::Http, None) => {
+            let body = b"Not Found";
+            stream.write_all(&http_header(404, "Not Found", "text/plain", body.len())).await?;
+            stream.write_all(body).await?;
+        }
+    }
+
+    stream.shutdown().await?;
+    Ok(())
+}
+
+async fn run_jaggrab_server() -> Result<(), Box<dyn Error>> {
+    let config = config::get();
+    let listener = TcpListener::bind(&config.network.jaggrab).await?;
+    let limiter = ConnectionLimiter::new(config.jaggrab.max_connections, config.jaggrab.max_connections_per_ip);
+
+    debug!("Initializing cache in main thread");
+    if let Err(e) = ensure_initialized() {
+        error!("Failed to initialize cache in main thread: {}", e);
+    }
+
+    loop {
+        match listener.accept().await {
+            Ok((stream, addr)) => {
+                match limiter.acquire(addr.ip()) {
+                    Ok(guard) => {
+                        tokio::spawn(async move {
+                            if let Err(e) = handle_jaggrab_client(stream, guard).await {
+                                error!("Connection handler error: {}", e);
+                            }
+                        });
+                    },
+                    // Dropping the stream closes it before anything is read, the client just sees it go.
+                    Err(limit) => debug!("Rejecting {}: {:?}", addr, limit),
+                }
+            },
+            Err(e) => {
+                error!("Error accepting connection: {}", e);
+            }
+        }
+    }
+}
+
+#[tokio::main]
+async fn main() -> Result<(), Box<dyn Error>> {
+    if std::env::var_os("RUST_LOG").is_none() {
+        unsafe {
+            std::env::set_var("RUST_LOG", "debug");
+        }
+    }
+    env_logger::init();
+    let config = config::init()?;
+
+    info!("Starting JAGGRAB System");
+    info!("---------------------------------------------");
+    info!("Starting JAGGRAB server: {}", config.network.jaggrab);
+    info!("---------------------------------------------");
+
+    tokio::select! {
+        result = run_jaggrab_server() => {
+            if let Err(e) = result {
+                error!("JAGGRAB server error: {}", e);
+            }
+        }
+    }
+
+    Ok(())
+}
@@ -0,0 +1,215 @@
+use std::cmp::min;
+use std::collections::VecDeque;
+use std::error::Error;
+use cache::file_handler::{ensure_initialized, get_checksum, get_data, get_master_index};
+use constants::js5_in::js5_in;
+use constants::js5_out::js5_out;
+use log::{debug, error, info};
+use rs2cache::js5_compression::Js5Compression;
+use rs2cache::js5_index::Js5Index;
+use rs2cache::store::ARCHIVESET;
+use tokio::io::{AsyncReadExt, AsyncWriteExt};
+use tokio::net::TcpStream;
+
+const USAGE: &str = "\
+Usage: js5_client [--addr <host:port>] [--key <0-255>] [--archive <id>]...
+
+Connects to a running JS5 server, rekeys the stream and checks every group it serves against the
+local cache. Defaults to the js5 address from the config, key 90 and every archive.";
+
+const BLOCK_SIZE: usize = 512;
+const BLOCK_HEADER_SIZE: usize = 1 + 2 + 1;
+const BLOCK_DELIMITER: u8 = 0xFF;
+/// Requests kept in flight, well below the server's queue limit.
+const WINDOW: usize = 20;
+
+/// Client side of a JS5 connection, undoes the XOR and the block framing of every response.
+struct Js5Client {
+    stream: TcpStream,
+    key: u8,
+}
+
+impl Js5Client {
+    async fn connect(addr: &str, revision: i32) -> Result<Js5Client, Box<dyn Error>> {
+        let mut stream = TcpStream::connect(addr).await?;
+        stream.write_all(&revision.to_be_bytes()).await?;
+
+        let response = stream.read_u8().await?;
+        if response as i32 != js5_out::SUCCESS {
+            return Err(format!("Server rejected the handshake with {}", response).into());
+        }
+
+        let mut client = Js5Client { stream, key: 0 };
+        client.send(js5_in::CONNECTED, [0, 0, 3]).await?;
+        client.send(js5_in::LOGGED_OUT, [0, 0, 0]).await?;
+        Ok(client)
+    }
+
+    async fn send(&mut self, opcode: u8, payload: [u8; 3]) -> Result<(), Box<dyn Error>> {
+        self.stream.write_all(&[opcode, payload[0], payload[1], payload[2]]).await?;
+        Ok(())
+    }
+
+    async fn rekey(&mut self, key: u8) -> Result<(), Box<dyn Error>> {
+        self.send(js5_in::REKEY, [key, 0, 0]).await?;
+        self.key = key;
+        Ok(())
+    }
+
+    async fn request(&mut self, archive: u8, group: u16) -> Result<(), Box<dyn Error>> {
+        let group = group.to_be_bytes();
+        self.send(js5_in::URGENT, [archive, group[0], group[1]]).await
+    }
+
+    async fn read(&mut self, length: usize) -> Result<Vec<u8>, Box<dyn Error>> {
+        let mut bytes = vec![0u8; length];
+        self.stream.read_exact(&mut bytes).await?;
+        for byte in &mut bytes {
+            *byte ^= self.key;
+        }
+        Ok(bytes)
+    }
+
+    /// Reads one response, `(archive, group, compression, payload)` with the block delimiters removed.
+    async fn response(&mut self) -> Result<(u8, u16, u8, Vec<u8>), Box<dyn Error>> {
+        let header = self.read(BLOCK_HEADER_SIZE).await?;
+        let archive = header[0];
+        let group = u16::from_be_bytes([header[1], header[2]]);
+        let compression = header[3] & 0x7F;
+
+        let mut payload = self.read(4).await?;
+        let length = u32::from_be_bytes([payload[0], payload[1], payload[2], payload[3]]) as usize;
+        let size = length + if compression != 0 { 8 } else { 4 };
+
+        let mut block_position = BLOCK_HEADER_SIZE + payload.len();
+        while payload.len() < size {
+            if block_position == BLOCK_SIZE {
+                let delimiter = self.read(1).await?[0];
+                if delimiter != BLOCK_DELIMITER {
+                    return Err(format!("Expected block delimiter in {}/{}, got {}", archive, group, delimiter).into());
+                }
+                block_position = 1;
+            }
+
+            let chunk = min(size - payload.len(), BLOCK_SIZE - block_position);
+            payload.extend(self.read(chunk).await?);
+            block_position += chunk;
+        }
+
+        Ok((archive, group, compression, payload))
+    }
+}
+
+/// Compares a response against the cache, the server sends the container minus its version trailer.
+fn verify(archive: u8, group: u16, compression: u8, payload: &[u8]) -> Result<(), String> {
+    if archive == ARCHIVESET && group == ARCHIVESET as u16 {
+        let master_index = get_master_index().map_err(|e| e.to_string())?;
+        if payload[4..] != master_index[..] {
+            return Err("master index differs".to_string());
+        }
+        return Ok(());
+    }
+
+    let data = get_data(archive, group).map_err(|e| e.to_string())?;
+    if data[0] != compression {
+        return Err(format!("compression {} instead of {}", compression, data[0]));
+    }
+    if data.len() < payload.len() + 1 || data[1..payload.len() + 1] != payload[..] {
+        return Err("data differs".to_string());
+    }
+    Ok(())
+}
+
+/// Group ids of `archive` from its index in the local cache.
+fn groups(archive: u8) -> Result<Vec<u16>, Box<dyn Error>> {
+    let index = Js5Index::read(Js5Compression::uncompress(get_data(ARCHIVESET, archive as u16)?, None)?)
+        .map_err(|e| format!("Failed to read index {}: {:?}", archive, e))?;
+    let mut groups: Vec<u16> = index.groups.keys().map(|group| *group as u16).collect();
+    groups.sort_unstable();
+    Ok(groups)
+}
+
+#[tokio::main]
+async fn main() -> Result<(), Box<dyn Error>> {
+    if std::env::var_os("RUST_LOG").is_none() {
+        unsafe {
+            std::env::set_var("RUST_LOG", "info");
+        }
+    }
+    env_logger::init();
+    let config = config::init()?;
+
+    let mut addr = config.network.js5.clone();
+    let mut key: u8 = 90;
+    let mut archives: Vec<u8> = Vec::new();
+
+    let args: Vec<String> = std::env::args().skip(1).collect();
+    let mut args = args.iter();
+    while let Some(arg) = args.next() {
+        if arg == "help" || arg == "--help" || arg == "-h" {
+            println!("{}", USAGE);
+            return Ok(());
+        }
+
+        let value = args.next().ok_or_else(|| format!("Missing value for {}\n\n{}", arg, USAGE))?;
+        match arg.as_str() {
+            "--addr" => addr = value.clone(),
+            "--key" => key = value.parse().map_err(|_| format!("Invalid key {:?}", value))?,
+            "--archive" => archives.push(value.parse().map_err(|_| format!("Invalid archive {:?}", value))?),
+            _ => return Err(format!("Unknown option {:?}\n\n{}", arg, USAGE).into()),
+        }
+    }
+
+    ensure_initialized()?;
+    if archives.is_empty() {
+        archives = (0..=u8::MAX).take_while(|archive| get_checksum(*archive as usize).is_ok()).collect();
+    }
+
+    let mut requests: VecDeque<(u8, u16)> = VecDeque::new();
+    requests.push_back((ARCHIVESET, ARCHIVESET as u16));
+    for archive in &archives {
+        requests.push_back((ARCHIVESET, *archive as u16));
+        for group in groups(*archive)? {
+            requests.push_back((*archive, group));
+        }
+    }
+
+    info!("Verifying {} groups from {} with key {}", requests.len(), addr, key);
+    let mut client = Js5Client::connect(&addr, config.world.revision).await?;
+    client.rekey(key).await?;
+
+    let total = requests.len();
+    let mut in_flight: VecDeque<(u8, u16)> = VecDeque::new();
+    let mut failures = 0;
+
+    while !requests.is_empty() || !in_flight.is_empty() {
+        while in_flight.len() < WINDOW {
+            let Some((archive, group)) = requests.pop_front() else {
+                break;
+            };
+            client.request(archive, group).await?;
+            in_flight.push_back((archive, group));
+        }
+
+        let (archive, group, compression, payload) = client.response().await?;
+        if in_flight.pop_front() != Some((archive, group)) {
+            return Err(format!("Unexpected response for {}/{}", archive, group).into());
+        }
+
+        match verify(archive, group, compression, &payload) {
+            Ok(()) => debug!("{}/{} ok, {} bytes", archive, group, payload.len()),
+            Err(e) => {
+                error!("{}/{}: {}", archive, group, e);
+                failures += 1;
+            }
+        }
+    }
+
+    if failures > 0 {
+        error!("{} of {} groups did not match the cache", failures, total);
+        std::process::exit(1);
+    }
+
+    info!("All {} groups match the cache", total);
+    Ok(())
+}
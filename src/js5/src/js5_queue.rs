@@ -0,0 +1,117 @@
+use std::collections::VecDeque;
+use std::time::Duration;
+
+/// How often a connection's queues are serviced.
+pub const SERVICE_INTERVAL: Duration = Duration::from_millis(50);
+/// Bytes a connection may be sent per service cycle.
+pub const CYCLE_BUDGET: usize = 64 * 1024;
+/// Share of [`CYCLE_BUDGET`] prefetch may use while the client is in game, so it doesn't fight
+/// the game connection for bandwidth.
+pub const LOGGED_IN_PREFETCH_BUDGET: usize = 8 * 1024;
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct GroupRequest {
+    pub urgent: bool,
+    pub archive: u8,
+    pub group: u16,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct QueueFull;
+
+/// What a connection is still allowed to send this cycle.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Js5Budget {
+    remaining: usize,
+    prefetch_remaining: usize,
+}
+
+impl Js5Budget {
+    pub fn spend(&mut self, request: &GroupRequest, bytes: usize) {
+        self.remaining = self.remaining.saturating_sub(bytes);
+        if !request.urgent {
+            self.prefetch_remaining = self.prefetch_remaining.saturating_sub(bytes);
+        }
+    }
+}
+
+/// Per connection urgent and prefetch queues.
+///
+/// Every cycle urgent requests are sent first, prefetch only gets what is left of the budget, and
+/// less of it while the client is logged in. A response is never split, so the request that runs
+/// the budget out is still sent whole.
+#[derive(Debug)]
+pub struct Js5Queue {
+    urgent: VecDeque<GroupRequest>,
+    prefetch: VecDeque<GroupRequest>,
+    /// Requests a client may have outstanding per queue before it is dropped.
+    limit: usize,
+    logged_in: bool,
+}
+
+impl Js5Queue {
+    pub fn new(limit: usize) -> Js5Queue {
+        Js5Queue {
+            urgent: VecDeque::new(),
+            prefetch: VecDeque::new(),
+            limit,
+            logged_in: false,
+        }
+    }
+
+    pub fn push(&mut self, request: GroupRequest) -> Result<(), QueueFull> {
+        if request.urgent {
+            if self.urgent.len() >= self.limit {
+                return Err(QueueFull);
+            }
+
+            // An urgent request for something already waiting in prefetch takes its place.
+            self.prefetch.retain(|queued| queued.archive != request.archive || queued.group != request.group);
+            self.urgent.push_back(request);
+        } else {
+            if self.prefetch.len() >= self.limit {
+                return Err(QueueFull);
+            }
+
+            self.prefetch.push_back(request);
+        }
+        Ok(())
+    }
+
+    pub fn set_logged_in(&mut self, logged_in: bool) {
+        self.logged_in = logged_in;
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.urgent.is_empty() && self.prefetch.is_empty()
+    }
+
+    pub fn len(&self) -> usize {
+        self.urgent.len() + self.prefetch.len()
+    }
+
+    /// Fresh budget for the next service cycle.
+    pub fn budget(&self) -> Js5Budget {
+        Js5Budget {
+            remaining: CYCLE_BUDGET,
+            prefetch_remaining: if self.logged_in { LOGGED_IN_PREFETCH_BUDGET } else { CYCLE_BUDGET },
+        }
+    }
+
+    /// Next request to send within `budget`, `None` once the cycle is used up.
+    pub fn next(&mut self, budget: &Js5Budget) -> Option<GroupRequest> {
+        if budget.remaining == 0 {
+            return None;
+        }
+
+        if let Some(request) = self.urgent.pop_front() {
+            return Some(request);
+        }
+
+        if budget.prefetch_remaining == 0 {
+            return None;
+        }
+
+        self.prefetch.pop_front()
+    }
+}
@@ -0,0 +1,86 @@
+use crate::js5_queue::{GroupRequest, Js5Queue, QueueFull, CYCLE_BUDGET, LOGGED_IN_PREFETCH_BUDGET};
+
+const LIMIT: usize = 200;
+
+fn request(urgent: bool, archive: u8, group: u16) -> GroupRequest {
+    GroupRequest { urgent, archive, group }
+}
+
+#[test]
+fn test_urgent_served_first() {
+    let mut queue = Js5Queue::new(LIMIT);
+    queue.push(request(false, 2, 1)).unwrap();
+    queue.push(request(true, 5, 7)).unwrap();
+    queue.push(request(false, 2, 2)).unwrap();
+
+    let budget = queue.budget();
+    assert_eq!(queue.next(&budget), Some(request(true, 5, 7)));
+    assert_eq!(queue.next(&budget), Some(request(false, 2, 1)));
+    assert_eq!(queue.next(&budget), Some(request(false, 2, 2)));
+    assert_eq!(queue.next(&budget), None);
+    assert!(queue.is_empty());
+}
+
+#[test]
+fn test_urgent_replaces_queued_prefetch() {
+    let mut queue = Js5Queue::new(LIMIT);
+    queue.push(request(false, 3, 10)).unwrap();
+    queue.push(request(false, 3, 11)).unwrap();
+    queue.push(request(true, 3, 10)).unwrap();
+
+    assert_eq!(queue.len(), 2);
+    let budget = queue.budget();
+    assert_eq!(queue.next(&budget), Some(request(true, 3, 10)));
+    assert_eq!(queue.next(&budget), Some(request(false, 3, 11)));
+}
+
+#[test]
+fn test_logged_in_throttles_prefetch() {
+    let mut queue = Js5Queue::new(LIMIT);
+    for group in 0..4 {
+        queue.push(request(false, 1, group)).unwrap();
+    }
+    queue.set_logged_in(true);
+
+    let mut budget = queue.budget();
+    let first = queue.next(&budget).unwrap();
+    budget.spend(&first, LOGGED_IN_PREFETCH_BUDGET);
+    assert_eq!(queue.next(&budget), None, "prefetch budget is used up while logged in");
+
+    // Urgent requests still go out from what is left of the cycle.
+    queue.push(request(true, 9, 9)).unwrap();
+    assert_eq!(queue.next(&budget), Some(request(true, 9, 9)));
+
+    queue.set_logged_in(false);
+    let mut budget = queue.budget();
+    let next = queue.next(&budget).unwrap();
+    budget.spend(&next, LOGGED_IN_PREFETCH_BUDGET);
+    assert!(queue.next(&budget).is_some(), "logged out prefetch gets the whole cycle");
+}
+
+#[test]
+fn test_cycle_budget_runs_out() {
+    let mut queue = Js5Queue::new(LIMIT);
+    queue.push(request(true, 1, 1)).unwrap();
+    queue.push(request(true, 1, 2)).unwrap();
+
+    let mut budget = queue.budget();
+    let first = queue.next(&budget).unwrap();
+    budget.spend(&first, CYCLE_BUDGET + 1);
+    assert_eq!(queue.next(&budget), None);
+
+    let budget = queue.budget();
+    assert_eq!(queue.next(&budget), Some(request(true, 1, 2)));
+}
+
+#[test]
+fn test_queue_full() {
+    let mut queue = Js5Queue::new(LIMIT);
+    for group in 0..LIMIT as u16 {
+        queue.push(request(false, 0, group)).unwrap();
+    }
+    assert_eq!(queue.push(request(false, 0, 1000)), Err(QueueFull));
+
+    // Urgent has its own limit.
+    assert_eq!(queue.push(request(true, 0, 1000)), Ok(()));
+}
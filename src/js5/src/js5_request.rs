@@ -2,9 +2,11 @@ use std::cmp::min;
 use std::error::Error;
 use log::debug;
 use rs2cache::store::ARCHIVESET;
-use cache::file_handler::{ensure_initialized, get_data, get_master_index};
+use cache::file_handler::{ensure_initialized, get_group, get_master_index};
+use constants::js5_in::js5_in;
 use engine::io::connection::Connection;
 use engine::io::packet::Packet;
+use crate::js5_queue::GroupRequest;
 
 const BLOCK_SIZE: usize = 512;
 pub const BLOCK_HEADER_SIZE: usize = 1 + 2 + 1;
@@ -12,13 +14,12 @@ pub const BLOCK_DELIMITER_SIZE: usize = 1;
 pub const BYTES_BEFORE_BLOCK: usize = BLOCK_SIZE - BLOCK_HEADER_SIZE;
 pub const BYTES_AFTER_BLOCK: usize = BLOCK_SIZE - BLOCK_DELIMITER_SIZE;
 
-#[derive(Debug)]
+/// Size of every request after the handshake, opcode and 3 bytes of payload.
+pub const REQUEST_SIZE: usize = 4;
+
+#[derive(Debug, PartialEq)]
 pub (crate) enum Js5Request {
-    Group {
-        urgent: bool,
-        archive: u8,
-        group: u16,
-    },
+    Group(GroupRequest),
     LoggedIn,
     LoggedOut,
     Rekey {
@@ -29,62 +30,124 @@ pub (crate) enum Js5Request {
     Invalid
 }
 
-// TODO - split into separate handlers
-// TODO - add request limit handling.
-
 impl Js5Request {
-    pub fn fulfill_request(connection: &mut Connection, request: &Js5Request) -> Result<(), Box<dyn Error>> {
-        // Ensure the cache is initialized in this thread before proceeding
-        ensure_initialized()?;
+    /// Reads one [`REQUEST_SIZE`] request off `packet`.
+    pub fn decode(packet: &mut Packet) -> Js5Request {
+        let opcode = packet.g1();
+        match opcode {
+            js5_in::PREFETCH | js5_in::URGENT => {
+                Js5Request::Group(GroupRequest {
+                    urgent: opcode == js5_in::URGENT,
+                    archive: packet.g1(),
+                    group: packet.g2()
+                })
+            }
 
-        if let Js5Request::Group { urgent, archive, group } = request {
-            if *archive == ARCHIVESET && *group == ARCHIVESET as u16 {
-                // Handle master index request
-                
-                let master_index = get_master_index()?;
-                let master_index_length = master_index.len();
-                        
-                connection.outbound = Packet::new(8 + master_index_length);
-                connection.outbound.p1(ARCHIVESET as i32);
-                connection.outbound.p2(ARCHIVESET as i32);
-                connection.outbound.p1(0);
-                
-                debug!("Master index length: {}", master_index_length);
-                connection.outbound.p4(master_index_length as i32);
-                connection.outbound.pbytes(&master_index, 0, master_index_length);
-            } else {
-                // Handle regular file request
-                let data = get_data(*archive, *group)?;
-                
-                let mut data_packet = Packet::from(data);
-                let data_len = data_packet.data.len();
-                
-                let length = 2 + data_len + (BLOCK_SIZE + data_len) / BYTES_BEFORE_BLOCK + (data_len + BLOCK_SIZE) / BYTES_AFTER_BLOCK + 1;
-                
-                connection.outbound = Packet::new(length);
-                connection.outbound.p1(*archive as i32);
-                connection.outbound.p2(*group as i32);
-
-                let compression = data_packet.g1();
-                connection.outbound.p1(if *urgent { compression | 0x80 } else { compression } as i32);
-                
-                let size: usize = (data_packet.g4() + if compression != 0 { 8 } else { 4 }) as usize;
-
-                let mut written = min(size, BYTES_BEFORE_BLOCK);
-                connection.outbound.pbytes(&data_packet.data, BLOCK_DELIMITER_SIZE, written);
-
-                while written < size {
-                    connection.outbound.p1(0xFF);
-
-                    let chunk_size = min(size - written, BYTES_AFTER_BLOCK);
-                    connection.outbound.pbytes(&data_packet.data, written + BLOCK_DELIMITER_SIZE, chunk_size);
-                    written += chunk_size;
+            js5_in::REKEY => {
+                let key = packet.g1();
+                if packet.g2() != 0 {
+                    Js5Request::Invalid
+                } else {
+                    Js5Request::Rekey { key }
+                }
+            }
+            js5_in::LOGGED_IN => {
+                if packet.g3() != 0 {
+                    Js5Request::Invalid
+                } else {
+                    Js5Request::LoggedIn
                 }
             }
+            js5_in::LOGGED_OUT => {
+                packet.g3();
+                Js5Request::LoggedOut
+            }
+            js5_in::CONNECTED => {
+                // Value is always '3'.
+                if packet.g3() != 3 {
+                    Js5Request::Invalid
+                } else {
+                    Js5Request::Connected
+                }
+            }
+            js5_in::DISCONNECT => {
+                packet.g3();
+                Js5Request::Disconnect
+            }
+            _ => {
+                debug!("Invalid opcode: {}", opcode);
+                packet.g3();
+                Js5Request::Invalid
+            }
+        }
+    }
+
+    /// Writes the response for `request` into the connection's outbound packet, returns its size.
+    pub fn fulfill_request(connection: &mut Connection, request: &GroupRequest) -> Result<usize, Box<dyn Error>> {
+        // Ensure the cache is initialized in this thread before proceeding
+        ensure_initialized()?;
+
+        let GroupRequest { urgent, archive, group } = *request;
+        if archive == ARCHIVESET && group == ARCHIVESET as u16 {
+            // Handle master index request
 
-            Ok(())
+            let master_index = get_master_index()?;
+            let master_index_length = master_index.len();
+
+            connection.outbound = Packet::new(8 + master_index_length);
+            connection.outbound.p1(ARCHIVESET as i32);
+            connection.outbound.p2(ARCHIVESET as i32);
+            connection.outbound.p1(0);
+
+            debug!("Master index length: {}", master_index_length);
+            connection.outbound.p4(master_index_length as i32);
+            connection.outbound.pbytes(&master_index, 0, master_index_length);
         } else {
-            Err("Invalid JS5 request".into())
+            // Handle regular file request, the group is shared with the cache and only read here.
+            let data = get_group(archive, group)?;
+            if data.len() < 5 {
+                return Err(format!("Archive: {}, group: {} is too short to be a container", archive, group).into());
+            }
+            let data_len = data.len();
+
+            let length = 2 + data_len + (BLOCK_SIZE + data_len) / BYTES_BEFORE_BLOCK + (data_len + BLOCK_SIZE) / BYTES_AFTER_BLOCK + 1;
+
+            // Written out straight after, so the buffer from the previous response can be reused.
+            connection.outbound.data.reserve(length);
+            connection.outbound.p1(archive as i32);
+            connection.outbound.p2(group as i32);
+
+            // The client tells prefetch responses apart by the high bit of the compression type.
+            let compression = data[0];
+            connection.outbound.p1(if urgent { compression } else { compression | 0x80 } as i32);
+
+            let compressed_length = u32::from_be_bytes([data[1], data[2], data[3], data[4]]) as usize;
+            let size = compressed_length + if compression != 0 { 8 } else { 4 };
+
+            let mut written = min(size, BYTES_BEFORE_BLOCK);
+            connection.outbound.pbytes(&data, BLOCK_DELIMITER_SIZE, written);
+
+            while written < size {
+                connection.outbound.p1(0xFF);
+
+                let chunk_size = min(size - written, BYTES_AFTER_BLOCK);
+                connection.outbound.pbytes(&data, written + BLOCK_DELIMITER_SIZE, chunk_size);
+                written += chunk_size;
+            }
         }
+
+        Ok(connection.outbound.position)
     }
-}
\ No newline at end of file
+}
+
+/// XORs the written part of `packet` with the key from the last [`Js5Request::Rekey`], a key of 0
+/// leaves it untouched.
+pub fn encrypt(packet: &mut Packet, key: u8) {
+    if key == 0 {
+        return;
+    }
+
+    for byte in &mut packet.data[..packet.position] {
+        *byte ^= key;
+    }
+}
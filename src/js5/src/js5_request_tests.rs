@@ -0,0 +1,36 @@
+use constants::js5_in::js5_in;
+use engine::io::packet::Packet;
+use crate::js5_queue::GroupRequest;
+use crate::js5_request::{encrypt, Js5Request};
+
+fn decode(bytes: [u8; 4]) -> Js5Request {
+    Js5Request::decode(&mut Packet::from(bytes.to_vec()))
+}
+
+#[test]
+fn test_decode_group_urgency() {
+    assert_eq!(decode([js5_in::URGENT, 7, 0x01, 0x02]), Js5Request::Group(GroupRequest { urgent: true, archive: 7, group: 0x102 }));
+    assert_eq!(decode([js5_in::PREFETCH, 7, 0x01, 0x02]), Js5Request::Group(GroupRequest { urgent: false, archive: 7, group: 0x102 }));
+}
+
+#[test]
+fn test_decode_rekey() {
+    assert_eq!(decode([js5_in::REKEY, 0x5A, 0, 0]), Js5Request::Rekey { key: 0x5A });
+    assert_eq!(decode([js5_in::REKEY, 0x5A, 0, 1]), Js5Request::Invalid);
+}
+
+#[test]
+fn test_encrypt_written_bytes() {
+    let mut packet = Packet::new(8);
+    packet.pbytes(&[0x00, 0xFF, 0x5A, 0x12], 0, 4);
+
+    encrypt(&mut packet, 0);
+    assert_eq!(&packet.data[..packet.position], &[0x00, 0xFF, 0x5A, 0x12]);
+
+    encrypt(&mut packet, 0x5A);
+    assert_eq!(&packet.data[..packet.position], &[0x5A, 0xA5, 0x00, 0x48]);
+
+    // XOR is its own inverse, which is all the client does to read it back.
+    encrypt(&mut packet, 0x5A);
+    assert_eq!(&packet.data[..packet.position], &[0x00, 0xFF, 0x5A, 0x12]);
+}
@@ -1,18 +1,38 @@
+mod js5_queue;
 mod js5_request;
 
+#[cfg(test)]
+mod js5_queue_tests;
+#[cfg(test)]
+mod js5_request_tests;
+
 use std::error::Error;
+use std::net::SocketAddr;
+use std::time::Duration;
 use cache::file_handler::ensure_initialized;
 use constants::js5_out::js5_out;
-use constants::server_addresses::server_addresses::JS5_ADDR;
 use engine::io::client_state::ConnectionState;
 use engine::io::connection::{try_write_packet, Connection};
+use engine::io::connection_limiter::{ConnectionGuard, ConnectionLimiter, LimitExceeded};
+use engine::io::packet::Packet;
 use log::{debug, error, info};
+use tokio::io::{AsyncReadExt, AsyncWriteExt};
 use tokio::net::{TcpListener, TcpStream};
-use constants::js5_in::js5_in;
-use crate::js5_request::Js5Request;
+use tokio::time::{interval, timeout, MissedTickBehavior};
+use crate::js5_queue::{Js5Queue, SERVICE_INTERVAL};
+use crate::js5_request::{encrypt, Js5Request, REQUEST_SIZE};
+
+/// How long a new connection has to send its version.
+const HANDSHAKE_TIMEOUT: Duration = Duration::from_secs(30);
+/// Connections that send nothing for this long are dropped so they stop holding a slot.
+const IDLE_TIMEOUT: Duration = Duration::from_secs(120);
+/// How long a rejected connection gets to send its version before it's told why and closed.
+const REJECT_TIMEOUT: Duration = Duration::from_secs(5);
 
 async fn run_js5_server() -> Result<(), Box<dyn Error>> {
-    let listener = TcpListener::bind(JS5_ADDR).await?;
+    let config = config::get();
+    let listener = TcpListener::bind(&config.network.js5).await?;
+    let limiter = ConnectionLimiter::new(config.js5.max_connections, config.js5.max_connections_per_ip);
 
     debug!("Initializing cache in main thread");
     if let Err(e) = ensure_initialized() {
@@ -23,12 +43,21 @@ async fn run_js5_server() -> Result<(), Box<dyn Error>> {
 
     loop {
         match listener.accept().await {
-            Ok((stream, _)) => {
-                tokio::spawn(async move {
-                    if let Err(e) = handle_js5_client(stream).await {
-                        error!("Connection handler error: {}", e);
+            Ok((stream, addr)) => {
+                match limiter.acquire(addr.ip()) {
+                    Ok(guard) => {
+                        debug!("Accepted {}, {} connections open", addr, limiter.connections());
+                        tokio::spawn(async move {
+                            if let Err(e) = handle_js5_client(stream, guard).await {
+                                error!("Connection handler error: {}", e);
+                            }
+                        });
+                    },
+                    Err(limit) => {
+                        debug!("Rejecting {}: {:?}", addr, limit);
+                        tokio::spawn(reject_js5_client(stream, limit));
                     }
-                });
+                }
             },
             Err(e) => {
                 error!("Error accepting connection: {}", e);
@@ -37,126 +66,140 @@ async fn run_js5_server() -> Result<(), Box<dyn Error>> {
     }
 }
 
-async fn handle_js5_client(stream: TcpStream) -> Result<(), Box<dyn Error>> {
+/// Tells a client over the connection limits why it was turned away. Its version is read first,
+/// closing with it unread could reset the connection before the status arrives.
+async fn reject_js5_client(mut stream: TcpStream, limit: LimitExceeded) {
+    let _ = timeout(REJECT_TIMEOUT, async {
+        let mut version = [0; 4];
+        stream.read_exact(&mut version).await?;
+        stream.write_all(&[limit.status() as u8]).await?;
+        stream.shutdown().await
+    }).await;
+}
+
+/// Serves an admitted connection, `_guard` holds its slot until the connection is done.
+async fn handle_js5_client(stream: TcpStream, _guard: ConnectionGuard) -> Result<(), Box<dyn Error>> {
     let addr = stream.peer_addr()?;
     info!("New connection from: {}", addr);
     
     let mut connection = Connection::new(stream);
-    
-    loop {
-        match connection.read_packet().await {
-            Ok(0) => {
-                debug!("Connection closed by client: {}", addr);
-                break;
-            },
-            Ok(n) => {
-                debug!("Received packet: {} bytes from: {}", n, addr);
-                if connection.state == ConnectionState::New {
-                    let client_version = if !connection.inbound().is_empty() {
-                        connection.inbound().g4()
-                    } else {
-                        return Err("Client sent no version packet".into());
-                    };
-
-                    debug!("Client version is {}", client_version);
-                    if client_version == 530 {
-                        connection.outbound().p1(js5_out::SUCCESS);
-                        connection.state = ConnectionState::Connected;
+    let mut queue = Js5Queue::new(config::get().js5.max_queued_requests);
+    // Every response byte is XORed with this once the client sends a rekey.
+    let mut key: u8 = 0;
+    // Requests can be split across reads, leftover bytes wait here for the rest.
+    let mut pending: Vec<u8> = Vec::new();
 
-                    } else {
-                        connection.outbound().p1(js5_out::CLIENT_OUT_OF_DATE);
-                        connection.state = ConnectionState::Closed;
+    let mut service = interval(SERVICE_INTERVAL);
+    service.set_missed_tick_behavior(MissedTickBehavior::Delay);
 
-                    }
-                } else if connection.state == ConnectionState::Connected {
-                    while connection.inbound().remaining() > 0 {
-                        let opcode = connection.inbound().g1();
-                        let request = match opcode {
-                            js5_in::PREFETCH | js5_in::URGENT => {
-                                Js5Request::Group {
-                                    urgent: opcode == js5_in::PREFETCH,
-                                    archive: connection.inbound.g1(),
-                                    group: connection.inbound.g2()
-                                }
-                            }
+    loop {
+        let read_timeout = if connection.state == ConnectionState::New { HANDSHAKE_TIMEOUT } else { IDLE_TIMEOUT };
 
-                            js5_in::REKEY => {
-                                let key = connection.inbound.g1();
-                                if connection.inbound.g2() != 0 {
-                                    Js5Request::Invalid
-                                } else {
-                                    Js5Request::Rekey { key }
-                                }
-                            }
-                            js5_in::LOGGED_IN => {
-                                if connection.inbound.g3() != 0 {
-                                    Js5Request::Invalid
-                                } else {
-                                    Js5Request::LoggedIn
-                                }
-                            }
-                            js5_in::LOGGED_OUT => {
-                                connection.inbound().g3();
-                                Js5Request::LoggedOut
-                            }
-                            js5_in::CONNECTED => {
-                                // Value is always '3'.
-                                if connection.inbound.g3() != 3 {
-                                    Js5Request::Invalid
-                                } else {
-                                    Js5Request::Connected
-                                }
-                            }
-                            js5_in::DISCONNECT => {
-                                connection.inbound().g3();
-                                Js5Request::Disconnect
-                            }
-                            _ => {
-                                debug!("Invalid opcode: {}", opcode);
-                                connection.inbound().g3();
-                                Js5Request::Invalid
-                            }
+        tokio::select! {
+            read = timeout(read_timeout, connection.read_packet()) => match read {
+                Err(_) => {
+                    debug!("Timed out waiting on {}", addr);
+                    break;
+                },
+                Ok(Ok(0)) => {
+                    debug!("Connection closed by client: {}, {} requests left queued", addr, queue.len());
+                    break;
+                },
+                Ok(Ok(n)) => {
+                    debug!("Received packet: {} bytes from: {}", n, addr);
+                    if connection.state == ConnectionState::New {
+                        let client_version = if !connection.inbound().is_empty() {
+                            connection.inbound().g4()
+                        } else {
+                            return Err("Client sent no version packet".into());
                         };
 
-                        match request {
-                            Js5Request::Group { .. } => {
-                                Js5Request::fulfill_request(&mut connection, &request)?;
-                                try_write_packet(&mut connection).await;
-                            }
+                        debug!("Client version is {}", client_version);
+                        if client_version != config::get().world.revision {
+                            connection.outbound().p1(js5_out::CLIENT_OUT_OF_DATE);
+                            connection.state = ConnectionState::Closed;
 
-                            Js5Request::Invalid => {
-                                // TODO - terminate early.
-                            }
-                            _ => {
-                                // Currently nothing.
+                        } else {
+                            connection.outbound().p1(js5_out::SUCCESS);
+                            connection.state = ConnectionState::Connected;
+
+                        }
+                    } else if connection.state == ConnectionState::Connected {
+                        pending.extend_from_slice(&connection.inbound.data);
+
+                        let complete = pending.len() - pending.len() % REQUEST_SIZE;
+                        for chunk in pending[..complete].chunks(REQUEST_SIZE) {
+                            let request = Js5Request::decode(&mut Packet::from(chunk.to_vec()));
+                            if !handle_request(&mut queue, &mut key, request, addr) {
+                                connection.state = ConnectionState::Closed;
+                                break;
                             }
-                            
-                            
                         }
-                        
+                        pending.drain(..complete);
+                    } else {
+                        error!("Client state is undefined.");
+                        connection.state = ConnectionState::Closed;
                     }
-                } else {
-                    error!("Client state is undefined.");
-                    connection.state = ConnectionState::Closed;
+                },
+                Ok(Err(e)) => {
+                    error!("Error reading from client: {}", e);
+                    break;
                 }
+            },
 
-                try_write_packet(&mut connection).await;
+            _ = service.tick(), if !queue.is_empty() && connection.state == ConnectionState::Connected => {
+                let mut budget = queue.budget();
+                while let Some(request) = queue.next(&budget) {
+                    let bytes = Js5Request::fulfill_request(&mut connection, &request)?;
+                    encrypt(&mut connection.outbound, key);
+                    try_write_packet(&mut connection).await;
+                    budget.spend(&request, bytes);
 
-                if connection.state == ConnectionState::Closed {
-                    connection.shutdown().await?;
-                    break;
+                    if connection.state == ConnectionState::Closed {
+                        break;
+                    }
                 }
-            },
-            Err(e) => {
-                error!("Error reading from client: {}", e);
-                break;
             }
         }
+
+        try_write_packet(&mut connection).await;
+
+        if connection.state == ConnectionState::Closed {
+            connection.shutdown().await?;
+            break;
+        }
     }
 
     Ok(())
 }
 
+/// Queues or applies a single request, returns false when the connection should be closed.
+fn handle_request(queue: &mut Js5Queue, key: &mut u8, request: Js5Request, addr: SocketAddr) -> bool {
+    match request {
+        Js5Request::Group(group) => {
+            if queue.push(group).is_err() {
+                debug!("Request queue full for {}, closing connection", addr);
+                return false;
+            }
+        }
+        Js5Request::LoggedIn => queue.set_logged_in(true),
+        Js5Request::LoggedOut => queue.set_logged_in(false),
+        Js5Request::Disconnect => return false,
+        Js5Request::Invalid => {
+            debug!("Invalid JS5 request from {}, closing connection", addr);
+            return false;
+        }
+        Js5Request::Rekey { key: new_key } => {
+            debug!("Rekeyed {} with {}", addr, new_key);
+            *key = new_key;
+        }
+        Js5Request::Connected => {
+            // Currently nothing.
+        }
+    }
+    true
+}
+
 #[tokio::main]
 async fn main() -> Result<(), Box<dyn Error>> {
     if std::env::var_os("RUST_LOG").is_none() {
@@ -165,10 +208,11 @@ async fn main() -> Result<(), Box<dyn Error>> {
         }
     }
     env_logger::init();
+    let config = config::init()?;
 
     info!("Starting JS5 System");
     info!("---------------------------------------------");
-    info!("Starting JS5 server: {}", JS5_ADDR);
+    info!("Starting JS5 server: {}", config.network.js5);
     info!("---------------------------------------------");
 
     tokio::select! {
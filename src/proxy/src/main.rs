@@ -1,7 +1,6 @@
 use std::error::Error;
 use std::time::Duration;
 use constants::proxy::proxy::{BUFFER_SIZE, READ_TIMEOUT_MS};
-use constants::server_addresses::server_addresses::{JS5_ADDR, WORLDLIST_ADDR, PROXY_ADDR};
 use constants::title_protocol::title_protocol;
 use engine::io::connection::{try_write_packet, Connection};
 use engine::io::packet::Packet;
@@ -28,12 +27,12 @@ fn choose_backend_and_consume(packet: &mut Packet) -> Destination {
     
     match opcode {
         title_protocol::INIT_JS5REMOTE_CONNECTION => {
-            debug!("Routing to JS5_ADDR: {}", JS5_ADDR);
+            debug!("Routing to JS5: {}", config::get().network.js5);
             Destination::JS5
         }
 
         title_protocol::REQUEST_WORLDLIST => {
-            debug!("Routing to WORLDLIST_ADDR: {}", WORLDLIST_ADDR);
+            debug!("Routing to worldlist: {}", config::get().network.worldlist);
             Destination::WorldList
         }
         
@@ -49,10 +48,10 @@ fn choose_backend_and_consume(packet: &mut Packet) -> Destination {
 }
 
 /// Helper function to get the address for a destination
-fn get_address(destination: &Destination) -> &str {
+fn get_address(destination: &Destination) -> &'static str {
     match destination {
-        Destination::JS5 => JS5_ADDR,
-        Destination::WorldList => WORLDLIST_ADDR,
+        Destination::JS5 => &config::get().network.js5,
+        Destination::WorldList => &config::get().network.worldlist,
         Destination::WorldSuitability => "world_suitability",
         Destination::Terminate => unreachable!(), // This should never be called
     }
@@ -206,7 +205,7 @@ async fn handle_proxy_client(client_stream: TcpStream) -> Result<(), Box<dyn Err
 }
 
 async fn run_proxy_server() -> Result<(), Box<dyn Error>> {
-    let listener = TcpListener::bind(PROXY_ADDR).await?;
+    let listener = TcpListener::bind(&config::get().network.proxy).await?;
 
     loop {
         match listener.accept().await {
@@ -232,10 +231,11 @@ async fn main() -> Result<(), Box<dyn Error>> {
         }
     }
     env_logger::init();
+    let config = config::init()?;
 
     info!("Starting TCP Proxy System");
     info!("---------------------------------------------");
-    info!("Starting proxy server: {}", PROXY_ADDR);
+    info!("Starting proxy server: {}", config.network.proxy);
     info!("---------------------------------------------");
 
     tokio::select! {
@@ -3,7 +3,6 @@ mod countries;
 use std::error::Error;
 use tokio::net::{TcpListener, TcpStream};
 use log::{debug, error, info};
-use constants::server_addresses::server_addresses::WORLDLIST_ADDR;
 use engine::io::connection::{try_write_packet, Connection};
 use engine::io::packet::Packet;
 use countries::COUNTRY_MAP;
@@ -33,22 +32,24 @@ async fn handle_worldlist_client(stream: TcpStream) -> Result<(), Box<dyn Error>
                 response.p1(1);
 
                 if checksum != 2 {
+                    let config = config::get();
+
                     response.p1(1); // Update
                     response.psmart(1); // Active world list
 
                     // World Block
                     write_country_info(&mut response, "Sweden");
 
-                    response.psmart(1); // Offset
+                    response.psmart(config.world.id as i32); // Offset
                     response.psmart(1); // Array size
                     response.psmart(1); // Active World count
 
-                    // Sweden world
+                    // Sweden world, id relative to the offset
                     response.psmart(0);
                     response.p1(0);
-                    response.p4(0);
+                    response.p4(if config.world.members { 0x1 } else { 0 }); // Flags
                     response.pjstr2("");
-                    response.pjstr2("localhost");
+                    response.pjstr2(&config.network.public_host);
 
                     // Default value
                     response.p4(1);
@@ -78,7 +79,7 @@ async fn handle_worldlist_client(stream: TcpStream) -> Result<(), Box<dyn Error>
 }
 
 async fn run_worldlist_server() -> Result<(), Box<dyn Error>> {
-    let listener = TcpListener::bind(WORLDLIST_ADDR).await?;
+    let listener = TcpListener::bind(&config::get().network.worldlist).await?;
 
     loop {
         match listener.accept().await {
@@ -104,10 +105,11 @@ async fn main() -> Result<(), Box<dyn Error>> {
         }
     }
     env_logger::init();
+    let config = config::init()?;
 
     info!("Starting Worldlist System");
     info!("---------------------------------------------");
-    info!("Starting Worldlist server: {}", WORLDLIST_ADDR);
+    info!("Starting Worldlist server: {}", config.network.worldlist);
     info!("---------------------------------------------");
 
     tokio::select! {